@@ -0,0 +1,193 @@
+//! Keeping the completed tables of a forest, so that later solvers can
+//! reuse them.
+
+use crate::Answer;
+use chalk_ir::interner::Interner;
+#[cfg(feature = "serde")]
+use chalk_ir::interner::SerdeInterner;
+use chalk_ir::{Goal, InEnvironment, UCanonical};
+use chalk_solve::RustIrDatabase;
+use rustc_hash::FxHashMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+mod dependency;
+
+pub(crate) use dependency::{Dependency, DependencyRecorder};
+
+/// The completed tables of SLG forests. A solver given an answer cache (see
+/// `SLGSolver::with_answer_cache`) saves the tables it completes to it, and
+/// takes the tables for new goals from it rather than solving them again.
+///
+/// Each table records the queries made of the `RustIrDatabase` while it was
+/// built, and the tables it took answers from. So when the program changes,
+/// `retain_valid` can keep the tables that did not look at anything that
+/// changed, and the work that went into them is not lost.
+///
+/// The cache holds nothing but IR, and is not tied to any one solver. With
+/// the `serde` feature, it can be serialized for interners implementing
+/// `SerdeInterner`, and so saved for later runs.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
+pub struct AnswerCache<I: Interner> {
+    /// The `max_size` of the solvers that saved the tables, which bounds
+    /// the size of their answers.
+    max_size: Option<usize>,
+
+    #[cfg_attr(feature = "serde", serde(with = "table_pairs"))]
+    tables: FxHashMap<UCanonical<InEnvironment<Goal<I>>>, CachedTable<I>>,
+
+    /// Queries made while pursuing strands rather than building tables.
+    /// Every table depends on these.
+    shared_dependencies: Vec<Dependency<I>>,
+}
+
+/// Serializes the tables as a sequence of pairs, since formats like JSON
+/// only allow strings as the keys of maps.
+#[cfg(feature = "serde")]
+mod table_pairs {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    type Tables<I> = FxHashMap<UCanonical<InEnvironment<Goal<I>>>, CachedTable<I>>;
+
+    pub(super) fn serialize<I: SerdeInterner, S: Serializer>(
+        tables: &Tables<I>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(tables)
+    }
+
+    pub(super) fn deserialize<'de, I: SerdeInterner, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Tables<I>, D::Error> {
+        let pairs =
+            Vec::<(UCanonical<InEnvironment<Goal<I>>>, CachedTable<I>)>::deserialize(deserializer)?;
+        Ok(pairs.into_iter().collect())
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
+pub(crate) struct CachedTable<I: Interner> {
+    pub(crate) coinductive: bool,
+    pub(crate) floundered: bool,
+    pub(crate) answers: Vec<Answer<I>>,
+    pub(crate) dependencies: Vec<Dependency<I>>,
+
+    /// The goals of the tables this table took answers from.
+    pub(crate) subgoals: Vec<UCanonical<InEnvironment<Goal<I>>>>,
+}
+
+impl<I: Interner> AnswerCache<I> {
+    pub fn new() -> Self {
+        AnswerCache {
+            max_size: None,
+            tables: FxHashMap::default(),
+            shared_dependencies: Vec::new(),
+        }
+    }
+
+    /// The number of tables in the cache.
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.tables.clear();
+        self.shared_dependencies.clear();
+    }
+
+    /// Drops the tables that depend on a query for which `db` gives a
+    /// different result than the database they were solved with, along
+    /// with the tables that took answers from them.
+    ///
+    /// `db` has to accept all the ids of the database the tables were
+    /// solved with, since the queries are made again.
+    pub fn retain_valid(&mut self, db: &dyn RustIrDatabase<I>) {
+        if !self
+            .shared_dependencies
+            .iter()
+            .all(|dependency| dependency.holds_in(db))
+        {
+            self.clear();
+            return;
+        }
+        self.tables.retain(|_, table| {
+            table
+                .dependencies
+                .iter()
+                .all(|dependency| dependency.holds_in(db))
+        });
+        self.remove_orphans();
+    }
+
+    /// Clears the cache if its tables were saved by solvers with another
+    /// `max_size`.
+    pub(crate) fn set_max_size(&mut self, max_size: usize) {
+        if self.max_size != Some(max_size) {
+            self.clear();
+            self.max_size = Some(max_size);
+        }
+    }
+
+    pub(crate) fn get(&self, goal: &UCanonical<InEnvironment<Goal<I>>>) -> Option<&CachedTable<I>> {
+        self.tables.get(goal)
+    }
+
+    pub(crate) fn contains(&self, goal: &UCanonical<InEnvironment<Goal<I>>>) -> bool {
+        self.tables.contains_key(goal)
+    }
+
+    pub(crate) fn insert(
+        &mut self,
+        goal: UCanonical<InEnvironment<Goal<I>>>,
+        table: CachedTable<I>,
+    ) {
+        self.tables.insert(goal, table);
+    }
+
+    pub(crate) fn add_shared_dependencies(&mut self, dependencies: Vec<Dependency<I>>) {
+        for dependency in dependencies {
+            if !self.shared_dependencies.contains(&dependency) {
+                self.shared_dependencies.push(dependency);
+            }
+        }
+    }
+
+    /// Removes the tables that took answers from tables not in the cache,
+    /// until there are none left.
+    pub(crate) fn remove_orphans(&mut self) {
+        loop {
+            let orphans: Vec<_> = self
+                .tables
+                .iter()
+                .filter(|(_, table)| {
+                    table
+                        .subgoals
+                        .iter()
+                        .any(|subgoal| !self.tables.contains_key(subgoal))
+                })
+                .map(|(goal, _)| goal.clone())
+                .collect();
+            if orphans.is_empty() {
+                return;
+            }
+            for goal in orphans {
+                self.tables.remove(&goal);
+            }
+        }
+    }
+}
+
+impl<I: Interner> Default for AnswerCache<I> {
+    fn default() -> Self {
+        AnswerCache::new()
+    }
+}
@@ -0,0 +1,395 @@
+use chalk_ir::interner::Interner;
+#[cfg(feature = "serde")]
+use chalk_ir::interner::SerdeInterner;
+use chalk_ir::*;
+use chalk_solve::coherence::SpecializationPriorities;
+use chalk_solve::rust_ir::*;
+use chalk_solve::RustIrDatabase;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::Arc;
+
+/// A query made of the `RustIrDatabase`, along with its result. Answers
+/// found with the help of the query are still valid for another database
+/// as long as that database gives the same result.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
+pub(crate) enum Dependency<I: Interner> {
+    CustomClauses(Vec<ProgramClause<I>>),
+    AssociatedTyData(AssocTypeId<I>, Arc<AssociatedTyDatum<I>>),
+    TraitDatum(TraitId<I>, Arc<TraitDatum<I>>),
+    AdtDatum(AdtId<I>, Arc<AdtDatum<I>>),
+    GeneratorDatum(GeneratorId<I>, Arc<GeneratorDatum<I>>),
+    GeneratorWitnessDatum(GeneratorId<I>, Arc<GeneratorWitnessDatum<I>>),
+    AdtRepr(AdtId<I>, Arc<AdtRepr<I>>),
+    FnDefDatum(FnDefId<I>, Arc<FnDefDatum<I>>),
+    ImplDatum(ImplId<I>, Arc<ImplDatum<I>>),
+    AssociatedTyValue(AssociatedTyValueId<I>, Arc<AssociatedTyValue<I>>),
+    OpaqueTyData(OpaqueTyId<I>, Arc<OpaqueTyDatum<I>>),
+    HiddenOpaqueType(OpaqueTyId<I>, Ty<I>),
+    ImplsForTrait(
+        TraitId<I>,
+        Vec<GenericArg<I>>,
+        CanonicalVarKinds<I>,
+        Vec<ImplId<I>>,
+    ),
+    LocalImplsToCoherenceCheck(TraitId<I>, Vec<ImplId<I>>),
+    SpecializationPriorities(TraitId<I>, Arc<SpecializationPriorities<I>>),
+    ImplProvidedFor(TraitId<I>, TyKind<I>, bool),
+    WellKnownTraitId(WellKnownTrait, Option<TraitId<I>>),
+    ProgramClausesForEnv(Environment<I>, ProgramClauses<I>),
+    IsObjectSafe(TraitId<I>, bool),
+    ClosureKind(ClosureId<I>, Substitution<I>, ClosureKind),
+    ClosureInputsAndOutput(
+        ClosureId<I>,
+        Substitution<I>,
+        Binders<FnDefInputsAndOutputDatum<I>>,
+    ),
+    ClosureUpvars(ClosureId<I>, Substitution<I>, Binders<Ty<I>>),
+    ClosureFnSubstitution(ClosureId<I>, Substitution<I>, Substitution<I>),
+    DiscriminantType(Ty<I>, Ty<I>),
+    FnDefVariance(FnDefId<I>, Variances<I>),
+    AdtVariance(AdtId<I>, Variances<I>),
+}
+
+impl<I: Interner> Dependency<I> {
+    /// Returns `true` if `db` gives the same result for the query.
+    pub(crate) fn holds_in(&self, db: &dyn RustIrDatabase<I>) -> bool {
+        match self {
+            Dependency::CustomClauses(clauses) => db.custom_clauses() == *clauses,
+            Dependency::AssociatedTyData(id, datum) => db.associated_ty_data(*id) == *datum,
+            Dependency::TraitDatum(id, datum) => db.trait_datum(*id) == *datum,
+            Dependency::AdtDatum(id, datum) => db.adt_datum(*id) == *datum,
+            Dependency::GeneratorDatum(id, datum) => db.generator_datum(*id) == *datum,
+            Dependency::GeneratorWitnessDatum(id, datum) => {
+                db.generator_witness_datum(*id) == *datum
+            }
+            Dependency::AdtRepr(id, repr) => db.adt_repr(*id) == *repr,
+            Dependency::FnDefDatum(id, datum) => db.fn_def_datum(*id) == *datum,
+            Dependency::ImplDatum(id, datum) => db.impl_datum(*id) == *datum,
+            Dependency::AssociatedTyValue(id, value) => db.associated_ty_value(*id) == *value,
+            Dependency::OpaqueTyData(id, datum) => db.opaque_ty_data(*id) == *datum,
+            Dependency::HiddenOpaqueType(id, ty) => db.hidden_opaque_type(*id) == *ty,
+            Dependency::ImplsForTrait(id, parameters, binders, impls) => {
+                db.impls_for_trait(*id, parameters, binders) == *impls
+            }
+            Dependency::LocalImplsToCoherenceCheck(id, impls) => {
+                db.local_impls_to_coherence_check(*id) == *impls
+            }
+            Dependency::SpecializationPriorities(id, priorities) => {
+                db.specialization_priorities(*id) == *priorities
+            }
+            Dependency::ImplProvidedFor(id, ty, provided) => {
+                db.impl_provided_for(*id, ty) == *provided
+            }
+            Dependency::WellKnownTraitId(well_known, id) => {
+                db.well_known_trait_id(*well_known) == *id
+            }
+            Dependency::ProgramClausesForEnv(environment, clauses) => {
+                db.program_clauses_for_env(environment) == *clauses
+            }
+            Dependency::IsObjectSafe(id, safe) => db.is_object_safe(*id) == *safe,
+            Dependency::ClosureKind(id, substs, kind) => db.closure_kind(*id, substs) == *kind,
+            Dependency::ClosureInputsAndOutput(id, substs, inputs_and_output) => {
+                db.closure_inputs_and_output(*id, substs) == *inputs_and_output
+            }
+            Dependency::ClosureUpvars(id, substs, upvars) => {
+                db.closure_upvars(*id, substs) == *upvars
+            }
+            Dependency::ClosureFnSubstitution(id, substs, fn_substs) => {
+                db.closure_fn_substitution(*id, substs) == *fn_substs
+            }
+            Dependency::DiscriminantType(ty, discriminant) => {
+                db.discriminant_type(ty.clone()) == *discriminant
+            }
+            Dependency::FnDefVariance(id, variances) => {
+                db.unification_database().fn_def_variance(*id) == *variances
+            }
+            Dependency::AdtVariance(id, variances) => {
+                db.unification_database().adt_variance(*id) == *variances
+            }
+        }
+    }
+}
+
+/// Wraps a `RustIrDatabase` and records the queries made of it.
+///
+/// Queries made while a table is being built (see `record_table`) are
+/// recorded for that table. All others, such as the variances looked up
+/// when unifying answers, are recorded as shared dependencies of every
+/// table.
+pub(crate) struct DependencyRecorder<'db, I: Interner> {
+    db: &'db dyn RustIrDatabase<I>,
+    table: RefCell<Option<Vec<Dependency<I>>>>,
+    shared: RefCell<Vec<Dependency<I>>>,
+}
+
+impl<'db, I: Interner> DependencyRecorder<'db, I> {
+    pub(crate) fn new(db: &'db dyn RustIrDatabase<I>) -> Self {
+        DependencyRecorder {
+            db,
+            table: RefCell::new(None),
+            shared: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Runs `op`, which builds a table, and returns its result along with
+    /// the queries it made.
+    pub(crate) fn record_table<R>(&self, op: impl FnOnce() -> R) -> (R, Vec<Dependency<I>>) {
+        let outer = self.table.replace(Some(Vec::new()));
+        let result = op();
+        let dependencies = self.table.replace(outer).unwrap();
+        (result, dependencies)
+    }
+
+    /// The queries made outside of building a table.
+    pub(crate) fn into_shared_dependencies(self) -> Vec<Dependency<I>> {
+        self.shared.into_inner()
+    }
+
+    fn record<T: Clone>(&self, result: T, dependency: impl FnOnce(T) -> Dependency<I>) -> T {
+        let dependency = dependency(result.clone());
+        let mut table = self.table.borrow_mut();
+        let mut shared = self.shared.borrow_mut();
+        let dependencies = match &mut *table {
+            Some(dependencies) => dependencies,
+            None => &mut *shared,
+        };
+        if !dependencies.contains(&dependency) {
+            dependencies.push(dependency);
+        }
+        result
+    }
+}
+
+impl<I: Interner> fmt::Debug for DependencyRecorder<'_, I> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "DependencyRecorder({:?})", self.db)
+    }
+}
+
+impl<I: Interner> UnificationDatabase<I> for DependencyRecorder<'_, I> {
+    fn fn_def_variance(&self, fn_def_id: FnDefId<I>) -> Variances<I> {
+        self.record(
+            self.db.unification_database().fn_def_variance(fn_def_id),
+            |variances| Dependency::FnDefVariance(fn_def_id, variances),
+        )
+    }
+
+    fn adt_variance(&self, adt_id: AdtId<I>) -> Variances<I> {
+        self.record(
+            self.db.unification_database().adt_variance(adt_id),
+            |variances| Dependency::AdtVariance(adt_id, variances),
+        )
+    }
+}
+
+impl<I: Interner> RustIrDatabase<I> for DependencyRecorder<'_, I> {
+    fn custom_clauses(&self) -> Vec<ProgramClause<I>> {
+        self.record(self.db.custom_clauses(), Dependency::CustomClauses)
+    }
+
+    fn associated_ty_data(&self, ty: AssocTypeId<I>) -> Arc<AssociatedTyDatum<I>> {
+        self.record(self.db.associated_ty_data(ty), |datum| {
+            Dependency::AssociatedTyData(ty, datum)
+        })
+    }
+
+    fn trait_datum(&self, trait_id: TraitId<I>) -> Arc<TraitDatum<I>> {
+        self.record(self.db.trait_datum(trait_id), |datum| {
+            Dependency::TraitDatum(trait_id, datum)
+        })
+    }
+
+    fn adt_datum(&self, adt_id: AdtId<I>) -> Arc<AdtDatum<I>> {
+        self.record(self.db.adt_datum(adt_id), |datum| {
+            Dependency::AdtDatum(adt_id, datum)
+        })
+    }
+
+    fn generator_datum(&self, generator_id: GeneratorId<I>) -> Arc<GeneratorDatum<I>> {
+        self.record(self.db.generator_datum(generator_id), |datum| {
+            Dependency::GeneratorDatum(generator_id, datum)
+        })
+    }
+
+    fn generator_witness_datum(
+        &self,
+        generator_id: GeneratorId<I>,
+    ) -> Arc<GeneratorWitnessDatum<I>> {
+        self.record(self.db.generator_witness_datum(generator_id), |datum| {
+            Dependency::GeneratorWitnessDatum(generator_id, datum)
+        })
+    }
+
+    fn adt_repr(&self, id: AdtId<I>) -> Arc<AdtRepr<I>> {
+        self.record(self.db.adt_repr(id), |repr| Dependency::AdtRepr(id, repr))
+    }
+
+    fn fn_def_datum(&self, fn_def_id: FnDefId<I>) -> Arc<FnDefDatum<I>> {
+        self.record(self.db.fn_def_datum(fn_def_id), |datum| {
+            Dependency::FnDefDatum(fn_def_id, datum)
+        })
+    }
+
+    fn impl_datum(&self, impl_id: ImplId<I>) -> Arc<ImplDatum<I>> {
+        self.record(self.db.impl_datum(impl_id), |datum| {
+            Dependency::ImplDatum(impl_id, datum)
+        })
+    }
+
+    // Inherent impls are only used by method lookup, which is not cached, so
+    // they are not recorded.
+    fn inherent_impl_datum(&self, impl_id: ImplId<I>) -> Arc<InherentImplDatum<I>> {
+        self.db.inherent_impl_datum(impl_id)
+    }
+
+    fn associated_ty_value(&self, id: AssociatedTyValueId<I>) -> Arc<AssociatedTyValue<I>> {
+        self.record(self.db.associated_ty_value(id), |value| {
+            Dependency::AssociatedTyValue(id, value)
+        })
+    }
+
+    fn opaque_ty_data(&self, id: OpaqueTyId<I>) -> Arc<OpaqueTyDatum<I>> {
+        self.record(self.db.opaque_ty_data(id), |datum| {
+            Dependency::OpaqueTyData(id, datum)
+        })
+    }
+
+    fn hidden_opaque_type(&self, id: OpaqueTyId<I>) -> Ty<I> {
+        self.record(self.db.hidden_opaque_type(id), |ty| {
+            Dependency::HiddenOpaqueType(id, ty)
+        })
+    }
+
+    fn impls_for_trait(
+        &self,
+        trait_id: TraitId<I>,
+        parameters: &[GenericArg<I>],
+        binders: &CanonicalVarKinds<I>,
+    ) -> Vec<ImplId<I>> {
+        self.record(
+            self.db.impls_for_trait(trait_id, parameters, binders),
+            |impls| {
+                Dependency::ImplsForTrait(trait_id, parameters.to_vec(), binders.clone(), impls)
+            },
+        )
+    }
+
+    fn inherent_impls(&self) -> Vec<ImplId<I>> {
+        self.db.inherent_impls()
+    }
+
+    fn local_impls_to_coherence_check(&self, trait_id: TraitId<I>) -> Vec<ImplId<I>> {
+        self.record(self.db.local_impls_to_coherence_check(trait_id), |impls| {
+            Dependency::LocalImplsToCoherenceCheck(trait_id, impls)
+        })
+    }
+
+    fn specialization_priorities(&self, trait_id: TraitId<I>) -> Arc<SpecializationPriorities<I>> {
+        self.record(self.db.specialization_priorities(trait_id), |priorities| {
+            Dependency::SpecializationPriorities(trait_id, priorities)
+        })
+    }
+
+    fn impl_provided_for(&self, auto_trait_id: TraitId<I>, ty: &TyKind<I>) -> bool {
+        self.record(self.db.impl_provided_for(auto_trait_id, ty), |provided| {
+            Dependency::ImplProvidedFor(auto_trait_id, ty.clone(), provided)
+        })
+    }
+
+    fn well_known_trait_id(&self, well_known_trait: WellKnownTrait) -> Option<TraitId<I>> {
+        self.record(self.db.well_known_trait_id(well_known_trait), |id| {
+            Dependency::WellKnownTraitId(well_known_trait, id)
+        })
+    }
+
+    fn program_clauses_for_env(&self, environment: &Environment<I>) -> ProgramClauses<I> {
+        self.record(self.db.program_clauses_for_env(environment), |clauses| {
+            Dependency::ProgramClausesForEnv(environment.clone(), clauses)
+        })
+    }
+
+    fn interner(&self) -> &I {
+        self.db.interner()
+    }
+
+    fn is_object_safe(&self, trait_id: TraitId<I>) -> bool {
+        self.record(self.db.is_object_safe(trait_id), |safe| {
+            Dependency::IsObjectSafe(trait_id, safe)
+        })
+    }
+
+    fn closure_kind(&self, closure_id: ClosureId<I>, substs: &Substitution<I>) -> ClosureKind {
+        self.record(self.db.closure_kind(closure_id, substs), |kind| {
+            Dependency::ClosureKind(closure_id, substs.clone(), kind)
+        })
+    }
+
+    fn closure_inputs_and_output(
+        &self,
+        closure_id: ClosureId<I>,
+        substs: &Substitution<I>,
+    ) -> Binders<FnDefInputsAndOutputDatum<I>> {
+        self.record(
+            self.db.closure_inputs_and_output(closure_id, substs),
+            |inputs_and_output| {
+                Dependency::ClosureInputsAndOutput(closure_id, substs.clone(), inputs_and_output)
+            },
+        )
+    }
+
+    fn closure_upvars(&self, closure_id: ClosureId<I>, substs: &Substitution<I>) -> Binders<Ty<I>> {
+        self.record(self.db.closure_upvars(closure_id, substs), |upvars| {
+            Dependency::ClosureUpvars(closure_id, substs.clone(), upvars)
+        })
+    }
+
+    fn closure_fn_substitution(
+        &self,
+        closure_id: ClosureId<I>,
+        substs: &Substitution<I>,
+    ) -> Substitution<I> {
+        self.record(
+            self.db.closure_fn_substitution(closure_id, substs),
+            |fn_substs| Dependency::ClosureFnSubstitution(closure_id, substs.clone(), fn_substs),
+        )
+    }
+
+    fn unification_database(&self) -> &dyn UnificationDatabase<I> {
+        self
+    }
+
+    fn trait_name(&self, trait_id: TraitId<I>) -> String {
+        self.db.trait_name(trait_id)
+    }
+
+    fn adt_name(&self, adt_id: AdtId<I>) -> String {
+        self.db.adt_name(adt_id)
+    }
+
+    fn assoc_type_name(&self, assoc_ty_id: AssocTypeId<I>) -> String {
+        self.db.assoc_type_name(assoc_ty_id)
+    }
+
+    fn opaque_type_name(&self, opaque_ty_id: OpaqueTyId<I>) -> String {
+        self.db.opaque_type_name(opaque_ty_id)
+    }
+
+    fn fn_def_name(&self, fn_def_id: FnDefId<I>) -> String {
+        self.db.fn_def_name(fn_def_id)
+    }
+
+    fn method_name(&self, impl_id: ImplId<I>, index: usize) -> String {
+        self.db.method_name(impl_id, index)
+    }
+
+    fn discriminant_type(&self, ty: Ty<I>) -> Ty<I> {
+        self.record(self.db.discriminant_type(ty.clone()), |discriminant| {
+            Dependency::DiscriminantType(ty, discriminant)
+        })
+    }
+}
@@ -1,12 +1,15 @@
+use crate::cache::{AnswerCache, CachedTable, Dependency};
 use crate::context::{AnswerResult, AnswerStream};
 use crate::logic::RootSearchFail;
 use crate::slg::SlgContextOps;
-use crate::table::AnswerIndex;
+use crate::table::{AnswerIndex, Table};
 use crate::tables::Tables;
 use crate::{TableIndex, TimeStamp};
 
 use chalk_ir::interner::Interner;
-use chalk_ir::{Goal, InEnvironment, Substitution, UCanonical};
+use chalk_ir::{Canonical, Goal, InEnvironment, Substitution, UCanonical};
+use chalk_solve::{ProofTree, SolverStats};
+use std::sync::{Arc, Mutex};
 use tracing::debug;
 
 pub(crate) struct Forest<I: Interner> {
@@ -17,6 +20,24 @@ pub(crate) struct Forest<I: Interner> {
     /// This effectively gives us way to track what depth
     /// and loop a table or strand was last followed.
     pub(crate) clock: TimeStamp,
+
+    /// If true, each answer records the clause and subgoal answers it
+    /// was derived from, so that proof trees can be built.
+    pub(crate) record_proofs: bool,
+
+    /// The number of strands that the current solve may still pursue, or
+    /// `None` if its work is not bounded.
+    pub(crate) steps_left: Option<usize>,
+
+    /// Set once the current solve has run out of `steps_left`.
+    pub(crate) budget_exhausted: bool,
+
+    /// The statistics of the current solve, if they are being collected.
+    pub(crate) stats: Option<SolverStats>,
+
+    /// The cache that completed tables are saved to and new tables are
+    /// taken from, if any.
+    pub(crate) cache: Option<Arc<Mutex<AnswerCache<I>>>>,
 }
 
 impl<I: Interner> Forest<I> {
@@ -24,7 +45,101 @@ impl<I: Interner> Forest<I> {
         Forest {
             tables: Tables::new(),
             clock: TimeStamp::default(),
+            record_proofs: false,
+            steps_left: None,
+            budget_exhausted: false,
+            stats: None,
+            cache: None,
+        }
+    }
+
+    /// Starts a new solve, which may pursue `budget` strands (or any
+    /// number, if `budget` is `None`), with fresh statistics.
+    pub(crate) fn start_solve(&mut self, budget: Option<usize>) {
+        self.steps_left = budget;
+        self.budget_exhausted = false;
+        if let Some(stats) = &mut self.stats {
+            *stats = SolverStats::default();
+        }
+    }
+
+    /// Updates the statistics, if they are being collected.
+    pub(crate) fn count(&mut self, update: impl FnOnce(&mut SolverStats)) {
+        if let Some(stats) = &mut self.stats {
+            update(stats);
+        }
+    }
+
+    /// Takes a step from the budget of the current solve. Returns `false`,
+    /// and records that the budget is exhausted, if there are none left.
+    pub(crate) fn take_step(&mut self) -> bool {
+        match &mut self.steps_left {
+            Some(0) => {
+                self.budget_exhausted = true;
+                false
+            }
+            Some(steps_left) => {
+                *steps_left -= 1;
+                true
+            }
+            None => true,
+        }
+    }
+
+    /// Takes the table for `goal` from the answer cache, if it is there.
+    /// Tables from the cache have no proofs for their answers, so none are
+    /// taken when proof trees are being recorded.
+    pub(crate) fn table_from_cache(
+        &self,
+        goal: &UCanonical<InEnvironment<Goal<I>>>,
+    ) -> Option<Table<I>> {
+        if self.record_proofs {
+            return None;
         }
+        let cache = self.cache.as_ref()?.lock().unwrap();
+        let cached = cache.get(goal)?;
+        let mut table = Table::new(goal.clone(), cached.coinductive);
+        if cached.floundered {
+            table.mark_floundered();
+        }
+        for answer in &cached.answers {
+            table.push_answer(answer.clone(), None);
+        }
+        table.from_cache = true;
+        Some(table)
+    }
+
+    /// Saves the completed tables to the answer cache, if there is one,
+    /// along with the queries made outside of building tables.
+    pub(crate) fn save_to_cache(&self, shared_dependencies: Vec<Dependency<I>>) {
+        let mut cache = match &self.cache {
+            Some(cache) => cache.lock().unwrap(),
+            None => return,
+        };
+        cache.add_shared_dependencies(shared_dependencies);
+        for table in self.tables.iter() {
+            if !table.is_complete() || table.from_cache || cache.contains(&table.table_goal) {
+                continue;
+            }
+            let subgoals = table
+                .subgoal_tables
+                .iter()
+                .map(|&subgoal_table| self.tables[subgoal_table].table_goal.clone())
+                .collect();
+            cache.insert(
+                table.table_goal.clone(),
+                CachedTable {
+                    coinductive: table.coinductive_goal,
+                    floundered: table.is_floundered(),
+                    answers: table.answers().to_vec(),
+                    dependencies: table.dependencies.clone(),
+                    subgoals,
+                },
+            );
+        }
+        // A table may be complete while a table it took answers from is
+        // not, if it only needed some of them.
+        cache.remove_orphans();
     }
 
     // Gets the next clock TimeStamp. This will never decrease.
@@ -51,6 +166,64 @@ impl<I: Interner> Forest<I> {
             answer,
         }
     }
+
+    /// Builds the proof tree for the first complete answer to `goal`,
+    /// which must already have been solved. Returns `None` if proof
+    /// trees are not being recorded or there is no such answer.
+    pub(crate) fn proof_tree(
+        &self,
+        interner: &I,
+        goal: &UCanonical<InEnvironment<Goal<I>>>,
+    ) -> Option<ProofTree<I>> {
+        if !self.record_proofs {
+            return None;
+        }
+        let table = self.tables.index_of(goal)?;
+        let mut answer = AnswerIndex::ZERO;
+        loop {
+            let subst = &self.tables[table].answer(answer)?.subst;
+            if subst.value.delayed_subgoals.is_empty() {
+                return Some(self.answer_proof_tree(interner, table, answer, &mut vec![]));
+            }
+            answer.increment();
+        }
+    }
+
+    fn answer_proof_tree(
+        &self,
+        interner: &I,
+        table: TableIndex,
+        answer: AnswerIndex,
+        ancestors: &mut Vec<(TableIndex, AnswerIndex)>,
+    ) -> ProofTree<I> {
+        // Show the table goal with the answer applied.
+        let table_goal = &self.tables[table].table_goal.canonical.value;
+        let answer_subst = &self.tables[table].answer(answer).unwrap().subst;
+        let goal = Canonical {
+            value: answer_subst.value.subst.apply(table_goal.clone(), interner),
+            binders: answer_subst.binders.clone(),
+        };
+
+        if ancestors.contains(&(table, answer)) {
+            return ProofTree::cycle(goal);
+        }
+
+        match self.tables[table].answer_proof(answer) {
+            Some(proof) => {
+                ancestors.push((table, answer));
+                let subgoals = proof
+                    .subgoal_answers
+                    .iter()
+                    .map(|&(subgoal_table, subgoal_answer)| {
+                        self.answer_proof_tree(interner, subgoal_table, subgoal_answer, ancestors)
+                    })
+                    .collect();
+                ancestors.pop();
+                ProofTree::new(goal, proof.clause.clone(), subgoals)
+            }
+            None => ProofTree::new(goal, None, vec![]),
+        }
+    }
 }
 
 struct ForestSolver<'me, I: Interner> {
@@ -87,7 +260,7 @@ impl<'me, I: Interner> AnswerStream<I> for ForestSolver<'me, I> {
                 }
 
                 Err(RootSearchFail::QuantumExceeded) => {
-                    if !should_continue() {
+                    if self.forest.budget_exhausted || !should_continue() {
                         return AnswerResult::QuantumExceeded;
                     }
                 }
@@ -58,12 +58,17 @@ use std::usize;
 
 use chalk_derive::{Fold, HasInterner, Visit};
 use chalk_ir::interner::Interner;
+#[cfg(feature = "serde")]
+use chalk_ir::interner::SerdeInterner;
 use chalk_ir::visit::ControlFlow;
 use chalk_ir::{
     AnswerSubst, Canonical, ConstrainedSubst, Constraint, DebruijnIndex, Goal, InEnvironment,
     Substitution,
 };
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
+pub mod cache;
 pub mod context;
 mod derived;
 pub mod forest;
@@ -182,6 +187,8 @@ pub struct FlounderedSubgoal<I: Interner> {
 /// goal for a particular table (modulo delayed literals). It contains
 /// a substitution
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct Answer<I: Interner> {
     /// Contains values for the unbound inference variables for which
     /// the table is true, along with any delayed subgoals (Which must
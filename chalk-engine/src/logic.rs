@@ -2,7 +2,7 @@ use crate::forest::Forest;
 use crate::normalize_deep::DeepNormalizer;
 use crate::slg::{ResolventOps, SlgContext, SlgContextOps};
 use crate::stack::{Stack, StackIndex};
-use crate::strand::{CanonicalStrand, SelectedSubgoal, Strand};
+use crate::strand::{CanonicalStrand, ProofRecord, SelectedSubgoal, Strand};
 use crate::table::{AnswerIndex, Table};
 use crate::{
     Answer, AnswerMode, CompleteAnswer, ExClause, FlounderedSubgoal, Literal, Minimums, TableIndex,
@@ -175,10 +175,10 @@ impl<I: Interner> Forest<I> {
         // Subgoal abstraction:
         let (ucanonical_subgoal, universe_map) = match subgoal {
             Literal::Positive(subgoal) => {
-                Forest::abstract_positive_literal(context, infer, subgoal.clone())?
+                self.abstract_positive_literal(context, infer, subgoal.clone())?
             }
             Literal::Negative(subgoal) => {
-                Forest::abstract_negative_literal(context, infer, subgoal.clone())?
+                self.abstract_negative_literal(context, infer, subgoal.clone())?
             }
         };
 
@@ -204,15 +204,31 @@ impl<I: Interner> Forest<I> {
     ) -> TableIndex {
         if let Some(table) = self.tables.index_of(&goal) {
             debug!(?table, "found existing table");
+            self.count(|stats| stats.cache_hits += 1);
             return table;
         }
 
+        if let Some(table) = self.table_from_cache(&goal) {
+            debug!("found table in the answer cache");
+            self.count(|stats| stats.cache_hits += 1);
+            return self.tables.insert(table);
+        }
+
         info!(
             table = ?self.tables.next_index(),
             "creating new table with goal = {:#?}",
             goal,
         );
-        let table = Self::build_table(context, self.tables.next_index(), goal);
+        let (mut table, dependencies) = context.record_dependencies(|| {
+            Self::build_table(context, self.tables.next_index(), goal, self.record_proofs)
+        });
+        table.dependencies = dependencies;
+        let strands = table.strands().count();
+        self.count(|stats| {
+            stats.cache_misses += 1;
+            stats.tables_created += 1;
+            stats.strands_created += strands;
+        });
         self.tables.insert(table)
     }
 
@@ -227,10 +243,14 @@ impl<I: Interner> Forest<I> {
     /// In terms of the NFTD paper, this corresponds to the *Program
     /// Clause Resolution* step being applied eagerly, as many times
     /// as possible.
+    ///
+    /// If `record_proofs` is true, each strand remembers the clause
+    /// it was created from.
     fn build_table(
         context: &SlgContextOps<I>,
         table_idx: TableIndex,
         goal: UCanonical<InEnvironment<Goal<I>>>,
+        record_proofs: bool,
     ) -> Table<I> {
         let coinductive = goal.is_coinductive(context.program());
         let mut table = Table::new(goal.clone(), coinductive);
@@ -288,10 +308,16 @@ impl<I: Interner> Forest<I> {
                                 &clause,
                             ) {
                                 info!("pushing initial strand with ex-clause: {:#?}", &resolvent,);
+                                let proof = if record_proofs {
+                                    Some(ProofRecord::new(Some(clause.clone())))
+                                } else {
+                                    None
+                                };
                                 let strand = Strand {
                                     ex_clause: resolvent,
                                     selected_subgoal: None,
                                     last_pursued_time: TimeStamp::default(),
+                                    proof,
                                 };
                                 let canonical_strand =
                                     Self::canonicalize_strand_from(context, &mut infer, &strand);
@@ -334,10 +360,16 @@ impl<I: Interner> Forest<I> {
                             ),
                             "pushing initial strand"
                         );
+                        let proof = if record_proofs {
+                            Some(ProofRecord::new(None))
+                        } else {
+                            None
+                        };
                         let strand = Strand {
                             ex_clause,
                             selected_subgoal: None,
                             last_pursued_time: TimeStamp::default(),
+                            proof,
                         };
                         let canonical_strand =
                             Self::canonicalize_strand_from(context, &mut infer, &strand);
@@ -359,6 +391,7 @@ impl<I: Interner> Forest<I> {
     /// of `subgoal`; but if the subgoal is getting too big, we return
     /// `None`, which causes the subgoal to flounder.
     fn abstract_positive_literal(
+        &mut self,
         context: &SlgContextOps<I>,
         infer: &mut InferenceTable<I>,
         subgoal: InEnvironment<Goal<I>>,
@@ -369,6 +402,7 @@ impl<I: Interner> Forest<I> {
             context.max_size(),
             &subgoal,
         ) {
+            self.count(|stats| stats.truncations += 1);
             None
         } else {
             let canonicalized_goal = infer
@@ -390,6 +424,7 @@ impl<I: Interner> Forest<I> {
     /// variables appear in `subgoal` (in which case the execution is
     /// said to "flounder").
     fn abstract_negative_literal(
+        &mut self,
         context: &SlgContextOps<I>,
         infer: &mut InferenceTable<I>,
         subgoal: InEnvironment<Goal<I>>,
@@ -439,6 +474,7 @@ impl<I: Interner> Forest<I> {
             context.max_size(),
             &inverted_subgoal,
         ) {
+            self.count(|stats| stats.truncations += 1);
             None
         } else {
             let canonicalized_goal = infer
@@ -532,6 +568,15 @@ impl<'forest, I: Interner> SolveState<'forest, I> {
             });
             match next_strand {
                 Some(mut canonical_strand) => {
+                    if !self.forest.take_step() {
+                        // We are out of budget. Put the strand back, so that
+                        // a later solve can pursue it, and give up.
+                        info!("budget exhausted");
+                        self.forest.tables[table].enqueue_strand(canonical_strand);
+                        self.unwind_stack();
+                        return Err(RootSearchFail::QuantumExceeded);
+                    }
+                    self.forest.count(|stats| stats.strands_pursued += 1);
                     debug!("starting next strand = {:#?}", canonical_strand);
 
                     canonical_strand.value.last_pursued_time = clock;
@@ -631,11 +676,13 @@ impl<'forest, I: Interner> SolveState<'forest, I> {
                     ex_clause: strand.ex_clause.clone(),
                     selected_subgoal: Some(next_subgoal),
                     last_pursued_time: strand.last_pursued_time,
+                    proof: strand.proof.clone(),
                 };
                 let table = self.stack.top().table;
                 let canonical_next_strand =
                     Forest::canonicalize_strand_from(self.context, infer, &next_strand);
                 self.forest.tables[table].enqueue_strand(canonical_next_strand);
+                self.forest.count(|stats| stats.strands_created += 1);
             }
         }
 
@@ -686,6 +733,10 @@ impl<'forest, I: Interner> SolveState<'forest, I> {
                         // subgoals may be eligble to be pursued again.
                         ex_clause.answer_time.increment();
 
+                        if let Some(proof) = &mut strand.proof {
+                            proof.subgoal_answers.push((subgoal_table, answer_index));
+                        }
+
                         // Ok, we've applied the answer to this Strand.
                         return Ok(());
                     }
@@ -817,6 +868,7 @@ impl<'forest, I: Interner> SolveState<'forest, I> {
         // recursively requested an answer for itself. This
         // means that we have to delay this subgoal until we
         // reach a trivial self-cycle.
+        self.forest.count(|stats| stats.cycles += 1);
 
         // This subgoal selection for the strand is finished, so take it
         let selected_subgoal = canonical_strand.value.selected_subgoal.take().unwrap();
@@ -863,6 +915,8 @@ impl<'forest, I: Interner> SolveState<'forest, I> {
         canonical_strand: CanonicalStrand<I>,
         minimums: Minimums,
     ) -> Result<(), RootSearchFail> {
+        self.forest.count(|stats| stats.cycles += 1);
+
         // We can't take this because we might need it later to clear the cycle
         let selected_subgoal = canonical_strand.value.selected_subgoal.as_ref().unwrap();
 
@@ -1048,9 +1102,9 @@ impl<'forest, I: Interner> SolveState<'forest, I> {
                         // Delayed Trivial Self Cycle, Variant 3
                         // example).
 
-                        let answer = self.forest.answer(table, answer_index);
-                        if let Some(strand) = self.create_refinement_strand(table, answer) {
+                        if let Some(strand) = self.create_refinement_strand(table, answer_index) {
                             self.forest.tables[table].enqueue_strand(strand);
+                            self.forest.count(|stats| stats.strands_created += 1);
                         }
 
                         NoRemainingSubgoalsResult::RootAnswerAvailable
@@ -1087,8 +1141,10 @@ impl<'forest, I: Interner> SolveState<'forest, I> {
     fn create_refinement_strand(
         &self,
         table: TableIndex,
-        answer: &Answer<I>,
+        answer_index: AnswerIndex,
     ) -> Option<CanonicalStrand<I>> {
+        let answer = self.forest.answer(table, answer_index);
+
         // If there are no delayed subgoals, then there is no need for
         // a refinement strand.
         if answer.subst.value.delayed_subgoals.is_empty() {
@@ -1129,6 +1185,9 @@ impl<'forest, I: Interner> SolveState<'forest, I> {
             },
             selected_subgoal: None,
             last_pursued_time: TimeStamp::default(),
+            proof: self.forest.tables[table]
+                .answer_proof(answer_index)
+                .cloned(),
         };
 
         Some(Forest::canonicalize_strand_from(
@@ -1362,6 +1421,7 @@ impl<'forest, I: Interner> SolveState<'forest, I> {
                     &strand.ex_clause.subgoals[subgoal_index],
                 ) {
                     Some((subgoal_table, universe_map)) => {
+                        self.forest.tables[self.stack.top().table].add_subgoal_table(subgoal_table);
                         canonical_strand.value.selected_subgoal = Some(SelectedSubgoal {
                             subgoal_index,
                             subgoal_table,
@@ -1416,6 +1476,7 @@ impl<'forest, I: Interner> SolveState<'forest, I> {
             binders,
             value: strand,
         } = canonical_strand;
+        let proof = strand.proof;
         let ExClause {
             subst,
             constraints,
@@ -1461,6 +1522,7 @@ impl<'forest, I: Interner> SolveState<'forest, I> {
             self.context.max_size(),
             &subst,
         ) {
+            self.forest.count(|stats| stats.truncations += 1);
             self.forest.tables[table].mark_floundered();
             return None;
         }
@@ -1563,7 +1625,8 @@ impl<'forest, I: Interner> SolveState<'forest, I> {
                     .is_empty(self.context.program().interner())
         };
 
-        if let Some(answer_index) = self.forest.tables[table].push_answer(answer) {
+        if let Some(answer_index) = self.forest.tables[table].push_answer(answer, proof) {
+            self.forest.count(|stats| stats.answers += 1);
             // See above, if we have a *complete* and trivial answer, we don't
             // want to follow any more strands
             if !ambiguous && is_trivial_answer {
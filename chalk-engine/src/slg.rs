@@ -1,3 +1,4 @@
+use crate::cache::{Dependency, DependencyRecorder};
 use crate::ExClause;
 
 use chalk_derive::HasInterner;
@@ -32,6 +33,10 @@ impl<I: Interner> SlgContext<I> {
 #[derive(Clone, Debug)]
 pub(crate) struct SlgContextOps<'me, I: Interner> {
     program: &'me dyn RustIrDatabase<I>,
+
+    /// Set if `program` is a recorder, recording the queries made of the
+    /// program for an answer cache.
+    recorder: Option<&'me DependencyRecorder<'me, I>>,
     max_size: usize,
     expected_answers: Option<usize>,
 }
@@ -44,11 +49,36 @@ impl<I: Interner> SlgContextOps<'_, I> {
     ) -> SlgContextOps<'_, I> {
         SlgContextOps {
             program,
+            recorder: None,
+            max_size,
+            expected_answers,
+        }
+    }
+
+    /// Like `new`, but the queries made of the program are recorded by
+    /// `recorder`.
+    pub(crate) fn recording<'me>(
+        recorder: &'me DependencyRecorder<'me, I>,
+        max_size: usize,
+        expected_answers: Option<usize>,
+    ) -> SlgContextOps<'me, I> {
+        SlgContextOps {
+            program: recorder,
+            recorder: Some(recorder),
             max_size,
             expected_answers,
         }
     }
 
+    /// Runs `op`, which builds a table, and returns its result along with
+    /// the queries it made of the program, if they are being recorded.
+    pub(crate) fn record_dependencies<R>(&self, op: impl FnOnce() -> R) -> (R, Vec<Dependency<I>>) {
+        match self.recorder {
+            Some(recorder) => recorder.record_table(op),
+            None => (op(), Vec::new()),
+        }
+    }
+
     fn identity_constrained_subst(
         &self,
         goal: &UCanonical<InEnvironment<Goal<I>>>,
@@ -291,8 +321,17 @@ impl<I: Interner> MayInvalidate<'_, I> {
                 !c1.const_eq(new_ty, c2, interner)
             }
 
-            // Only variants left are placeholder = concrete, which always fails
-            (ConstValue::Placeholder(_), _) | (ConstValue::Concrete(_), _) => true,
+            (ConstValue::Unevaluated(u1), ConstValue::Unevaluated(u2)) => {
+                u1.op != u2.op
+                    || self.aggregate_consts(&u1.lhs, &u2.lhs)
+                    || self.aggregate_consts(&u1.rhs, &u2.rhs)
+            }
+
+            // Only variants left are mismatched kinds of constants, like
+            // placeholder = concrete, which always fails
+            (ConstValue::Placeholder(_), _)
+            | (ConstValue::Concrete(_), _)
+            | (ConstValue::Unevaluated(_), _) => true,
         }
     }
 
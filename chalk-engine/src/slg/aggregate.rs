@@ -548,9 +548,23 @@ impl<I: Interner> AntiUnifier<'_, '_, I> {
                 }
             }
 
-            (ConstValue::Placeholder(_), _) | (_, ConstValue::Placeholder(_)) => {
-                self.new_const_variable(ty)
+            (ConstValue::Unevaluated(u1), ConstValue::Unevaluated(u2)) if u1.op == u2.op => {
+                ConstData {
+                    ty,
+                    value: ConstValue::Unevaluated(UnevaluatedConst {
+                        op: u1.op,
+                        lhs: self.aggregate_consts(&u1.lhs, &u2.lhs),
+                        rhs: self.aggregate_consts(&u1.rhs, &u2.rhs),
+                    }),
+                }
+                .intern(interner)
             }
+
+            (ConstValue::Placeholder(_), _)
+            | (_, ConstValue::Placeholder(_))
+            | (ConstValue::Concrete(_), _)
+            | (_, ConstValue::Concrete(_))
+            | (ConstValue::Unevaluated(_), _) => self.new_const_variable(ty),
         }
     }
 
@@ -687,6 +687,12 @@ impl<'i, I: Interner> Zipper<'i, I> for AnswerSubstitutor<'i, I> {
                 Ok(())
             }
 
+            (ConstValue::Unevaluated(u1), ConstValue::Unevaluated(u2)) => {
+                assert_eq!(u1.op, u2.op);
+                Zip::zip_with(self, variance, &u1.lhs, &u2.lhs)?;
+                Zip::zip_with(self, variance, &u1.rhs, &u2.rhs)
+            }
+
             (ConstValue::InferenceVar(_), _) | (_, ConstValue::InferenceVar(_)) => panic!(
                 "unexpected inference var in answer `{:?}` or pending goal `{:?}`",
                 answer, pending,
@@ -694,7 +700,8 @@ impl<'i, I: Interner> Zipper<'i, I> for AnswerSubstitutor<'i, I> {
 
             (ConstValue::BoundVar(_), _)
             | (ConstValue::Placeholder(_), _)
-            | (ConstValue::Concrete(_), _) => panic!(
+            | (ConstValue::Concrete(_), _)
+            | (ConstValue::Unevaluated(_), _) => panic!(
                 "structural mismatch between answer `{:?}` and pending goal `{:?}`",
                 answer, pending,
             ),
@@ -1,17 +1,22 @@
+use crate::cache::{AnswerCache, DependencyRecorder};
 use crate::context::{AnswerResult, AnswerStream};
 use crate::forest::Forest;
 use crate::slg::aggregate::AggregateOps;
 use crate::slg::SlgContextOps;
 use chalk_ir::interner::Interner;
 use chalk_ir::{Canonical, ConstrainedSubst, Goal, InEnvironment, UCanonical};
-use chalk_solve::{RustIrDatabase, Solution, Solver, SubstitutionResult};
+use chalk_solve::{ProofTree, RustIrDatabase, Solution, Solver, SolverStats, SubstitutionResult};
 
 use std::fmt;
+use std::sync::{Arc, Mutex};
 
 pub struct SLGSolver<I: Interner> {
     pub(crate) forest: Forest<I>,
     pub(crate) max_size: usize,
     pub(crate) expected_answers: Option<usize>,
+
+    /// The number of strands each solve may pursue, if limited.
+    pub(crate) budget: Option<usize>,
 }
 
 impl<I: Interner> SLGSolver<I> {
@@ -20,7 +25,62 @@ impl<I: Interner> SLGSolver<I> {
             forest: Forest::new(),
             max_size,
             expected_answers,
+            budget: None,
+        }
+    }
+
+    /// Limits each solve to pursuing `budget` strands. A solve that runs
+    /// out gives up with an ambiguous solution, and `budget_exhausted`
+    /// returns `true` until the next solve.
+    pub fn with_budget(mut self, budget: usize) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Enables recording of proof trees, which are then returned from
+    /// `solve_with_proof_tree`.
+    pub fn with_proof_trees(mut self) -> Self {
+        self.forest.record_proofs = true;
+        self
+    }
+
+    /// Enables collecting statistics, which are then returned from `stats`.
+    pub fn with_stats(mut self) -> Self {
+        self.forest.stats = Some(SolverStats::default());
+        self
+    }
+
+    /// Shares `cache` with the solver: the tables it completes are saved
+    /// to the cache, and tables for new goals are taken from it when they
+    /// are there. The tables in the cache must have been solved with a
+    /// database that agrees with the one given to this solver, which
+    /// `AnswerCache::retain_valid` ensures when the program changes.
+    pub fn with_answer_cache(mut self, cache: Arc<Mutex<AnswerCache<I>>>) -> Self {
+        cache.lock().unwrap().set_max_size(self.max_size);
+        self.forest.cache = Some(cache);
+        self
+    }
+
+    /// Starts a solve of a goal in `program`, running `op` with the forest
+    /// and the context. With an answer cache, the queries made of the
+    /// program are recorded, and the tables completed by the solve are
+    /// saved to the cache.
+    fn solve_with<R>(
+        &mut self,
+        program: &dyn RustIrDatabase<I>,
+        op: impl FnOnce(&mut Forest<I>, &SlgContextOps<'_, I>) -> R,
+    ) -> R {
+        self.forest.start_solve(self.budget);
+        if self.forest.cache.is_none() {
+            let ops = SlgContextOps::new(program, self.max_size, self.expected_answers);
+            return op(&mut self.forest, &ops);
         }
+        let recorder = DependencyRecorder::new(program);
+        let ops = SlgContextOps::recording(&recorder, self.max_size, self.expected_answers);
+        let result = op(&mut self.forest, &ops);
+        self.forest
+            .save_to_cache(recorder.into_shared_dependencies());
+        result
     }
 }
 
@@ -36,8 +96,9 @@ impl<I: Interner> Solver<I> for SLGSolver<I> {
         program: &dyn RustIrDatabase<I>,
         goal: &UCanonical<InEnvironment<Goal<I>>>,
     ) -> Option<Solution<I>> {
-        let ops = SlgContextOps::new(program, self.max_size, self.expected_answers);
-        ops.make_solution(goal, self.forest.iter_answers(&ops, goal), || true)
+        self.solve_with(program, |forest, ops| {
+            ops.make_solution(goal, forest.iter_answers(ops, goal), || true)
+        })
     }
 
     fn solve_limited(
@@ -46,8 +107,21 @@ impl<I: Interner> Solver<I> for SLGSolver<I> {
         goal: &UCanonical<InEnvironment<Goal<I>>>,
         should_continue: &dyn std::ops::Fn() -> bool,
     ) -> Option<Solution<I>> {
-        let ops = SlgContextOps::new(program, self.max_size, self.expected_answers);
-        ops.make_solution(goal, self.forest.iter_answers(&ops, goal), should_continue)
+        self.solve_with(program, |forest, ops| {
+            ops.make_solution(goal, forest.iter_answers(ops, goal), should_continue)
+        })
+    }
+
+    fn solve_with_proof_tree(
+        &mut self,
+        program: &dyn RustIrDatabase<I>,
+        goal: &UCanonical<InEnvironment<Goal<I>>>,
+    ) -> Option<(Solution<I>, Option<ProofTree<I>>)> {
+        self.solve_with(program, |forest, ops| {
+            let solution = ops.make_solution(goal, forest.iter_answers(ops, goal), || true)?;
+            let tree = forest.proof_tree(program.interner(), goal);
+            Some((solution, tree))
+        })
     }
 
     fn solve_multiple(
@@ -56,34 +130,45 @@ impl<I: Interner> Solver<I> for SLGSolver<I> {
         goal: &UCanonical<InEnvironment<Goal<I>>>,
         f: &mut dyn FnMut(SubstitutionResult<Canonical<ConstrainedSubst<I>>>, bool) -> bool,
     ) -> bool {
-        let ops = SlgContextOps::new(program, self.max_size, self.expected_answers);
-        let mut answers = self.forest.iter_answers(&ops, goal);
-        loop {
-            let subst = match answers.next_answer(|| true) {
-                AnswerResult::Answer(answer) => {
-                    if !answer.ambiguous {
-                        SubstitutionResult::Definite(answer.subst)
-                    } else if answer
-                        .subst
-                        .value
-                        .subst
-                        .is_identity_subst(ops.program().interner())
-                    {
-                        SubstitutionResult::Floundered
-                    } else {
-                        SubstitutionResult::Ambiguous(answer.subst)
+        self.solve_with(program, |forest, ops| {
+            let mut answers = forest.iter_answers(ops, goal);
+            loop {
+                let subst = match answers.next_answer(|| true) {
+                    AnswerResult::Answer(answer) => {
+                        if !answer.ambiguous {
+                            SubstitutionResult::Definite(answer.subst)
+                        } else if answer
+                            .subst
+                            .value
+                            .subst
+                            .is_identity_subst(ops.program().interner())
+                        {
+                            SubstitutionResult::Floundered
+                        } else {
+                            SubstitutionResult::Ambiguous(answer.subst)
+                        }
                     }
-                }
-                AnswerResult::Floundered => SubstitutionResult::Floundered,
-                AnswerResult::NoMoreSolutions => {
-                    return true;
-                }
-                AnswerResult::QuantumExceeded => continue,
-            };
+                    AnswerResult::Floundered => SubstitutionResult::Floundered,
+                    AnswerResult::NoMoreSolutions => {
+                        return true;
+                    }
+                    // We always continue, so this only happens when we run out
+                    // of budget.
+                    AnswerResult::QuantumExceeded => return false,
+                };
 
-            if !f(subst, !answers.peek_answer(|| true).is_no_more_solutions()) {
-                return false;
+                if !f(subst, !answers.peek_answer(|| true).is_no_more_solutions()) {
+                    return false;
+                }
             }
-        }
+        })
+    }
+
+    fn budget_exhausted(&self) -> bool {
+        self.forest.budget_exhausted
+    }
+
+    fn stats(&self) -> Option<SolverStats> {
+        self.forest.stats
     }
 }
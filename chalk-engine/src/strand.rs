@@ -5,7 +5,7 @@ use std::fmt::Debug;
 use chalk_derive::HasInterner;
 use chalk_ir::fold::{Fold, Folder};
 use chalk_ir::interner::Interner;
-use chalk_ir::{Canonical, DebruijnIndex, Fallible, UniverseMap};
+use chalk_ir::{Canonical, DebruijnIndex, Fallible, ProgramClause, UniverseMap};
 
 #[derive(Clone, Debug, HasInterner)]
 pub(crate) struct Strand<I: Interner> {
@@ -15,6 +15,30 @@ pub(crate) struct Strand<I: Interner> {
     pub(crate) selected_subgoal: Option<SelectedSubgoal>,
 
     pub(crate) last_pursued_time: TimeStamp,
+
+    /// How this strand was derived; only recorded if the forest is
+    /// recording proof trees.
+    pub(crate) proof: Option<ProofRecord<I>>,
+}
+
+/// The derivation of a strand (and, eventually, of the answer it produces):
+/// the program clause it was resolved against and the answers to its
+/// positive subgoals that have been merged into it so far.
+#[derive(Clone, Debug)]
+pub(crate) struct ProofRecord<I: Interner> {
+    /// `None` if the strand came from simplifying a non-domain goal.
+    pub(crate) clause: Option<ProgramClause<I>>,
+
+    pub(crate) subgoal_answers: Vec<(TableIndex, AnswerIndex)>,
+}
+
+impl<I: Interner> ProofRecord<I> {
+    pub(crate) fn new(clause: Option<ProgramClause<I>>) -> Self {
+        ProofRecord {
+            clause,
+            subgoal_answers: vec![],
+        }
+    }
 }
 
 pub(crate) type CanonicalStrand<I> = Canonical<Strand<I>>;
@@ -49,6 +73,7 @@ impl<I: Interner> Fold<I> for Strand<I> {
             ex_clause: self.ex_clause.fold_with(folder, outer_binder)?,
             last_pursued_time: self.last_pursued_time,
             selected_subgoal: self.selected_subgoal.clone(),
+            proof: self.proof,
         })
     }
 }
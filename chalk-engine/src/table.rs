@@ -1,6 +1,7 @@
+use crate::cache::Dependency;
 use crate::index_struct;
-use crate::strand::CanonicalStrand;
-use crate::{Answer, AnswerMode};
+use crate::strand::{CanonicalStrand, ProofRecord};
+use crate::{Answer, AnswerMode, TableIndex};
 use rustc_hash::FxHashMap;
 use std::collections::hash_map::Entry;
 use std::collections::VecDeque;
@@ -28,6 +29,10 @@ pub(crate) struct Table<I: Interner> {
     /// for an answer N, we will first check this vector.
     answers: Vec<Answer<I>>,
 
+    /// The derivation of each answer in `answers`, if proof trees are
+    /// being recorded.
+    answer_proofs: Vec<Option<ProofRecord<I>>>,
+
     /// An alternative storage for the answers we have so far, used to
     /// detect duplicates. Not every answer in `answers` will be
     /// represented here -- we discard answers from `answers_hash`
@@ -45,6 +50,16 @@ pub(crate) struct Table<I: Interner> {
     strands: VecDeque<CanonicalStrand<I>>,
 
     pub(crate) answer_mode: AnswerMode,
+
+    /// The queries made of the program while building the table, if they
+    /// are being recorded for an answer cache.
+    pub(crate) dependencies: Vec<Dependency<I>>,
+
+    /// The tables that strands of this table took answers from.
+    pub(crate) subgoal_tables: Vec<TableIndex>,
+
+    /// True if the table was taken from an answer cache, complete.
+    pub(crate) from_cache: bool,
 }
 
 index_struct! {
@@ -62,10 +77,14 @@ impl<I: Interner> Table<I> {
             table_goal,
             coinductive_goal,
             answers: Vec::new(),
+            answer_proofs: Vec::new(),
             floundered: false,
             answers_hash: FxHashMap::default(),
             strands: VecDeque::new(),
             answer_mode: AnswerMode::Complete,
+            dependencies: Vec::new(),
+            subgoal_tables: Vec::new(),
+            from_cache: false,
         }
     }
 
@@ -86,6 +105,20 @@ impl<I: Interner> Table<I> {
         mem::replace(&mut self.strands, VecDeque::new())
     }
 
+    /// Returns true if the table has no strands left. Outside of a solve,
+    /// when no strands are set aside on the stack, this means that all of
+    /// the table's answers have been found.
+    pub(crate) fn is_complete(&self) -> bool {
+        self.strands.is_empty()
+    }
+
+    /// Records that a strand of this table took answers from `table`.
+    pub(crate) fn add_subgoal_table(&mut self, table: TableIndex) {
+        if !self.subgoal_tables.contains(&table) {
+            self.subgoal_tables.push(table);
+        }
+    }
+
     /// Remove the next strand from the queue that meets the given criteria
     pub(crate) fn dequeue_next_strand_that(
         &mut self,
@@ -106,6 +139,7 @@ impl<I: Interner> Table<I> {
         self.floundered = true;
         self.strands = Default::default();
         self.answers = Default::default();
+        self.answer_proofs = Default::default();
     }
 
     /// Returns true if the table is floundered.
@@ -124,7 +158,11 @@ impl<I: Interner> Table<I> {
     /// `true` is returned here, that a *new* answer was added (instead of an)
     /// existing answer replaced.
     #[instrument(level = "debug", skip(self))]
-    pub(super) fn push_answer(&mut self, answer: Answer<I>) -> Option<AnswerIndex> {
+    pub(super) fn push_answer(
+        &mut self,
+        answer: Answer<I>,
+        proof: Option<ProofRecord<I>>,
+    ) -> Option<AnswerIndex> {
         assert!(!self.floundered);
         debug!(
             "pre-existing entry: {:?}",
@@ -156,13 +194,23 @@ impl<I: Interner> Table<I> {
 
         let index = self.answers.len();
         self.answers.push(answer);
+        self.answer_proofs.push(proof);
         Some(AnswerIndex::from(index))
     }
 
+    pub(crate) fn answers(&self) -> &[Answer<I>] {
+        &self.answers
+    }
+
     pub(super) fn answer(&self, index: AnswerIndex) -> Option<&Answer<I>> {
         self.answers.get(index.value)
     }
 
+    /// The recorded derivation of the answer with the given index, if any.
+    pub(super) fn answer_proof(&self, index: AnswerIndex) -> Option<&ProofRecord<I>> {
+        self.answer_proofs.get(index.value)?.as_ref()
+    }
+
     pub(super) fn next_answer_index(&self) -> AnswerIndex {
         AnswerIndex::from(self.answers.len())
     }
@@ -40,6 +40,10 @@ impl<I: Interner> Tables<I> {
         index
     }
 
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Table<I>> {
+        self.tables.iter()
+    }
+
     pub(super) fn index_of(
         &self,
         literal: &UCanonical<InEnvironment<Goal<I>>>,
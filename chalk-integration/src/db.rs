@@ -1,9 +1,10 @@
 use crate::{
+    differential::{self, SolverComparison},
     error::ChalkError,
     interner::ChalkIr,
     lowering::lower_goal,
     program::Program,
-    query::{Lowering, LoweringDatabase},
+    query::{ArcEq, Lowering, LoweringDatabase},
     tls, SolverChoice,
 };
 use chalk_ir::{
@@ -12,12 +13,15 @@ use chalk_ir::{
     ProgramClause, ProgramClauses, Substitution, TraitId, Ty, TyKind, UCanonical,
     UnificationDatabase, Variances,
 };
+use chalk_solve::coherence::SpecializationPriorities;
 use chalk_solve::rust_ir::{
     AdtDatum, AdtRepr, AssociatedTyDatum, AssociatedTyValue, AssociatedTyValueId, ClosureKind,
     FnDefDatum, FnDefInputsAndOutputDatum, GeneratorDatum, GeneratorWitnessDatum, ImplDatum,
-    OpaqueTyDatum, TraitDatum, WellKnownTrait,
+    InherentImplDatum, OpaqueTyDatum, TraitDatum, WellKnownTrait,
+};
+use chalk_solve::{
+    FailureExplanation, MethodCandidate, RustIrDatabase, Solution, SolverStats, SubstitutionResult,
 };
-use chalk_solve::{RustIrDatabase, Solution, SubstitutionResult};
 use salsa::Database;
 use std::fmt;
 use std::sync::Arc;
@@ -35,6 +39,7 @@ impl ChalkDatabase {
         let mut db = ChalkDatabase::default();
         db.set_program_text(Arc::new(program_text.to_string()));
         db.set_solver_choice(solver_choice);
+        db.set_answer_cache(ArcEq::new(Default::default()));
         db
     }
 
@@ -57,6 +62,23 @@ impl ChalkDatabase {
         solution
     }
 
+    /// Returns `true` if the last goal solved with this database's solver
+    /// ran out of the budget of the solver choice, so that its solution
+    /// only says that the solver gave up.
+    pub fn budget_exhausted(&self) -> bool {
+        let solver = self.solver();
+        let exhausted = solver.lock().unwrap().budget_exhausted();
+        exhausted
+    }
+
+    /// Returns the statistics for the last goal solved with this database's
+    /// solver, if the solver choice asks for them.
+    pub fn solver_stats(&self) -> Option<SolverStats> {
+        let solver = self.solver();
+        let stats = solver.lock().unwrap().stats();
+        stats
+    }
+
     /// Solves a given goal, producing the solution. This will do only
     /// as much work towards `goal` as it has to (and that works is
     /// cached for future attempts). Calls provided function `f` to
@@ -70,6 +92,46 @@ impl ChalkDatabase {
         let solution = solver.lock().unwrap().solve_multiple(self, goal, f);
         solution
     }
+
+    /// Explains why a given goal has no solution, listing the impls that
+    /// were considered and why each did not apply. Returns `None` if the
+    /// goal has a solution or is not a trait goal.
+    pub fn explain_failure(
+        &self,
+        goal: &UCanonical<InEnvironment<Goal<ChalkIr>>>,
+    ) -> Option<FailureExplanation<ChalkIr>> {
+        let solver = self.solver();
+        let explanation = chalk_solve::explain_failure(self, &mut **solver.lock().unwrap(), goal);
+        explanation
+    }
+
+    /// Looks up the methods named `name` that can be called on a receiver
+    /// of the given type, going through the autoderef and autoref probes
+    /// like rustc does.
+    pub fn lookup_method(
+        &self,
+        receiver: &UCanonical<InEnvironment<Ty<ChalkIr>>>,
+        name: &str,
+    ) -> Vec<MethodCandidate<ChalkIr>> {
+        let solver = self.solver();
+        let candidates =
+            chalk_solve::lookup_method(self, &mut **solver.lock().unwrap(), receiver, name);
+        candidates
+    }
+
+    /// Solves a given goal with both the SLG and the recursive solver, so
+    /// that their solutions can be compared. The current solver choice is
+    /// used for the solver of its kind, and the default choice for the other.
+    pub fn compare_solvers(
+        &self,
+        goal: &UCanonical<InEnvironment<Goal<ChalkIr>>>,
+    ) -> SolverComparison {
+        let (slg, recursive) = match self.solver_choice() {
+            slg @ SolverChoice::SLG { .. } => (slg, SolverChoice::recursive_default()),
+            recursive @ SolverChoice::Recursive { .. } => (SolverChoice::slg_default(), recursive),
+        };
+        differential::compare_solvers(self, goal, slg, recursive)
+    }
 }
 
 impl UnificationDatabase<ChalkIr> for ChalkDatabase {
@@ -99,6 +161,10 @@ impl RustIrDatabase<ChalkIr> for ChalkDatabase {
         self.program_ir().unwrap().impl_datum(id)
     }
 
+    fn inherent_impl_datum(&self, id: ImplId<ChalkIr>) -> Arc<InherentImplDatum<ChalkIr>> {
+        self.program_ir().unwrap().inherent_impl_datum(id)
+    }
+
     fn associated_ty_value(
         &self,
         id: AssociatedTyValueId<ChalkIr>,
@@ -148,12 +214,26 @@ impl RustIrDatabase<ChalkIr> for ChalkDatabase {
             .impls_for_trait(trait_id, generic_args, binders)
     }
 
+    fn inherent_impls(&self) -> Vec<ImplId<ChalkIr>> {
+        self.program_ir().unwrap().inherent_impls()
+    }
+
     fn local_impls_to_coherence_check(&self, trait_id: TraitId<ChalkIr>) -> Vec<ImplId<ChalkIr>> {
         self.program_ir()
             .unwrap()
             .local_impls_to_coherence_check(trait_id)
     }
 
+    fn specialization_priorities(
+        &self,
+        trait_id: TraitId<ChalkIr>,
+    ) -> Arc<SpecializationPriorities<ChalkIr>> {
+        self.coherence()
+            .ok()
+            .and_then(|priorities| priorities.get(&trait_id).cloned())
+            .unwrap_or_else(|| Arc::new(SpecializationPriorities::new()))
+    }
+
     fn impl_provided_for(&self, auto_trait_id: TraitId<ChalkIr>, ty: &TyKind<ChalkIr>) -> bool {
         self.program_ir()
             .unwrap()
@@ -243,6 +323,10 @@ impl RustIrDatabase<ChalkIr> for ChalkDatabase {
         self.program_ir().unwrap().fn_def_name(fn_def_id)
     }
 
+    fn method_name(&self, impl_id: ImplId<ChalkIr>, index: usize) -> String {
+        self.program_ir().unwrap().method_name(impl_id, index)
+    }
+
     fn discriminant_type(&self, ty: Ty<ChalkIr>) -> Ty<ChalkIr> {
         self.program_ir().unwrap().discriminant_type(ty)
     }
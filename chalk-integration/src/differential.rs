@@ -0,0 +1,201 @@
+//! Differential testing of the solvers: solving a goal with both the SLG and
+//! the recursive solver, and reporting how their solutions differ.
+
+use crate::interner::ChalkIr;
+use crate::SolverChoice;
+use chalk_ir::{Constraints, Goal, InEnvironment, Substitution, UCanonical};
+use chalk_solve::{Guidance, RustIrDatabase, Solution};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::fmt;
+
+/// The solutions that the SLG and the recursive solver found for a goal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolverComparison {
+    pub slg: Option<Solution<ChalkIr>>,
+    pub recursive: Option<Solution<ChalkIr>>,
+}
+
+/// A part of a solution on which the two solvers disagree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Difference {
+    /// The part of the solution: `outcome`, the variable of a substitution
+    /// entry (like `?0`), or `constraint`.
+    pub part: String,
+
+    /// What the SLG solver found for this part, if anything.
+    pub slg: Option<String>,
+
+    /// What the recursive solver found for this part, if anything.
+    pub recursive: Option<String>,
+}
+
+/// Solves `goal` with the solvers described by `slg` and `recursive`, which
+/// should be an SLG and a recursive solver choice respectively.
+pub fn compare_solvers(
+    db: &dyn RustIrDatabase<ChalkIr>,
+    goal: &UCanonical<InEnvironment<Goal<ChalkIr>>>,
+    slg: SolverChoice,
+    recursive: SolverChoice,
+) -> SolverComparison {
+    SolverComparison {
+        slg: slg.into_solver().solve(db, goal).map(normalize),
+        recursive: recursive.into_solver().solve(db, goal).map(normalize),
+    }
+}
+
+/// Sorts the constraints of `solution`, since the solvers may produce them
+/// in different orders.
+pub fn normalize(mut solution: Solution<ChalkIr>) -> Solution<ChalkIr> {
+    if let Solution::Unique(constrained) = &mut solution {
+        let interner = &ChalkIr;
+        let mut sorted = constrained.value.constraints.as_slice(interner).to_vec();
+        sorted.sort_by_key(|c| format!("{:?}", c));
+        constrained.value.constraints = Constraints::from_iter(interner, sorted);
+    }
+    solution
+}
+
+impl SolverComparison {
+    /// Returns `true` if the solvers found the same solution, up to the
+    /// order of constraints. The solutions are compared as text, since the
+    /// solvers may name canonical variables differently.
+    pub fn agree(&self) -> bool {
+        display(&self.slg) == display(&self.recursive)
+    }
+
+    /// Returns the parts of the solutions on which the solvers disagree.
+    /// Like the solutions themselves, the parts are written with the names
+    /// from the program in `tls`, if one is set.
+    pub fn differences(&self) -> Vec<Difference> {
+        let slg = Parts::new(&self.slg);
+        let recursive = Parts::new(&self.recursive);
+        let mut differences = vec![];
+
+        if slg.outcome != recursive.outcome {
+            differences.push(Difference {
+                part: "outcome".to_string(),
+                slg: Some(slg.outcome.to_string()),
+                recursive: Some(recursive.outcome.to_string()),
+            });
+        }
+
+        let entries = slg.substitution.len().max(recursive.substitution.len());
+        for index in 0..entries {
+            let slg_value = slg.substitution.get(index).cloned();
+            let recursive_value = recursive.substitution.get(index).cloned();
+            if slg_value != recursive_value {
+                differences.push(Difference {
+                    part: format!("?{}", index),
+                    slg: slg_value,
+                    recursive: recursive_value,
+                });
+            }
+        }
+
+        for constraint in &slg.constraints {
+            if !recursive.constraints.contains(constraint) {
+                differences.push(Difference {
+                    part: "constraint".to_string(),
+                    slg: Some(constraint.clone()),
+                    recursive: None,
+                });
+            }
+        }
+        for constraint in &recursive.constraints {
+            if !slg.constraints.contains(constraint) {
+                differences.push(Difference {
+                    part: "constraint".to_string(),
+                    slg: None,
+                    recursive: Some(constraint.clone()),
+                });
+            }
+        }
+
+        differences
+    }
+}
+
+/// Shows both solutions, followed by the differences if there are any.
+impl fmt::Display for SolverComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.agree() {
+            return write!(f, "solvers agree: {}", display(&self.slg));
+        }
+        writeln!(f, "solvers disagree:")?;
+        writeln!(f, "  slg:       {}", display(&self.slg))?;
+        write!(f, "  recursive: {}", display(&self.recursive))?;
+        for difference in self.differences() {
+            let show = |part: &Option<String>| match part {
+                Some(part) => format!("`{}`", part),
+                None => "nothing".to_string(),
+            };
+            write!(
+                f,
+                "\n  {}: slg has {}, recursive has {}",
+                difference.part,
+                show(&difference.slg),
+                show(&difference.recursive)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn display(solution: &Option<Solution<ChalkIr>>) -> String {
+    match solution {
+        Some(solution) => solution.display(&ChalkIr).to_string(),
+        None => "No possible solution".to_string(),
+    }
+}
+
+/// A solution split into the parts that are compared separately.
+struct Parts {
+    outcome: &'static str,
+    substitution: Vec<String>,
+    constraints: Vec<String>,
+}
+
+impl Parts {
+    fn new(solution: &Option<Solution<ChalkIr>>) -> Self {
+        let interner = &ChalkIr;
+        let substitution = |subst: &Substitution<ChalkIr>| {
+            subst
+                .iter(interner)
+                .map(|arg| format!("{:?}", arg))
+                .collect()
+        };
+        let (outcome, substitution, constraints) = match solution {
+            None => ("no solution", vec![], vec![]),
+            Some(Solution::Unique(constrained)) => (
+                "unique",
+                substitution(&constrained.value.subst),
+                constrained
+                    .value
+                    .constraints
+                    .iter(interner)
+                    .map(|c| format!("{:?}", c))
+                    .collect(),
+            ),
+            Some(Solution::Ambig(Guidance::Definite(subst))) => (
+                "ambiguous with definite guidance",
+                substitution(&subst.value),
+                vec![],
+            ),
+            Some(Solution::Ambig(Guidance::Suggested(subst))) => (
+                "ambiguous with suggested guidance",
+                substitution(&subst.value),
+                vec![],
+            ),
+            Some(Solution::Ambig(Guidance::Unknown)) => {
+                ("ambiguous without guidance", vec![], vec![])
+            }
+        };
+        Parts {
+            outcome,
+            substitution,
+            constraints,
+        }
+    }
+}
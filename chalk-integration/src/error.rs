@@ -1,6 +1,11 @@
 use crate::interner::ChalkIr;
-use chalk_parse::ast::{Identifier, Kind};
+use crate::program::Program;
+use crate::RawId;
+use chalk_ir::TraitId;
+use chalk_parse::ast::{Identifier, Kind, Span};
+use chalk_parse::SyntaxError;
 use chalk_solve::coherence::CoherenceError;
+use chalk_solve::rust_ir::ImplType;
 use chalk_solve::wf::WfError;
 use string_cache::DefaultAtom as Atom;
 
@@ -8,40 +13,217 @@ use string_cache::DefaultAtom as Atom;
 /// processing.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct ChalkError {
-    /// For now, we just convert the error into a string, which makes
-    /// it trivially hashable etc.
+    kind: Box<ChalkErrorKind>,
+
+    /// The error message. This is rendered when the error is created, since
+    /// the ids in solver errors can only be printed by name while the
+    /// program they refer to is set in `tls`.
     error_text: String,
+
+    /// The location of the error in the program text, if known.
+    primary_label: Option<Label>,
+
+    /// Other locations in the program text related to the error.
+    secondary_labels: Vec<Label>,
 }
 
-impl From<Box<dyn std::error::Error>> for ChalkError {
-    fn from(value: Box<dyn std::error::Error>) -> Self {
+/// The original error wrapped by a [`ChalkError`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ChalkErrorKind {
+    /// The program or goal text could not be parsed.
+    Parse,
+    Lowering(RustIrError),
+    WellFormedness(WfError<ChalkIr>),
+    Coherence(CoherenceError<ChalkIr>),
+}
+
+/// A message attached to a span of the program text.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+impl Label {
+    pub fn new(span: Span, message: impl Into<String>) -> Self {
+        Label {
+            span,
+            message: message.into(),
+        }
+    }
+}
+
+impl ChalkError {
+    fn new(kind: ChalkErrorKind, error_text: String) -> Self {
         ChalkError {
-            error_text: value.to_string(),
+            kind: Box::new(kind),
+            error_text,
+            primary_label: None,
+            secondary_labels: vec![],
+        }
+    }
+
+    pub fn kind(&self) -> &ChalkErrorKind {
+        &self.kind
+    }
+
+    pub fn primary_label(&self) -> Option<&Label> {
+        self.primary_label.as_ref()
+    }
+
+    pub fn secondary_labels(&self) -> &[Label] {
+        &self.secondary_labels
+    }
+
+    /// Adds labels pointing at the items that well-formedness and coherence
+    /// errors refer to. Those errors only carry item ids, so the spans are
+    /// looked up in the item spans that lowering recorded in `program`.
+    pub(crate) fn with_program_labels(mut self, program: &Program) -> Self {
+        let span = |raw_id: RawId| program.item_spans.get(raw_id);
+        let trait_impls = |trait_id: TraitId<ChalkIr>, local_only: bool| {
+            program
+                .impl_data
+                .iter()
+                .filter(move |(_, impl_datum)| {
+                    impl_datum.trait_id() == trait_id
+                        && (!local_only || impl_datum.impl_type == ImplType::Local)
+                })
+                .filter_map(move |(impl_id, _)| span(impl_id.0))
+                .map(|span| Label::new(span, "impl of this trait"))
+        };
+
+        let (primary, secondary): (_, Vec<_>) = match &*self.kind {
+            ChalkErrorKind::Parse | ChalkErrorKind::Lowering(_) => return self,
+            ChalkErrorKind::WellFormedness(WfError::IllFormedTypeDecl(id)) => (
+                span(id.0).map(|span| Label::new(span, "type is not well-formed")),
+                vec![],
+            ),
+            ChalkErrorKind::WellFormedness(WfError::IllFormedOpaqueTypeDecl(id)) => (
+                span(id.0).map(|span| Label::new(span, "opaque type is not well-formed")),
+                vec![],
+            ),
+            ChalkErrorKind::WellFormedness(WfError::IllFormedTraitImpl(id)) => (
+                span(id.0).map(|span| Label::new(span, "trait declared here")),
+                trait_impls(*id, false).collect(),
+            ),
+            ChalkErrorKind::Coherence(CoherenceError::OverlappingImpls(id))
+            | ChalkErrorKind::Coherence(CoherenceError::SpecializedNonDefaultValue(id)) => (
+                span(id.0).map(|span| Label::new(span, "trait declared here")),
+                trait_impls(*id, false).collect(),
+            ),
+            ChalkErrorKind::Coherence(CoherenceError::FailedOrphanCheck(id)) => (
+                span(id.0).map(|span| Label::new(span, "trait declared here")),
+                trait_impls(*id, true).collect(),
+            ),
+        };
+        self.primary_label = primary;
+        self.secondary_labels = secondary;
+        self
+    }
+
+    /// Renders this error along with annotated snippets of `source`, the
+    /// program text it came from. The primary label is underlined with `^`
+    /// and the secondary labels with `-`.
+    pub fn render(&self, source: &str) -> String {
+        let labels: Vec<_> = self
+            .primary_label
+            .iter()
+            .map(|label| ('^', label))
+            .chain(self.secondary_labels.iter().map(|label| ('-', label)))
+            .map(|(marker, label)| (marker, label, SourceLocation::new(source, label.span)))
+            .collect();
+
+        let mut output = format!("error: {}", self.error_text);
+        let gutter = labels
+            .iter()
+            .map(|(_, _, location)| location.line.to_string().len())
+            .max()
+            .unwrap_or(0);
+        if let Some((_, _, location)) = labels.first() {
+            output.push_str(&format!(
+                "\n{:gutter$}--> {}:{}",
+                "",
+                location.line,
+                location.column,
+                gutter = gutter
+            ));
         }
+        for (marker, label, location) in &labels {
+            output.push_str(&format!(
+                "\n{:gutter$} |\n{:>gutter$} | {}\n{:gutter$} | {}{} {}",
+                "",
+                location.line,
+                location.text,
+                "",
+                " ".repeat(location.column - 1),
+                marker.to_string().repeat(location.width),
+                label.message,
+                gutter = gutter
+            ));
+        }
+        output
+    }
+}
+
+/// The line and column (both starting at 1) that a span starts at, along
+/// with the text of that line.
+struct SourceLocation<'s> {
+    line: usize,
+    column: usize,
+    text: &'s str,
+
+    /// The width of the span in characters, truncated to the end of the line.
+    width: usize,
+}
+
+impl<'s> SourceLocation<'s> {
+    fn new(source: &'s str, span: Span) -> Self {
+        let lo = span.lo.min(source.len());
+        let hi = span.hi.max(lo).min(source.len());
+        let line_start = source[..lo].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[lo..].find('\n').map_or(source.len(), |i| lo + i);
+        SourceLocation {
+            line: source[..lo].matches('\n').count() + 1,
+            column: source[line_start..lo].chars().count() + 1,
+            text: &source[line_start..line_end],
+            width: source[lo..hi.min(line_end)].chars().count().max(1),
+        }
+    }
+}
+
+impl From<Box<dyn std::error::Error>> for ChalkError {
+    fn from(value: Box<dyn std::error::Error>) -> Self {
+        ChalkError::new(ChalkErrorKind::Parse, value.to_string())
+    }
+}
+
+impl From<SyntaxError> for ChalkError {
+    fn from(value: SyntaxError) -> Self {
+        let mut error = ChalkError::new(ChalkErrorKind::Parse, format!("parse error: {}", value));
+        error.primary_label = value.span.map(|span| Label::new(span, "syntax error"));
+        error
     }
 }
 
 impl From<WfError<ChalkIr>> for ChalkError {
     fn from(value: WfError<ChalkIr>) -> Self {
-        ChalkError {
-            error_text: value.to_string(),
-        }
+        let error_text = value.to_string();
+        ChalkError::new(ChalkErrorKind::WellFormedness(value), error_text)
     }
 }
 
 impl From<CoherenceError<ChalkIr>> for ChalkError {
     fn from(value: CoherenceError<ChalkIr>) -> Self {
-        ChalkError {
-            error_text: value.to_string(),
-        }
+        let error_text = value.to_string();
+        ChalkError::new(ChalkErrorKind::Coherence(value), error_text)
     }
 }
 
 impl From<RustIrError> for ChalkError {
     fn from(value: RustIrError) -> Self {
-        ChalkError {
-            error_text: value.to_string(),
-        }
+        let mut error = ChalkError::new(ChalkErrorKind::Lowering(value.clone()), value.to_string());
+        error.primary_label = value.label();
+        error
     }
 }
 
@@ -53,7 +235,7 @@ impl std::fmt::Display for ChalkError {
 
 impl std::error::Error for ChalkError {}
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum RustIrError {
     InvalidParameterName(Identifier),
     InvalidTraitName(Identifier),
@@ -98,6 +280,106 @@ pub enum RustIrError {
     },
     CannotApplyTypeParameter(Identifier),
     InvalidExternAbi(Atom),
+    UnresolvedImport(Identifier),
+    UnknownCrate(Identifier),
+    NestedCrate(Identifier),
+    SecondCurrentCrate(Identifier),
+    MismatchedConstOperandTypes {
+        lhs: chalk_ir::Ty<ChalkIr>,
+        rhs: chalk_ir::Ty<ChalkIr>,
+    },
+    MismatchedConstParameterType {
+        identifier: Identifier,
+        expected: chalk_ir::Ty<ChalkIr>,
+        actual: chalk_ir::Ty<ChalkIr>,
+    },
+}
+
+impl RustIrError {
+    /// A label for the identifier this error is about, if any.
+    fn label(&self) -> Option<Label> {
+        let (identifier, message) = match self {
+            RustIrError::InvalidParameterName(name) | RustIrError::InvalidTraitName(name) => {
+                (name, "not found in this scope".to_string())
+            }
+            RustIrError::NotTrait(name) => (name, "not a trait".to_string()),
+            RustIrError::NotStruct(name) => (name, "not a struct".to_string()),
+            RustIrError::UnresolvedImport(name) => (name, "no item or module here".to_string()),
+            RustIrError::UnknownCrate(name) => (name, "no crate by this name".to_string()),
+            RustIrError::NestedCrate(name) => (name, "not at the top level".to_string()),
+            RustIrError::SecondCurrentCrate(name) => {
+                (name, "another crate is already current".to_string())
+            }
+            RustIrError::AutoTraitAssociatedTypes(name)
+            | RustIrError::AutoTraitParameters(name)
+            | RustIrError::AutoTraitWhereClauses(name) => {
+                (name, "auto trait declared here".to_string())
+            }
+            RustIrError::InvalidFundamentalTypesParameters(name) => {
+                (name, "fundamental type declared here".to_string())
+            }
+            RustIrError::NegativeImplAssociatedValues(name) => {
+                (name, "negative impl of this trait".to_string())
+            }
+            RustIrError::MissingAssociatedType(name) => {
+                (name, "associated type not found".to_string())
+            }
+            RustIrError::IncorrectNumberOfVarianceParameters {
+                identifier,
+                expected,
+                actual,
+            }
+            | RustIrError::IncorrectNumberOfTypeParameters {
+                identifier,
+                expected,
+                actual,
+            }
+            | RustIrError::IncorrectNumberOfAssociatedTypeParameters {
+                identifier,
+                expected,
+                actual,
+            } => (
+                identifier,
+                format!("expected {} parameters, found {}", expected, actual),
+            ),
+            RustIrError::IncorrectParameterKind {
+                identifier,
+                expected,
+                actual,
+            }
+            | RustIrError::IncorrectTraitParameterKind {
+                identifier,
+                expected,
+                actual,
+            }
+            | RustIrError::IncorrectAssociatedTypeParameterKind {
+                identifier,
+                expected,
+                actual,
+            } => (
+                identifier,
+                format!("expected {}, found {}", expected, actual),
+            ),
+            RustIrError::CannotApplyTypeParameter(name) => {
+                (name, "type parameter cannot be applied".to_string())
+            }
+            RustIrError::MismatchedConstParameterType {
+                identifier,
+                expected,
+                actual,
+            } => (
+                identifier,
+                format!(
+                    "expected a const of type `{:?}`, found `{:?}`",
+                    expected, actual
+                ),
+            ),
+            RustIrError::DuplicateOrShadowedParameters
+            | RustIrError::InvalidExternAbi(_)
+            | RustIrError::MismatchedConstOperandTypes { .. } => return None,
+        };
+        Some(Label::new(identifier.span, message))
+    }
 }
 
 impl std::fmt::Display for RustIrError {
@@ -200,6 +482,32 @@ impl std::fmt::Display for RustIrError {
                 write!(f, "cannot apply type parameter `{}`", name)
             }
             RustIrError::InvalidExternAbi(abi) => write!(f, "invalid extern ABI `{}`", abi),
+            RustIrError::UnresolvedImport(path) => write!(f, "unresolved import `{}`", path),
+            RustIrError::UnknownCrate(name) => write!(f, "unknown crate `{}`", name),
+            RustIrError::NestedCrate(name) => {
+                write!(f, "crate `{}` is declared inside another module", name)
+            }
+            RustIrError::SecondCurrentCrate(name) => {
+                write!(
+                    f,
+                    "crate `{}` is marked current, but so is another one",
+                    name
+                )
+            }
+            RustIrError::MismatchedConstOperandTypes { lhs, rhs } => write!(
+                f,
+                "mismatched operand types in const expression: `{:?}` and `{:?}`",
+                lhs, rhs
+            ),
+            RustIrError::MismatchedConstParameterType {
+                identifier,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "mismatched type for a const parameter of `{}`: expected `{:?}`, found `{:?}`",
+                identifier, expected, actual
+            ),
         }
     }
 }
@@ -0,0 +1,204 @@
+//! A fuzz driver, which runs random programs from the [`generator`] through
+//! lowering, the `display` code, the coherence and WF checks and both
+//! solvers, looking for panics and inconsistencies.
+//!
+//! [`generator`]: crate::generator
+
+use crate::db::ChalkDatabase;
+use crate::generator::Generator;
+use crate::interner::ChalkIr;
+use crate::lowering::{lower_goal, Lower};
+use crate::program::Program;
+use crate::query::LoweringDatabase;
+use crate::{tls, SolverChoice};
+use chalk_solve::display::{write_items, WriterState};
+use chalk_solve::ext::*;
+use chalk_solve::logging_db::RecordedItemId;
+use chalk_solve::RustIrDatabase;
+use std::any::Any;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+
+/// The outcome of fuzzing one random program.
+#[derive(Debug)]
+pub struct FuzzCase {
+    pub seed: u64,
+
+    /// The program, as written by the `display` code.
+    pub text: String,
+
+    pub failures: Vec<FuzzFailure>,
+}
+
+/// A problem found while fuzzing.
+#[derive(Debug)]
+pub enum FuzzFailure {
+    /// The generated program was rejected. This is a bug in the generator,
+    /// which should only produce well-formed programs.
+    Rejected(String),
+
+    /// Something panicked during `stage`.
+    Panic {
+        stage: &'static str,
+        message: String,
+    },
+
+    /// Writing out the program and parsing it back in produced a different
+    /// program.
+    RoundTrip(String),
+
+    /// The SLG and recursive solvers found different solutions for `goal`.
+    /// `comparison` is the rendered [`SolverComparison`].
+    ///
+    /// [`SolverComparison`]: crate::differential::SolverComparison
+    Disagreement { goal: String, comparison: String },
+}
+
+/// Generates a program from `seed` and checks it, along with `goals` random
+/// goals against it.
+pub fn fuzz(seed: u64, goals: usize) -> FuzzCase {
+    let mut case = FuzzCase {
+        seed,
+        text: String::new(),
+        failures: vec![],
+    };
+    let mut generator = Generator::new(seed);
+    let ast = generator.program();
+
+    let (program, errors) = ast.lower();
+    if let Some(error) = errors.first() {
+        case.failures.push(FuzzFailure::Rejected(error.to_string()));
+        return case;
+    }
+    let program = Arc::new(program);
+
+    case.text = match catch_panic("writing the program", || write_program(&program)) {
+        Ok(text) => text,
+        Err(failure) => {
+            case.failures.push(failure);
+            return case;
+        }
+    };
+
+    let db = ChalkDatabase::with(&case.text, SolverChoice::default());
+    match db.program_ir() {
+        Ok(reparsed) if reparsed == program => {}
+        Ok(_) => case.failures.push(FuzzFailure::RoundTrip(
+            "the written program lowers to a different program".to_string(),
+        )),
+        Err(error) => case
+            .failures
+            .push(FuzzFailure::RoundTrip(error.to_string())),
+    }
+
+    let checked = match catch_panic("checking the program", || db.checked_program()) {
+        Ok(Ok(checked)) => checked,
+        Ok(Err(error)) => {
+            case.failures.push(FuzzFailure::Rejected(error.to_string()));
+            return case;
+        }
+        Err(failure) => {
+            case.failures.push(failure);
+            return case;
+        }
+    };
+
+    for _ in 0..goals {
+        let goal = generator.goal(&ast);
+        let result = catch_panic("solving", || {
+            tls::set_current_program(&checked, || {
+                let goal = lower_goal(&goal, &checked).map_err(|error| error.to_string())?;
+                let text = format!("{:?}", goal);
+                let comparison = db.compare_solvers(&goal.into_peeled_goal(db.interner()));
+                Ok((text, comparison.agree(), comparison.to_string()))
+            })
+        });
+        match result {
+            Ok(Ok((_, true, _))) => {}
+            Ok(Ok((goal, false, comparison))) => {
+                case.failures
+                    .push(FuzzFailure::Disagreement { goal, comparison });
+            }
+            Ok(Err(error)) => case.failures.push(FuzzFailure::Rejected(error)),
+            Err(failure) => case.failures.push(failure),
+        }
+    }
+    case
+}
+
+/// Writes out all the items of `program`, in the order they were declared.
+fn write_program(program: &Arc<Program>) -> String {
+    let mut ids: Vec<_> = program
+        .adt_data
+        .keys()
+        .map(|&id| (id.0, RecordedItemId::from(id)))
+        .chain(
+            program
+                .trait_data
+                .keys()
+                .map(|&id| (id.0, RecordedItemId::from(id))),
+        )
+        .chain(
+            program
+                .impl_data
+                .keys()
+                .map(|&id| (id.0, RecordedItemId::from(id))),
+        )
+        .collect();
+    ids.sort_by_key(|(raw_id, _)| *raw_id);
+
+    let mut text = String::new();
+    tls::set_current_program(program, || {
+        write_items::<_, ChalkIr, Program, _, _>(
+            &mut text,
+            &WriterState::new(&**program),
+            ids.into_iter().map(|(_, id)| id),
+        )
+    })
+    .expect("writing to a string never fails");
+    text
+}
+
+fn catch_panic<R>(stage: &'static str, op: impl FnOnce() -> R) -> Result<R, FuzzFailure> {
+    panic::catch_unwind(AssertUnwindSafe(op)).map_err(|payload| FuzzFailure::Panic {
+        stage,
+        message: panic_message(&*payload),
+    })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+impl fmt::Display for FuzzFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FuzzFailure::Rejected(error) => write!(f, "program rejected: {}", error),
+            FuzzFailure::Panic { stage, message } => {
+                write!(f, "panic while {}: {}", stage, message)
+            }
+            FuzzFailure::RoundTrip(error) => write!(f, "display round trip failed: {}", error),
+            FuzzFailure::Disagreement { goal, comparison } => {
+                write!(f, "goal {}\n{}", goal, comparison)
+            }
+        }
+    }
+}
+
+impl fmt::Display for FuzzCase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "seed {}:", self.seed)?;
+        writeln!(f, "{}", self.text)?;
+        for failure in &self.failures {
+            writeln!(f, "{}", failure)?;
+        }
+        Ok(())
+    }
+}
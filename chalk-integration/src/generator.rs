@@ -0,0 +1,436 @@
+//! Random program and goal generation, for fuzzing the solvers.
+//!
+//! The programs are built directly as `chalk_parse` ASTs and are meant to be
+//! well-formed: they lower without errors and pass the coherence and WF
+//! checks. They contain structs (some of them `#[fundamental]`), traits with
+//! associated types, auto traits with negative impls, and generic impls with
+//! where clauses. Each trait has at most one impl per struct, so impls never
+//! overlap.
+//!
+//! Generation is deterministic: the same seed always produces the same
+//! programs and goals.
+
+use chalk_parse::ast::*;
+use string_cache::DefaultAtom as Atom;
+
+/// The most structs, traits and auto traits in a program.
+const MAX_ADTS: usize = 4;
+const MAX_TRAITS: usize = 3;
+const MAX_AUTO_TRAITS: usize = 1;
+
+/// The most type parameters of a struct.
+const MAX_ADT_PARAMETERS: usize = 2;
+
+/// How deeply types are nested.
+const MAX_TY_DEPTH: usize = 2;
+
+/// Generates random programs, and goals against them.
+pub struct Generator {
+    rng: Rng,
+}
+
+/// What the goal generator needs to know about a struct.
+struct AdtInfo {
+    name: Atom,
+    parameters: usize,
+}
+
+/// What the goal generator needs to know about a trait.
+struct TraitInfo {
+    name: Atom,
+    /// The number of parameters, not counting `Self`.
+    parameters: usize,
+    assoc_ty: Option<Atom>,
+    auto: bool,
+}
+
+impl Generator {
+    pub fn new(seed: u64) -> Self {
+        Generator {
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Generates a random program.
+    pub fn program(&mut self) -> Program {
+        let adts: Vec<AdtInfo> = (0..self.rng.range(1, MAX_ADTS))
+            .map(|index| AdtInfo {
+                name: Atom::from(format!("S{}", index)),
+                parameters: self.rng.below(MAX_ADT_PARAMETERS + 1),
+            })
+            .collect();
+        let mut traits: Vec<TraitInfo> = (0..self.rng.range(1, MAX_TRAITS))
+            .map(|index| TraitInfo {
+                name: Atom::from(format!("Tr{}", index)),
+                parameters: self.rng.below(2),
+                assoc_ty: if self.rng.chance(2) {
+                    Some(Atom::from(format!("Assoc{}", index)))
+                } else {
+                    None
+                },
+                auto: false,
+            })
+            .collect();
+        traits.extend(
+            (0..self.rng.below(MAX_AUTO_TRAITS + 1)).map(|index| TraitInfo {
+                name: Atom::from(format!("Auto{}", index)),
+                parameters: 0,
+                assoc_ty: None,
+                auto: true,
+            }),
+        );
+
+        let mut items = vec![];
+        for adt in &adts {
+            items.push(Item::AdtDefn(self.adt_defn(adt)));
+        }
+        for trait_info in &traits {
+            items.push(Item::TraitDefn(trait_defn(trait_info)));
+        }
+        for trait_info in &traits {
+            for adt in &adts {
+                // Auto traits get the occasional negative impl; other traits
+                // are implemented for about half of the structs.
+                let odds = if trait_info.auto { 4 } else { 2 };
+                if self.rng.chance(odds) {
+                    items.push(Item::Impl(self.impl_defn(trait_info, adt, &adts, &traits)));
+                }
+            }
+        }
+        Program {
+            items,
+            item_spans: vec![],
+            comments: vec![],
+        }
+    }
+
+    /// Generates a random goal against `program`, which should be a program
+    /// built by [`Generator::program`].
+    pub fn goal(&mut self, program: &Program) -> Goal {
+        let (adts, traits) = program_info(program);
+        let trait_info = &traits[self.rng.below(traits.len())];
+        let param = ident("T");
+        match self.rng.below(4) {
+            // A goal about concrete types.
+            0 => {
+                let ty = self.ty(&adts, &[], MAX_TY_DEPTH);
+                let trait_ref = self.trait_ref(ty, trait_info, &adts, &[]);
+                implemented(trait_ref)
+            }
+
+            // A goal with an inference variable.
+            1 => {
+                let params = [param.clone()];
+                let ty = self.ty(&adts, &params, MAX_TY_DEPTH);
+                let trait_ref = self.trait_ref(ty, trait_info, &adts, &params);
+                Goal::Exists(
+                    vec![VariableKind::Ty(param)],
+                    Box::new(implemented(trait_ref)),
+                )
+            }
+
+            // A goal about a placeholder type, with an assumption about it.
+            2 => {
+                let params = [param.clone()];
+                let assumption = &traits[self.rng.below(traits.len())];
+                let assumed = self.trait_ref(ty_param(&param), assumption, &adts, &params);
+                let ty = self.ty(&adts, &params, MAX_TY_DEPTH);
+                let trait_ref = self.trait_ref(ty, trait_info, &adts, &params);
+                Goal::ForAll(
+                    vec![VariableKind::Ty(param)],
+                    Box::new(Goal::Implies(
+                        vec![Clause {
+                            variable_kinds: vec![],
+                            consequence: DomainGoal::Holds {
+                                where_clause: WhereClause::Implemented { trait_ref: assumed },
+                            },
+                            conditions: vec![],
+                        }],
+                        Box::new(implemented(trait_ref)),
+                    )),
+                )
+            }
+
+            // A normalization goal, if the trait has an associated type. (The
+            // SLG solver finds `<S as Tr>::Assoc = T` goals ambiguous, since
+            // `T` could also be the placeholder type for the projection, so
+            // those would only turn up the known disagreement.)
+            _ => {
+                let assoc_ty = match &trait_info.assoc_ty {
+                    Some(assoc_ty) => assoc_ty,
+                    None => return self.goal(program),
+                };
+                let ty = self.ty(&adts, &[], MAX_TY_DEPTH);
+                let trait_ref = self.trait_ref(ty, trait_info, &adts, &[]);
+                Goal::Exists(
+                    vec![VariableKind::Ty(param.clone())],
+                    Box::new(Goal::Leaf(LeafGoal::DomainGoal {
+                        goal: DomainGoal::Normalize {
+                            projection: ProjectionTy {
+                                trait_ref,
+                                name: ident(assoc_ty),
+                                args: vec![],
+                            },
+                            ty: ty_param(&param),
+                        },
+                    })),
+                )
+            }
+        }
+    }
+
+    fn adt_defn(&mut self, adt: &AdtInfo) -> AdtDefn {
+        AdtDefn {
+            name: ident(&adt.name),
+            variable_kinds: (0..adt.parameters)
+                .map(|index| VariableKind::Ty(ident(&format!("T{}", index))))
+                .collect(),
+            where_clauses: vec![],
+            variants: vec![Variant {
+                name: ident(&adt.name),
+                fields: vec![],
+            }],
+            flags: AdtFlags {
+                upstream: false,
+                fundamental: adt.parameters > 0 && self.rng.chance(4),
+                phantom_data: false,
+                kind: AdtKind::Struct,
+            },
+            repr: AdtRepr {
+                c: false,
+                packed: false,
+                int: None,
+            },
+            variances: None,
+        }
+    }
+
+    fn impl_defn(
+        &mut self,
+        trait_info: &TraitInfo,
+        adt: &AdtInfo,
+        adts: &[AdtInfo],
+        traits: &[TraitInfo],
+    ) -> Impl {
+        let params: Vec<_> = (0..adt.parameters)
+            .map(|index| ident(&format!("T{}", index)))
+            .collect();
+        let self_ty = adt_ty(adt, params.iter().map(ty_param).collect());
+        if trait_info.auto {
+            return Impl {
+                variable_kinds: params.into_iter().map(VariableKind::Ty).collect(),
+                trait_ref: TraitRef {
+                    trait_name: ident(&trait_info.name),
+                    args: vec![GenericArg::Ty(self_ty)],
+                },
+                polarity: Polarity::Negative,
+                where_clauses: vec![],
+                assoc_ty_values: vec![],
+                impl_type: ImplType::Local,
+                default: false,
+            };
+        }
+
+        let trait_ref = self.trait_ref(self_ty, trait_info, adts, &params);
+        let mut where_clauses = vec![];
+        for param in &params {
+            if self.rng.chance(3) {
+                let bound = &traits[self.rng.below(traits.len())];
+                let trait_ref = self.trait_ref(ty_param(param), bound, adts, &params);
+                where_clauses.push(QuantifiedWhereClause {
+                    variable_kinds: vec![],
+                    where_clause: WhereClause::Implemented { trait_ref },
+                });
+            }
+        }
+        let assoc_ty_values = trait_info
+            .assoc_ty
+            .iter()
+            .map(|assoc_ty| AssocTyValue {
+                name: ident(assoc_ty),
+                variable_kinds: vec![],
+                value: self.ty(adts, &params, MAX_TY_DEPTH),
+                where_clauses: vec![],
+                default: false,
+            })
+            .collect();
+        Impl {
+            variable_kinds: params.into_iter().map(VariableKind::Ty).collect(),
+            trait_ref,
+            polarity: Polarity::Positive,
+            where_clauses,
+            assoc_ty_values,
+            impl_type: ImplType::Local,
+            default: false,
+        }
+    }
+
+    fn trait_ref(
+        &mut self,
+        self_ty: Ty,
+        trait_info: &TraitInfo,
+        adts: &[AdtInfo],
+        params: &[Identifier],
+    ) -> TraitRef {
+        let mut args = vec![GenericArg::Ty(self_ty)];
+        for _ in 0..trait_info.parameters {
+            args.push(GenericArg::Ty(self.ty(adts, params, 1)));
+        }
+        TraitRef {
+            trait_name: ident(&trait_info.name),
+            args,
+        }
+    }
+
+    /// Generates a type built from scalars, the structs in `adts` and the
+    /// type parameters in `params`, nested at most `depth` deep.
+    fn ty(&mut self, adts: &[AdtInfo], params: &[Identifier], depth: usize) -> Ty {
+        match self.rng.below(4) {
+            0 if !params.is_empty() => ty_param(&params[self.rng.below(params.len())]),
+            1 => Ty::Scalar {
+                ty: [
+                    ScalarType::Bool,
+                    ScalarType::Int(IntTy::I32),
+                    ScalarType::Uint(UintTy::U32),
+                ][self.rng.below(3)],
+            },
+            _ => {
+                let candidates: Vec<_> = adts
+                    .iter()
+                    .filter(|adt| depth > 0 || adt.parameters == 0)
+                    .collect();
+                if candidates.is_empty() {
+                    return Ty::Scalar {
+                        ty: ScalarType::Uint(UintTy::U32),
+                    };
+                }
+                let adt = candidates[self.rng.below(candidates.len())];
+                let args = (0..adt.parameters)
+                    .map(|_| self.ty(adts, params, depth - 1))
+                    .collect();
+                adt_ty(adt, args)
+            }
+        }
+    }
+}
+
+fn trait_defn(trait_info: &TraitInfo) -> TraitDefn {
+    TraitDefn {
+        name: ident(&trait_info.name),
+        variable_kinds: (0..trait_info.parameters)
+            .map(|index| VariableKind::Ty(ident(&format!("P{}", index))))
+            .collect(),
+        where_clauses: vec![],
+        assoc_ty_defns: trait_info
+            .assoc_ty
+            .iter()
+            .map(|assoc_ty| AssocTyDefn {
+                name: ident(assoc_ty),
+                variable_kinds: vec![],
+                bounds: vec![],
+                where_clauses: vec![],
+            })
+            .collect(),
+        flags: TraitFlags {
+            auto: trait_info.auto,
+            marker: false,
+            upstream: false,
+            fundamental: false,
+            non_enumerable: false,
+            coinductive: false,
+            object_safe: false,
+        },
+        well_known: None,
+    }
+}
+
+/// Collects the structs and traits of `program`.
+fn program_info(program: &Program) -> (Vec<AdtInfo>, Vec<TraitInfo>) {
+    let mut adts = vec![];
+    let mut traits = vec![];
+    for item in &program.items {
+        match item {
+            Item::AdtDefn(defn) => adts.push(AdtInfo {
+                name: defn.name.str.clone(),
+                parameters: defn.variable_kinds.len(),
+            }),
+            Item::TraitDefn(defn) => traits.push(TraitInfo {
+                name: defn.name.str.clone(),
+                parameters: defn.variable_kinds.len(),
+                assoc_ty: defn
+                    .assoc_ty_defns
+                    .first()
+                    .map(|defn| defn.name.str.clone()),
+                auto: defn.flags.auto,
+            }),
+            _ => {}
+        }
+    }
+    (adts, traits)
+}
+
+fn ident(name: &str) -> Identifier {
+    Identifier {
+        str: Atom::from(name),
+        span: Span::new(0, 0),
+    }
+}
+
+fn ty_param(name: &Identifier) -> Ty {
+    Ty::Id { name: name.clone() }
+}
+
+fn adt_ty(adt: &AdtInfo, args: Vec<Ty>) -> Ty {
+    if args.is_empty() {
+        Ty::Id {
+            name: ident(&adt.name),
+        }
+    } else {
+        Ty::Apply {
+            name: ident(&adt.name),
+            args: args.into_iter().map(GenericArg::Ty).collect(),
+        }
+    }
+}
+
+fn implemented(trait_ref: TraitRef) -> Goal {
+    Goal::Leaf(LeafGoal::DomainGoal {
+        goal: DomainGoal::Holds {
+            where_clause: WhereClause::Implemented { trait_ref },
+        },
+    })
+}
+
+/// A small xorshift pseudo-random number generator, so that generation is
+/// reproducible from a seed without pulling in a dependency.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // Xorshift gets stuck at zero, and is slow to get going from small
+        // seeds, so mix the seed up first.
+        Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a number in `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// Returns a number in `lo..=hi`.
+    fn range(&mut self, lo: usize, hi: usize) -> usize {
+        lo + self.below(hi - lo + 1)
+    }
+
+    /// Returns `true` with a probability of one in `n`.
+    fn chance(&mut self, n: usize) -> bool {
+        self.below(n) == 0
+    }
+}
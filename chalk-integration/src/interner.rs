@@ -1,7 +1,9 @@
 use crate::tls;
+#[cfg(feature = "serde")]
+use chalk_ir::interner::SerdeInterner;
 use chalk_ir::{
     interner::{HasInterner, Interner},
-    TyKind,
+    Scalar, TyKind,
 };
 use chalk_ir::{
     AdtId, AliasTy, AssocTypeId, CanonicalVarKind, CanonicalVarKinds, ConstData, Constraint,
@@ -13,6 +15,8 @@ use chalk_ir::{
     GenericArg, GenericArgData, Goal, GoalData, LifetimeData, ProgramClause, ProgramClauseData,
     QuantifiedWhereClause, Variance,
 };
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 use std::fmt::Debug;
 use std::sync::Arc;
@@ -21,6 +25,7 @@ use string_cache::DefaultAtom;
 pub type Identifier = DefaultAtom;
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RawId {
     pub index: u32,
 }
@@ -32,6 +37,7 @@ impl Debug for RawId {
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ChalkFnAbi {
     Rust,
     C,
@@ -50,6 +56,27 @@ impl Debug for ChalkFnAbi {
     }
 }
 
+/// The value of a concrete constant, whose type is that of the constant.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ChalkConcreteConst {
+    Bool(bool),
+    Char(char),
+    Int(i128),
+    Uint(u128),
+}
+
+impl Debug for ChalkConcreteConst {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChalkConcreteConst::Bool(value) => write!(fmt, "{}", value),
+            ChalkConcreteConst::Char(value) => write!(fmt, "{:?}", value),
+            ChalkConcreteConst::Int(value) => write!(fmt, "{}", value),
+            ChalkConcreteConst::Uint(value) => write!(fmt, "{}", value),
+        }
+    }
+}
+
 /// The default "interner" and the only interner used by chalk
 /// itself. In this interner, no interning actually occurs.
 #[derive(Debug, Copy, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
@@ -59,7 +86,7 @@ impl Interner for ChalkIr {
     type InternedType = Arc<TyData<ChalkIr>>;
     type InternedLifetime = LifetimeData<ChalkIr>;
     type InternedConst = Arc<ConstData<ChalkIr>>;
-    type InternedConcreteConst = u32;
+    type InternedConcreteConst = ChalkConcreteConst;
     type InternedGenericArg = GenericArgData<ChalkIr>;
     type InternedGoal = Arc<GoalData<ChalkIr>>;
     type InternedGoals = Vec<Goal<ChalkIr>>;
@@ -261,8 +288,36 @@ impl Interner for ChalkIr {
         constant
     }
 
-    fn const_eq(&self, _ty: &Arc<TyData<ChalkIr>>, c1: &u32, c2: &u32) -> bool {
-        c1 == c2
+    fn const_eq(
+        &self,
+        ty: &Arc<TyData<ChalkIr>>,
+        c1: &ChalkConcreteConst,
+        c2: &ChalkConcreteConst,
+    ) -> bool {
+        // Constants are only equal as values of their type.
+        match (&ty.kind, c1, c2) {
+            (
+                TyKind::Scalar(Scalar::Bool),
+                ChalkConcreteConst::Bool(c1),
+                ChalkConcreteConst::Bool(c2),
+            ) => c1 == c2,
+            (
+                TyKind::Scalar(Scalar::Char),
+                ChalkConcreteConst::Char(c1),
+                ChalkConcreteConst::Char(c2),
+            ) => c1 == c2,
+            (
+                TyKind::Scalar(Scalar::Int(_)),
+                ChalkConcreteConst::Int(c1),
+                ChalkConcreteConst::Int(c2),
+            ) => c1 == c2,
+            (
+                TyKind::Scalar(Scalar::Uint(_)),
+                ChalkConcreteConst::Uint(c1),
+                ChalkConcreteConst::Uint(c2),
+            ) => c1 == c2,
+            _ => false,
+        }
     }
 
     fn intern_generic_arg(&self, generic_arg: GenericArgData<ChalkIr>) -> GenericArgData<ChalkIr> {
@@ -404,3 +459,65 @@ impl Interner for ChalkIr {
 impl HasInterner for ChalkIr {
     type Interner = ChalkIr;
 }
+
+/// Ids are serialized as their index, so they only mean something to the
+/// program they were lowered in (or a later version of it that keeps its
+/// item ids; see `Program::keeps_item_ids_of`).
+#[cfg(feature = "serde")]
+impl SerdeInterner for ChalkIr {
+    fn serde_interner() -> Self {
+        ChalkIr
+    }
+
+    fn serialize_def_id<S: Serializer>(def_id: &RawId, serializer: S) -> Result<S::Ok, S::Error> {
+        def_id.serialize(serializer)
+    }
+
+    fn deserialize_def_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<RawId, D::Error> {
+        RawId::deserialize(deserializer)
+    }
+
+    fn serialize_adt_id<S: Serializer>(adt_id: &RawId, serializer: S) -> Result<S::Ok, S::Error> {
+        adt_id.serialize(serializer)
+    }
+
+    fn deserialize_adt_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<RawId, D::Error> {
+        RawId::deserialize(deserializer)
+    }
+
+    fn serialize_concrete_const<S: Serializer>(
+        value: &ChalkConcreteConst,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.serialize(serializer)
+    }
+
+    fn deserialize_concrete_const<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ChalkConcreteConst, D::Error> {
+        ChalkConcreteConst::deserialize(deserializer)
+    }
+
+    fn serialize_fn_abi<S: Serializer>(abi: &ChalkFnAbi, serializer: S) -> Result<S::Ok, S::Error> {
+        abi.serialize(serializer)
+    }
+
+    fn deserialize_fn_abi<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ChalkFnAbi, D::Error> {
+        ChalkFnAbi::deserialize(deserializer)
+    }
+
+    fn serialize_identifier<S: Serializer>(
+        identifier: &Identifier,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(identifier)
+    }
+
+    fn deserialize_identifier<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Identifier, D::Error> {
+        String::deserialize(deserializer).map(Identifier::from)
+    }
+}
@@ -2,7 +2,10 @@
 #![cfg_attr(feature = "bench", feature(test))]
 
 pub mod db;
+pub mod differential;
 pub mod error;
+pub mod fuzz;
+pub mod generator;
 pub mod interner;
 pub mod lowering;
 pub mod program;
@@ -11,12 +14,14 @@ pub mod query;
 pub mod test_macros;
 pub mod tls;
 
+use chalk_engine::cache::AnswerCache;
 use chalk_engine::solve::SLGSolver;
 use chalk_ir::interner::HasInterner;
 use chalk_ir::Binders;
 use chalk_recursive::RecursiveSolver;
 use chalk_solve::Solver;
 use interner::ChalkIr;
+use std::sync::{Arc, Mutex};
 
 pub use interner::{Identifier, RawId};
 
@@ -50,12 +55,22 @@ pub enum SolverChoice {
     SLG {
         max_size: usize,
         expected_answers: Option<usize>,
+        /// The number of strands each solve may pursue, if limited.
+        budget: Option<usize>,
+        /// Whether to collect statistics.
+        stats: bool,
+        /// Whether to keep answers across changes to the program.
+        answer_cache: bool,
     },
     /// Run the recursive solver.
     Recursive {
         overflow_depth: usize,
         caching_enabled: bool,
         max_size: usize,
+        /// The number of goals each solve may take on, if limited.
+        budget: Option<usize>,
+        /// Whether to collect statistics.
+        stats: bool,
     },
 }
 
@@ -65,6 +80,9 @@ impl SolverChoice {
         SolverChoice::SLG {
             max_size,
             expected_answers,
+            budget: None,
+            stats: false,
+            answer_cache: false,
         }
     }
 
@@ -79,6 +97,8 @@ impl SolverChoice {
             overflow_depth: 100,
             caching_enabled: true,
             max_size: 30,
+            budget: None,
+            stats: false,
         }
     }
 
@@ -88,24 +108,110 @@ impl SolverChoice {
             overflow_depth,
             caching_enabled: true,
             max_size,
+            budget: None,
+            stats: false,
+        }
+    }
+
+    /// Limits the work of each solve to `budget` steps (or removes the limit,
+    /// if `budget` is `None`). A step is a strand pursued by the SLG solver,
+    /// or a goal taken on by the recursive solver. When a solve runs out, the
+    /// solver gives up with an ambiguous solution and reports that its
+    /// budget was exhausted.
+    pub fn with_budget(mut self, budget: Option<usize>) -> Self {
+        match &mut self {
+            SolverChoice::SLG { budget: limit, .. }
+            | SolverChoice::Recursive { budget: limit, .. } => *limit = budget,
+        }
+        self
+    }
+
+    /// Makes the solver collect statistics about the work it does for each
+    /// goal, or stop collecting them.
+    pub fn with_stats(mut self, stats: bool) -> Self {
+        match &mut self {
+            SolverChoice::SLG { stats: collect, .. }
+            | SolverChoice::Recursive { stats: collect, .. } => *collect = stats,
+        }
+        self
+    }
+
+    /// Makes the SLG solver of a database keep the answers it finds when
+    /// the program changes, as long as they do not depend on the parts of
+    /// the program that changed. The recursive solver does not support
+    /// this, so its choice is left as is.
+    pub fn with_answer_cache(mut self, answer_cache: bool) -> Self {
+        if let SolverChoice::SLG {
+            answer_cache: keep, ..
+        } = &mut self
+        {
+            *keep = answer_cache;
+        }
+        self
+    }
+
+    /// Returns `true` if the solver keeps answers across changes to the
+    /// program.
+    pub fn caches_answers(&self) -> bool {
+        match self {
+            SolverChoice::SLG { answer_cache, .. } => *answer_cache,
+            SolverChoice::Recursive { .. } => false,
+        }
+    }
+
+    /// Returns `true` if the solver collects statistics.
+    pub fn collects_stats(&self) -> bool {
+        match self {
+            SolverChoice::SLG { stats, .. } | SolverChoice::Recursive { stats, .. } => *stats,
         }
     }
 
     pub fn into_solver(self) -> Box<dyn Solver<ChalkIr>> {
+        self.into_solver_sharing_answers(&Default::default())
+    }
+
+    /// Like `into_solver`, but if the choice keeps answers across changes
+    /// to the program, the solver shares them through `answer_cache`.
+    pub fn into_solver_sharing_answers(
+        self,
+        answer_cache: &Arc<Mutex<AnswerCache<ChalkIr>>>,
+    ) -> Box<dyn Solver<ChalkIr>> {
         match self {
             SolverChoice::SLG {
                 max_size,
                 expected_answers,
-            } => Box::new(SLGSolver::new(max_size, expected_answers)),
+                budget,
+                stats,
+                answer_cache: keep_answers,
+            } => {
+                let mut solver = SLGSolver::new(max_size, expected_answers);
+                if let Some(budget) = budget {
+                    solver = solver.with_budget(budget);
+                }
+                if stats {
+                    solver = solver.with_stats();
+                }
+                if keep_answers {
+                    solver = solver.with_answer_cache(answer_cache.clone());
+                }
+                Box::new(solver)
+            }
             SolverChoice::Recursive {
                 overflow_depth,
                 caching_enabled,
                 max_size,
-            } => Box::new(RecursiveSolver::new(
-                overflow_depth,
-                max_size,
-                caching_enabled,
-            )),
+                budget,
+                stats,
+            } => {
+                let mut solver = RecursiveSolver::new(overflow_depth, max_size, caching_enabled);
+                if let Some(budget) = budget {
+                    solver = solver.with_budget(budget);
+                }
+                if stats {
+                    solver = solver.with_stats();
+                }
+                Box::new(solver)
+            }
         }
     }
 }
@@ -9,12 +9,13 @@ use chalk_ir::{
 use chalk_parse::ast::*;
 use chalk_solve::rust_ir::{self, IntoWhereClauses};
 use program_lowerer::ProgramLowerer;
+use std::cell::RefCell;
 use std::collections::BTreeMap;
 use string_cache::DefaultAtom as Atom;
 use tracing::debug;
 
 use crate::error::RustIrError;
-use crate::interner::{ChalkFnAbi, ChalkIr};
+use crate::interner::{ChalkConcreteConst, ChalkFnAbi, ChalkIr};
 use crate::program::Program as LoweredProgram;
 use crate::{Identifier as Ident, TypeSort};
 use env::*;
@@ -34,19 +35,33 @@ pub trait Lower {
     fn lower(&self) -> Self::Lowered;
 }
 
+/// Lowering a program carries on past errors where it can, so it returns
+/// all the errors it found, in the order they appear in the program, along
+/// with the program. If there are any errors, the program is only partially
+/// lowered: types that could not be lowered are replaced with error types
+/// (`TyKind::Error`), and where clauses and items that could not be lowered
+/// are left out.
 impl Lower for Program {
-    type Lowered = LowerResult<LoweredProgram>;
+    type Lowered = (LoweredProgram, Vec<RustIrError>);
 
     fn lower(&self) -> Self::Lowered {
         let mut lowerer = ProgramLowerer::default();
 
+        // The items in modules are lowered along with the others, under
+        // their full paths.
+        let program = lowerer.extract_modules(self);
+
         // Make a vector mapping each thing in `items` to an id,
         // based just on its position:
-        let raw_ids = self.items.iter().map(|_| lowerer.next_item_id()).collect();
+        let raw_ids = program
+            .items
+            .iter()
+            .map(|_| lowerer.next_item_id())
+            .collect();
 
-        lowerer.extract_associated_types(self, &raw_ids)?;
-        lowerer.extract_ids(self, &raw_ids)?;
-        lowerer.lower(self, &raw_ids)
+        lowerer.extract_associated_types(&program, &raw_ids);
+        lowerer.extract_ids(&program, &raw_ids);
+        lowerer.lower(&program, &raw_ids)
     }
 }
 
@@ -96,6 +111,7 @@ lower_param_map!(AdtDefn, None);
 lower_param_map!(FnDefn, None);
 lower_param_map!(ClosureDefn, None);
 lower_param_map!(Impl, None);
+lower_param_map!(InherentImpl, None);
 lower_param_map!(AssocTyDefn, None);
 lower_param_map!(AssocTyValue, None);
 lower_param_map!(Clause, None);
@@ -128,7 +144,13 @@ impl Lower for VariableKind {
                 n,
             ),
             VariableKind::Lifetime(n) => (chalk_ir::VariableKind::Lifetime, n),
-            VariableKind::Const(ref n) => (chalk_ir::VariableKind::Const(get_type_of_u32()), n),
+            VariableKind::Const(ref n, ref ty) => {
+                let ty = match ty {
+                    Some(ty) => chalk_ir::TyKind::Scalar(ty.lower()).intern(&ChalkIr),
+                    None => get_type_of_u32(),
+                };
+                (chalk_ir::VariableKind::Const(ty), n)
+            }
         };
 
         chalk_ir::WithKind::new(kind, n.str.clone())
@@ -139,12 +161,11 @@ impl LowerWithEnv for [QuantifiedWhereClause] {
     type Lowered = Vec<chalk_ir::QuantifiedWhereClause<ChalkIr>>;
 
     fn lower(&self, env: &Env) -> LowerResult<Self::Lowered> {
-        self.iter()
-            .flat_map(|wc| match wc.lower(env) {
-                Ok(v) => v.into_iter().map(Ok).collect(),
-                Err(e) => vec![Err(e)],
-            })
-            .collect()
+        Ok(self
+            .iter()
+            .filter_map(|wc| env.recover(wc.lower(env)))
+            .flatten()
+            .collect())
     }
 }
 
@@ -473,6 +494,7 @@ impl LowerWithEnv for TraitBound {
                     actual: param.kind(),
                 })?;
             }
+            check_const_type(&self.trait_name, binder, param)?;
         }
 
         Ok(rust_ir::TraitBound {
@@ -510,6 +532,7 @@ impl LowerWithEnv for AliasEqBound {
                     actual: arg.kind(),
                 })?;
             }
+            check_const_type(&self.name, param, arg)?;
         }
 
         Ok(rust_ir::AliasEqBound {
@@ -556,7 +579,10 @@ impl LowerWithEnv for [QuantifiedInlineBound] {
         let mut auto_traits = Vec::new();
 
         for b in self {
-            let id = env.lookup_trait(trait_identifier(&b.bound))?;
+            let id = match env.recover(env.lookup_trait(trait_identifier(&b.bound))) {
+                Some(id) => id,
+                None => continue,
+            };
             if env.auto_trait(id) {
                 auto_traits.push((b, id))
             } else {
@@ -566,11 +592,11 @@ impl LowerWithEnv for [QuantifiedInlineBound] {
 
         auto_traits.sort_by_key(|b| b.1);
 
-        regular_traits
+        Ok(regular_traits
             .iter()
             .chain(auto_traits.iter())
-            .map(|(b, _)| b.lower(env))
-            .collect()
+            .filter_map(|(b, _)| env.recover(b.lower(env)))
+            .collect())
     }
 }
 
@@ -646,6 +672,7 @@ impl LowerWithEnv for ProjectionTy {
                     actual: arg.kind(),
                 })?;
             }
+            check_const_type(&self.name, param, arg)?;
         }
 
         args.extend(trait_substitution.iter(interner).cloned());
@@ -660,156 +687,165 @@ impl LowerWithEnv for ProjectionTy {
 impl LowerWithEnv for Ty {
     type Lowered = chalk_ir::Ty<ChalkIr>;
 
+    /// Types that cannot be lowered become error types, so an error in a
+    /// type never stops the lowering of the item it appears in.
     fn lower(&self, env: &Env) -> LowerResult<Self::Lowered> {
-        let interner = env.interner();
-        Ok(match self {
-            Ty::Id { name } => {
-                let parameter = env.lookup_generic_arg(&name)?;
-                parameter.ty(interner).map(|ty| ty.clone()).ok_or_else(|| {
-                    RustIrError::IncorrectParameterKind {
-                        identifier: name.clone(),
-                        expected: Kind::Ty,
-                        actual: parameter.kind(),
+        Ok(env
+            .recover(try_lower_ty(self, env))
+            .unwrap_or_else(|| chalk_ir::TyKind::Error.intern(env.interner())))
+    }
+}
+
+fn try_lower_ty(ty: &Ty, env: &Env) -> LowerResult<chalk_ir::Ty<ChalkIr>> {
+    let interner = env.interner();
+    Ok(match ty {
+        Ty::Id { name } => {
+            let parameter = env.lookup_generic_arg(&name)?;
+            parameter.ty(interner).map(|ty| ty.clone()).ok_or_else(|| {
+                RustIrError::IncorrectParameterKind {
+                    identifier: name.clone(),
+                    expected: Kind::Ty,
+                    actual: parameter.kind(),
+                }
+            })?
+        }
+        Ty::Dyn {
+            ref bounds,
+            ref lifetime,
+        } => chalk_ir::TyKind::Dyn(chalk_ir::DynTy {
+            bounds: env.in_binders(
+                // FIXME: Figure out a proper name for this type parameter
+                Some(chalk_ir::WithKind::new(
+                    chalk_ir::VariableKind::Ty(TyVariableKind::General),
+                    Atom::from(FIXME_SELF),
+                )),
+                |env| {
+                    Ok(QuantifiedWhereClauses::from_iter(
+                        interner,
+                        bounds.lower(env)?.iter().flat_map(|qil| {
+                            qil.into_where_clauses(
+                                interner,
+                                chalk_ir::TyKind::BoundVar(BoundVar::new(
+                                    DebruijnIndex::INNERMOST,
+                                    0,
+                                ))
+                                .intern(interner),
+                            )
+                        }),
+                    ))
+                },
+            )?,
+            lifetime: lifetime.lower(env)?,
+        })
+        .intern(interner),
+
+        Ty::Apply { name, ref args } => {
+            macro_rules! tykind {
+                ($k:expr, $tykind:ident, $id:expr) => {{
+                    if $k.binders.len(interner) != args.len() {
+                        Err(RustIrError::IncorrectNumberOfTypeParameters {
+                            identifier: name.clone(),
+                            expected: $k.binders.len(interner),
+                            actual: args.len(),
+                        })?;
                     }
-                })?
-            }
-            Ty::Dyn {
-                ref bounds,
-                ref lifetime,
-            } => chalk_ir::TyKind::Dyn(chalk_ir::DynTy {
-                bounds: env.in_binders(
-                    // FIXME: Figure out a proper name for this type parameter
-                    Some(chalk_ir::WithKind::new(
-                        chalk_ir::VariableKind::Ty(TyVariableKind::General),
-                        Atom::from(FIXME_SELF),
-                    )),
-                    |env| {
-                        Ok(QuantifiedWhereClauses::from_iter(
-                            interner,
-                            bounds.lower(env)?.iter().flat_map(|qil| {
-                                qil.into_where_clauses(
-                                    interner,
-                                    chalk_ir::TyKind::BoundVar(BoundVar::new(
-                                        DebruijnIndex::INNERMOST,
-                                        0,
-                                    ))
-                                    .intern(interner),
-                                )
-                            }),
-                        ))
-                    },
-                )?,
-                lifetime: lifetime.lower(env)?,
-            })
-            .intern(interner),
 
-            Ty::Apply { name, ref args } => {
-                macro_rules! tykind {
-                    ($k:expr, $tykind:ident, $id:expr) => {{
-                        if $k.binders.len(interner) != args.len() {
-                            Err(RustIrError::IncorrectNumberOfTypeParameters {
+                    let substitution = chalk_ir::Substitution::from_fallible(
+                        interner,
+                        args.iter().map(|t| Ok(t.lower(env)?)),
+                    )?;
+
+                    for (param, arg) in $k
+                        .binders
+                        .binders
+                        .iter(interner)
+                        .zip(substitution.iter(interner))
+                    {
+                        if param.kind() != arg.kind() {
+                            Err(RustIrError::IncorrectParameterKind {
                                 identifier: name.clone(),
-                                expected: $k.binders.len(interner),
-                                actual: args.len(),
+                                expected: param.kind(),
+                                actual: arg.kind(),
                             })?;
                         }
-
-                        let substitution = chalk_ir::Substitution::from_fallible(
-                            interner,
-                            args.iter().map(|t| Ok(t.lower(env)?)),
-                        )?;
-
-                        for (param, arg) in $k
-                            .binders
-                            .binders
-                            .iter(interner)
-                            .zip(substitution.iter(interner))
-                        {
-                            if param.kind() != arg.kind() {
-                                Err(RustIrError::IncorrectParameterKind {
-                                    identifier: name.clone(),
-                                    expected: param.kind(),
-                                    actual: arg.kind(),
-                                })?;
-                            }
-                        }
-                        chalk_ir::TyKind::$tykind($id, substitution).intern(interner)
-                    }};
-                }
-                match env.lookup_type(&name)? {
-                    TypeLookup::Parameter(_) => {
-                        return Err(RustIrError::CannotApplyTypeParameter(name.clone()))
+                        check_const_type(name, param, arg)?;
                     }
-                    TypeLookup::Adt(id) => tykind!(env.adt_kind(id), Adt, id),
-                    TypeLookup::FnDef(id) => tykind!(env.fn_def_kind(id), FnDef, id),
-                    TypeLookup::Closure(id) => tykind!(env.closure_kind(id), Closure, id),
-                    TypeLookup::Opaque(id) => tykind!(env.opaque_kind(id), OpaqueType, id),
-                    TypeLookup::Generator(id) => tykind!(env.generator_kind(id), Generator, id),
-                    TypeLookup::Foreign(_) | TypeLookup::Trait(_) => {
-                        panic!("Unexpected apply type")
-                    }
-                }
+                    chalk_ir::TyKind::$tykind($id, substitution).intern(interner)
+                }};
             }
-
-            Ty::Projection { ref proj } => {
-                chalk_ir::TyKind::Alias(chalk_ir::AliasTy::Projection(proj.lower(env)?))
-                    .intern(interner)
+            match env.lookup_type(&name)? {
+                TypeLookup::Parameter(_) => {
+                    return Err(RustIrError::CannotApplyTypeParameter(name.clone()))
+                }
+                TypeLookup::Adt(id) => tykind!(env.adt_kind(id), Adt, id),
+                TypeLookup::FnDef(id) => tykind!(env.fn_def_kind(id), FnDef, id),
+                TypeLookup::Closure(id) => tykind!(env.closure_kind(id), Closure, id),
+                TypeLookup::Opaque(id) => tykind!(env.opaque_kind(id), OpaqueType, id),
+                TypeLookup::Generator(id) => tykind!(env.generator_kind(id), Generator, id),
+                TypeLookup::Foreign(_) | TypeLookup::Trait(_) => {
+                    panic!("Unexpected apply type")
+                }
             }
+        }
 
-            Ty::ForAll {
-                lifetime_names,
-                types,
-                sig,
-            } => {
-                let quantified_env = env.introduce(lifetime_names.iter().map(|id| {
-                    chalk_ir::WithKind::new(chalk_ir::VariableKind::Lifetime, id.str.clone())
-                }))?;
-
-                let mut lowered_tys = Vec::with_capacity(types.len());
-                for ty in types {
-                    lowered_tys.push(ty.lower(&quantified_env)?.cast(interner));
-                }
+        Ty::Projection { ref proj } => {
+            chalk_ir::TyKind::Alias(chalk_ir::AliasTy::Projection(proj.lower(env)?))
+                .intern(interner)
+        }
 
-                let function = chalk_ir::FnPointer {
-                    num_binders: lifetime_names.len(),
-                    substitution: chalk_ir::FnSubst(Substitution::from_iter(interner, lowered_tys)),
-                    sig: sig.lower()?,
-                };
-                chalk_ir::TyKind::Function(function).intern(interner)
+        Ty::ForAll {
+            lifetime_names,
+            types,
+            sig,
+        } => {
+            let quantified_env = env.introduce(lifetime_names.iter().map(|id| {
+                chalk_ir::WithKind::new(chalk_ir::VariableKind::Lifetime, id.str.clone())
+            }))?;
+
+            let mut lowered_tys = Vec::with_capacity(types.len());
+            for ty in types {
+                lowered_tys.push(ty.lower(&quantified_env)?.cast(interner));
             }
-            Ty::Tuple { ref types } => chalk_ir::TyKind::Tuple(
-                types.len(),
-                chalk_ir::Substitution::from_fallible(
-                    interner,
-                    types.iter().map(|t| Ok(t.lower(env)?)),
-                )?,
-            )
-            .intern(interner),
 
-            Ty::Scalar { ty } => chalk_ir::TyKind::Scalar(ty.lower()).intern(interner),
+            let function = chalk_ir::FnPointer {
+                num_binders: lifetime_names.len(),
+                substitution: chalk_ir::FnSubst(Substitution::from_iter(interner, lowered_tys)),
+                sig: sig.lower()?,
+            };
+            chalk_ir::TyKind::Function(function).intern(interner)
+        }
+        Ty::Tuple { ref types } => chalk_ir::TyKind::Tuple(
+            types.len(),
+            chalk_ir::Substitution::from_fallible(
+                interner,
+                types.iter().map(|t| Ok(t.lower(env)?)),
+            )?,
+        )
+        .intern(interner),
 
-            Ty::Array { ty, len } => {
-                chalk_ir::TyKind::Array(ty.lower(env)?, len.lower(env)?).intern(interner)
-            }
+        Ty::Scalar { ty } => chalk_ir::TyKind::Scalar(ty.lower()).intern(interner),
 
-            Ty::Slice { ty } => chalk_ir::TyKind::Slice(ty.lower(env)?).intern(interner),
+        Ty::Array { ty, len } => {
+            chalk_ir::TyKind::Array(ty.lower(env)?, len.lower(env)?).intern(interner)
+        }
 
-            Ty::Raw { mutability, ty } => {
-                chalk_ir::TyKind::Raw(mutability.lower(), ty.lower(env)?).intern(interner)
-            }
+        Ty::Slice { ty } => chalk_ir::TyKind::Slice(ty.lower(env)?).intern(interner),
 
-            Ty::Ref {
-                mutability,
-                lifetime,
-                ty,
-            } => chalk_ir::TyKind::Ref(mutability.lower(), lifetime.lower(env)?, ty.lower(env)?)
-                .intern(interner),
+        Ty::Raw { mutability, ty } => {
+            chalk_ir::TyKind::Raw(mutability.lower(), ty.lower(env)?).intern(interner)
+        }
 
-            Ty::Str => chalk_ir::TyKind::Str.intern(interner),
+        Ty::Ref {
+            mutability,
+            lifetime,
+            ty,
+        } => chalk_ir::TyKind::Ref(mutability.lower(), lifetime.lower(env)?, ty.lower(env)?)
+            .intern(interner),
 
-            Ty::Never => chalk_ir::TyKind::Never.intern(interner),
-        })
-    }
+        Ty::Str => chalk_ir::TyKind::Str.intern(interner),
+
+        Ty::Never => chalk_ir::TyKind::Never.intern(interner),
+    })
 }
 
 impl LowerWithEnv for Const {
@@ -829,11 +865,60 @@ impl LowerWithEnv for Const {
                     })
                     .map(|c| c.clone())
             }
-            Const::Value(value) => Ok(chalk_ir::ConstData {
-                ty: get_type_of_u32(),
-                value: chalk_ir::ConstValue::Concrete(chalk_ir::ConcreteConst { interned: *value }),
+            Const::Value(value) => {
+                let (scalar, interned) = match *value {
+                    ConstValue::Bool(b) => (chalk_ir::Scalar::Bool, ChalkConcreteConst::Bool(b)),
+                    ConstValue::Char(c) => (chalk_ir::Scalar::Char, ChalkConcreteConst::Char(c)),
+                    ConstValue::Int(i, int) => (
+                        chalk_ir::Scalar::Int(int.lower()),
+                        ChalkConcreteConst::Int(i),
+                    ),
+                    ConstValue::Uint(u, uint) => (
+                        chalk_ir::Scalar::Uint(uint.lower()),
+                        ChalkConcreteConst::Uint(u),
+                    ),
+                };
+                Ok(chalk_ir::ConstData {
+                    ty: chalk_ir::TyKind::Scalar(scalar).intern(interner),
+                    value: chalk_ir::ConstValue::Concrete(chalk_ir::ConcreteConst { interned }),
+                }
+                .intern(interner))
+            }
+            Const::Unevaluated(lhs, op, rhs) => {
+                let lhs = lhs.lower(env)?;
+                let rhs = rhs.lower(env)?;
+                let ty = lhs.data(interner).ty.clone();
+                let rhs_ty = &rhs.data(interner).ty;
+                if ty != *rhs_ty {
+                    return Err(RustIrError::MismatchedConstOperandTypes {
+                        lhs: ty,
+                        rhs: rhs_ty.clone(),
+                    });
+                }
+                Ok(chalk_ir::ConstData {
+                    ty,
+                    value: chalk_ir::ConstValue::Unevaluated(chalk_ir::UnevaluatedConst {
+                        op: op.lower(),
+                        lhs,
+                        rhs,
+                    }),
+                }
+                .intern(interner))
             }
-            .intern(interner)),
+        }
+    }
+}
+
+impl Lower for ConstOp {
+    type Lowered = chalk_ir::ConstOp;
+
+    fn lower(&self) -> Self::Lowered {
+        match self {
+            ConstOp::Add => chalk_ir::ConstOp::Add,
+            ConstOp::Sub => chalk_ir::ConstOp::Sub,
+            ConstOp::Mul => chalk_ir::ConstOp::Mul,
+            ConstOp::Div => chalk_ir::ConstOp::Div,
+            ConstOp::Rem => chalk_ir::ConstOp::Rem,
         }
     }
 }
@@ -929,6 +1014,50 @@ impl LowerWithEnv for (&Impl, ImplId<ChalkIr>, &AssociatedTyValueIds) {
     }
 }
 
+impl LowerWithEnv for InherentImpl {
+    type Lowered = rust_ir::InherentImplDatum<ChalkIr>;
+
+    fn lower(&self, env: &Env) -> LowerResult<Self::Lowered> {
+        let binders = env.in_binders(self.all_parameters(), |env| {
+            let methods = self
+                .methods
+                .iter()
+                .map(|method| {
+                    let argument_types: LowerResult<_> =
+                        method.argument_types.iter().map(|t| t.lower(env)).collect();
+                    Ok(rust_ir::MethodDatum {
+                        self_kind: method.self_kind.lower(),
+                        argument_types: argument_types?,
+                        return_type: method.return_type.lower(env)?,
+                    })
+                })
+                .collect::<LowerResult<_>>()?;
+            Ok(rust_ir::InherentImplDatumBound {
+                self_ty: self.self_ty.lower(env)?,
+                where_clauses: self.where_clauses.lower(env)?,
+                methods,
+            })
+        })?;
+
+        Ok(rust_ir::InherentImplDatum {
+            binders,
+            impl_type: self.impl_type.lower(),
+        })
+    }
+}
+
+impl Lower for SelfKind {
+    type Lowered = rust_ir::SelfKind;
+
+    fn lower(&self) -> Self::Lowered {
+        match self {
+            SelfKind::Value => rust_ir::SelfKind::Value,
+            SelfKind::Ref => rust_ir::SelfKind::Ref,
+            SelfKind::RefMut => rust_ir::SelfKind::RefMut,
+        }
+    }
+}
+
 impl LowerWithEnv for Clause {
     type Lowered = Vec<chalk_ir::ProgramClause<ChalkIr>>;
 
@@ -1013,6 +1142,21 @@ impl LowerWithEnv for (&TraitDefn, chalk_ir::TraitId<ChalkIr>) {
 }
 
 pub fn lower_goal(goal: &Goal, program: &LoweredProgram) -> LowerResult<chalk_ir::Goal<ChalkIr>> {
+    lower_in_program(program, |env| goal.lower(env))
+}
+
+/// Lowers a type written outside of any item, such as the receiver type of a
+/// method lookup, against `program`.
+pub fn lower_ty(ty: &Ty, program: &LoweredProgram) -> LowerResult<chalk_ir::Ty<ChalkIr>> {
+    lower_in_program(program, |env| ty.lower(env))
+}
+
+/// Runs `lower` in an environment of the items of `program`, as seen from
+/// the crate root.
+fn lower_in_program<T>(
+    program: &LoweredProgram,
+    lower: impl FnOnce(&Env) -> LowerResult<T>,
+) -> LowerResult<T> {
     let interner = &ChalkIr;
     let associated_ty_lookups: BTreeMap<_, _> = program
         .associated_ty_data
@@ -1037,6 +1181,8 @@ pub fn lower_goal(goal: &Goal, program: &LoweredProgram) -> LowerResult<chalk_ir
         .map(|(&trait_id, datum)| (trait_id, datum.flags.auto))
         .collect();
 
+    let root = Ident::from("");
+    let errors = RefCell::new(vec![]);
     let env = Env {
         adt_ids: &program.adt_ids,
         fn_def_ids: &program.fn_def_ids,
@@ -1052,11 +1198,20 @@ pub fn lower_goal(goal: &Goal, program: &LoweredProgram) -> LowerResult<chalk_ir
         opaque_ty_kinds: &program.opaque_ty_kinds,
         associated_ty_lookups: &associated_ty_lookups,
         foreign_ty_ids: &program.foreign_ty_ids,
+        module_scopes: &program.module_scopes,
+        module: &root,
         parameter_map: BTreeMap::new(),
         auto_traits: &auto_traits,
+        errors: &errors,
     };
 
-    goal.lower(&env)
+    // A goal with errors in it is never worth solving, so report the first
+    // error even if lowering recovered from it.
+    let lowered = env.recover(lower(&env));
+    match errors.into_inner().into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(lowered.unwrap()),
+    }
 }
 
 impl LowerWithEnv for Goal {
@@ -1133,8 +1288,30 @@ impl Lower for WellKnownTrait {
             WellKnownTrait::Unpin => rust_ir::WellKnownTrait::Unpin,
             WellKnownTrait::CoerceUnsized => rust_ir::WellKnownTrait::CoerceUnsized,
             WellKnownTrait::DiscriminantKind => rust_ir::WellKnownTrait::DiscriminantKind,
+            WellKnownTrait::Deref => rust_ir::WellKnownTrait::Deref,
+        }
+    }
+}
+
+/// Checks that `arg`, if it is a const, has the type of the const parameter
+/// `param` of `identifier`.
+fn check_const_type(
+    identifier: &Identifier,
+    param: &chalk_ir::VariableKind<ChalkIr>,
+    arg: &chalk_ir::GenericArg<ChalkIr>,
+) -> LowerResult<()> {
+    let interner = &ChalkIr;
+    if let (chalk_ir::VariableKind::Const(expected), Some(arg)) = (param, arg.constant(interner)) {
+        let actual = &arg.data(interner).ty;
+        if actual != expected {
+            Err(RustIrError::MismatchedConstParameterType {
+                identifier: identifier.clone(),
+                expected: expected.clone(),
+                actual: actual.clone(),
+            })?;
         }
     }
+    Ok(())
 }
 
 trait Kinded {
@@ -6,6 +6,7 @@ use chalk_ir::{
 use chalk_ir::{cast::Cast, ForeignDefId, WithKind};
 use chalk_parse::ast::*;
 use chalk_solve::rust_ir::AssociatedTyValueId;
+use std::cell::RefCell;
 use std::collections::BTreeMap;
 
 use crate::error::RustIrError;
@@ -30,6 +31,12 @@ pub type AssociatedTyValueIds =
     BTreeMap<(chalk_ir::ImplId<ChalkIr>, Ident), AssociatedTyValueId<ChalkIr>>;
 pub type ForeignIds = BTreeMap<Ident, chalk_ir::ForeignDefId<ChalkIr>>;
 
+/// For each module, by its path (the program root's path is empty), the full
+/// paths of the names in scope in it: the items and modules declared in it,
+/// and the ones it imports with `use`. The modules of a declared crate also
+/// map `crate`, which can't be the name of an item, to the crate's root.
+pub type ModuleScopes = BTreeMap<Ident, BTreeMap<Ident, Ident>>;
+
 pub type ParameterMap = BTreeMap<Ident, chalk_ir::WithKind<ChalkIr, BoundVar>>;
 
 pub type LowerResult<T> = Result<T, RustIrError>;
@@ -51,9 +58,40 @@ pub struct Env<'k> {
     pub foreign_ty_ids: &'k ForeignIds,
     pub generator_ids: &'k GeneratorIds,
     pub generator_kinds: &'k GeneratorKinds,
+    pub module_scopes: &'k ModuleScopes,
+    /// The path of the module the names are written in.
+    pub module: &'k Ident,
     /// GenericArg identifiers are used as keys, therefore
     /// all identifiers in an environment must be unique (no shadowing).
     pub parameter_map: ParameterMap,
+    /// Errors that lowering recovered from; see `recover`.
+    pub errors: &'k RefCell<Vec<RustIrError>>,
+}
+
+/// Resolves `path`, written in `module`, to the full path of the item or
+/// module it names. A path starts from `crate`, `self`, `super`, or a name in
+/// scope in `module`; names that are not in scope there are looked up in the
+/// program root, which thus acts as a prelude. Each further segment of the path
+/// is looked up among the names in scope in the module before it.
+pub fn resolve_path(module_scopes: &ModuleScopes, module: &Ident, path: &str) -> Option<Ident> {
+    let in_scope = |module: &Ident, name: &str| module_scopes.get(module)?.get(&Ident::from(name));
+    let mut segments = path.split("::");
+    let mut full_path = match segments.next()? {
+        "crate" => in_scope(module, "crate").cloned().unwrap_or_default(),
+        "self" => module.clone(),
+        "super" => match module.rfind("::") {
+            Some(i) => Ident::from(&module[..i]),
+            None if !module.is_empty() => Ident::from(""),
+            None => return None,
+        },
+        name => in_scope(module, name)
+            .or_else(|| in_scope(&Ident::from(""), name))?
+            .clone(),
+    };
+    for segment in segments {
+        full_path = in_scope(&full_path, segment)?.clone();
+    }
+    Some(full_path)
 }
 
 /// Information about an associated type **declaration** (i.e., an
@@ -91,6 +129,19 @@ impl Env<'_> {
         &ChalkIr
     }
 
+    /// Returns the value of `result`, or records its error and returns
+    /// `None`, so that the caller can skip over the erroneous part and carry
+    /// on lowering the rest of the program.
+    pub fn recover<T>(&self, result: LowerResult<T>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.errors.borrow_mut().push(error);
+                None
+            }
+        }
+    }
+
     pub fn lookup_generic_arg(
         &self,
         name: &Identifier,
@@ -152,20 +203,24 @@ impl Env<'_> {
 
     pub fn lookup_type(&self, name: &Identifier) -> LowerResult<TypeLookup> {
         if let Some(id) = self.parameter_map.get(&name.str) {
-            Ok(TypeLookup::Parameter(id))
-        } else if let Some(id) = self.adt_ids.get(&name.str) {
+            return Ok(TypeLookup::Parameter(id));
+        }
+        let path = self
+            .resolve(name)
+            .ok_or_else(|| RustIrError::NotStruct(name.clone()))?;
+        if let Some(id) = self.adt_ids.get(&path) {
             Ok(TypeLookup::Adt(*id))
-        } else if let Some(id) = self.fn_def_ids.get(&name.str) {
+        } else if let Some(id) = self.fn_def_ids.get(&path) {
             Ok(TypeLookup::FnDef(*id))
-        } else if let Some(id) = self.closure_ids.get(&name.str) {
+        } else if let Some(id) = self.closure_ids.get(&path) {
             Ok(TypeLookup::Closure(*id))
-        } else if let Some(id) = self.opaque_ty_ids.get(&name.str) {
+        } else if let Some(id) = self.opaque_ty_ids.get(&path) {
             Ok(TypeLookup::Opaque(*id))
-        } else if let Some(id) = self.foreign_ty_ids.get(&name.str) {
+        } else if let Some(id) = self.foreign_ty_ids.get(&path) {
             Ok(TypeLookup::Foreign(*id))
-        } else if let Some(id) = self.trait_ids.get(&name.str) {
+        } else if let Some(id) = self.trait_ids.get(&path) {
             Ok(TypeLookup::Trait(*id))
-        } else if let Some(id) = self.generator_ids.get(&name.str) {
+        } else if let Some(id) = self.generator_ids.get(&path) {
             Ok(TypeLookup::Generator(*id))
         } else {
             Err(RustIrError::NotStruct(name.clone()))
@@ -178,16 +233,26 @@ impl Env<'_> {
 
     pub fn lookup_trait(&self, name: &Identifier) -> LowerResult<TraitId<ChalkIr>> {
         if let Some(_) = self.parameter_map.get(&name.str) {
+            return Err(RustIrError::NotTrait(name.clone()));
+        }
+        let path = self
+            .resolve(name)
+            .ok_or_else(|| RustIrError::InvalidTraitName(name.clone()))?;
+        if let Some(_) = self.adt_ids.get(&path) {
             Err(RustIrError::NotTrait(name.clone()))
-        } else if let Some(_) = self.adt_ids.get(&name.str) {
-            Err(RustIrError::NotTrait(name.clone()))
-        } else if let Some(id) = self.trait_ids.get(&name.str) {
+        } else if let Some(id) = self.trait_ids.get(&path) {
             Ok(*id)
         } else {
             Err(RustIrError::InvalidTraitName(name.clone()))
         }
     }
 
+    /// The full path of the item that `name`, a path written in the current
+    /// module, refers to; see `resolve_path`.
+    fn resolve(&self, name: &Identifier) -> Option<Ident> {
+        resolve_path(self.module_scopes, self.module, &name.str)
+    }
+
     pub fn trait_kind(&self, id: chalk_ir::TraitId<ChalkIr>) -> &TypeKind {
         &self.trait_kinds[&id]
     }
@@ -9,6 +9,7 @@ use chalk_solve::rust_ir::{
     GeneratorWitnessDatum, GeneratorWitnessExistential, OpaqueTyDatum, OpaqueTyDatumBound,
 };
 use rust_ir::IntoWhereClauses;
+use std::cell::RefCell;
 use std::collections::{BTreeMap, HashSet};
 use std::sync::Arc;
 use string_cache::DefaultAtom as Atom;
@@ -17,7 +18,7 @@ use super::{env::*, Lower, LowerParameterMap, LowerWithEnv, FIXME_SELF};
 use crate::error::RustIrError;
 use crate::program::Program as LoweredProgram;
 use crate::RawId;
-use crate::{interner::ChalkIr, TypeKind, TypeSort};
+use crate::{interner::ChalkIr, Identifier as Ident, TypeKind, TypeSort};
 
 #[derive(Default)]
 pub(super) struct ProgramLowerer {
@@ -40,6 +41,17 @@ pub(super) struct ProgramLowerer {
     opaque_ty_kinds: OpaqueTyVariableKinds,
     object_safe_traits: HashSet<TraitId<ChalkIr>>,
     foreign_ty_ids: ForeignIds,
+    module_scopes: ModuleScopes,
+
+    /// The crates the current crate can see, itself included, and whether
+    /// each is upstream of it.
+    crates: BTreeMap<Ident, bool>,
+
+    /// The path of the module of each item, once the modules are flattened.
+    item_modules: Vec<Ident>,
+
+    /// The errors found so far, in the order they appear in the program.
+    errors: RefCell<Vec<RustIrError>>,
 }
 
 impl ProgramLowerer {
@@ -49,17 +61,177 @@ impl ProgramLowerer {
         RawId { index }
     }
 
-    /// Create ids for associated type declarations and values
-    pub fn extract_associated_types(
+    /// Flattens the modules of `program` into a program of all their items,
+    /// each named by its full path, and records the names in scope in each
+    /// module. Crates are flattened like modules, except for the ones the
+    /// current crate cannot see, which are left out.
+    pub fn extract_modules(&mut self, program: &Program) -> Program {
+        self.extract_crates(program);
+        let mut items = vec![];
+        let mut uses = vec![];
+        let root = Ident::from("");
+        self.flatten_module(program, root.clone(), &root, false, &mut items, &mut uses);
+        self.resolve_uses(uses);
+        Program {
+            items,
+            ..Program::default()
+        }
+    }
+
+    /// Finds the current crate among the crates declared at the top level of
+    /// `program`, and the crates it depends on, directly or not.
+    fn extract_crates(&mut self, program: &Program) {
+        let crates: Vec<&CrateDefn> = program
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Crate(defn) => Some(defn),
+                _ => None,
+            })
+            .collect();
+
+        let mut current = None;
+        for defn in &crates {
+            if defn.current {
+                match current {
+                    None => current = Some(*defn),
+                    Some(_) => self
+                        .errors
+                        .get_mut()
+                        .push(RustIrError::SecondCurrentCrate(defn.name.clone())),
+                }
+            }
+            for dependency in &defn.dependencies {
+                if !crates.iter().any(|defn| defn.name.str == dependency.str) {
+                    self.errors
+                        .get_mut()
+                        .push(RustIrError::UnknownCrate(dependency.clone()));
+                }
+            }
+        }
+        let current = match current.or_else(|| crates.last().copied()) {
+            Some(current) => current,
+            None => return,
+        };
+
+        self.crates.insert(current.name.str.clone(), false);
+        let mut stack = vec![current];
+        while let Some(defn) = stack.pop() {
+            for dependency in &defn.dependencies {
+                if self.crates.contains_key(&dependency.str) {
+                    continue;
+                }
+                self.crates.insert(dependency.str.clone(), true);
+                stack.extend(crates.iter().filter(|defn| defn.name.str == dependency.str));
+            }
+        }
+    }
+
+    /// Flattens the items of `program`, the module at path `module` in the
+    /// crate whose root is at path `crate_root`. The items of an `upstream`
+    /// crate are marked as upstream.
+    fn flatten_module(
         &mut self,
         program: &Program,
-        raw_ids: &Vec<RawId>,
-    ) -> LowerResult<()> {
+        module: Ident,
+        crate_root: &Ident,
+        upstream: bool,
+        items: &mut Vec<Item>,
+        uses: &mut Vec<(Ident, UseDecl)>,
+    ) {
+        let scope = self.module_scopes.entry(module.clone()).or_default();
+        if !crate_root.is_empty() {
+            scope.insert(Ident::from("crate"), crate_root.clone());
+        }
+        for item in &program.items {
+            match item {
+                Item::Module(defn) => {
+                    let path = join_path(&module, &defn.name.str);
+                    self.module_scopes
+                        .get_mut(&module)
+                        .unwrap()
+                        .insert(defn.name.str.clone(), path.clone());
+                    self.flatten_module(&defn.program, path, crate_root, upstream, items, uses);
+                }
+                Item::Crate(defn) if !module.is_empty() => {
+                    self.errors
+                        .get_mut()
+                        .push(RustIrError::NestedCrate(defn.name.clone()));
+                }
+                Item::Crate(defn) => {
+                    let upstream = match self.crates.get(&defn.name.str) {
+                        Some(&upstream) => upstream,
+                        None => continue,
+                    };
+                    let path = defn.name.str.clone();
+                    self.module_scopes
+                        .get_mut(&module)
+                        .unwrap()
+                        .insert(path.clone(), path.clone());
+                    self.flatten_module(&defn.program, path.clone(), &path, upstream, items, uses);
+                }
+                Item::Use(decl) => uses.push((module.clone(), decl.clone())),
+                item => {
+                    let mut item = item.clone();
+                    if let Some(name) = defined_name(&mut item) {
+                        let path = join_path(&module, &name.str);
+                        self.module_scopes
+                            .get_mut(&module)
+                            .unwrap()
+                            .insert(name.str.clone(), path.clone());
+                        name.str = path;
+                    }
+                    if upstream {
+                        mark_upstream(&mut item);
+                    }
+                    items.push(item);
+                    self.item_modules.push(module.clone());
+                }
+            }
+        }
+    }
+
+    /// Brings the names that `uses` import into scope. As an import can go
+    /// through a name that another one imports, they are resolved over and
+    /// over until no more of them can be. A declared item or module takes
+    /// precedence over an import of the same name.
+    fn resolve_uses(&mut self, mut uses: Vec<(Ident, UseDecl)>) {
+        loop {
+            let unresolved = uses.len();
+            let module_scopes = &mut self.module_scopes;
+            uses.retain(|(module, decl)| {
+                match resolve_path(module_scopes, module, &decl.path.str) {
+                    Some(path) => {
+                        module_scopes
+                            .get_mut(module)
+                            .unwrap()
+                            .entry(decl.name().str)
+                            .or_insert(path);
+                        false
+                    }
+                    None => true,
+                }
+            });
+            if uses.len() == unresolved {
+                break;
+            }
+        }
+        for (_, decl) in uses {
+            self.errors
+                .get_mut()
+                .push(RustIrError::UnresolvedImport(decl.path));
+        }
+    }
+
+    /// Create ids for associated type declarations and values
+    pub fn extract_associated_types(&mut self, program: &Program, raw_ids: &Vec<RawId>) {
         for (item, &raw_id) in program.items.iter().zip(raw_ids) {
             match item {
                 Item::TraitDefn(d) => {
                     if d.flags.auto && !d.assoc_ty_defns.is_empty() {
-                        Err(RustIrError::AutoTraitAssociatedTypes(d.name.clone()))?;
+                        self.errors
+                            .get_mut()
+                            .push(RustIrError::AutoTraitAssociatedTypes(d.name.clone()));
                     }
                     for defn in &d.assoc_ty_defns {
                         let addl_variable_kinds = defn.all_parameters();
@@ -83,32 +255,31 @@ impl ProgramLowerer {
                 _ => {}
             }
         }
-        Ok(())
     }
 
-    pub fn extract_ids(&mut self, program: &Program, raw_ids: &Vec<RawId>) -> LowerResult<()> {
+    pub fn extract_ids(&mut self, program: &Program, raw_ids: &Vec<RawId>) {
         for (item, &raw_id) in program.items.iter().zip(raw_ids) {
             match item {
                 Item::AdtDefn(defn) => {
-                    let type_kind = defn.lower_type_kind()?;
+                    let type_kind = defn.lower_type_kind();
                     let id = AdtId(raw_id);
                     self.adt_ids.insert(type_kind.name.clone(), id);
                     self.adt_kinds.insert(id, type_kind);
                 }
                 Item::FnDefn(defn) => {
-                    let type_kind = defn.lower_type_kind()?;
+                    let type_kind = defn.lower_type_kind();
                     let id = FnDefId(raw_id);
                     self.fn_def_ids.insert(type_kind.name.clone(), id);
                     self.fn_def_kinds.insert(id, type_kind);
                 }
                 Item::ClosureDefn(defn) => {
-                    let type_kind = defn.lower_type_kind()?;
+                    let type_kind = defn.lower_type_kind();
                     let id = ClosureId(raw_id);
                     self.closure_ids.insert(defn.name.str.clone(), id);
                     self.closure_kinds.insert(id, type_kind);
                 }
                 Item::TraitDefn(defn) => {
-                    let type_kind = defn.lower_type_kind()?;
+                    let type_kind = defn.lower_type_kind();
                     let id = TraitId(raw_id);
                     self.trait_ids.insert(type_kind.name.clone(), id);
                     self.trait_kinds.insert(id, type_kind);
@@ -119,7 +290,7 @@ impl ProgramLowerer {
                     }
                 }
                 Item::OpaqueTyDefn(defn) => {
-                    let type_kind = defn.lower_type_kind()?;
+                    let type_kind = defn.lower_type_kind();
                     let id = OpaqueTyId(raw_id);
                     self.opaque_ty_ids.insert(defn.name.str.clone(), id);
                     self.opaque_ty_kinds.insert(id, type_kind);
@@ -131,16 +302,22 @@ impl ProgramLowerer {
                 Item::GeneratorDefn(defn) => {
                     let id = GeneratorId(raw_id);
                     self.generator_ids.insert(defn.name.str.clone(), id);
-                    self.generator_kinds.insert(id, defn.lower_type_kind()?);
+                    self.generator_kinds.insert(id, defn.lower_type_kind());
                 }
-                Item::Impl(_) => continue,
+                Item::Impl(_) | Item::InherentImpl(_) => continue,
                 Item::Clause(_) => continue,
+                Item::Module(_) | Item::Use(_) | Item::Crate(_) => continue,
             };
         }
-        Ok(())
     }
 
-    pub fn lower(self, program: &Program, raw_ids: &Vec<RawId>) -> LowerResult<LoweredProgram> {
+    /// Lowers each item of `program`. An item that cannot be lowered is left
+    /// out of the lowered program, and its error is recorded.
+    pub fn lower(
+        self,
+        program: &Program,
+        raw_ids: &Vec<RawId>,
+    ) -> (LoweredProgram, Vec<RustIrError>) {
         let mut adt_data = BTreeMap::new();
         let mut adt_reprs = BTreeMap::new();
         let mut adt_variances = BTreeMap::new();
@@ -152,6 +329,8 @@ impl ProgramLowerer {
         let mut trait_data = BTreeMap::new();
         let mut well_known_traits = BTreeMap::new();
         let mut impl_data = BTreeMap::new();
+        let mut inherent_impl_data = BTreeMap::new();
+        let mut method_names = BTreeMap::new();
         let mut associated_ty_data = BTreeMap::new();
         let mut associated_ty_values = BTreeMap::new();
         let mut opaque_ty_data = BTreeMap::new();
@@ -160,7 +339,7 @@ impl ProgramLowerer {
         let mut hidden_opaque_types = BTreeMap::new();
         let mut custom_clauses = Vec::new();
 
-        for (item, &raw_id) in program.items.iter().zip(raw_ids) {
+        let mut lower_item = |item: &Item, raw_id: RawId, module: &Ident| -> LowerResult<()> {
             let empty_env = Env {
                 adt_ids: &self.adt_ids,
                 adt_kinds: &self.adt_kinds,
@@ -178,6 +357,9 @@ impl ProgramLowerer {
                 parameter_map: BTreeMap::new(),
                 auto_traits: &self.auto_traits,
                 foreign_ty_ids: &self.foreign_ty_ids,
+                module_scopes: &self.module_scopes,
+                module,
+                errors: &self.errors,
             };
 
             match *item {
@@ -333,6 +515,7 @@ impl ProgramLowerer {
                         let value = empty_env.in_binders(variable_kinds, |env| {
                             Ok(rust_ir::AssociatedTyValueBound {
                                 ty: atv.value.lower(env)?,
+                                where_clauses: atv.where_clauses.lower(env)?,
                             })
                         })?;
 
@@ -342,10 +525,21 @@ impl ProgramLowerer {
                                 impl_id,
                                 associated_ty_id: lookup.id,
                                 value,
+                                default: atv.default || impl_defn.default,
                             }),
                         );
                     }
                 }
+                Item::InherentImpl(ref impl_defn) => {
+                    let impl_id = ImplId(raw_id);
+                    inherent_impl_data.insert(impl_id, Arc::new(impl_defn.lower(&empty_env)?));
+                    let names = impl_defn
+                        .methods
+                        .iter()
+                        .map(|method| method.name.str.clone())
+                        .collect::<Vec<_>>();
+                    method_names.insert(impl_id, names);
+                }
                 Item::Clause(ref clause) => {
                     custom_clauses.extend(clause.lower(&empty_env)?);
                 }
@@ -463,11 +657,25 @@ impl ProgramLowerer {
                     generator_data.insert(id, Arc::new(generator_datum));
                     generator_witness_data.insert(id, Arc::new(generator_witness));
                 }
-                Item::Foreign(_) => {}
+                Item::Foreign(_) | Item::Module(_) | Item::Use(_) | Item::Crate(_) => {}
+            }
+            Ok(())
+        };
+
+        for ((item, &raw_id), module) in program.items.iter().zip(raw_ids).zip(&self.item_modules) {
+            if let Err(error) = lower_item(item, raw_id, module) {
+                self.errors.borrow_mut().push(error);
             }
         }
 
-        Ok(LoweredProgram {
+        let item_spans = program
+            .items
+            .iter()
+            .zip(raw_ids)
+            .filter_map(|(item, &raw_id)| Some((raw_id, item.name_span()?)))
+            .collect();
+
+        let program = LoweredProgram {
             adt_ids: self.adt_ids,
             fn_def_ids: self.fn_def_ids,
             closure_ids: self.closure_ids,
@@ -491,6 +699,8 @@ impl ProgramLowerer {
             trait_data,
             well_known_traits,
             impl_data,
+            inherent_impl_data,
+            method_names,
             associated_ty_values,
             associated_ty_data,
             opaque_ty_ids: self.opaque_ty_ids,
@@ -499,27 +709,70 @@ impl ProgramLowerer {
             hidden_opaque_types,
             custom_clauses,
             object_safe_traits: self.object_safe_traits,
+            specialization_priorities: BTreeMap::new(),
             foreign_ty_ids: self.foreign_ty_ids,
-        })
+            module_scopes: self.module_scopes,
+            item_spans,
+        };
+        (program, self.errors.into_inner())
+    }
+}
+
+/// The path of the item named `name` in `module`.
+fn join_path(module: &Ident, name: &Ident) -> Ident {
+    if module.is_empty() {
+        name.clone()
+    } else {
+        Ident::from(format!("{}::{}", module, name))
+    }
+}
+
+/// The name that `item` defines, if any.
+fn defined_name(item: &mut Item) -> Option<&mut Identifier> {
+    match item {
+        Item::AdtDefn(defn) => Some(&mut defn.name),
+        Item::FnDefn(defn) => Some(&mut defn.name),
+        Item::ClosureDefn(defn) => Some(&mut defn.name),
+        Item::TraitDefn(defn) => Some(&mut defn.name),
+        Item::OpaqueTyDefn(defn) => Some(&mut defn.name),
+        Item::GeneratorDefn(defn) => Some(&mut defn.name),
+        Item::Foreign(ForeignDefn(name)) => Some(name),
+        Item::Impl(_)
+        | Item::InherentImpl(_)
+        | Item::Clause(_)
+        | Item::Module(_)
+        | Item::Use(_)
+        | Item::Crate(_) => None,
+    }
+}
+
+/// Marks `item` as coming from a crate upstream of the current one.
+fn mark_upstream(item: &mut Item) {
+    match item {
+        Item::AdtDefn(defn) => defn.flags.upstream = true,
+        Item::TraitDefn(defn) => defn.flags.upstream = true,
+        Item::Impl(defn) => defn.impl_type = ImplType::External,
+        Item::InherentImpl(defn) => defn.impl_type = ImplType::External,
+        _ => {}
     }
 }
 
 trait LowerTypeKind {
-    fn lower_type_kind(&self) -> LowerResult<TypeKind>;
+    fn lower_type_kind(&self) -> TypeKind;
 }
 
 macro_rules! lower_type_kind {
     ($type: ident, $sort: ident, $params: expr) => {
         impl LowerTypeKind for $type {
-            fn lower_type_kind(&self) -> LowerResult<TypeKind> {
-                Ok(TypeKind {
+            fn lower_type_kind(&self) -> TypeKind {
+                TypeKind {
                     sort: TypeSort::$sort,
                     name: self.name.str.clone(),
                     binders: chalk_ir::Binders::new(
                         VariableKinds::from_iter(&ChalkIr, $params(self).anonymize()),
                         crate::Unit,
                     ),
-                })
+                }
             }
         }
     };
@@ -1,5 +1,5 @@
 use crate::interner::ChalkIr;
-use crate::{tls, Identifier, TypeKind};
+use crate::{tls, Identifier, RawId, TypeKind};
 use chalk_ir::{could_match::CouldMatch, UnificationDatabase};
 use chalk_ir::{debug::Angle, Variance};
 use chalk_ir::{
@@ -8,15 +8,18 @@ use chalk_ir::{
     OpaqueTyId, ProgramClause, ProgramClauseImplication, ProgramClauses, ProjectionTy, Scalar,
     Substitution, TraitId, Ty, TyKind, UintTy, Variances,
 };
+use chalk_parse::ast::Span;
+use chalk_solve::coherence::SpecializationPriorities;
 use chalk_solve::rust_ir::{
     AdtDatum, AdtRepr, AssociatedTyDatum, AssociatedTyValue, AssociatedTyValueId, ClosureKind,
     FnDefDatum, FnDefInputsAndOutputDatum, GeneratorDatum, GeneratorWitnessDatum, ImplDatum,
-    ImplType, OpaqueTyDatum, TraitDatum, WellKnownTrait,
+    ImplType, InherentImplDatum, OpaqueTyDatum, TraitDatum, WellKnownTrait,
 };
 use chalk_solve::split::Split;
 use chalk_solve::RustIrDatabase;
 use std::collections::{BTreeMap, HashSet};
 use std::fmt;
+use std::iter::FromIterator;
 use std::sync::Arc;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -72,6 +75,12 @@ pub struct Program {
     /// For each impl:
     pub impl_data: BTreeMap<ImplId<ChalkIr>, Arc<ImplDatum<ChalkIr>>>,
 
+    /// For each inherent impl:
+    pub inherent_impl_data: BTreeMap<ImplId<ChalkIr>, Arc<InherentImplDatum<ChalkIr>>>,
+
+    /// For each inherent impl, the names of its methods:
+    pub method_names: BTreeMap<ImplId<ChalkIr>, Vec<Identifier>>,
+
     /// For each associated ty value `type Foo = XXX` found in an impl:
     pub associated_ty_values:
         BTreeMap<AssociatedTyValueId<ChalkIr>, Arc<AssociatedTyValue<ChalkIr>>>,
@@ -103,11 +112,83 @@ pub struct Program {
     /// Store the traits marked with `#[object_safe]`
     pub object_safe_traits: HashSet<TraitId<ChalkIr>>,
 
+    /// For each trait, the specialization priorities of its impls. Filled in
+    /// when the program is checked.
+    pub specialization_priorities:
+        BTreeMap<TraitId<ChalkIr>, Arc<SpecializationPriorities<ChalkIr>>>,
+
     /// For each foreign type `extern { type A; }`
     pub foreign_ty_ids: BTreeMap<Identifier, ForeignDefId<ChalkIr>>,
+
+    /// From module path to the names in scope in the module. Used during
+    /// lowering only.
+    pub module_scopes: BTreeMap<Identifier, BTreeMap<Identifier, Identifier>>,
+
+    /// The span of the name of each item in the program text, used to point
+    /// at the items that errors refer to.
+    pub item_spans: ItemSpans,
+}
+
+/// The span of the name of each item in the program text, by the raw id of
+/// the item. Programs that only differ in where their items are in the text
+/// are the same program, so the spans are ignored when comparing programs.
+#[derive(Clone, Debug, Default)]
+pub struct ItemSpans(BTreeMap<RawId, Span>);
+
+impl ItemSpans {
+    pub fn get(&self, raw_id: RawId) -> Option<Span> {
+        self.0.get(&raw_id).copied()
+    }
+}
+
+impl FromIterator<(RawId, Span)> for ItemSpans {
+    fn from_iter<T: IntoIterator<Item = (RawId, Span)>>(iter: T) -> Self {
+        ItemSpans(iter.into_iter().collect())
+    }
+}
+
+impl PartialEq for ItemSpans {
+    fn eq(&self, _: &ItemSpans) -> bool {
+        true
+    }
 }
 
+impl Eq for ItemSpans {}
+
 impl Program {
+    /// Returns `true` if all the item ids of `previous` are still ids of
+    /// items of the same kind in this program, so that the queries made of
+    /// `previous` can be made of this program too.
+    pub fn keeps_item_ids_of(&self, previous: &Program) -> bool {
+        fn keeps_keys<K: Ord, V, W>(previous: &BTreeMap<K, V>, current: &BTreeMap<K, W>) -> bool {
+            previous.keys().all(|key| current.contains_key(key))
+        }
+
+        keeps_keys(&previous.adt_data, &self.adt_data)
+            && keeps_keys(&previous.adt_reprs, &self.adt_reprs)
+            && keeps_keys(&previous.adt_variances, &self.adt_variances)
+            && keeps_keys(&previous.fn_def_data, &self.fn_def_data)
+            && keeps_keys(&previous.fn_def_variances, &self.fn_def_variances)
+            && keeps_keys(&previous.closure_upvars, &self.closure_upvars)
+            && keeps_keys(
+                &previous.closure_inputs_and_output,
+                &self.closure_inputs_and_output,
+            )
+            && keeps_keys(&previous.closure_closure_kind, &self.closure_closure_kind)
+            && keeps_keys(&previous.generator_data, &self.generator_data)
+            && keeps_keys(
+                &previous.generator_witness_data,
+                &self.generator_witness_data,
+            )
+            && keeps_keys(&previous.trait_data, &self.trait_data)
+            && keeps_keys(&previous.impl_data, &self.impl_data)
+            && keeps_keys(&previous.inherent_impl_data, &self.inherent_impl_data)
+            && keeps_keys(&previous.associated_ty_values, &self.associated_ty_values)
+            && keeps_keys(&previous.opaque_ty_data, &self.opaque_ty_data)
+            && keeps_keys(&previous.hidden_opaque_types, &self.hidden_opaque_types)
+            && keeps_keys(&previous.associated_ty_data, &self.associated_ty_data)
+    }
+
     /// Returns the ids for all impls declared in this crate.
     pub(crate) fn local_impl_ids(&self) -> Vec<ImplId<ChalkIr>> {
         self.impl_data
@@ -396,6 +477,10 @@ impl RustIrDatabase<ChalkIr> for Program {
         self.impl_data[&id].clone()
     }
 
+    fn inherent_impl_datum(&self, id: ImplId<ChalkIr>) -> Arc<InherentImplDatum<ChalkIr>> {
+        self.inherent_impl_data[&id].clone()
+    }
+
     fn associated_ty_value(
         &self,
         id: AssociatedTyValueId<ChalkIr>,
@@ -469,6 +554,23 @@ impl RustIrDatabase<ChalkIr> for Program {
             .collect()
     }
 
+    /// The priorities are worked out by the coherence check when the
+    /// program is checked; before that, as within the check itself, no impl
+    /// specializes another.
+    fn specialization_priorities(
+        &self,
+        trait_id: TraitId<ChalkIr>,
+    ) -> Arc<SpecializationPriorities<ChalkIr>> {
+        self.specialization_priorities
+            .get(&trait_id)
+            .cloned()
+            .unwrap_or_else(|| Arc::new(SpecializationPriorities::new()))
+    }
+
+    fn inherent_impls(&self) -> Vec<ImplId<ChalkIr>> {
+        self.inherent_impl_data.keys().copied().collect()
+    }
+
     fn impl_provided_for(
         &self,
         auto_trait_id: TraitId<ChalkIr>,
@@ -590,6 +692,10 @@ impl RustIrDatabase<ChalkIr> for Program {
             .to_string()
     }
 
+    fn method_name(&self, impl_id: ImplId<ChalkIr>, index: usize) -> String {
+        self.method_names[&impl_id][index].to_string()
+    }
+
     // Mirrors current (07a63e6d1fabf3560e8e1e17c1d56b10a06152d9) implementation in rustc
     fn discriminant_type(&self, ty: Ty<ChalkIr>) -> Ty<ChalkIr> {
         let interner = self.interner();
@@ -8,6 +8,7 @@ use crate::program::Program;
 use crate::program_environment::ProgramEnvironment;
 use crate::tls;
 use crate::SolverChoice;
+use chalk_engine::cache::AnswerCache;
 use chalk_ir::{Substitution, TraitId};
 use chalk_solve::clauses::builder::ClauseBuilder;
 use chalk_solve::clauses::program_clauses::ToProgramClauses;
@@ -34,6 +35,18 @@ pub trait LoweringDatabase:
     #[salsa::input]
     fn solver_choice(&self) -> SolverChoice;
 
+    /// The answers kept across changes to the program, for solver choices
+    /// that ask for it.
+    #[salsa::input]
+    fn answer_cache(&self) -> ArcEq<ProgramAnswerCache>;
+
+    /// The lowered IR along with all the errors found while lowering it. If
+    /// there are any, the program is only partially lowered; see the `Lower`
+    /// impl for `Program`. Syntax errors are reported the same way, before
+    /// the lowering errors: the items that could be parsed are lowered.
+    fn partial_program_ir(&self) -> (Arc<Program>, Vec<ChalkError>);
+
+    /// The lowered IR, or the first error found while lowering it.
     fn program_ir(&self) -> Result<Arc<Program>, ChalkError>;
 
     /// Performs coherence check and computes which impls specialize
@@ -122,9 +135,24 @@ impl<T> Clone for ArcEq<T> {
     }
 }
 
-fn program_ir(db: &dyn LoweringDatabase) -> Result<Arc<Program>, ChalkError> {
+fn partial_program_ir(db: &dyn LoweringDatabase) -> (Arc<Program>, Vec<ChalkError>) {
     let text = db.program_text();
-    Ok(Arc::new(chalk_parse::parse_program(&text)?.lower()?))
+    let (program, syntax_errors) = chalk_parse::parse_program_with_errors(&text);
+    let (program, lowering_errors) = program.lower();
+    let errors = syntax_errors
+        .into_iter()
+        .map(ChalkError::from)
+        .chain(lowering_errors.into_iter().map(ChalkError::from))
+        .collect();
+    (Arc::new(program), errors)
+}
+
+fn program_ir(db: &dyn LoweringDatabase) -> Result<Arc<Program>, ChalkError> {
+    let (program, errors) = db.partial_program_ir();
+    match errors.into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(program),
+    }
 }
 
 fn orphan_check(db: &dyn LoweringDatabase) -> Result<(), ChalkError> {
@@ -133,43 +161,60 @@ fn orphan_check(db: &dyn LoweringDatabase) -> Result<(), ChalkError> {
     tls::set_current_program(&program, || -> Result<(), ChalkError> {
         let local_impls = program.local_impl_ids();
         for impl_id in local_impls {
-            let mut solver = db.solver_choice().into_solver();
-            orphan::perform_orphan_check::<ChalkIr>(db.upcast(), &mut *solver, impl_id)?;
+            let mut solver = checking_solver_choice(db).into_solver();
+            // Like the coherence check, this uses the program rather than
+            // `db`, which would ask for the result of the coherence check.
+            orphan::perform_orphan_check::<ChalkIr>(&*program, &mut *solver, impl_id)?;
         }
         Ok(())
     })
+    .map_err(|error| error.with_program_labels(&program))
 }
 
 fn coherence(
     db: &dyn LoweringDatabase,
 ) -> Result<BTreeMap<TraitId<ChalkIr>, Arc<SpecializationPriorities<ChalkIr>>>, ChalkError> {
     let program = db.program_ir()?;
-    let solver_choice = db.solver_choice();
+    let solver_choice = checking_solver_choice(db);
     let priorities_map = tls::set_current_program(&program, || -> Result<_, ChalkError> {
         let solver_builder = || solver_choice.into_solver();
         let priorities_map: Result<BTreeMap<_, _>, ChalkError> = program
             .trait_data
             .keys()
             .map(|&trait_id| {
+                // The specialization priorities of `db` are the result of
+                // this query, so the check is done on the program itself,
+                // in which no impl specializes another yet.
                 let solver: CoherenceSolver<ChalkIr> =
-                    CoherenceSolver::new(db.upcast(), &solver_builder, trait_id);
+                    CoherenceSolver::new(&*program, &solver_builder, trait_id);
                 let priorities = solver.specialization_priorities()?;
                 Ok((trait_id, priorities))
             })
             .collect();
         let priorities_map = priorities_map?;
         Ok(priorities_map)
-    });
+    })
+    .map_err(|error| error.with_program_labels(&program));
     let () = db.orphan_check()?;
     priorities_map
 }
 
+/// The solver used to check the program. Unlike the goals posed by the user,
+/// the checks are not limited by the budget of the solver choice, since
+/// running out of it would make them report errors that aren't there.
+fn checking_solver_choice(db: &dyn LoweringDatabase) -> SolverChoice {
+    db.solver_choice().with_budget(None)
+}
+
 fn checked_program(db: &dyn LoweringDatabase) -> Result<Arc<Program>, ChalkError> {
     let program = db.program_ir()?;
 
-    db.coherence()?;
+    let program = Arc::new(Program {
+        specialization_priorities: db.coherence()?,
+        ..(*program).clone()
+    });
 
-    let solver_choice = db.solver_choice();
+    let solver_choice = checking_solver_choice(db);
     let () = tls::set_current_program(&program, || -> Result<(), ChalkError> {
         let solver_builder = || solver_choice.into_solver();
         let solver: wf::WfSolver<ChalkIr> = wf::WfSolver::new(db.upcast(), &solver_builder);
@@ -186,7 +231,8 @@ fn checked_program(db: &dyn LoweringDatabase) -> Result<Arc<Program>, ChalkError
         }
 
         Ok(())
-    })?;
+    })
+    .map_err(|error| error.with_program_labels(&program))?;
 
     Ok(program)
 }
@@ -251,5 +297,53 @@ fn environment(db: &dyn LoweringDatabase) -> Result<Arc<ProgramEnvironment>, Cha
 fn solver(db: &dyn LoweringDatabase) -> ArcEq<Mutex<Box<dyn Solver<ChalkIr>>>> {
     db.salsa_runtime().report_untracked_read();
     let choice = db.solver_choice();
-    ArcEq::new(Mutex::new(choice.into_solver()))
+    if !choice.caches_answers() {
+        return ArcEq::new(Mutex::new(choice.into_solver()));
+    }
+    let answer_cache = db.answer_cache();
+    if let Ok(program) = db.program_ir() {
+        answer_cache.check_against(db.upcast(), &program);
+    }
+    ArcEq::new(Mutex::new(
+        choice.into_solver_sharing_answers(&answer_cache.answers),
+    ))
+}
+
+/// The answer cache of a database, along with the program its answers
+/// were last checked against.
+#[derive(Debug, Default)]
+pub struct ProgramAnswerCache {
+    answers: Arc<Mutex<AnswerCache<ChalkIr>>>,
+    checked_program: Mutex<Option<Arc<Program>>>,
+}
+
+impl ProgramAnswerCache {
+    /// Drops the answers that no longer hold in `program`, given by `db`.
+    /// If some item ids of the program they were last checked against are
+    /// gone, there is no way to tell what the answers depend on, so all of
+    /// them are dropped.
+    fn check_against(&self, db: &dyn RustIrDatabase<ChalkIr>, program: &Arc<Program>) {
+        let mut checked_program = self.checked_program.lock().unwrap();
+        if let Some(previous) = &*checked_program {
+            if Arc::ptr_eq(previous, program) {
+                return;
+            }
+            let mut answers = self.answers.lock().unwrap();
+            if program.keeps_item_ids_of(previous) {
+                answers.retain_valid(db);
+            } else {
+                answers.clear();
+            }
+        }
+        *checked_program = Some(program.clone());
+    }
+
+    /// The number of tables in the cache.
+    pub fn len(&self) -> usize {
+        self.answers.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
@@ -168,9 +168,11 @@ where
 {
     let p: Arc<dyn DebugContext> = p.clone();
     PROGRAM.with(|prog_cell| {
-        *prog_cell.borrow_mut() = Some(p);
+        // Queries like the coherence check set the program as well, and
+        // may run while solving a goal that has set it already.
+        let old = prog_cell.replace(Some(p));
         let r = op();
-        *prog_cell.borrow_mut() = None;
+        *prog_cell.borrow_mut() = old;
         r
     })
 }
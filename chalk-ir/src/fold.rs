@@ -583,6 +583,13 @@ where
                 }),
             }
             .intern(folder.interner())),
+            ConstValue::Unevaluated(unevaluated) => Ok(ConstData {
+                ty: fold_ty()?,
+                value: ConstValue::Unevaluated(
+                    unevaluated.clone().fold_with(folder, outer_binder)?,
+                ),
+            }
+            .intern(folder.interner())),
         }
     }
 }
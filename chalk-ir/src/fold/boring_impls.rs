@@ -224,6 +224,7 @@ copy_fold!(Scalar);
 copy_fold!(ClausePriority);
 copy_fold!(Mutability);
 copy_fold!(Safety);
+copy_fold!(ConstOp);
 
 #[doc(hidden)]
 #[macro_export]
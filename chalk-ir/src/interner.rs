@@ -654,6 +654,88 @@ pub trait Interner: Debug + Copy + Eq + Ord + Hash {
     fn variances_data<'a>(&self, variances: &'a Self::InternedVariances) -> &'a [Variance];
 }
 
+/// An interner whose types can be serialized and deserialized with serde,
+/// through the `serde` feature.
+///
+/// Interned values (types, goals, substitutions and so on) are encoded
+/// through their data, so they are looked up in the interner given by
+/// [`serde_interner`] when serializing, and interned with it again when
+/// deserializing. The interner only has to say how to encode the ids and
+/// other opaque data it supplies itself.
+///
+/// [`serde_interner`]: SerdeInterner::serde_interner
+#[cfg(feature = "serde")]
+pub trait SerdeInterner: Interner {
+    /// The interner to look up and intern values with. Since serde gives
+    /// no way to pass it in, it has to be available from anywhere.
+    fn serde_interner() -> Self;
+
+    /// Serialize the id of an item, such as a trait or an impl.
+    fn serialize_def_id<S: serde::Serializer>(
+        def_id: &Self::DefId,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>;
+
+    /// Deserialize an id serialized by [`serialize_def_id`].
+    ///
+    /// [`serialize_def_id`]: SerdeInterner::serialize_def_id
+    fn deserialize_def_id<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self::DefId, D::Error>;
+
+    /// Serialize the id of an ADT.
+    fn serialize_adt_id<S: serde::Serializer>(
+        adt_id: &Self::InternedAdtId,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>;
+
+    /// Deserialize an id serialized by [`serialize_adt_id`].
+    ///
+    /// [`serialize_adt_id`]: SerdeInterner::serialize_adt_id
+    fn deserialize_adt_id<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self::InternedAdtId, D::Error>;
+
+    /// Serialize the value of a concrete constant.
+    fn serialize_concrete_const<S: serde::Serializer>(
+        value: &Self::InternedConcreteConst,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>;
+
+    /// Deserialize a value serialized by [`serialize_concrete_const`].
+    ///
+    /// [`serialize_concrete_const`]: SerdeInterner::serialize_concrete_const
+    fn deserialize_concrete_const<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self::InternedConcreteConst, D::Error>;
+
+    /// Serialize the ABI of a function.
+    fn serialize_fn_abi<S: serde::Serializer>(
+        abi: &Self::FnAbi,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>;
+
+    /// Deserialize an ABI serialized by [`serialize_fn_abi`].
+    ///
+    /// [`serialize_fn_abi`]: SerdeInterner::serialize_fn_abi
+    fn deserialize_fn_abi<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self::FnAbi, D::Error>;
+
+    /// Serialize the name of an associated type.
+    fn serialize_identifier<S: serde::Serializer>(
+        identifier: &Self::Identifier,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>;
+
+    /// Deserialize a name serialized by [`serialize_identifier`].
+    ///
+    /// [`serialize_identifier`]: SerdeInterner::serialize_identifier
+    fn deserialize_identifier<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self::Identifier, D::Error>;
+}
+
 /// Implemented by types that have an associated interner (which
 /// are virtually all of the types in chalk-ir, for example).
 /// This lets us map from a type like `Ty<I>` to the parameter `I`.
@@ -11,6 +11,10 @@ use crate::fold::shift::Shift;
 use crate::fold::{Fold, Folder, Subst, SuperFold};
 use crate::visit::{ControlFlow, SuperVisit, Visit, VisitExt, Visitor};
 use chalk_derive::{Fold, HasInterner, SuperVisit, Visit, Zip};
+#[cfg(feature = "serde")]
+use interner::SerdeInterner;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 
 pub use crate::debug::SeparatorTraitRef;
@@ -18,6 +22,7 @@ pub use crate::debug::SeparatorTraitRef;
 extern crate bitflags;
 /// Uninhabited (empty) type, used in combination with `PhantomData`.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Void {}
 
 /// Many of our internal operations (e.g., unification) are an attempt
@@ -72,8 +77,12 @@ use interner::{HasInterner, Interner};
 pub mod could_match;
 pub mod debug;
 
+#[cfg(feature = "serde")]
+mod serde_impls;
+
 /// Variance
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Variance {
     /// a <: b
     Covariant,
@@ -149,6 +158,8 @@ impl Variance {
 #[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, HasInterner)]
 /// The set of assumptions we've made so far, and the current number of
 /// universal (forall) quantifiers we're within.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct Environment<I: Interner> {
     /// The clauses in the environment.
     pub clauses: ProgramClauses<I>,
@@ -198,6 +209,14 @@ impl<I: Interner> Environment<I> {
 /// A goal with an environment to solve it in.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, Visit)]
 #[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "G: Serialize, G::Interner: SerdeInterner",
+        deserialize = "G: Deserialize<'de>, G::Interner: SerdeInterner"
+    ))
+)]
 pub struct InEnvironment<G: HasInterner> {
     pub environment: Environment<G::Interner>,
     pub goal: G,
@@ -237,6 +256,7 @@ impl<G: HasInterner> HasInterner for InEnvironment<G> {
 /// Different signed int types.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum IntTy {
     Isize,
     I8,
@@ -249,6 +269,7 @@ pub enum IntTy {
 /// Different unsigned int types.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum UintTy {
     Usize,
     U8,
@@ -261,6 +282,7 @@ pub enum UintTy {
 /// Different kinds of float types.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum FloatTy {
     F32,
     F64,
@@ -269,6 +291,7 @@ pub enum FloatTy {
 /// Types of scalar values.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Scalar {
     Bool,
     Char,
@@ -279,6 +302,7 @@ pub enum Scalar {
 
 /// Whether a function is safe or not.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Safety {
     /// Safe
     Safe,
@@ -288,6 +312,7 @@ pub enum Safety {
 
 /// Whether a type is mutable or not.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Mutability {
     /// Mutable
     Mut,
@@ -303,6 +328,7 @@ pub enum Mutability {
 /// `Goal(!U1)` (the quantifier was moved to the environment and replaced with a universe index)
 /// See https://rustc-dev-guide.rust-lang.org/borrow_check/region_inference.html#placeholders-and-universes for more.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct UniverseIndex {
     /// The counter for the universe index, starts with 0.
     pub counter: usize,
@@ -581,6 +607,8 @@ bitflags! {
 }
 /// Type data, which holds the actual type information.
 #[derive(Clone, PartialEq, Eq, Hash, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub enum TyKind<I: Interner> {
     /// Abstract data types, i.e., structs, unions, or enumerations.
     /// For example, a type like `Vec<T>`.
@@ -708,17 +736,7 @@ impl<I: Interner> TyKind<I> {
             TyKind::Array(ty, const_ty) => {
                 let flags = ty.data(interner).flags;
                 let const_data = const_ty.data(interner);
-                flags
-                    | const_data.ty.data(interner).flags
-                    | match const_data.value {
-                        ConstValue::BoundVar(_) | ConstValue::Concrete(_) => TypeFlags::empty(),
-                        ConstValue::InferenceVar(_) => {
-                            TypeFlags::HAS_CT_INFER | TypeFlags::STILL_FURTHER_SPECIALIZABLE
-                        }
-                        ConstValue::Placeholder(_) => {
-                            TypeFlags::HAS_CT_PLACEHOLDER | TypeFlags::STILL_FURTHER_SPECIALIZABLE
-                        }
-                    }
+                flags | const_data.ty.data(interner).flags | const_ty.compute_flags(interner)
             }
             TyKind::Placeholder(_) => TypeFlags::HAS_TY_PLACEHOLDER,
             TyKind::Dyn(dyn_ty) => {
@@ -778,6 +796,7 @@ impl<I: Interner> TyKind<I> {
 /// they appear in the *innermost* binder enclosing the `...`. The
 /// indices identify the location *within* that binder.
 #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BoundVar {
     /// Debruijn index, which identifies the binder.
     pub debruijn: DebruijnIndex,
@@ -869,6 +888,7 @@ impl BoundVar {
 ///
 /// [de Bruijn index]: https://en.wikipedia.org/wiki/De_Bruijn_index
 #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DebruijnIndex {
     depth: u32,
 }
@@ -1027,6 +1047,8 @@ impl DebruijnIndex {
 /// a bound type with debruijn index 1 (i.e., skipping through one
 /// level of binder).
 #[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct DynTy<I: Interner> {
     /// The unknown self type.
     pub bounds: Binders<QuantifiedWhereClauses<I>>,
@@ -1044,6 +1066,7 @@ where
 
 /// A type, lifetime or constant whose value is being inferred.
 #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct InferenceVar {
     index: u32,
 }
@@ -1083,13 +1106,24 @@ impl InferenceVar {
 /// A function signature.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, HasInterner, Debug)]
 #[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct FnSig<I: Interner> {
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "I::serialize_fn_abi",
+            deserialize_with = "I::deserialize_fn_abi"
+        )
+    )]
     pub abi: I::FnAbi,
     pub safety: Safety,
     pub variadic: bool,
 }
 /// A wrapper for the substs on a Fn.
 #[derive(Clone, PartialEq, Eq, Hash, HasInterner, Fold, Visit)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct FnSubst<I: Interner>(pub Substitution<I>);
 
 impl<I: Interner> Copy for FnSubst<I> where I::InternedSubstitution: Copy {}
@@ -1098,6 +1132,8 @@ impl<I: Interner> Copy for FnSubst<I> where I::InternedSubstitution: Copy {}
 /// and we use deBruijn indices within `self.ty`
 #[derive(Clone, PartialEq, Eq, Hash, HasInterner)]
 #[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct FnPointer<I: Interner> {
     pub num_binders: usize,
     pub sig: FnSig<I>,
@@ -1180,12 +1216,34 @@ impl<I: Interner> Const<I> {
             ConstValue::InferenceVar(_) => false,
             ConstValue::Placeholder(_) => false,
             ConstValue::Concrete(_) => false,
+            ConstValue::Unevaluated(unevaluated) => {
+                unevaluated.lhs.needs_shift(interner) || unevaluated.rhs.needs_shift(interner)
+            }
+        }
+    }
+
+    /// Compute type flags for the value of the constant, which does not
+    /// include those of its type.
+    fn compute_flags(&self, interner: &I) -> TypeFlags {
+        match &self.data(interner).value {
+            ConstValue::BoundVar(_) | ConstValue::Concrete(_) => TypeFlags::empty(),
+            ConstValue::InferenceVar(_) => {
+                TypeFlags::HAS_CT_INFER | TypeFlags::STILL_FURTHER_SPECIALIZABLE
+            }
+            ConstValue::Placeholder(_) => {
+                TypeFlags::HAS_CT_PLACEHOLDER | TypeFlags::STILL_FURTHER_SPECIALIZABLE
+            }
+            ConstValue::Unevaluated(unevaluated) => {
+                unevaluated.lhs.compute_flags(interner) | unevaluated.rhs.compute_flags(interner)
+            }
         }
     }
 }
 
 /// Constant data, containing the constant's type and value.
 #[derive(Clone, PartialEq, Eq, Hash, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct ConstData<I: Interner> {
     /// Type that holds the constant.
     pub ty: Ty<I>,
@@ -1195,6 +1253,8 @@ pub struct ConstData<I: Interner> {
 
 /// A constant value, not necessarily concrete.
 #[derive(Clone, PartialEq, Eq, Hash, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub enum ConstValue<I: Interner> {
     /// Bound var (e.g. a parameter).
     BoundVar(BoundVar),
@@ -1204,10 +1264,10 @@ pub enum ConstValue<I: Interner> {
     Placeholder(PlaceholderIndex),
     /// Concrete constant value.
     Concrete(ConcreteConst<I>),
+    /// Constant expression that is not evaluated, like `N + 1`.
+    Unevaluated(UnevaluatedConst<I>),
 }
 
-impl<I: Interner> Copy for ConstValue<I> where I::InternedConcreteConst: Copy {}
-
 impl<I: Interner> ConstData<I> {
     /// Wraps the constant data in a `Const`.
     pub fn intern(self, interner: &I) -> Const<I> {
@@ -1218,8 +1278,17 @@ impl<I: Interner> ConstData<I> {
 /// Concrete constant, whose value is known (as opposed to
 /// inferred constants and placeholders).
 #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct ConcreteConst<I: Interner> {
     /// The interned constant.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "I::serialize_concrete_const",
+            deserialize_with = "I::deserialize_concrete_const"
+        )
+    )]
     pub interned: I::InternedConcreteConst,
 }
 
@@ -1230,6 +1299,46 @@ impl<I: Interner> ConcreteConst<I> {
     }
 }
 
+/// A constant expression, like `N + 1`, that chalk does not evaluate. Two
+/// such expressions are equal only if they apply the same operator to equal
+/// operands, so `N + 1` and `1 + N` are different constants.
+#[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
+pub struct UnevaluatedConst<I: Interner> {
+    /// The operator applied.
+    pub op: ConstOp,
+    /// The left operand.
+    pub lhs: Const<I>,
+    /// The right operand.
+    pub rhs: Const<I>,
+}
+
+/// An operator of an [`UnevaluatedConst`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[allow(missing_docs)]
+pub enum ConstOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl ConstOp {
+    /// The symbol of the operator in Rust, like `+`.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            ConstOp::Add => "+",
+            ConstOp::Sub => "-",
+            ConstOp::Mul => "*",
+            ConstOp::Div => "/",
+            ConstOp::Rem => "%",
+        }
+    }
+}
+
 /// A Rust lifetime.
 #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, HasInterner)]
 pub struct Lifetime<I: Interner> {
@@ -1310,6 +1419,8 @@ impl<I: Interner> Lifetime<I> {
 
 /// Lifetime data, including what kind of lifetime it is and what it points to.
 #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub enum LifetimeData<I: Interner> {
     /// See TyKind::BoundVar.
     BoundVar(BoundVar),
@@ -1342,6 +1453,7 @@ impl<I: Interner> LifetimeData<I> {
 /// Two indexes are required, the one of the universe itself
 /// and the relative index inside the universe.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PlaceholderIndex {
     /// Index *of* the universe.
     pub ui: UniverseIndex,
@@ -1381,6 +1493,7 @@ impl PlaceholderIndex {
 /// inference process.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TyVariableKind {
     General,
     Integer,
@@ -1390,6 +1503,8 @@ pub enum TyVariableKind {
 /// The "kind" of variable. Type, lifetime or constant.
 #[derive(Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub enum VariableKind<I: Interner> {
     Ty(TyVariableKind),
     Lifetime,
@@ -1502,19 +1617,7 @@ impl<I: Interner> GenericArg<I> {
             GenericArgData::Lifetime(lifetime) => lifetime.compute_flags(interner),
             GenericArgData::Const(constant) => {
                 let data = constant.data(&interner);
-                let flags = data.ty.data(interner).flags;
-                match data.value {
-                    ConstValue::BoundVar(_) => flags,
-                    ConstValue::InferenceVar(_) => {
-                        flags | TypeFlags::HAS_CT_INFER | TypeFlags::STILL_FURTHER_SPECIALIZABLE
-                    }
-                    ConstValue::Placeholder(_) => {
-                        flags
-                            | TypeFlags::HAS_CT_PLACEHOLDER
-                            | TypeFlags::STILL_FURTHER_SPECIALIZABLE
-                    }
-                    ConstValue::Concrete(_) => flags,
-                }
+                data.ty.data(interner).flags | constant.compute_flags(interner)
             }
         }
     }
@@ -1522,6 +1625,8 @@ impl<I: Interner> GenericArg<I> {
 
 /// Generic arguments data.
 #[derive(Clone, PartialEq, Eq, Hash, Visit, Fold, Zip)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub enum GenericArgData<I: Interner> {
     /// Type argument
     Ty(Ty<I>),
@@ -1548,6 +1653,14 @@ impl<I: Interner> GenericArgData<I> {
 
 /// A value with an associated variable kind.
 #[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "I: SerdeInterner, T: Serialize",
+        deserialize = "I: SerdeInterner, T: Deserialize<'de>"
+    ))
+)]
 pub struct WithKind<I: Interner, T> {
     /// The associated variable kind.
     pub kind: VariableKind<I>,
@@ -1607,6 +1720,8 @@ pub type CanonicalVarKind<I: Interner> = WithKind<I, UniverseIndex>;
 
 /// An alias, which is a trait indirection such as a projection or opaque type.
 #[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, HasInterner, Zip)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub enum AliasTy<I: Interner> {
     /// An associated type projection.
     Projection(ProjectionTy<I>),
@@ -1637,6 +1752,8 @@ impl<I: Interner> AliasTy<I> {
 
 /// A projection `<P0 as TraitName<P1..Pn>>::AssocItem<Pn+1..Pm>`.
 #[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct ProjectionTy<I: Interner> {
     /// The id for the associated type member.
     pub associated_ty_id: AssocTypeId<I>,
@@ -1659,6 +1776,8 @@ impl<I: Interner> ProjectionTy<I> {
 
 /// An opaque type `opaque type T<..>: Trait = HiddenTy`.
 #[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct OpaqueTy<I: Interner> {
     /// The id for the opaque type.
     pub opaque_ty_id: OpaqueTyId<I>,
@@ -1675,6 +1794,8 @@ impl<I: Interner> Copy for OpaqueTy<I> where I::InternedSubstitution: Copy {}
 /// - `<P0 as Trait<P1..Pn>>` (e.g. `i32 as Copy`), which casts the type to
 ///   that specific trait.
 #[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct TraitRef<I: Interner> {
     /// The trait id.
     pub trait_id: TraitId<I>,
@@ -1713,6 +1834,8 @@ impl<I: Interner> TraitRef<I> {
 /// is a superset of the value of `'b`.
 #[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, HasInterner, Zip)]
 #[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct LifetimeOutlives<I: Interner> {
     pub a: Lifetime<I>,
     pub b: Lifetime<I>,
@@ -1723,6 +1846,8 @@ impl<I: Interner> Copy for LifetimeOutlives<I> where I::InternedLifetime: Copy {
 /// Type outlives, which for `T: 'a` checks that the type `T`
 /// lives at least as long as the lifetime `'a`
 #[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, HasInterner, Zip)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct TypeOutlives<I: Interner> {
     /// The type which must outlive the given lifetime.
     pub ty: Ty<I>,
@@ -1739,6 +1864,8 @@ where
 
 /// Where clauses that can be written by a Rust programmer.
 #[derive(Clone, PartialEq, Eq, Hash, Fold, SuperVisit, HasInterner, Zip)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub enum WhereClause<I: Interner> {
     /// Type implements a trait.
     Implemented(TraitRef<I>),
@@ -1760,6 +1887,8 @@ where
 
 /// Checks whether a type or trait ref is well-formed.
 #[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, HasInterner, Zip)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub enum WellFormed<I: Interner> {
     /// A predicate which is true when some trait ref is well-formed.
     /// For example, given the following trait definitions:
@@ -1798,6 +1927,8 @@ where
 
 /// Checks whether a type or trait ref can be derived from the contents of the environment.
 #[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, HasInterner, Zip)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub enum FromEnv<I: Interner> {
     /// A predicate which enables deriving everything which should be true if we *know* that
     /// some trait ref is well-formed. For example given the above trait definitions, we can use
@@ -1837,6 +1968,8 @@ where
 /// logical statement. As much as possible, the Chalk solver should avoid
 /// decomposing this enum, and instead treat its values opaquely.
 #[derive(Clone, PartialEq, Eq, Hash, Fold, SuperVisit, HasInterner, Zip)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub enum DomainGoal<I: Interner> {
     /// Simple goal that is true if the where clause is true.
     Holds(WhereClause<I>),
@@ -1997,6 +2130,8 @@ impl<I: Interner> DomainGoal<I> {
 /// Equality goal: tries to prove that two values are equal.
 #[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, Zip)]
 #[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct EqGoal<I: Interner> {
     pub a: GenericArg<I>,
     pub b: GenericArg<I>,
@@ -2007,6 +2142,8 @@ impl<I: Interner> Copy for EqGoal<I> where I::InternedGenericArg: Copy {}
 /// Subtype goal: tries to prove that `a` is a subtype of `b`
 #[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, Zip)]
 #[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct SubtypeGoal<I: Interner> {
     pub a: Ty<I>,
     pub b: Ty<I>,
@@ -2020,6 +2157,8 @@ impl<I: Interner> Copy for SubtypeGoal<I> where I::InternedType: Copy {}
 /// `U = V`.
 #[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, Zip)]
 #[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct Normalize<I: Interner> {
     pub alias: AliasTy<I>,
     pub ty: Ty<I>,
@@ -2035,6 +2174,8 @@ where
 /// Proves **equality** between an alias and a type.
 #[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, Zip)]
 #[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct AliasEq<I: Interner> {
     pub alias: AliasTy<I>,
     pub ty: Ty<I>,
@@ -2059,6 +2200,14 @@ impl<I: Interner> HasInterner for AliasEq<I> {
 /// (IOW, we use deBruijn indices, where binders are introduced in reverse order
 /// of `self.binders`.)
 #[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: Serialize, T::Interner: SerdeInterner",
+        deserialize = "T: Deserialize<'de>, T::Interner: SerdeInterner"
+    ))
+)]
 pub struct Binders<T: HasInterner> {
     /// The binders that quantify over the value.
     pub binders: VariableKinds<T::Interner>,
@@ -2297,6 +2446,8 @@ where
 /// `conditions = cond_1 && cond_2 && ...` is the conjunction of the individual
 /// conditions.
 #[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, HasInterner, Zip)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct ProgramClauseImplication<I: Interner> {
     /// The consequence of the clause, which holds if the conditions holds.
     pub consequence: DomainGoal<I>,
@@ -2313,6 +2464,7 @@ pub struct ProgramClauseImplication<I: Interner> {
 
 /// Specifies how important an implication is.
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ClausePriority {
     /// High priority, the solver should prioritize this.
     High,
@@ -2333,6 +2485,8 @@ impl std::ops::BitAnd for ClausePriority {
 
 /// Contains the data for a program clause.
 #[derive(Clone, PartialEq, Eq, Hash, Fold, HasInterner, Zip)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct ProgramClauseData<I: Interner>(pub Binders<ProgramClauseImplication<I>>);
 
 impl<I: Interner> ProgramClauseImplication<I> {
@@ -2402,6 +2556,14 @@ impl<I: Interner> ProgramClause<I> {
 /// first appearance; the kind/universe of the variable is recorded in the
 /// `binders` field.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: Serialize, T::Interner: SerdeInterner",
+        deserialize = "T: Deserialize<'de>, T::Interner: SerdeInterner"
+    ))
+)]
 pub struct Canonical<T: HasInterner> {
     /// The item that is canonicalized.
     pub value: T,
@@ -2421,6 +2583,14 @@ impl<T: HasInterner> HasInterner for Canonical<T> {
 ///
 /// To produce one of these values, use the `u_canonicalize` method.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: Serialize, T::Interner: SerdeInterner",
+        deserialize = "T: Deserialize<'de>, T::Interner: SerdeInterner"
+    ))
+)]
 pub struct UCanonical<T: HasInterner> {
     /// The wrapped `Canonical`.
     pub canonical: Canonical<T>,
@@ -2583,6 +2753,8 @@ where
 
 #[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, HasInterner, Zip)]
 /// A general goal; this is the full range of questions you can pose to Chalk.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub enum GoalData<I: Interner> {
     /// Introduces a binding at depth 0, shifting other bindings up
     /// (deBruijn index).
@@ -2639,6 +2811,7 @@ impl<I: Interner> GoalData<I> {
 
 /// Kinds of quantifiers in the logic, such as `forall` and `exists`.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum QuantifierKind {
     /// Universal quantifier `ForAll`.
     ///
@@ -2665,6 +2838,8 @@ pub enum QuantifierKind {
 /// for later checking. This allows for decoupling between type and region
 /// checking in the compiler.
 #[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, HasInterner, Zip)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub enum Constraint<I: Interner> {
     /// Outlives constraint `'a: 'b`, indicating that the value of `'a` must be
     /// a superset of the value of `'b`.
@@ -3046,6 +3221,8 @@ impl<I: Interner> Variances<I> {
 /// and the constraints represents any region constraints that must
 /// additionally be solved.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, Visit, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct ConstrainedSubst<I: Interner> {
     /// The substitution that is being constrained.
     ///
@@ -3058,6 +3235,8 @@ pub struct ConstrainedSubst<I: Interner> {
 
 /// The resulting substitution after solving a goal.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, Visit, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct AnswerSubst<I: Interner> {
     /// The substitution result.
     ///
@@ -0,0 +1,90 @@
+//! Serde support for the interned types and ids of the IR, which can't
+//! simply derive it. The other types derive it behind the `serde` feature.
+//!
+//! Interned values are encoded through their data, and are interned again
+//! with [`SerdeInterner::serde_interner`] when deserialized.
+
+use crate::interner::SerdeInterner;
+use crate::*;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Implements `Serialize` and `Deserialize` for an interned value, going
+/// through its data.
+macro_rules! serde_interned {
+    ($ty:ident, $data:ident, $get:ident) => {
+        impl<I: SerdeInterner> Serialize for $ty<I> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.$get(&I::serde_interner()).serialize(serializer)
+            }
+        }
+
+        impl<'de, I: SerdeInterner> Deserialize<'de> for $ty<I> {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let data = $data::deserialize(deserializer)?;
+                Ok($ty::new(&I::serde_interner(), data))
+            }
+        }
+    };
+}
+
+serde_interned!(Ty, TyKind, kind);
+serde_interned!(Lifetime, LifetimeData, data);
+serde_interned!(Const, ConstData, data);
+serde_interned!(GenericArg, GenericArgData, data);
+serde_interned!(Goal, GoalData, data);
+serde_interned!(ProgramClause, ProgramClauseData, data);
+
+/// Implements `Serialize` and `Deserialize` for an interned slice, as a
+/// sequence of its elements.
+macro_rules! serde_interned_slice {
+    ($seq:ident, $elem:ty) => {
+        impl<I: SerdeInterner> Serialize for $seq<I> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.as_slice(&I::serde_interner()).serialize(serializer)
+            }
+        }
+
+        impl<'de, I: SerdeInterner> Deserialize<'de> for $seq<I> {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let elements = Vec::<$elem>::deserialize(deserializer)?;
+                Ok($seq::from_iter(&I::serde_interner(), elements))
+            }
+        }
+    };
+}
+
+serde_interned_slice!(QuantifiedWhereClauses, QuantifiedWhereClause<I>);
+serde_interned_slice!(ProgramClauses, ProgramClause<I>);
+serde_interned_slice!(VariableKinds, VariableKind<I>);
+serde_interned_slice!(CanonicalVarKinds, CanonicalVarKind<I>);
+serde_interned_slice!(Goals, Goal<I>);
+serde_interned_slice!(Constraints, InEnvironment<Constraint<I>>);
+serde_interned_slice!(Substitution, GenericArg<I>);
+serde_interned_slice!(Variances, Variance);
+
+/// Implements `Serialize` and `Deserialize` for ids, with the methods of
+/// the interner for the id they wrap.
+macro_rules! serde_id {
+    ($serialize:ident, $deserialize:ident => $($id:ident),*) => {
+        $(
+            impl<I: SerdeInterner> Serialize for $id<I> {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    I::$serialize(&self.0, serializer)
+                }
+            }
+
+            impl<'de, I: SerdeInterner> Deserialize<'de> for $id<I> {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    I::$deserialize(deserializer).map($id)
+                }
+            }
+        )*
+    };
+}
+
+serde_id!(serialize_adt_id, deserialize_adt_id => AdtId);
+serde_id!(
+    serialize_def_id, deserialize_def_id =>
+    TraitId, ImplId, ClauseId, AssocTypeId, OpaqueTyId, FnDefId, ClosureId, GeneratorId,
+    ForeignDefId
+);
@@ -5,7 +5,7 @@
 //! The more interesting impls of `Visit` remain in the `visit` module.
 
 use crate::{
-    try_break, AdtId, AssocTypeId, ClausePriority, ClosureId, Constraints, ControlFlow,
+    try_break, AdtId, AssocTypeId, ClausePriority, ClosureId, ConstOp, Constraints, ControlFlow,
     DebruijnIndex, FloatTy, FnDefId, ForeignDefId, GeneratorId, GenericArg, Goals, ImplId, IntTy,
     Interner, Mutability, OpaqueTyId, PlaceholderIndex, ProgramClause, ProgramClauses,
     QuantifiedWhereClauses, QuantifierKind, Safety, Scalar, Substitution, SuperVisit, TraitId,
@@ -206,6 +206,7 @@ const_visit!(IntTy);
 const_visit!(FloatTy);
 const_visit!(Mutability);
 const_visit!(Safety);
+const_visit!(ConstOp);
 
 #[doc(hidden)]
 #[macro_export]
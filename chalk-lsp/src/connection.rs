@@ -0,0 +1,78 @@
+//! The connection between the server and a client, as a pair of channels
+//! of messages.
+
+use crate::protocol::Message;
+use std::io;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+/// One end of a connection: messages to the other end go to `sender`, and
+/// messages from it come from `receiver`.
+pub struct Connection {
+    pub sender: Sender<Message>,
+    pub receiver: Receiver<Message>,
+}
+
+/// The threads that move the messages of a connection over stdio.
+pub struct IoThreads {
+    writer: JoinHandle<io::Result<()>>,
+}
+
+impl Connection {
+    /// Creates a connection to a client on the other end of stdin and stdout.
+    /// Messages are read and written by threads of their own, which stop at
+    /// the end of stdin and once the connection is dropped, respectively.
+    pub fn stdio() -> (Connection, IoThreads) {
+        let (reader_sender, receiver) = channel();
+        thread::spawn(move || -> io::Result<()> {
+            let stdin = io::stdin();
+            let mut stdin = stdin.lock();
+            while let Some(message) = Message::read(&mut stdin)? {
+                if reader_sender.send(message).is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        });
+
+        let (sender, writer_receiver) = channel::<Message>();
+        let writer = thread::spawn(move || {
+            let stdout = io::stdout();
+            let mut stdout = stdout.lock();
+            for message in writer_receiver {
+                message.write(&mut stdout)?;
+            }
+            Ok(())
+        });
+
+        (Connection { sender, receiver }, IoThreads { writer })
+    }
+
+    /// Creates the two ends of a connection within the process, for a
+    /// server and a client running on different threads.
+    pub fn memory() -> (Connection, Connection) {
+        let (server_sender, client_receiver) = channel();
+        let (client_sender, server_receiver) = channel();
+        (
+            Connection {
+                sender: server_sender,
+                receiver: server_receiver,
+            },
+            Connection {
+                sender: client_sender,
+                receiver: client_receiver,
+            },
+        )
+    }
+}
+
+impl IoThreads {
+    /// Waits for all the messages to be written, once the connection has
+    /// been dropped.
+    pub fn join(self) -> io::Result<()> {
+        match self.writer.join() {
+            Ok(result) => result,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! An open document, and what the server can tell about it.
+
+use crate::protocol::{
+    Diagnostic, DiagnosticRelatedInformation, Hover, Location, MarkupContent, Position, Range,
+    ERROR,
+};
+use chalk_integration::db::ChalkDatabase;
+use chalk_integration::error::ChalkError;
+use chalk_integration::interner::ChalkIr;
+use chalk_integration::program::Program;
+use chalk_integration::query::LoweringDatabase;
+use chalk_integration::{tls, Identifier, SolverChoice};
+use chalk_parse::ast::{Item, Span};
+use chalk_solve::display::{write_items, WriterState};
+use chalk_solve::logging_db::RecordedItemId;
+use std::collections::BTreeMap;
+
+/// The text of a document, along with the program it is lowered to.
+pub struct Document {
+    text: String,
+    lines: LineIndex,
+    db: ChalkDatabase,
+}
+
+impl Document {
+    pub fn new(text: String) -> Self {
+        Document {
+            lines: LineIndex::new(&text),
+            db: ChalkDatabase::with(&text, SolverChoice::default()),
+            text,
+        }
+    }
+
+    /// The errors in the program. As with the REPL, all syntax and lowering
+    /// errors are reported at once; the later checks only run on a program
+    /// without them. Errors without a location are reported at the start of the
+    /// document.
+    pub fn diagnostics(&self, uri: &str) -> Vec<Diagnostic> {
+        let (_, errors) = self.db.partial_program_ir();
+        let errors = if errors.is_empty() {
+            self.db.checked_program().err().into_iter().collect()
+        } else {
+            errors
+        };
+        errors.iter().map(|err| self.diagnostic(uri, err)).collect()
+    }
+
+    fn diagnostic(&self, uri: &str, error: &ChalkError) -> Diagnostic {
+        let labels = error
+            .primary_label()
+            .into_iter()
+            .chain(error.secondary_labels());
+        Diagnostic {
+            range: error
+                .primary_label()
+                .map(|label| self.range(label.span))
+                .unwrap_or_default(),
+            severity: ERROR,
+            source: "chalk".to_string(),
+            message: error.to_string(),
+            related_information: labels
+                .map(|label| DiagnosticRelatedInformation {
+                    location: Location {
+                        uri: uri.to_string(),
+                        range: self.range(label.span),
+                    },
+                    message: label.message.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Where the item, module, crate or associated type named at `position` is
+    /// defined. Names are looked up in the whole program, modules included,
+    /// so a name can lead to more than one definition, and a type parameter
+    /// named like an item leads to that item.
+    pub fn definition(&self, position: Position) -> Vec<Range> {
+        let (name, _) = match self.name_at(position) {
+            Some(name) => name,
+            None => return vec![],
+        };
+        let items = match chalk_parse::parse_program(&self.text) {
+            Ok(program) => program.items,
+            Err(_) => return vec![],
+        };
+
+        let mut definitions = vec![];
+        self.definitions_in(&items, &name, &mut definitions);
+        definitions
+    }
+
+    fn definitions_in(&self, items: &[Item], name: &Identifier, definitions: &mut Vec<Range>) {
+        for item in items {
+            let defined_name = match item {
+                Item::AdtDefn(defn) => &defn.name,
+                Item::FnDefn(defn) => &defn.name,
+                Item::ClosureDefn(defn) => &defn.name,
+                Item::OpaqueTyDefn(defn) => &defn.name,
+                Item::GeneratorDefn(defn) => &defn.name,
+                Item::Foreign(defn) => &defn.0,
+                Item::TraitDefn(defn) => {
+                    definitions.extend(
+                        defn.assoc_ty_defns
+                            .iter()
+                            .filter(|assoc_ty| assoc_ty.name.str == *name)
+                            .map(|assoc_ty| self.range(assoc_ty.name.span)),
+                    );
+                    &defn.name
+                }
+                Item::Module(defn) => {
+                    self.definitions_in(&defn.program.items, name, definitions);
+                    &defn.name
+                }
+                Item::Crate(defn) => {
+                    self.definitions_in(&defn.program.items, name, definitions);
+                    &defn.name
+                }
+                Item::InherentImpl(defn) => {
+                    definitions.extend(
+                        defn.methods
+                            .iter()
+                            .filter(|method| method.name.str == *name)
+                            .map(|method| self.range(method.name.span)),
+                    );
+                    continue;
+                }
+                Item::Impl(_) | Item::Clause(_) | Item::Use(_) => continue,
+            };
+            if defined_name.str == *name {
+                definitions.push(self.range(defined_name.span));
+            }
+        }
+    }
+
+    /// The lowered form of the trait or ADT named at `position`, written back
+    /// out as a declaration. Like definitions, names are looked up in the
+    /// whole program.
+    pub fn hover(&self, position: Position) -> Option<Hover> {
+        let (name, range) = self.name_at(position)?;
+        let (program, _) = self.db.partial_program_ir();
+        let id = match lookup(&program.trait_ids, &name) {
+            Some(&id) => RecordedItemId::from(id),
+            None => RecordedItemId::from(*lookup(&program.adt_ids, &name)?),
+        };
+        let mut datum = String::new();
+        tls::set_current_program(&program, || {
+            write_items::<_, ChalkIr, Program, _, _>(
+                &mut datum,
+                &WriterState::new(&*program),
+                Some(id),
+            )
+        })
+        .expect("writing to a string never fails");
+        Some(Hover {
+            contents: MarkupContent {
+                kind: "markdown".to_string(),
+                value: format!("```rust\n{}```", datum),
+            },
+            range,
+        })
+    }
+
+    /// The name at `position`, along with its range, if there is one.
+    fn name_at(&self, position: Position) -> Option<(Identifier, Range)> {
+        let offset = self.lines.offset(&self.text, position)?;
+        let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+        let lo = self.text[..offset]
+            .rfind(|c: char| !is_name_char(c))
+            .map_or(0, |i| i + 1);
+        let hi = self.text[offset..]
+            .find(|c: char| !is_name_char(c))
+            .map_or(self.text.len(), |i| offset + i);
+        let name = &self.text[lo..hi];
+        if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        Some((Identifier::from(name), self.range(Span::new(lo, hi))))
+    }
+
+    fn range(&self, span: Span) -> Range {
+        Range {
+            start: self.lines.position(&self.text, span.lo),
+            end: self.lines.position(&self.text, span.hi),
+        }
+    }
+}
+
+/// Looks up the item named `name` among `ids`, which are keyed by full path:
+/// an item of the crate root by that name, or else the first item of a
+/// module by that name.
+fn lookup<'p, T>(ids: &'p BTreeMap<Identifier, T>, name: &Identifier) -> Option<&'p T> {
+    ids.get(name).or_else(|| {
+        ids.iter()
+            .find(|(path, _)| path.ends_with(&format!("::{}", name)))
+            .map(|(_, id)| id)
+    })
+}
+
+/// Converts between byte offsets in a text and LSP positions, which count
+/// UTF-16 code units within a line.
+struct LineIndex {
+    /// The offset at which each line starts.
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(text: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        LineIndex { line_starts }
+    }
+
+    fn position(&self, text: &str, offset: usize) -> Position {
+        let offset = offset.min(text.len());
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+        let line_start = self.line_starts[line];
+        let character = text[line_start..offset]
+            .chars()
+            .map(char::len_utf16)
+            .sum::<usize>();
+        Position {
+            line: line as u32,
+            character: character as u32,
+        }
+    }
+
+    /// The offset of `position`, or `None` if it is past the end of the
+    /// text. A position past the end of its line is taken to be at the end.
+    fn offset(&self, text: &str, position: Position) -> Option<usize> {
+        let line_start = *self.line_starts.get(position.line as usize)?;
+        let line_end = text[line_start..]
+            .find('\n')
+            .map_or(text.len(), |i| line_start + i);
+        let mut character = 0;
+        for (i, c) in text[line_start..line_end].char_indices() {
+            if character >= position.character as usize {
+                return Some(line_start + i);
+            }
+            character += c.len_utf16();
+        }
+        Some(line_end)
+    }
+}
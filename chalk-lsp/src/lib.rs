@@ -0,0 +1,25 @@
+//! A language server for `.chalk` programs.
+//!
+//! The server speaks the parts of the [Language Server Protocol] it needs
+//! to: it keeps the text of the open documents in sync, and for each one
+//! provides
+//!
+//! - diagnostics for the errors found by parsing, lowering, and the
+//!   well-formedness and coherence checks;
+//! - go-to-definition for the names of items and associated types;
+//! - hover, showing the lowered `TraitDatum` or `AdtDatum` of a trait or
+//!   an ADT.
+//!
+//! Each document is a program of its own.
+//!
+//! [Language Server Protocol]: https://microsoft.github.io/language-server-protocol/
+
+#![deny(rust_2018_idioms)]
+
+mod connection;
+mod document;
+pub mod protocol;
+mod server;
+
+pub use connection::{Connection, IoThreads};
+pub use server::run;
@@ -0,0 +1,15 @@
+use chalk_lsp::Connection;
+use std::process::exit;
+
+fn main() {
+    let (connection, io_threads) = Connection::stdio();
+    let result = chalk_lsp::run(&connection);
+    drop(connection);
+    if let Err(err) = result
+        .map_err(|err| err.to_string())
+        .and_then(|()| io_threads.join().map_err(|err| err.to_string()))
+    {
+        eprintln!("error: {}", err);
+        exit(1);
+    }
+}
@@ -0,0 +1,261 @@
+//! The messages of the protocol, and the parts of the LSP types the server
+//! uses. Messages are JSON-RPC 2.0 requests, responses and notifications;
+//! on the wire, each one is preceded by a `Content-Length` header.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{self, BufRead, Write};
+
+/// A message from the client to the server, or the other way round.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Message {
+    Request(Request),
+    Response(Response),
+    Notification(Notification),
+}
+
+/// The id of a request, which its response repeats.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RequestId {
+    Number(i64),
+    String(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Request {
+    pub id: RequestId,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub id: RequestId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ResponseError>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ResponseError {
+    pub code: i32,
+    pub message: String,
+}
+
+/// The error code for requests with methods the server does not know.
+pub const METHOD_NOT_FOUND: i32 = -32601;
+
+/// The error code for requests with parameters the server can't make sense
+/// of.
+pub const INVALID_PARAMS: i32 = -32602;
+
+/// The error code for requests that come in after a `shutdown` request.
+pub const INVALID_REQUEST: i32 = -32600;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+impl Request {
+    pub fn new(id: RequestId, method: &str, params: impl Serialize) -> Self {
+        Request {
+            id,
+            method: method.to_string(),
+            params: serde_json::to_value(params).unwrap(),
+        }
+    }
+}
+
+impl Response {
+    pub fn ok(id: RequestId, result: impl Serialize) -> Self {
+        Response {
+            id,
+            result: Some(serde_json::to_value(result).unwrap()),
+            error: None,
+        }
+    }
+
+    pub fn error(id: RequestId, code: i32, message: String) -> Self {
+        Response {
+            id,
+            result: None,
+            error: Some(ResponseError { code, message }),
+        }
+    }
+}
+
+impl Notification {
+    pub fn new(method: &str, params: impl Serialize) -> Self {
+        Notification {
+            method: method.to_string(),
+            params: serde_json::to_value(params).unwrap(),
+        }
+    }
+}
+
+impl Message {
+    /// Reads a message, or returns `None` at the end of the input.
+    pub fn read(input: &mut dyn BufRead) -> io::Result<Option<Message>> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        let mut content_length = None;
+        loop {
+            let mut header = String::new();
+            if input.read_line(&mut header)? == 0 {
+                return Ok(None);
+            }
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            let (name, value) = match header.find(':') {
+                Some(i) => (&header[..i], header[i + 1..].trim()),
+                None => return Err(invalid(format!("malformed header `{}`", header))),
+            };
+            if name.eq_ignore_ascii_case("Content-Length") {
+                let length = value
+                    .parse::<usize>()
+                    .map_err(|_| invalid(format!("bad content length `{}`", value)))?;
+                content_length = Some(length);
+            }
+        }
+
+        let content_length =
+            content_length.ok_or_else(|| invalid("missing content length".to_string()))?;
+        let mut content = vec![0; content_length];
+        input.read_exact(&mut content)?;
+        serde_json::from_slice(&content)
+            .map(Some)
+            .map_err(|err| invalid(err.to_string()))
+    }
+
+    /// Writes the message, with its header.
+    pub fn write(&self, output: &mut dyn Write) -> io::Result<()> {
+        let mut content = serde_json::to_value(self).unwrap();
+        content["jsonrpc"] = Value::from("2.0");
+        let content = content.to_string();
+        write!(
+            output,
+            "Content-Length: {}\r\n\r\n{}",
+            content.len(),
+            content
+        )?;
+        output.flush()
+    }
+}
+
+/// A position in a document, as a line and the UTF-16 code unit within
+/// that line, both starting at 0.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Location {
+    pub uri: String,
+    pub range: Range,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub range: Range,
+    /// 1 for errors, the only severity the server reports.
+    pub severity: u8,
+    pub source: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related_information: Vec<DiagnosticRelatedInformation>,
+}
+
+/// The severity of errors, the most severe one.
+pub const ERROR: u8 = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiagnosticRelatedInformation {
+    pub location: Location,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublishDiagnosticsParams {
+    pub uri: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextDocumentIdentifier {
+    pub uri: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextDocumentItem {
+    pub uri: String,
+    #[serde(default)]
+    pub language_id: String,
+    #[serde(default)]
+    pub version: i64,
+    pub text: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidOpenTextDocumentParams {
+    pub text_document: TextDocumentItem,
+}
+
+/// A change to a document. The server asks for the whole text to be sent
+/// each time.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextDocumentContentChangeEvent {
+    pub text: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidChangeTextDocumentParams {
+    pub text_document: TextDocumentIdentifier,
+    pub content_changes: Vec<TextDocumentContentChangeEvent>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidCloseTextDocumentParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextDocumentPositionParams {
+    pub text_document: TextDocumentIdentifier,
+    pub position: Position,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hover {
+    pub contents: MarkupContent,
+    pub range: Range,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarkupContent {
+    /// `plaintext` or `markdown`.
+    pub kind: String,
+    pub value: String,
+}
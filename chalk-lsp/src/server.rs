@@ -0,0 +1,171 @@
+//! The main loop of the server, which handles the messages from the client.
+
+use crate::connection::Connection;
+use crate::document::Document;
+use crate::protocol::*;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::error::Error;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// Serves the client on the other end of `connection`, until it asks the
+/// server to exit or goes away.
+pub fn run(connection: &Connection) -> Result<()> {
+    let mut server = Server {
+        connection,
+        documents: HashMap::new(),
+        shutting_down: false,
+    };
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => server.request(request)?,
+            Message::Notification(notification) if notification.method == "exit" => break,
+            Message::Notification(notification) => server.notification(notification)?,
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+struct Server<'c> {
+    connection: &'c Connection,
+
+    /// The open documents, by URI.
+    documents: HashMap<String, Document>,
+
+    /// Whether the client has asked the server to shut down, after which it
+    /// only waits to be told to exit.
+    shutting_down: bool,
+}
+
+impl Server<'_> {
+    fn request(&mut self, request: Request) -> Result<()> {
+        let Request { id, method, params } = request;
+        let response = if self.shutting_down {
+            Response::error(
+                id,
+                INVALID_REQUEST,
+                "the server is shutting down".to_string(),
+            )
+        } else {
+            match method.as_str() {
+                "initialize" => Response::ok(id, capabilities()),
+                "shutdown" => {
+                    self.shutting_down = true;
+                    Response::ok(id, Value::Null)
+                }
+                "textDocument/definition" => match parse::<TextDocumentPositionParams>(params) {
+                    Ok(params) => {
+                        let uri = params.text_document.uri;
+                        let locations: Vec<_> = match self.documents.get(&uri) {
+                            Some(document) => document
+                                .definition(params.position)
+                                .into_iter()
+                                .map(|range| Location {
+                                    uri: uri.clone(),
+                                    range,
+                                })
+                                .collect(),
+                            None => vec![],
+                        };
+                        Response::ok(id, locations)
+                    }
+                    Err(message) => Response::error(id, INVALID_PARAMS, message),
+                },
+                "textDocument/hover" => match parse::<TextDocumentPositionParams>(params) {
+                    Ok(params) => {
+                        let hover = self
+                            .documents
+                            .get(&params.text_document.uri)
+                            .and_then(|document| document.hover(params.position));
+                        Response::ok(id, hover)
+                    }
+                    Err(message) => Response::error(id, INVALID_PARAMS, message),
+                },
+                _ => Response::error(
+                    id,
+                    METHOD_NOT_FOUND,
+                    format!("unknown request `{}`", method),
+                ),
+            }
+        };
+        self.send(Message::Response(response))
+    }
+
+    /// Handles a notification. Notifications the server does not know, or
+    /// can't make sense of, are ignored, as they have no response to carry
+    /// an error.
+    fn notification(&mut self, notification: Notification) -> Result<()> {
+        let Notification { method, params } = notification;
+        match method.as_str() {
+            "textDocument/didOpen" => {
+                if let Ok(params) = parse::<DidOpenTextDocumentParams>(params) {
+                    let uri = params.text_document.uri;
+                    self.documents
+                        .insert(uri.clone(), Document::new(params.text_document.text));
+                    self.publish_diagnostics(uri)?;
+                }
+            }
+            "textDocument/didChange" => {
+                if let Ok(params) = parse::<DidChangeTextDocumentParams>(params) {
+                    if let Some(change) = params.content_changes.into_iter().last() {
+                        let uri = params.text_document.uri;
+                        self.documents
+                            .insert(uri.clone(), Document::new(change.text));
+                        self.publish_diagnostics(uri)?;
+                    }
+                }
+            }
+            "textDocument/didClose" => {
+                if let Ok(params) = parse::<DidCloseTextDocumentParams>(params) {
+                    let uri = params.text_document.uri;
+                    self.documents.remove(&uri);
+                    self.publish_diagnostics(uri)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Sends the diagnostics of the document at `uri`, or clears them if the
+    /// document is no longer open.
+    fn publish_diagnostics(&self, uri: String) -> Result<()> {
+        let diagnostics = match self.documents.get(&uri) {
+            Some(document) => document.diagnostics(&uri),
+            None => vec![],
+        };
+        self.send(Message::Notification(Notification::new(
+            "textDocument/publishDiagnostics",
+            PublishDiagnosticsParams { uri, diagnostics },
+        )))
+    }
+
+    fn send(&self, message: Message) -> Result<()> {
+        self.connection
+            .sender
+            .send(message)
+            .map_err(|_| "the client went away".into())
+    }
+}
+
+/// What the server can do, as a reply to `initialize`.
+fn capabilities() -> Value {
+    json!({
+        "capabilities": {
+            // The client sends the whole text of a document when it changes.
+            "textDocumentSync": 1,
+            "definitionProvider": true,
+            "hoverProvider": true,
+        },
+        "serverInfo": {
+            "name": "chalk-lsp",
+        },
+    })
+}
+
+fn parse<T: DeserializeOwned>(params: Value) -> std::result::Result<T, String> {
+    serde_json::from_value(params).map_err(|err| err.to_string())
+}
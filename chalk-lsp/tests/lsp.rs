@@ -0,0 +1,319 @@
+//! Tests that drive the server with an in-process client.
+
+use chalk_lsp::protocol::*;
+use chalk_lsp::Connection;
+use serde_json::{json, Value};
+use std::thread::{self, JoinHandle};
+
+const URI: &str = "file:///test.chalk";
+
+struct Client {
+    connection: Connection,
+    server: Option<JoinHandle<()>>,
+    next_id: i64,
+}
+
+impl Client {
+    fn start() -> Self {
+        let (server, connection) = Connection::memory();
+        let server = thread::spawn(move || chalk_lsp::run(&server).unwrap());
+        let mut client = Client {
+            connection,
+            server: Some(server),
+            next_id: 0,
+        };
+        let result = client.request("initialize", json!({ "capabilities": {} }));
+        assert_eq!(result.unwrap()["capabilities"]["hoverProvider"], true);
+        client.notify("initialized", json!({}));
+        client
+    }
+
+    fn request(&mut self, method: &str, params: impl serde::Serialize) -> Result<Value, i32> {
+        self.next_id += 1;
+        let id = RequestId::Number(self.next_id);
+        self.send(Message::Request(Request::new(id.clone(), method, params)));
+        match self.receive() {
+            Message::Response(response) => {
+                assert_eq!(response.id, id);
+                match response.error {
+                    Some(error) => Err(error.code),
+                    None => Ok(response.result.unwrap()),
+                }
+            }
+            message => panic!("expected a response, got {:?}", message),
+        }
+    }
+
+    fn notify(&self, method: &str, params: impl serde::Serialize) {
+        self.send(Message::Notification(Notification::new(method, params)));
+    }
+
+    fn send(&self, message: Message) {
+        self.connection.sender.send(message).unwrap();
+    }
+
+    fn receive(&self) -> Message {
+        self.connection.receiver.recv().unwrap()
+    }
+
+    /// Waits for the diagnostics the server publishes for `URI`.
+    fn diagnostics(&self) -> Vec<Diagnostic> {
+        match self.receive() {
+            Message::Notification(notification) => {
+                assert_eq!(notification.method, "textDocument/publishDiagnostics");
+                let params: PublishDiagnosticsParams =
+                    serde_json::from_value(notification.params).unwrap();
+                assert_eq!(params.uri, URI);
+                params.diagnostics
+            }
+            message => panic!("expected diagnostics, got {:?}", message),
+        }
+    }
+
+    fn open(&self, text: &str) -> Vec<Diagnostic> {
+        self.notify(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": {
+                    "uri": URI,
+                    "languageId": "chalk",
+                    "version": 0,
+                    "text": text,
+                }
+            }),
+        );
+        self.diagnostics()
+    }
+
+    fn change(&self, text: &str) -> Vec<Diagnostic> {
+        self.notify(
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": URI },
+                "contentChanges": [{ "text": text }],
+            }),
+        );
+        self.diagnostics()
+    }
+
+    fn definition(&mut self, line: u32, character: u32) -> Vec<Range> {
+        let locations: Vec<Location> = serde_json::from_value(
+            self.request("textDocument/definition", position(line, character))
+                .unwrap(),
+        )
+        .unwrap();
+        locations
+            .into_iter()
+            .map(|location| {
+                assert_eq!(location.uri, URI);
+                location.range
+            })
+            .collect()
+    }
+
+    fn hover(&mut self, line: u32, character: u32) -> Option<Hover> {
+        serde_json::from_value(
+            self.request("textDocument/hover", position(line, character))
+                .unwrap(),
+        )
+        .unwrap()
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            return;
+        }
+        assert_eq!(self.request("shutdown", Value::Null), Ok(Value::Null));
+        self.notify("exit", Value::Null);
+        self.server.take().unwrap().join().unwrap();
+    }
+}
+
+fn position(line: u32, character: u32) -> TextDocumentPositionParams {
+    TextDocumentPositionParams {
+        text_document: TextDocumentIdentifier {
+            uri: URI.to_string(),
+        },
+        position: Position { line, character },
+    }
+}
+
+fn range(line: u32, start: u32, end: u32) -> Range {
+    Range {
+        start: Position {
+            line,
+            character: start,
+        },
+        end: Position {
+            line,
+            character: end,
+        },
+    }
+}
+
+#[test]
+fn lowering_diagnostics() {
+    let client = Client::start();
+    let diagnostics = client.open("struct Foo { }\nimpl Bar for Foo { }\n");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].range, range(1, 5, 8));
+    assert_eq!(diagnostics[0].severity, ERROR);
+    assert!(
+        diagnostics[0].message.contains("Bar"),
+        "{}",
+        diagnostics[0].message
+    );
+
+    let diagnostics = client.change("struct Foo { }\ntrait Bar { }\nimpl Bar for Foo { }\n");
+    assert_eq!(diagnostics, vec![]);
+}
+
+#[test]
+fn parse_diagnostics() {
+    let client = Client::start();
+    let diagnostics = client.open("struct Foo {");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, ERROR);
+
+    // Each syntax error is reported where it was found.
+    let diagnostics = client.change("struct Foo { a: }\ntrait Bar { }\nimpl Bar for { }\n");
+    let ranges: Vec<_> = diagnostics.iter().map(|d| d.range).collect();
+    assert_eq!(ranges, vec![range(0, 16, 17), range(2, 13, 14)]);
+}
+
+#[test]
+fn coherence_diagnostics() {
+    let client = Client::start();
+    let diagnostics = client.open(
+        "trait Foo { }\n\
+         struct Bar { }\n\
+         impl Foo for Bar { }\n\
+         impl Foo for Bar { }\n",
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert!(
+        diagnostics[0].message.contains("overlapping impls"),
+        "{}",
+        diagnostics[0].message
+    );
+}
+
+#[test]
+fn well_formedness_diagnostics() {
+    let client = Client::start();
+    let diagnostics = client.open(
+        "trait Copy { }\n\
+         struct Foo<T> where T: Copy { }\n\
+         struct Bar { }\n\
+         struct Baz { x: Foo<Bar> }\n",
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert!(
+        diagnostics[0].message.contains("Baz"),
+        "{}",
+        diagnostics[0].message
+    );
+}
+
+#[test]
+fn closing_clears_diagnostics() {
+    let client = Client::start();
+    assert_eq!(client.open("impl Bar for Foo { }").len(), 1);
+    client.notify(
+        "textDocument/didClose",
+        json!({ "textDocument": { "uri": URI } }),
+    );
+    assert_eq!(client.diagnostics(), vec![]);
+}
+
+#[test]
+fn definition() {
+    let mut client = Client::start();
+    let diagnostics = client.open(
+        "trait Iterator { type Item; }\n\
+         struct Foo<T> { }\n\
+         impl<T> Iterator for Foo<T> { type Item = T; }\n",
+    );
+    assert_eq!(diagnostics, vec![]);
+
+    // The trait and the struct in the impl header.
+    assert_eq!(client.definition(2, 10), vec![range(0, 6, 14)]);
+    assert_eq!(client.definition(2, 22), vec![range(1, 7, 10)]);
+    // The associated type value.
+    assert_eq!(client.definition(2, 37), vec![range(0, 22, 26)]);
+    // A type parameter has no definition.
+    assert_eq!(client.definition(2, 26), vec![]);
+    // Nor does whitespace.
+    assert_eq!(client.definition(1, 17), vec![]);
+}
+
+#[test]
+fn definition_in_modules() {
+    let mut client = Client::start();
+    let diagnostics = client.open(
+        "mod a {\n\
+         \x20   trait Foo { }\n\
+         }\n\
+         struct Bar { }\n\
+         impl a::Foo for Bar { }\n",
+    );
+    assert_eq!(diagnostics, vec![]);
+
+    // The module and the trait in it.
+    assert_eq!(client.definition(4, 5), vec![range(0, 4, 5)]);
+    assert_eq!(client.definition(4, 9), vec![range(1, 10, 13)]);
+
+    let hover = client.hover(4, 9).unwrap();
+    assert_eq!(hover.range, range(4, 8, 11));
+    assert_eq!(hover.contents.value, "```rust\ntrait Foo {}\n```");
+}
+
+#[test]
+fn hover() {
+    let mut client = Client::start();
+    client.open(
+        "trait Clone { }\n\
+         struct Foo { }\n\
+         impl Clone for Foo { }\n",
+    );
+
+    let hover = client.hover(2, 7).unwrap();
+    assert_eq!(hover.range, range(2, 5, 10));
+    assert_eq!(hover.contents.kind, "markdown");
+    assert_eq!(hover.contents.value, "```rust\ntrait Clone {}\n```");
+
+    let hover = client.hover(2, 16).unwrap();
+    assert_eq!(hover.range, range(2, 15, 18));
+    assert_eq!(hover.contents.value, "```rust\nstruct Foo {}\n```");
+
+    assert_eq!(client.hover(2, 0), None);
+}
+
+#[test]
+fn unknown_request() {
+    let mut client = Client::start();
+    assert_eq!(
+        client.request("textDocument/completion", position(0, 0)),
+        Err(METHOD_NOT_FOUND)
+    );
+    assert_eq!(
+        client.request("textDocument/hover", json!({})),
+        Err(INVALID_PARAMS)
+    );
+}
+
+#[test]
+fn framing() {
+    let message = Message::Notification(Notification::new("exit", Value::Null));
+    let mut bytes = vec![];
+    message.write(&mut bytes).unwrap();
+    message.write(&mut bytes).unwrap();
+    assert!(bytes.starts_with(b"Content-Length: "));
+
+    let mut input = &bytes[..];
+    assert_eq!(Message::read(&mut input).unwrap(), Some(message.clone()));
+    assert_eq!(Message::read(&mut input).unwrap(), Some(message));
+    assert_eq!(Message::read(&mut input).unwrap(), None);
+}
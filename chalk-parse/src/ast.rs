@@ -1,7 +1,7 @@
 use std::fmt;
 use string_cache::DefaultAtom as Atom;
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
 pub struct Span {
     pub lo: usize,
     pub hi: usize,
@@ -13,9 +13,32 @@ impl Span {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
 pub struct Program {
     pub items: Vec<Item>,
+
+    /// The span of each item in `items`. This is empty for programs that
+    /// were not parsed from text.
+    pub item_spans: Vec<Span>,
+
+    /// The comments between the items, in the order they appear.
+    pub comments: Vec<Comment>,
+}
+
+/// A `//` comment. Comments can only appear between items.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Comment {
+    /// The text of the comment, including the leading `//`.
+    pub text: String,
+    pub span: Span,
+}
+
+/// An item or comment of a program, as it is parsed.
+pub(crate) enum ProgramElement {
+    Item(Box<Item>, Span),
+    Comment(Comment),
+    /// A syntax error, recovered from by skipping the item it is in.
+    Error,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -27,13 +50,91 @@ pub enum Item {
     OpaqueTyDefn(OpaqueTyDefn),
     GeneratorDefn(GeneratorDefn),
     Impl(Impl),
+    InherentImpl(InherentImpl),
     Clause(Clause),
     Foreign(ForeignDefn),
+    Module(ModuleDefn),
+    Use(UseDecl),
+    Crate(CrateDefn),
+}
+
+impl Item {
+    /// The span of this item's name or, for impls, of the trait name in the
+    /// impl header, or of the self type for inherent impls. Clauses have no name, and so no span.
+    pub fn name_span(&self) -> Option<Span> {
+        match self {
+            Item::AdtDefn(defn) => Some(defn.name.span),
+            Item::FnDefn(defn) => Some(defn.name.span),
+            Item::ClosureDefn(defn) => Some(defn.name.span),
+            Item::TraitDefn(defn) => Some(defn.name.span),
+            Item::OpaqueTyDefn(defn) => Some(defn.name.span),
+            Item::GeneratorDefn(defn) => Some(defn.name.span),
+            Item::Impl(defn) => Some(defn.trait_ref.trait_name.span),
+            Item::InherentImpl(defn) => match &defn.self_ty {
+                Ty::Id { name } | Ty::Apply { name, .. } => Some(name.span),
+                _ => None,
+            },
+            Item::Clause(_) => None,
+            Item::Foreign(defn) => Some(defn.0.span),
+            Item::Module(defn) => Some(defn.name.span),
+            Item::Use(decl) => Some(decl.path.span),
+            Item::Crate(defn) => Some(defn.name.span),
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct ForeignDefn(pub Identifier);
 
+/// A `mod name { ... }` block. The items in it are named by their path from
+/// the crate root, like `name::Item`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ModuleDefn {
+    pub name: Identifier,
+    pub program: Program,
+}
+
+/// A `crate name depends a, b { ... }` block, declaring a crate of the
+/// program and the crates it depends on. Like a module, it names its items
+/// by their path, like `name::Item`. Whether they are upstream or local is
+/// decided by where the crate sits relative to the current crate, which is
+/// the one marked `#[current]`, or else the last one declared.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CrateDefn {
+    pub name: Identifier,
+    pub dependencies: Vec<Identifier>,
+    pub current: bool,
+    pub program: Program,
+}
+
+/// A `use path;` or `use path as alias;` declaration, which brings the item
+/// or module at `path` into scope in the module it is in.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct UseDecl {
+    pub path: Identifier,
+    pub alias: Option<Identifier>,
+}
+
+impl UseDecl {
+    /// The name the declaration brings into scope: the alias, if there is
+    /// one, or else the last segment of the path.
+    pub fn name(&self) -> Identifier {
+        match &self.alias {
+            Some(alias) => alias.clone(),
+            None => {
+                let name = self.path.str.rsplit("::").next().unwrap();
+                Identifier {
+                    str: Atom::from(name),
+                    span: Span::new(
+                        self.path.span.hi.saturating_sub(name.len()),
+                        self.path.span.hi,
+                    ),
+                }
+            }
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct AdtDefn {
     pub name: Identifier,
@@ -110,6 +211,8 @@ pub struct FnDefn {
     pub name: Identifier,
     pub variable_kinds: Vec<VariableKind>,
     pub where_clauses: Vec<QuantifiedWhereClause>,
+    /// The names of the arguments, including the variadic one, if any.
+    pub argument_names: Vec<Identifier>,
     pub argument_types: Vec<Ty>,
     pub return_type: Ty,
     pub sig: FnSig,
@@ -121,6 +224,7 @@ pub struct ClosureDefn {
     pub name: Identifier,
     pub kind: ClosureKind,
     pub variable_kinds: Vec<VariableKind>,
+    pub argument_names: Vec<Identifier>,
     pub argument_types: Vec<Ty>,
     pub return_type: Ty,
     pub upvars: Vec<Ty>,
@@ -158,6 +262,7 @@ pub enum WellKnownTrait {
     Unpin,
     CoerceUnsized,
     DiscriminantKind,
+    Deref,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -194,7 +299,8 @@ pub enum VariableKind {
     IntegerTy(Identifier),
     FloatTy(Identifier),
     Lifetime(Identifier),
-    Const(Identifier),
+    /// A const parameter, with its type if it is not `u32`.
+    Const(Identifier, Option<ScalarType>),
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -208,7 +314,77 @@ pub enum GenericArg {
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum Const {
     Id(Identifier),
-    Value(u32),
+    Value(ConstValue),
+    /// A const expression, like `{N + 1}`, which is not evaluated.
+    Unevaluated(Box<Const>, ConstOp, Box<Const>),
+}
+
+/// A constant literal. Integer literals are `u32` unless they have a suffix.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ConstValue {
+    Bool(bool),
+    Char(char),
+    Int(i128, IntTy),
+    Uint(u128, UintTy),
+}
+
+impl ConstValue {
+    /// The integer literal with the given digits and suffix, if it is in the
+    /// range of its type. `isize` and `usize` are taken to be 64 bits wide.
+    pub fn int(
+        negative: bool,
+        digits: &str,
+        suffix: Option<ScalarType>,
+    ) -> Result<ConstValue, &'static str> {
+        let out_of_range = "integer literal out of range for its type";
+        let magnitude: u128 = digits.parse().map_err(|_| out_of_range)?;
+        match suffix.unwrap_or(ScalarType::Uint(UintTy::U32)) {
+            ScalarType::Int(ty) => {
+                let bits = match ty {
+                    IntTy::I8 => 8,
+                    IntTy::I16 => 16,
+                    IntTy::I32 => 32,
+                    IntTy::I64 | IntTy::Isize => 64,
+                    IntTy::I128 => 128,
+                };
+                // The magnitude of the most negative value.
+                let min = 1u128 << (bits - 1);
+                if magnitude > min || (magnitude == min && !negative) {
+                    return Err(out_of_range);
+                }
+                let value = magnitude as i128;
+                Ok(ConstValue::Int(
+                    if negative { value.wrapping_neg() } else { value },
+                    ty,
+                ))
+            }
+            ScalarType::Uint(ty) => {
+                let max = match ty {
+                    UintTy::U8 => u8::MAX as u128,
+                    UintTy::U16 => u16::MAX as u128,
+                    UintTy::U32 => u32::MAX as u128,
+                    UintTy::U64 | UintTy::Usize => u64::MAX as u128,
+                    UintTy::U128 => u128::MAX,
+                };
+                if magnitude > max || (negative && magnitude != 0) {
+                    return Err(out_of_range);
+                }
+                Ok(ConstValue::Uint(magnitude, ty))
+            }
+            ScalarType::Bool | ScalarType::Char | ScalarType::Float(_) => {
+                Err("not an integer type")
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ConstOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -242,7 +418,7 @@ pub struct AliasEqBound {
     pub value: Ty,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
 pub enum Kind {
     Ty,
     Lifetime,
@@ -267,6 +443,33 @@ pub struct Impl {
     pub where_clauses: Vec<QuantifiedWhereClause>,
     pub assoc_ty_values: Vec<AssocTyValue>,
     pub impl_type: ImplType,
+    /// Whether this is a `default impl`, all of whose values are `default`.
+    pub default: bool,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct InherentImpl {
+    pub variable_kinds: Vec<VariableKind>,
+    pub self_ty: Ty,
+    pub where_clauses: Vec<QuantifiedWhereClause>,
+    pub methods: Vec<MethodDefn>,
+    pub impl_type: ImplType,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MethodDefn {
+    pub name: Identifier,
+    pub self_kind: SelfKind,
+    pub argument_names: Vec<Identifier>,
+    pub argument_types: Vec<Ty>,
+    pub return_type: Ty,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SelfKind {
+    Value,
+    Ref,
+    RefMut,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -280,6 +483,7 @@ pub struct AssocTyValue {
     pub name: Identifier,
     pub variable_kinds: Vec<VariableKind>,
     pub value: Ty,
+    pub where_clauses: Vec<QuantifiedWhereClause>,
     pub default: bool,
 }
 
@@ -423,7 +627,7 @@ impl Polarity {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
 pub struct Identifier {
     pub str: Atom,
     pub span: Span,
@@ -0,0 +1,862 @@
+//! A pretty-printer for programs, which lays them out in a canonical way.
+//!
+//! Items are written one after the other, with a blank line wherever the
+//! source had one or more, and comments are kept where they were. Item
+//! bodies with members have one member per line; everything else, including
+//! where clauses, is written on the line of the item header. Attributes go
+//! on lines of their own, and empty bodies are written as `{ }`.
+//!
+//! Parsing the formatted program gives back the same program, apart from
+//! the spans of its identifiers. The `Display` impls for the parts of items,
+//! like types and goals, write them in the same syntax.
+
+use crate::ast::*;
+use std::fmt::{self, Display, Write};
+
+const INDENT: &str = "    ";
+
+/// Formats `program`, which was parsed from `text`. The text is only used to
+/// find the blank lines between items and comments; for a program that was
+/// not parsed from text, pass an empty one.
+pub fn format_program(program: &Program, text: &str) -> String {
+    // Merge the items and comments, in the order they appear.
+    let mut elements: Vec<(Option<Span>, Element<'_>)> = program
+        .items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| (program.item_spans.get(i).copied(), Element::Item(item)))
+        .chain(
+            program
+                .comments
+                .iter()
+                .map(|comment| (Some(comment.span), Element::Comment(&comment.text))),
+        )
+        .collect();
+    elements.sort_by_key(|(span, _)| span.map_or(0, |span| span.lo));
+
+    let mut out = String::new();
+    let mut previous: Option<Span> = None;
+    for (span, element) in elements {
+        let gap = match (previous, span) {
+            (Some(previous), Some(span)) => text.get(previous.hi..span.lo),
+            _ => None,
+        };
+        let newlines = gap.map_or(1, |gap| gap.matches('\n').count());
+        match element {
+            // A comment on the same line as the end of an item stays there.
+            Element::Comment(comment) if newlines == 0 && !out.is_empty() => {
+                out.pop();
+                writeln!(out, " {}", comment).unwrap();
+            }
+            element => {
+                if newlines > 1 && !out.is_empty() {
+                    out.push('\n');
+                }
+                match element {
+                    Element::Item(item) => write_item(&mut out, item, text).unwrap(),
+                    Element::Comment(comment) => writeln!(out, "{}", comment).unwrap(),
+                }
+            }
+        }
+        previous = span;
+    }
+    out
+}
+
+enum Element<'a> {
+    Item(&'a Item),
+    Comment(&'a str),
+}
+
+/// Writes an item, along with the newline that ends it.
+fn write_item(out: &mut String, item: &Item, text: &str) -> fmt::Result {
+    match item {
+        Item::AdtDefn(defn) => write_adt_defn(out, defn),
+        Item::FnDefn(defn) => write_fn_defn(out, defn),
+        Item::ClosureDefn(defn) => write_closure_defn(out, defn),
+        Item::TraitDefn(defn) => write_trait_defn(out, defn),
+        Item::OpaqueTyDefn(defn) => {
+            write!(
+                out,
+                "opaque type {}{}",
+                defn.name,
+                Angle(&defn.variable_kinds)
+            )?;
+            write_bounds(out, &defn.bounds)?;
+            write_where_clauses(out, &defn.where_clauses)?;
+            writeln!(out, " = {};", defn.ty)
+        }
+        Item::GeneratorDefn(defn) => write_generator_defn(out, defn),
+        Item::Impl(defn) => write_impl(out, defn),
+        Item::InherentImpl(defn) => write_inherent_impl(out, defn),
+        Item::Clause(clause) => {
+            write!(
+                out,
+                "forall{} {{ {}",
+                Angle(&clause.variable_kinds),
+                clause.consequence
+            )?;
+            if !clause.conditions.is_empty() {
+                write!(out, " if {}", Goals(&clause.conditions))?;
+            }
+            writeln!(out, " }}")
+        }
+        Item::Foreign(ForeignDefn(name)) => writeln!(out, "extern type {};", name),
+        Item::Module(defn) => {
+            write!(out, "mod {}", defn.name)?;
+            write_module_body(out, &defn.program, text)
+        }
+        Item::Use(decl) => {
+            write!(out, "use {}", decl.path)?;
+            if let Some(alias) = &decl.alias {
+                write!(out, " as {}", alias)?;
+            }
+            writeln!(out, ";")
+        }
+        Item::Crate(defn) => {
+            write_attr(out, defn.current, "current")?;
+            write!(out, "crate {}", defn.name)?;
+            if !defn.dependencies.is_empty() {
+                write!(out, " depends {}", Comma(&defn.dependencies))?;
+            }
+            write_module_body(out, &defn.program, text)
+        }
+    }
+}
+
+/// Writes the braced body of a module or crate, with its items indented.
+fn write_module_body(out: &mut String, program: &Program, text: &str) -> fmt::Result {
+    let body = format_program(program, text);
+    if body.is_empty() {
+        return writeln!(out, " {{ }}");
+    }
+    writeln!(out, " {{")?;
+    for line in body.lines() {
+        if line.is_empty() {
+            writeln!(out)?;
+        } else {
+            writeln!(out, "{}{}", INDENT, line)?;
+        }
+    }
+    writeln!(out, "}}")
+}
+
+fn write_adt_defn(out: &mut String, defn: &AdtDefn) -> fmt::Result {
+    write_variances(out, &defn.variances)?;
+    let flags = &defn.flags;
+    write_attr(out, flags.upstream, "upstream")?;
+    write_attr(out, flags.fundamental, "fundamental")?;
+    write_attr(out, flags.phantom_data, "phantom_data")?;
+    write_attr(out, defn.repr.c, "repr(C)")?;
+    write_attr(out, defn.repr.packed, "repr(packed)")?;
+    if let Some(int) = &defn.repr.int {
+        writeln!(out, "#[repr({})]", int)?;
+    }
+
+    let keyword = match flags.kind {
+        AdtKind::Struct => "struct",
+        AdtKind::Enum => "enum",
+        AdtKind::Union => "union",
+    };
+    write!(
+        out,
+        "{} {}{}",
+        keyword,
+        defn.name,
+        Angle(&defn.variable_kinds)
+    )?;
+    write_where_clauses(out, &defn.where_clauses)?;
+    match flags.kind {
+        AdtKind::Enum => write_body(out, &defn.variants, |out, variant| {
+            write!(out, "{}", variant.name)?;
+            if is_tuple_variant(variant) {
+                let tys: Vec<_> = variant.fields.iter().map(|field| &field.ty).collect();
+                write!(out, "({})", Comma(&tys))?;
+            } else if !variant.fields.is_empty() {
+                let fields: Vec<_> = variant.fields.iter().map(FieldDisplay).collect();
+                write!(out, " {{ {} }}", Comma(&fields))?;
+            }
+            writeln!(out, ",")
+        }),
+        AdtKind::Struct | AdtKind::Union => {
+            let fields = defn
+                .variants
+                .first()
+                .map_or(&[][..], |variant| &variant.fields);
+            write_body(out, fields, |out, field| {
+                writeln!(out, "{},", FieldDisplay(field))
+            })
+        }
+    }
+}
+
+/// Whether the variant was written with parentheses. The fields of such a
+/// variant are named after their indices, which no other field name can be.
+fn is_tuple_variant(variant: &Variant) -> bool {
+    match variant.fields.first() {
+        Some(field) => field.name.str.starts_with(|c: char| c.is_ascii_digit()),
+        None => false,
+    }
+}
+
+fn write_fn_defn(out: &mut String, defn: &FnDefn) -> fmt::Result {
+    write_variances(out, &defn.variances)?;
+    write!(
+        out,
+        "{}fn {}{}(",
+        defn.sig,
+        defn.name,
+        Angle(&defn.variable_kinds)
+    )?;
+    for (i, ty) in defn.argument_types.iter().enumerate() {
+        if i > 0 {
+            write!(out, ", ")?;
+        }
+        write!(out, "{}: {}", argument_name(&defn.argument_names, i), ty)?;
+    }
+    if defn.sig.variadic {
+        if !defn.argument_types.is_empty() {
+            write!(out, ", ")?;
+        }
+        let i = defn.argument_types.len();
+        write!(out, "{}: ...", argument_name(&defn.argument_names, i))?;
+    }
+    write!(out, "){}", ReturnType(&defn.return_type))?;
+    write_where_clauses(out, &defn.where_clauses)?;
+    writeln!(out, ";")
+}
+
+fn write_closure_defn(out: &mut String, defn: &ClosureDefn) -> fmt::Result {
+    let receiver = match defn.kind {
+        ClosureKind::Fn => "&self",
+        ClosureKind::FnMut => "&mut self",
+        ClosureKind::FnOnce => "self",
+    };
+    write!(
+        out,
+        "closure {}{}({},",
+        defn.name,
+        Angle(&defn.variable_kinds),
+        receiver
+    )?;
+    for (i, ty) in defn.argument_types.iter().enumerate() {
+        if i > 0 {
+            write!(out, ",")?;
+        }
+        write!(out, " {}: {}", argument_name(&defn.argument_names, i), ty)?;
+    }
+    write!(out, "){}", ReturnType(&defn.return_type))?;
+    if defn.upvars.is_empty() {
+        writeln!(out, " {{ }}")
+    } else {
+        writeln!(out, " {{ {} }}", Separated("; ", &defn.upvars))
+    }
+}
+
+/// The name of the `i`th argument, or `_` for arguments of functions that
+/// were not parsed from text.
+fn argument_name(names: &[Identifier], i: usize) -> &str {
+    names.get(i).map_or("_", |name| &name.str)
+}
+
+fn write_trait_defn(out: &mut String, defn: &TraitDefn) -> fmt::Result {
+    let flags = &defn.flags;
+    write_attr(out, flags.auto, "auto")?;
+    write_attr(out, flags.marker, "marker")?;
+    write_attr(out, flags.upstream, "upstream")?;
+    write_attr(out, flags.fundamental, "fundamental")?;
+    write_attr(out, flags.non_enumerable, "non_enumerable")?;
+    write_attr(out, flags.coinductive, "coinductive")?;
+    write_attr(out, flags.object_safe, "object_safe")?;
+    if let Some(well_known) = defn.well_known {
+        writeln!(out, "#[lang({})]", lang_item(well_known))?;
+    }
+
+    write!(out, "trait {}{}", defn.name, Angle(&defn.variable_kinds))?;
+    write_where_clauses(out, &defn.where_clauses)?;
+    write_body(out, &defn.assoc_ty_defns, |out, assoc_ty| {
+        write!(
+            out,
+            "type {}{}",
+            assoc_ty.name,
+            Angle(&assoc_ty.variable_kinds)
+        )?;
+        write_bounds(out, &assoc_ty.bounds)?;
+        write_where_clauses(out, &assoc_ty.where_clauses)?;
+        writeln!(out, ";")
+    })
+}
+
+fn lang_item(well_known: WellKnownTrait) -> &'static str {
+    match well_known {
+        WellKnownTrait::Sized => "sized",
+        WellKnownTrait::Copy => "copy",
+        WellKnownTrait::Clone => "clone",
+        WellKnownTrait::Drop => "drop",
+        WellKnownTrait::FnOnce => "fn_once",
+        WellKnownTrait::FnMut => "fn_mut",
+        WellKnownTrait::Fn => "fn",
+        WellKnownTrait::Unsize => "unsize",
+        WellKnownTrait::Unpin => "unpin",
+        WellKnownTrait::CoerceUnsized => "coerce_unsized",
+        WellKnownTrait::DiscriminantKind => "discriminant_kind",
+        WellKnownTrait::Deref => "deref",
+    }
+}
+
+fn write_generator_defn(out: &mut String, defn: &GeneratorDefn) -> fmt::Result {
+    let movability = match defn.movability {
+        Movability::Static => "static ",
+        Movability::Movable => "",
+    };
+    writeln!(
+        out,
+        "generator {}{}{}[resume = {}, yield = {}]{} {{",
+        movability,
+        defn.name,
+        Angle(&defn.variable_kinds),
+        defn.resume_ty,
+        defn.yield_ty,
+        ReturnType(&defn.return_ty)
+    )?;
+    writeln!(out, "{}upvars [{}]", INDENT, Separated("; ", &defn.upvars))?;
+    write!(out, "{}witnesses ", INDENT)?;
+    if !defn.witness_lifetimes.is_empty() {
+        write!(out, "exists<{}> ", Comma(&defn.witness_lifetimes))?;
+    }
+    writeln!(out, "[{}]", Separated("; ", &defn.witness_types))?;
+    writeln!(out, "}}")
+}
+
+fn write_impl(out: &mut String, defn: &Impl) -> fmt::Result {
+    if let ImplType::External = defn.impl_type {
+        writeln!(out, "#[upstream]")?;
+    }
+    let polarity = match defn.polarity {
+        Polarity::Positive => "",
+        Polarity::Negative => "!",
+    };
+    if defn.default {
+        write!(out, "default ")?;
+    }
+    let trait_ref = &defn.trait_ref;
+    write!(
+        out,
+        "impl{} {}{}{} for {}",
+        Angle(&defn.variable_kinds),
+        polarity,
+        trait_ref.trait_name,
+        Angle(&trait_ref.args[1..]),
+        trait_ref.args[0]
+    )?;
+    write_where_clauses(out, &defn.where_clauses)?;
+    write_body(out, &defn.assoc_ty_values, |out, value| {
+        if value.default {
+            write!(out, "default ")?;
+        }
+        write!(
+            out,
+            "type {}{} = {}",
+            value.name,
+            Angle(&value.variable_kinds),
+            value.value
+        )?;
+        write_where_clauses(out, &value.where_clauses)?;
+        writeln!(out, ";")
+    })
+}
+
+fn write_inherent_impl(out: &mut String, defn: &InherentImpl) -> fmt::Result {
+    if let ImplType::External = defn.impl_type {
+        writeln!(out, "#[upstream]")?;
+    }
+    write!(out, "impl{} {}", Angle(&defn.variable_kinds), defn.self_ty)?;
+    write_where_clauses(out, &defn.where_clauses)?;
+    write_body(out, &defn.methods, |out, method| {
+        let receiver = match method.self_kind {
+            SelfKind::Value => "self",
+            SelfKind::Ref => "&self",
+            SelfKind::RefMut => "&mut self",
+        };
+        write!(out, "fn {}({}", method.name, receiver)?;
+        for (i, ty) in method.argument_types.iter().enumerate() {
+            write!(
+                out,
+                ", {}: {}",
+                argument_name(&method.argument_names, i),
+                ty
+            )?;
+        }
+        writeln!(out, "){};", ReturnType(&method.return_type))
+    })
+}
+
+fn write_attr(out: &mut String, present: bool, attr: &str) -> fmt::Result {
+    if present {
+        writeln!(out, "#[{}]", attr)?;
+    }
+    Ok(())
+}
+
+fn write_variances(out: &mut String, variances: &Option<Vec<Variance>>) -> fmt::Result {
+    if let Some(variances) = variances {
+        let variances: Vec<_> = variances
+            .iter()
+            .map(|variance| match variance {
+                Variance::Invariant => "Invariant",
+                Variance::Covariant => "Covariant",
+                Variance::Contravariant => "Contravariant",
+            })
+            .collect();
+        writeln!(out, "#[variance({})]", Comma(&variances))?;
+    }
+    Ok(())
+}
+
+fn write_bounds(out: &mut String, bounds: &[QuantifiedInlineBound]) -> fmt::Result {
+    if !bounds.is_empty() {
+        write!(out, ": {}", Separated(" + ", bounds))?;
+    }
+    Ok(())
+}
+
+fn write_where_clauses(out: &mut String, where_clauses: &[QuantifiedWhereClause]) -> fmt::Result {
+    if !where_clauses.is_empty() {
+        write!(out, " where {}", Comma(where_clauses))?;
+    }
+    Ok(())
+}
+
+/// Writes the braces of an item body, with each of `members` on a line of
+/// its own between them. `write_member` writes a member and its newline.
+fn write_body<T>(
+    out: &mut String,
+    members: &[T],
+    mut write_member: impl FnMut(&mut String, &T) -> fmt::Result,
+) -> fmt::Result {
+    if members.is_empty() {
+        return writeln!(out, " {{ }}");
+    }
+    writeln!(out, " {{")?;
+    for member in members {
+        out.push_str(INDENT);
+        write_member(out, member)?;
+    }
+    writeln!(out, "}}")
+}
+
+/// Writes the values, separated by `.0`.
+struct Separated<'a, T>(&'static str, &'a [T]);
+
+impl<T: Display> Display for Separated<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, value) in self.1.iter().enumerate() {
+            if i > 0 {
+                f.write_str(self.0)?;
+            }
+            write!(f, "{}", value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the values, separated by commas.
+struct Comma<'a, T>(&'a [T]);
+
+impl<T: Display> Display for Comma<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Separated(", ", self.0))
+    }
+}
+
+/// Writes the values between angle brackets, or nothing if there are none.
+struct Angle<'a, T>(&'a [T]);
+
+impl<T: Display> Display for Angle<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            write!(f, "<{}>", Comma(self.0))
+        }
+    }
+}
+
+/// Writes ` -> ty`, or nothing for the unit type, which is what a missing
+/// return type means.
+struct ReturnType<'a>(&'a Ty);
+
+impl Display for ReturnType<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Ty::Tuple { types } if types.is_empty() => Ok(()),
+            ty => write!(f, " -> {}", ty),
+        }
+    }
+}
+
+struct FieldDisplay<'a>(&'a Field);
+
+impl Display for FieldDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.0.name, self.0.ty)
+    }
+}
+
+/// Writes a trait reference as `Self: Trait<Args>`, or with `as` in place of
+/// the colon.
+struct TraitRefDisplay<'a>(&'a TraitRef, &'static str);
+
+impl Display for TraitRefDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let TraitRefDisplay(trait_ref, separator) = self;
+        write!(
+            f,
+            "{}{}{}{}",
+            trait_ref.args[0],
+            separator,
+            trait_ref.trait_name,
+            Angle(&trait_ref.args[1..])
+        )
+    }
+}
+
+/// Writes a list of goals where each goal must be a single one, as in the
+/// conditions of a clause: conjunctions within it are put in parentheses.
+struct Goals<'a>(&'a [Box<Goal>]);
+
+impl Display for Goals<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, goal) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            match **goal {
+                Goal::And(..) => write!(f, "({})", goal)?,
+                _ => write!(f, "{}", goal)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ty::Id { name } => write!(f, "{}", name),
+            Ty::Dyn { bounds, lifetime } => {
+                write!(f, "dyn {} + {}", Separated(" + ", bounds), lifetime)
+            }
+            Ty::Apply { name, args } => write!(f, "{}<{}>", name, Comma(args)),
+            Ty::Projection { proj } => write!(f, "{}", proj),
+            Ty::ForAll {
+                lifetime_names,
+                types,
+                sig,
+            } => {
+                if !lifetime_names.is_empty() {
+                    write!(f, "for<{}> ", Comma(lifetime_names))?;
+                }
+                let (return_type, argument_types) = types.split_last().unwrap();
+                write!(f, "{}fn({}", sig, Comma(argument_types))?;
+                if sig.variadic {
+                    if !argument_types.is_empty() {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "...")?;
+                }
+                write!(f, "){}", ReturnType(return_type))
+            }
+            Ty::Tuple { types } if types.len() == 1 => write!(f, "({},)", types[0]),
+            Ty::Tuple { types } => write!(f, "({})", Comma(types)),
+            Ty::Scalar { ty } => write!(f, "{}", ty),
+            Ty::Slice { ty } => write!(f, "[{}]", ty),
+            Ty::Array { ty, len } => write!(f, "[{}; {}]", ty, len),
+            Ty::Raw { mutability, ty } => match mutability {
+                Mutability::Mut => write!(f, "*mut {}", ty),
+                Mutability::Not => write!(f, "*const {}", ty),
+            },
+            Ty::Ref {
+                mutability,
+                lifetime,
+                ty,
+            } => match mutability {
+                Mutability::Mut => write!(f, "&{} mut {}", lifetime, ty),
+                Mutability::Not => write!(f, "&{} {}", lifetime, ty),
+            },
+            Ty::Str => write!(f, "str"),
+            Ty::Never => write!(f, "!"),
+        }
+    }
+}
+
+/// Writes the qualifiers of a function: `unsafe extern "C" `, say.
+impl Display for FnSig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Safety::Unsafe = self.safety {
+            write!(f, "unsafe ")?;
+        }
+        if self.abi != FnAbi::default() {
+            write!(f, "extern \"{}\" ", self.abi.0)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ScalarType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ScalarType::Bool => "bool",
+            ScalarType::Char => "char",
+            ScalarType::Int(IntTy::Isize) => "isize",
+            ScalarType::Int(IntTy::I8) => "i8",
+            ScalarType::Int(IntTy::I16) => "i16",
+            ScalarType::Int(IntTy::I32) => "i32",
+            ScalarType::Int(IntTy::I64) => "i64",
+            ScalarType::Int(IntTy::I128) => "i128",
+            ScalarType::Uint(UintTy::Usize) => "usize",
+            ScalarType::Uint(UintTy::U8) => "u8",
+            ScalarType::Uint(UintTy::U16) => "u16",
+            ScalarType::Uint(UintTy::U32) => "u32",
+            ScalarType::Uint(UintTy::U64) => "u64",
+            ScalarType::Uint(UintTy::U128) => "u128",
+            ScalarType::Float(FloatTy::F32) => "f32",
+            ScalarType::Float(FloatTy::F64) => "f64",
+        })
+    }
+}
+
+impl Display for Lifetime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lifetime::Id { name } => write!(f, "{}", name),
+            Lifetime::Static => write!(f, "'static"),
+            Lifetime::Erased => write!(f, "'erased"),
+            Lifetime::Empty => write!(f, "'empty"),
+        }
+    }
+}
+
+impl Display for Const {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Const::Id(name) => write!(f, "{}", name),
+            Const::Value(value) => write!(f, "{}", value),
+            Const::Unevaluated(lhs, op, rhs) => write!(f, "{{{} {} {}}}", lhs, op, rhs),
+        }
+    }
+}
+
+impl Display for ConstValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ConstValue::Bool(value) => write!(f, "{}", value),
+            ConstValue::Char(value) => match value {
+                '\n' => write!(f, "'\\n'"),
+                '\r' => write!(f, "'\\r'"),
+                '\t' => write!(f, "'\\t'"),
+                '\0' => write!(f, "'\\0'"),
+                '\'' | '\\' => write!(f, "'\\{}'", value),
+                _ => write!(f, "'{}'", value),
+            },
+            ConstValue::Int(value, ty) => write!(f, "{}{}", value, ScalarType::Int(ty)),
+            ConstValue::Uint(value, UintTy::U32) => write!(f, "{}", value),
+            ConstValue::Uint(value, ty) => write!(f, "{}{}", value, ScalarType::Uint(ty)),
+        }
+    }
+}
+
+impl Display for ConstOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ConstOp::Add => "+",
+            ConstOp::Sub => "-",
+            ConstOp::Mul => "*",
+            ConstOp::Div => "/",
+            ConstOp::Rem => "%",
+        })
+    }
+}
+
+impl Display for GenericArg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenericArg::Ty(ty) => write!(f, "{}", ty),
+            GenericArg::Lifetime(lifetime) => write!(f, "{}", lifetime),
+            GenericArg::Id(name) => write!(f, "{}", name),
+            GenericArg::Const(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl Display for VariableKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VariableKind::Ty(name) | VariableKind::Lifetime(name) => write!(f, "{}", name),
+            VariableKind::IntegerTy(name) => write!(f, "int {}", name),
+            VariableKind::FloatTy(name) => write!(f, "float {}", name),
+            VariableKind::Const(name, None) => write!(f, "const {}", name),
+            VariableKind::Const(name, Some(ty)) => write!(f, "const {}: {}", name, ty),
+        }
+    }
+}
+
+impl Display for ProjectionTy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "<{}>::{}{}",
+            TraitRefDisplay(&self.trait_ref, " as "),
+            self.name,
+            Angle(&self.args)
+        )
+    }
+}
+
+impl Display for TraitBound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.trait_name, Angle(&self.args_no_self))
+    }
+}
+
+impl Display for AliasEqBound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}<", self.trait_bound.trait_name)?;
+        for arg in &self.trait_bound.args_no_self {
+            write!(f, "{}, ", arg)?;
+        }
+        write!(f, "{}{} = {}>", self.name, Angle(&self.args), self.value)
+    }
+}
+
+impl Display for InlineBound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InlineBound::TraitBound(bound) => write!(f, "{}", bound),
+            InlineBound::AliasEqBound(bound) => write!(f, "{}", bound),
+        }
+    }
+}
+
+impl Display for QuantifiedInlineBound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.variable_kinds.is_empty() {
+            write!(f, "forall<{}> ", Comma(&self.variable_kinds))?;
+        }
+        write!(f, "{}", self.bound)
+    }
+}
+
+impl Display for WhereClause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WhereClause::Implemented { trait_ref } => {
+                write!(f, "{}", TraitRefDisplay(trait_ref, ": "))
+            }
+            WhereClause::ProjectionEq { projection, ty } => {
+                let trait_ref = &projection.trait_ref;
+                write!(f, "{}: {}<", trait_ref.args[0], trait_ref.trait_name)?;
+                for arg in &trait_ref.args[1..] {
+                    write!(f, "{}, ", arg)?;
+                }
+                write!(
+                    f,
+                    "{}{} = {}>",
+                    projection.name,
+                    Angle(&projection.args),
+                    ty
+                )
+            }
+            WhereClause::LifetimeOutlives { a, b } => write!(f, "{}: {}", a, b),
+            WhereClause::TypeOutlives { ty, lifetime } => write!(f, "{}: {}", ty, lifetime),
+        }
+    }
+}
+
+impl Display for QuantifiedWhereClause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.variable_kinds.is_empty() {
+            write!(f, "forall<{}> ", Comma(&self.variable_kinds))?;
+        }
+        write!(f, "{}", self.where_clause)
+    }
+}
+
+impl Display for DomainGoal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DomainGoal::Holds { where_clause } => write!(f, "{}", where_clause),
+            DomainGoal::Normalize { projection, ty } => {
+                write!(f, "Normalize({} -> {})", projection, ty)
+            }
+            DomainGoal::TraitRefWellFormed { trait_ref } => {
+                write!(f, "WellFormed({})", TraitRefDisplay(trait_ref, ": "))
+            }
+            DomainGoal::TyWellFormed { ty } => write!(f, "WellFormed({})", ty),
+            DomainGoal::TyFromEnv { ty } => write!(f, "FromEnv({})", ty),
+            DomainGoal::TraitRefFromEnv { trait_ref } => {
+                write!(f, "FromEnv({})", TraitRefDisplay(trait_ref, ": "))
+            }
+            DomainGoal::IsLocal { ty } => write!(f, "IsLocal({})", ty),
+            DomainGoal::IsUpstream { ty } => write!(f, "IsUpstream({})", ty),
+            DomainGoal::IsFullyVisible { ty } => write!(f, "IsFullyVisible({})", ty),
+            DomainGoal::LocalImplAllowed { trait_ref } => {
+                write!(f, "LocalImplAllowed({})", TraitRefDisplay(trait_ref, ": "))
+            }
+            DomainGoal::Compatible => write!(f, "Compatible"),
+            DomainGoal::DownstreamType { ty } => write!(f, "DownstreamType({})", ty),
+            DomainGoal::Reveal => write!(f, "Reveal"),
+            DomainGoal::ObjectSafe { id } => write!(f, "ObjectSafe({})", id),
+        }
+    }
+}
+
+impl Display for LeafGoal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LeafGoal::DomainGoal { goal } => write!(f, "{}", goal),
+            LeafGoal::UnifyGenericArgs { a, b } => write!(f, "{} = {}", a, b),
+            LeafGoal::SubtypeGenericArgs { a, b } => write!(f, "Subtype({}, {})", a, b),
+        }
+    }
+}
+
+/// Writes a clause the way it is written in the hypotheses of an `if` goal.
+impl Display for Clause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.variable_kinds.is_empty() {
+            write!(f, "forall<{}> {{ ", Comma(&self.variable_kinds))?;
+        }
+        write!(f, "{}", self.consequence)?;
+        if !self.conditions.is_empty() {
+            write!(f, " :- {}", Goals(&self.conditions))?;
+        }
+        if !self.variable_kinds.is_empty() {
+            write!(f, " }}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Goal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Goal::ForAll(variable_kinds, goal) => {
+                write!(f, "forall<{}> {{ {} }}", Comma(variable_kinds), goal)
+            }
+            Goal::Exists(variable_kinds, goal) => {
+                write!(f, "exists<{}> {{ {} }}", Comma(variable_kinds), goal)
+            }
+            Goal::Implies(clauses, goal) => {
+                write!(f, "if ({}) {{ {} }}", Separated("; ", clauses), goal)
+            }
+            Goal::And(first, rest) => {
+                write!(f, "{}", Goals(std::slice::from_ref(first)))?;
+                write!(f, ", {}", Goals(rest))
+            }
+            Goal::Not(goal) => write!(f, "not {{ {} }}", goal),
+            Goal::Compatible(goal) => write!(f, "compatible {{ {} }}", goal),
+            Goal::Leaf(leaf) => write!(f, "{}", leaf),
+        }
+    }
+}
@@ -5,30 +5,92 @@
 extern crate lalrpop_util;
 
 pub mod ast;
+pub mod format;
 #[rustfmt::skip]
 lalrpop_mod!(pub parser);
 
+use ast::Span;
+use lalrpop_util::lexer::Token;
 use lalrpop_util::ParseError;
-use std::fmt::Write;
+use std::fmt::{self, Write};
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
+/// A syntax error found while parsing a program.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SyntaxError {
+    pub message: String,
+
+    /// The part of the program text the error is about. This is `None` for
+    /// errors found only once a whole construct has been parsed, like an
+    /// unknown `repr` flag.
+    pub span: Option<Span>,
+}
+
+impl SyntaxError {
+    fn new(error: ParseError<usize, Token<'_>, &'static str>) -> Self {
+        let span = match error {
+            ParseError::InvalidToken { location } => Some(Span::new(location, location + 1)),
+            ParseError::UnrecognizedEOF { location, .. } => Some(Span::new(location, location)),
+            ParseError::UnrecognizedToken {
+                token: (lo, _, hi), ..
+            }
+            | ParseError::ExtraToken { token: (lo, _, hi) } => Some(Span::new(lo, hi)),
+            ParseError::User { .. } => None,
+        };
+        SyntaxError {
+            message: error.to_string(),
+            span,
+        }
+    }
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
 pub fn parse_program(text: &str) -> Result<ast::Program> {
-    match parser::ProgramParser::new().parse(text) {
-        Ok(v) => Ok(v),
-        Err(e) => Err(format!("parse error: {}", e))?,
+    match parse_program_with_errors(text) {
+        (program, errors) if errors.is_empty() => Ok(program),
+        (_, errors) => Err(format!("parse error: {}", errors[0]))?,
     }
 }
 
+/// Parses `text`, skipping ahead to the start of the next item whenever a
+/// syntax error is found. Returns the items that could be parsed, along with
+/// all the syntax errors in the order they appear in `text`. A few errors,
+/// like invalid tokens, cannot be recovered from; if one is found, the
+/// program returned is empty.
+pub fn parse_program_with_errors(text: &str) -> (ast::Program, Vec<SyntaxError>) {
+    let mut recovered = vec![];
+    let result = parser::ProgramParser::new().parse(&mut recovered, text);
+    let mut errors: Vec<_> = recovered
+        .into_iter()
+        .map(|recovery| SyntaxError::new(recovery.error))
+        .collect();
+    let program = match result {
+        Ok(program) => program,
+        Err(error) => {
+            errors.push(SyntaxError::new(error));
+            ast::Program::default()
+        }
+    };
+    (program, errors)
+}
+
 pub fn parse_ty(text: &str) -> Result<ast::Ty> {
-    match parser::TyParser::new().parse(text) {
+    match parser::TyParser::new().parse(&mut vec![], text) {
         Ok(v) => Ok(v),
         Err(e) => Err(format!("error parsing `{}`: {}", text, e))?,
     }
 }
 
 pub fn parse_goal(text: &str) -> Result<Box<ast::Goal>> {
-    match parser::GoalParser::new().parse(text) {
+    match parser::GoalParser::new().parse(&mut vec![], text) {
         Ok(v) => Ok(v),
         Err(e) => {
             let position_string = |start: usize, end: usize| {
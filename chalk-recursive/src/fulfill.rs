@@ -1,5 +1,5 @@
 use crate::solve::SolveDatabase;
-use crate::Minimums;
+use crate::{Minimums, UCanonicalGoal};
 use chalk_ir::cast::Cast;
 use chalk_ir::fold::Fold;
 use chalk_ir::interner::{HasInterner, Interner};
@@ -135,6 +135,10 @@ pub(super) struct Fulfill<'s, I: Interner, Solver: SolveDatabase<I>> {
     /// refuted. In such a case the solution will be either `CannotProve`, or `Err`
     /// in the case where some other goal leads to an error.
     cannot_prove: bool,
+
+    /// The subgoals that were uniquely proven along the way, recorded only
+    /// when the solver is recording proof trees.
+    proven_subgoals: Vec<UCanonicalGoal<I>>,
 }
 
 impl<'s, I: Interner, Solver: SolveDatabase<I>> Fulfill<'s, I, Solver> {
@@ -153,6 +157,7 @@ impl<'s, I: Interner, Solver: SolveDatabase<I>> Fulfill<'s, I, Solver> {
             obligations: vec![],
             constraints: FxHashSet::default(),
             cannot_prove: false,
+            proven_subgoals: vec![],
         };
 
         let ProgramClauseImplication {
@@ -203,6 +208,7 @@ impl<'s, I: Interner, Solver: SolveDatabase<I>> Fulfill<'s, I, Solver> {
             obligations: vec![],
             constraints: FxHashSet::default(),
             cannot_prove: false,
+            proven_subgoals: vec![],
         };
 
         if let Err(e) = fulfill.push_goal(&canonical_goal.environment, canonical_goal.goal.clone())
@@ -224,6 +230,7 @@ impl<'s, I: Interner, Solver: SolveDatabase<I>> Fulfill<'s, I, Solver> {
                     goal,
                 ) {
                     // the goal is too big. Record that we should return Ambiguous
+                    self.solver.count(|stats| stats.truncations += 1);
                     self.cannot_prove = true;
                     return;
                 }
@@ -236,6 +243,7 @@ impl<'s, I: Interner, Solver: SolveDatabase<I>> Fulfill<'s, I, Solver> {
                     goal,
                 ) {
                     // the goal is too big. Record that we should return Ambiguous
+                    self.solver.count(|stats| stats.truncations += 1);
                     self.cannot_prove = true;
                     return;
                 }
@@ -354,7 +362,17 @@ impl<'s, I: Interner, Solver: SolveDatabase<I>> Fulfill<'s, I, Solver> {
         let interner = self.solver.interner();
         let (quantified, free_vars) = canonicalize(&mut self.infer, interner, wc);
         let (quantified, universes) = u_canonicalize(&mut self.infer, interner, &quantified);
+        let subgoal = if self.solver.records_proofs() {
+            Some(quantified.clone())
+        } else {
+            None
+        };
         let result = self.solver.solve_goal(quantified, minimums);
+        if let (Some(subgoal), Ok(solution)) = (subgoal, &result) {
+            if solution.is_unique() && !self.proven_subgoals.contains(&subgoal) {
+                self.proven_subgoals.push(subgoal);
+            }
+        }
         Ok(PositiveSolution {
             free_vars,
             universes,
@@ -457,6 +475,13 @@ impl<'s, I: Interner, Solver: SolveDatabase<I>> Fulfill<'s, I, Solver> {
             // directly.
             assert!(obligations.is_empty());
             while let Some(obligation) = self.obligations.pop() {
+                if !self.solver.should_continue() {
+                    // The solve was cancelled, so we can't say anything
+                    // about the obligations we haven't proven yet.
+                    self.cannot_prove = true;
+                    return Ok(Outcome::Incomplete);
+                }
+
                 let ambiguous = match &obligation {
                     Obligation::Prove(wc) => {
                         let PositiveSolution {
@@ -513,9 +538,20 @@ impl<'s, I: Interner, Solver: SolveDatabase<I>> Fulfill<'s, I, Solver> {
     }
 
     /// Try to fulfill all pending obligations and build the resulting
-    /// solution. The returned solution will transform `subst` substitution with
-    /// the outcome of type inference by updating the replacements it provides.
-    pub(super) fn solve(mut self, minimums: &mut Minimums) -> Fallible<Solution<I>> {
+    /// solution, along with the subgoals that were proven along the way (if
+    /// proof trees are being recorded).
+    pub(super) fn solve(
+        mut self,
+        minimums: &mut Minimums,
+    ) -> Fallible<(Solution<I>, Vec<UCanonicalGoal<I>>)> {
+        let solution = self.solve_obligations(minimums)?;
+        Ok((solution, self.proven_subgoals))
+    }
+
+    /// Builds the solution for `solve`. The returned solution will transform
+    /// `subst` substitution with the outcome of type inference by updating the
+    /// replacements it provides.
+    fn solve_obligations(&mut self, minimums: &mut Minimums) -> Fallible<Solution<I>> {
         let outcome = match self.fulfill(minimums) {
             Ok(o) => o,
             Err(e) => return Err(e),
@@ -535,7 +571,7 @@ impl<'s, I: Interner, Solver: SolveDatabase<I>> Fulfill<'s, I, Solver> {
                 &mut self.infer,
                 self.solver.interner(),
                 ConstrainedSubst {
-                    subst: self.subst,
+                    subst: self.subst.clone(),
                     constraints,
                 },
             );
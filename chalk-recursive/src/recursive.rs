@@ -1,12 +1,14 @@
 use crate::search_graph::DepthFirstNumber;
 use crate::search_graph::SearchGraph;
-use crate::solve::{SolveDatabase, SolveIteration};
+use crate::solve::{ProofStep, SolveDatabase, SolveIteration};
 use crate::stack::{Stack, StackDepth};
 use crate::{Minimums, UCanonicalGoal};
 use chalk_ir::Fallible;
 use chalk_ir::{interner::Interner, NoSolution};
 use chalk_ir::{Canonical, ConstrainedSubst, Goal, InEnvironment, UCanonical};
-use chalk_solve::{coinductive_goal::IsCoinductive, RustIrDatabase, Solution};
+use chalk_solve::{
+    coinductive_goal::IsCoinductive, Guidance, ProofTree, RustIrDatabase, Solution, SolverStats,
+};
 use rustc_hash::FxHashMap;
 use std::fmt;
 use tracing::debug;
@@ -28,8 +30,23 @@ struct RecursiveContext<I: Interner> {
     max_size: usize,
 
     caching_enabled: bool,
+
+    /// If proof trees are being recorded, this maps each goal that was
+    /// successfully solved to the step that (most recently) solved it.
+    proofs: Option<ProofMap<I>>,
+
+    /// The number of new goals each solve may take on, if limited.
+    budget: Option<usize>,
+
+    /// Set if the last solve ran out of `budget`.
+    budget_exhausted: bool,
+
+    /// The statistics of the last solve, if they are being collected.
+    stats: Option<SolverStats>,
 }
 
+type ProofMap<I> = FxHashMap<UCanonicalGoal<I>, (ProofStep<I>, Solution<I>)>;
+
 /// A Solver is the basic context in which you can propose goals for a given
 /// program. **All questions posed to the solver are in canonical, closed form,
 /// so that each question is answered with effectively a "clean slate"**. This
@@ -38,6 +55,19 @@ struct RecursiveContext<I: Interner> {
 struct Solver<'me, I: Interner> {
     program: &'me dyn RustIrDatabase<I>,
     context: &'me mut RecursiveContext<I>,
+
+    /// Polled before each new goal is solved; once it returns `false`, the
+    /// solve is cancelled.
+    should_continue: &'me dyn Fn() -> bool,
+
+    /// Set once `should_continue` has returned `false`, or the budget has
+    /// run out. From then on every new goal is answered with
+    /// `Ambig(Unknown)`, and nothing more is moved into the cache, since
+    /// results may depend on those answers.
+    cancelled: bool,
+
+    /// The number of new goals this solve may still take on, if limited.
+    steps_left: Option<usize>,
 }
 
 pub struct RecursiveSolver<I: Interner> {
@@ -54,6 +84,27 @@ impl<I: Interner> RecursiveSolver<I> {
             )),
         }
     }
+
+    /// Enables recording of proof trees, which are then returned from
+    /// `solve_with_proof_tree`.
+    pub fn with_proof_trees(mut self) -> Self {
+        self.ctx.proofs = Some(FxHashMap::default());
+        self
+    }
+
+    /// Limits each solve to taking on `budget` goals that are not already
+    /// cached. A solve that runs out gives up with an ambiguous solution,
+    /// and `budget_exhausted` returns `true` until the next solve.
+    pub fn with_budget(mut self, budget: usize) -> Self {
+        self.ctx.budget = Some(budget);
+        self
+    }
+
+    /// Enables collecting statistics, which are then returned from `stats`.
+    pub fn with_stats(mut self) -> Self {
+        self.ctx.stats = Some(SolverStats::default());
+        self
+    }
 }
 
 impl<I: Interner> fmt::Debug for RecursiveSolver<I> {
@@ -90,16 +141,74 @@ impl<I: Interner> RecursiveContext<I> {
             cache: FxHashMap::default(),
             max_size,
             caching_enabled,
+            proofs: None,
+            budget: None,
+            budget_exhausted: false,
+            stats: None,
+        }
+    }
+
+    /// Builds the proof tree for `goal` from the recorded proof steps, or
+    /// `None` if proof trees are not being recorded. `ancestors` are the
+    /// goals further up the tree, used to cut off cycles.
+    fn proof_tree(
+        &self,
+        interner: &I,
+        goal: &UCanonicalGoal<I>,
+        ancestors: &mut Vec<UCanonicalGoal<I>>,
+    ) -> Option<ProofTree<I>> {
+        let proofs = self.proofs.as_ref()?;
+        let (step, solution) = match proofs.get(goal) {
+            Some(recorded) => recorded,
+            None => return Some(ProofTree::new(goal.canonical.clone(), None, vec![])),
+        };
+
+        // Show the goal with whatever we learned about its variables.
+        let instantiated_goal = match solution.definite_subst(interner) {
+            Some(subst) => Canonical {
+                value: subst
+                    .value
+                    .subst
+                    .apply(goal.canonical.value.clone(), interner),
+                binders: subst.binders,
+            },
+            None => goal.canonical.clone(),
+        };
+
+        if ancestors.contains(goal) {
+            return Some(ProofTree::cycle(instantiated_goal));
         }
+
+        ancestors.push(goal.clone());
+        let subgoals = step
+            .subgoals
+            .iter()
+            .filter_map(|subgoal| self.proof_tree(interner, subgoal, ancestors))
+            .collect();
+        ancestors.pop();
+
+        Some(ProofTree::new(
+            instantiated_goal,
+            step.clause.clone(),
+            subgoals,
+        ))
     }
 
     pub(crate) fn solver<'me>(
         &'me mut self,
         program: &'me dyn RustIrDatabase<I>,
+        should_continue: &'me dyn Fn() -> bool,
     ) -> Solver<'me, I> {
+        self.budget_exhausted = false;
+        if let Some(stats) = &mut self.stats {
+            *stats = SolverStats::default();
+        }
         Solver {
             program,
+            steps_left: self.budget,
             context: self,
+            should_continue,
+            cancelled: false,
         }
     }
 }
@@ -120,6 +229,10 @@ impl<'me, I: Interner> Solver<'me, I> {
     /// }`, `into_peeled_goal` can be used to create a canonical goal
     /// `SomeType<!1>: Foo<?0>`. This function will then return a
     /// solution with the substitution `?0 := u8`.
+    ///
+    /// If the solve is cancelled by `should_continue`, the result is
+    /// `Ambig(Unknown)`, and only the goals solved before the cancellation
+    /// are cached.
     pub(crate) fn solve_root_goal(
         &mut self,
         canonical_goal: &UCanonicalGoal<I>,
@@ -127,7 +240,12 @@ impl<'me, I: Interner> Solver<'me, I> {
         debug!("solve_root_goal(canonical_goal={:?})", canonical_goal);
         assert!(self.context.stack.is_empty());
         let minimums = &mut Minimums::new();
-        self.solve_goal(canonical_goal.clone(), minimums)
+        let result = self.solve_goal(canonical_goal.clone(), minimums);
+        if self.cancelled {
+            info!("solve_root_goal: cancelled");
+            return Ok(Solution::Ambig(Guidance::Unknown));
+        }
+        result
     }
 
     #[instrument(level = "debug", skip(self))]
@@ -149,13 +267,17 @@ impl<'me, I: Interner> Solver<'me, I> {
         loop {
             let minimums = &mut Minimums::new();
             let (current_answer, current_prio) = self.solve_iteration(&canonical_goal, minimums);
+            if current_answer.is_ok() {
+                self.count(|stats| stats.answers += 1);
+            }
 
             debug!(
                 "solve_new_subgoal: loop iteration result = {:?} with minimums {:?}",
                 current_answer, minimums
             );
 
-            if !self.context.stack[depth].read_and_reset_cycle_flag() {
+            // If we were cancelled, iterating again won't get us anywhere.
+            if !self.context.stack[depth].read_and_reset_cycle_flag() || self.cancelled {
                 // None of our subgoals depended on us directly.
                 // We can return.
                 self.context.search_graph[dfn].solution = current_answer;
@@ -189,6 +311,23 @@ impl<'me, I: Interner> Solver<'me, I> {
             self.context.search_graph.rollback_to(dfn + 1);
         }
     }
+
+    /// Takes a step from the budget of this solve. Once there are none left,
+    /// this cancels the solve and returns `false`.
+    fn take_step(&mut self) -> bool {
+        match &mut self.steps_left {
+            Some(0) => {
+                self.context.budget_exhausted = true;
+                self.cancelled = true;
+                false
+            }
+            Some(steps_left) => {
+                *steps_left -= 1;
+                true
+            }
+            None => true,
+        }
+    }
 }
 
 impl<'me, I: Interner> SolveDatabase<I> for Solver<'me, I> {
@@ -204,13 +343,16 @@ impl<'me, I: Interner> SolveDatabase<I> for Solver<'me, I> {
         // First check the cache.
         if let Some(value) = self.context.cache.get(&goal) {
             debug!("solve_reduced_goal: cache hit, value={:?}", value);
-            return value.clone();
+            let value = value.clone();
+            self.count(|stats| stats.cache_hits += 1);
+            return value;
         }
 
         // Next, check if the goal is in the search tree already.
         if let Some(dfn) = self.context.search_graph.lookup(&goal) {
             // Check if this table is still on the stack.
             if let Some(depth) = self.context.search_graph[dfn].stack_depth {
+                self.count(|stats| stats.cycles += 1);
                 self.context.stack[depth].flag_cycle();
                 // Mixed cycles are not allowed. For more information about this
                 // see the corresponding section in the coinduction chapter:
@@ -222,6 +364,10 @@ impl<'me, I: Interner> SolveDatabase<I> for Solver<'me, I> {
                 {
                     return Err(NoSolution);
                 }
+            } else {
+                // The goal was solved earlier, but is waiting for the rest of
+                // its cycle before it can be moved to the cache.
+                self.count(|stats| stats.cache_hits += 1);
             }
 
             minimums.update_from(self.context.search_graph[dfn].links);
@@ -234,9 +380,17 @@ impl<'me, I: Interner> SolveDatabase<I> for Solver<'me, I> {
                 previous_solution, previous_solution_priority
             );
             previous_solution
+        } else if !self.should_continue() || !self.take_step() {
+            // We were cancelled or ran out of budget, so give up on the goal
+            // without creating a table for it.
+            Ok(Solution::Ambig(Guidance::Unknown))
         } else {
             // Otherwise, push the goal onto the stack and create a table.
             // The initial result for this table depends on whether the goal is coinductive.
+            self.count(|stats| {
+                stats.cache_misses += 1;
+                stats.tables_created += 1;
+            });
             let coinductive_goal = goal.is_coinductive(self.program);
             let depth = self.context.stack.push(coinductive_goal);
             let dfn = self.context.search_graph.insert(
@@ -260,7 +414,10 @@ impl<'me, I: Interner> SolveDatabase<I> for Solver<'me, I> {
             // cache now. This is a sort of hack to alleviate the
             // worst of the repeated work that we do during tabling.
             if subgoal_minimums.positive >= dfn {
-                if self.context.caching_enabled {
+                if self.cancelled {
+                    debug!("solve_reduced_goal: SCC head encountered, rolling back as cancelled");
+                    self.context.search_graph.rollback_to(dfn);
+                } else if self.context.caching_enabled {
                     self.context
                         .search_graph
                         .move_to_cache(dfn, &mut self.context.cache);
@@ -278,6 +435,28 @@ impl<'me, I: Interner> SolveDatabase<I> for Solver<'me, I> {
         }
     }
 
+    fn records_proofs(&self) -> bool {
+        self.context.proofs.is_some()
+    }
+
+    fn record_proof_step(
+        &mut self,
+        goal: &UCanonicalGoal<I>,
+        step: ProofStep<I>,
+        solution: &Solution<I>,
+    ) {
+        if let Some(proofs) = &mut self.context.proofs {
+            proofs.insert(goal.clone(), (step, solution.clone()));
+        }
+    }
+
+    fn should_continue(&mut self) -> bool {
+        if !self.cancelled && !(self.should_continue)() {
+            self.cancelled = true;
+        }
+        !self.cancelled
+    }
+
     fn interner(&self) -> &I {
         &self.program.interner()
     }
@@ -286,6 +465,12 @@ impl<'me, I: Interner> SolveDatabase<I> for Solver<'me, I> {
         self.program
     }
 
+    fn count(&mut self, update: impl FnOnce(&mut SolverStats)) {
+        if let Some(stats) = &mut self.context.stats {
+            update(stats);
+        }
+    }
+
     fn max_size(&self) -> usize {
         self.context.max_size
     }
@@ -297,17 +482,38 @@ impl<I: Interner> chalk_solve::Solver<I> for RecursiveSolver<I> {
         program: &dyn RustIrDatabase<I>,
         goal: &UCanonical<InEnvironment<Goal<I>>>,
     ) -> Option<chalk_solve::Solution<I>> {
-        self.ctx.solver(program).solve_root_goal(goal).ok()
+        self.ctx
+            .solver(program, &|| true)
+            .solve_root_goal(goal)
+            .ok()
     }
 
     fn solve_limited(
         &mut self,
         program: &dyn RustIrDatabase<I>,
         goal: &UCanonical<InEnvironment<Goal<I>>>,
-        _should_continue: &dyn std::ops::Fn() -> bool,
+        should_continue: &dyn std::ops::Fn() -> bool,
     ) -> Option<chalk_solve::Solution<I>> {
-        // TODO support should_continue in recursive solver
-        self.ctx.solver(program).solve_root_goal(goal).ok()
+        self.ctx
+            .solver(program, should_continue)
+            .solve_root_goal(goal)
+            .ok()
+    }
+
+    fn solve_with_proof_tree(
+        &mut self,
+        program: &dyn RustIrDatabase<I>,
+        goal: &UCanonical<InEnvironment<Goal<I>>>,
+    ) -> Option<(chalk_solve::Solution<I>, Option<ProofTree<I>>)> {
+        let solution = self
+            .ctx
+            .solver(program, &|| true)
+            .solve_root_goal(goal)
+            .ok()?;
+        let tree = self
+            .ctx
+            .proof_tree(program.interner(), goal, &mut Vec::new());
+        Some((solution, tree))
     }
 
     fn solve_multiple(
@@ -321,4 +527,12 @@ impl<I: Interner> chalk_solve::Solver<I> for RecursiveSolver<I> {
     ) -> bool {
         unimplemented!("Recursive solver doesn't support multiple answers")
     }
+
+    fn budget_exhausted(&self) -> bool {
+        self.ctx.budget_exhausted
+    }
+
+    fn stats(&self) -> Option<SolverStats> {
+        self.ctx.stats
+    }
 }
@@ -11,9 +11,25 @@ use chalk_ir::{
 use chalk_solve::clauses::program_clauses_that_could_match;
 use chalk_solve::debug_span;
 use chalk_solve::infer::InferenceTable;
-use chalk_solve::{Guidance, RustIrDatabase, Solution};
+use chalk_solve::{Guidance, RustIrDatabase, Solution, SolverStats};
 use tracing::{debug, instrument};
 
+/// The program clause and proven subgoals behind one solution. These are only
+/// filled in when proof trees are being recorded.
+pub(super) struct ProofStep<I: Interner> {
+    pub(super) clause: Option<ProgramClause<I>>,
+    pub(super) subgoals: Vec<UCanonicalGoal<I>>,
+}
+
+impl<I: Interner> ProofStep<I> {
+    fn empty() -> Self {
+        ProofStep {
+            clause: None,
+            subgoals: vec![],
+        }
+    }
+}
+
 pub(super) trait SolveDatabase<I: Interner>: Sized {
     fn solve_goal(
         &mut self,
@@ -21,6 +37,25 @@ pub(super) trait SolveDatabase<I: Interner>: Sized {
         minimums: &mut Minimums,
     ) -> Fallible<Solution<I>>;
 
+    /// True if proof trees are being recorded.
+    fn records_proofs(&self) -> bool;
+
+    /// Records how `goal` was (most recently) solved. Only called when
+    /// `records_proofs` is true.
+    fn record_proof_step(
+        &mut self,
+        goal: &UCanonicalGoal<I>,
+        step: ProofStep<I>,
+        solution: &Solution<I>,
+    );
+
+    /// Returns `false` once the solve has been cancelled, after which any
+    /// remaining work should be abandoned with an ambiguous result.
+    fn should_continue(&mut self) -> bool;
+
+    /// Updates the statistics, if they are being collected.
+    fn count(&mut self, update: impl FnOnce(&mut SolverStats));
+
     fn max_size(&self) -> usize;
 
     fn interner(&self) -> &I;
@@ -40,6 +75,7 @@ pub(super) trait SolveIteration<I: Interner>: SolveDatabase<I> {
         canonical_goal: &UCanonicalGoal<I>,
         minimums: &mut Minimums,
     ) -> (Fallible<Solution<I>>, ClausePriority) {
+        let original_goal = canonical_goal;
         let UCanonical {
             universes,
             canonical:
@@ -68,26 +104,21 @@ pub(super) trait SolveIteration<I: Interner>: SolveDatabase<I> {
                 // or from the lowered program, which includes fallback
                 // clauses. We try each approach in turn:
 
-                let (prog_solution, prog_prio) = {
+                let (prog_solution, prog_prio, step) = {
                     debug_span!("prog_clauses");
 
                     self.solve_from_clauses(&canonical_goal, minimums)
                 };
                 debug!(?prog_solution);
 
+                self.record_proof(original_goal, step, &prog_solution);
                 (prog_solution, prog_prio)
             }
 
             _ => {
-                let canonical_goal = UCanonical {
-                    universes,
-                    canonical: Canonical {
-                        binders,
-                        value: InEnvironment { environment, goal },
-                    },
-                };
-
-                self.solve_via_simplification(&canonical_goal, minimums)
+                let (solution, prio, step) = self.solve_via_simplification(original_goal, minimums);
+                self.record_proof(original_goal, step, &solution);
+                (solution, prio)
             }
         }
     }
@@ -102,16 +133,39 @@ where
 
 /// Helper methods for `solve_iteration`, private to this module.
 trait SolveIterationHelpers<I: Interner>: SolveDatabase<I> {
+    /// Records the proof step for `canonical_goal`, if proof trees are
+    /// being recorded and the goal was successfully solved.
+    fn record_proof(
+        &mut self,
+        canonical_goal: &UCanonicalGoal<I>,
+        step: ProofStep<I>,
+        solution: &Fallible<Solution<I>>,
+    ) {
+        if let (true, Ok(solution)) = (self.records_proofs(), solution) {
+            self.record_proof_step(canonical_goal, step, solution);
+        }
+    }
+
     #[instrument(level = "debug", skip(self, minimums))]
     fn solve_via_simplification(
         &mut self,
         canonical_goal: &UCanonicalGoal<I>,
         minimums: &mut Minimums,
-    ) -> (Fallible<Solution<I>>, ClausePriority) {
+    ) -> (Fallible<Solution<I>>, ClausePriority, ProofStep<I>) {
         let (infer, subst, goal) = self.new_inference_table(canonical_goal);
         match Fulfill::new_with_simplification(self, infer, subst, goal) {
-            Ok(fulfill) => (fulfill.solve(minimums), ClausePriority::High),
-            Err(e) => (Err(e), ClausePriority::High),
+            Ok(fulfill) => match fulfill.solve(minimums) {
+                Ok((solution, subgoals)) => (
+                    Ok(solution),
+                    ClausePriority::High,
+                    ProofStep {
+                        clause: None,
+                        subgoals,
+                    },
+                ),
+                Err(e) => (Err(e), ClausePriority::High, ProofStep::empty()),
+            },
+            Err(e) => (Err(e), ClausePriority::High, ProofStep::empty()),
         }
     }
 
@@ -122,7 +176,7 @@ trait SolveIterationHelpers<I: Interner>: SolveDatabase<I> {
         &mut self,
         canonical_goal: &UCanonical<InEnvironment<DomainGoal<I>>>,
         minimums: &mut Minimums,
-    ) -> (Fallible<Solution<I>>, ClausePriority) {
+    ) -> (Fallible<Solution<I>>, ClausePriority, ProofStep<I>) {
         let mut clauses = vec![];
 
         let db = self.db();
@@ -137,7 +191,11 @@ trait SolveIterationHelpers<I: Interner>: SolveDatabase<I> {
         match program_clauses_that_could_match(db, canonical_goal) {
             Ok(goal_clauses) => clauses.extend(goal_clauses.into_iter().filter(could_match)),
             Err(Floundered) => {
-                return (Ok(Solution::Ambig(Guidance::Unknown)), ClausePriority::High);
+                return (
+                    Ok(Solution::Ambig(Guidance::Unknown)),
+                    ClausePriority::High,
+                    ProofStep::empty(),
+                );
             }
         }
 
@@ -150,12 +208,17 @@ trait SolveIterationHelpers<I: Interner>: SolveDatabase<I> {
         );
 
         let mut cur_solution = None;
+        let mut cur_step = ProofStep::empty();
         for program_clause in clauses {
             debug_span!("solve_from_clauses", clause = ?program_clause);
 
             // If we have a completely ambiguous answer, it's not going to get better, so stop
             if cur_solution == Some((Solution::Ambig(Guidance::Unknown), ClausePriority::High)) {
-                return (Ok(Solution::Ambig(Guidance::Unknown)), ClausePriority::High);
+                return (
+                    Ok(Solution::Ambig(Guidance::Unknown)),
+                    ClausePriority::High,
+                    cur_step,
+                );
             }
 
             let ProgramClauseData(implication) = program_clause.data(self.interner());
@@ -167,8 +230,22 @@ trait SolveIterationHelpers<I: Interner>: SolveDatabase<I> {
                 Err(e) => (Err(e), ClausePriority::High),
             };
 
-            if let (Ok(solution), priority) = res {
+            if let (Ok((solution, subgoals)), priority) = res {
                 debug!(?solution, ?priority, "Ok");
+                // The proof we report is the one for the first clause that
+                // succeeded, unless a later clause overrides it by priority.
+                let overrides = match &cur_solution {
+                    None => true,
+                    Some((_, cur_priority)) => {
+                        *cur_priority == ClausePriority::Low && priority == ClausePriority::High
+                    }
+                };
+                if overrides {
+                    cur_step = ProofStep {
+                        clause: Some(program_clause.clone()),
+                        subgoals,
+                    };
+                }
                 cur_solution = Some(match cur_solution {
                     None => (solution, priority),
                     Some((cur, cur_priority)) => combine::with_priorities(
@@ -184,7 +261,10 @@ trait SolveIterationHelpers<I: Interner>: SolveDatabase<I> {
                 debug!("Error");
             }
         }
-        cur_solution.map_or((Err(NoSolution), ClausePriority::High), |(s, p)| (Ok(s), p))
+        match cur_solution {
+            Some((s, p)) => (Ok(s), p, cur_step),
+            None => (Err(NoSolution), ClausePriority::High, ProofStep::empty()),
+        }
     }
 
     fn new_inference_table<T: Fold<I, Result = T> + HasInterner<Interner = I> + Clone>(
@@ -45,7 +45,10 @@ pub fn add_builtin_program_clauses<I: Interner>(
             // DiscriminantKind is automatically implemented for all types
             WellKnownTrait::DiscriminantKind => builder.push_fact(trait_ref),
             // There are no builtin impls provided for the following traits:
-            WellKnownTrait::Unpin | WellKnownTrait::Drop | WellKnownTrait::CoerceUnsized => (),
+            WellKnownTrait::Unpin
+            | WellKnownTrait::Drop
+            | WellKnownTrait::CoerceUnsized
+            | WellKnownTrait::Deref => (),
         }
         Ok(())
     })
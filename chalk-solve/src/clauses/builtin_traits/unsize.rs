@@ -1,7 +1,7 @@
 use std::collections::HashSet;
 use std::iter;
 
-use crate::clauses::ClauseBuilder;
+use crate::clauses::{super_traits::super_traits, ClauseBuilder};
 use crate::rust_ir::AdtKind;
 use crate::{Interner, RustIrDatabase, TraitRef, WellKnownTrait};
 use chalk_ir::{
@@ -160,6 +160,100 @@ fn auto_trait_ids<'a, I: Interner>(
         .filter(move |&id| db.trait_datum(id).is_auto_trait())
 }
 
+/// Pushes the clauses for upcasting `dyn Sub` to `dyn Super`, where `Super`
+/// is a super trait of `Sub`. There is one clause for each way `Super` is a
+/// super trait of `Sub`, e.g. `Super<u32>` and `Super<u64>` for
+/// `trait Sub: Super<u32> + Super<u64>`.
+fn add_upcast_clauses<I: Interner>(
+    db: &dyn RustIrDatabase<I>,
+    builder: &mut ClauseBuilder<'_, I>,
+    trait_ref: TraitRef<I>,
+    (dyn_a, principal_a): (&DynTy<I>, TraitId<I>),
+    (dyn_b, principal_b): (&DynTy<I>, TraitId<I>),
+) {
+    let interner = db.interner();
+    let bounds_a = &dyn_a.bounds;
+    let bounds_b = &dyn_b.bounds;
+    let source_ty = trait_ref.self_type_parameter(interner);
+    let target_ty = trait_ref
+        .substitution
+        .at(interner, 1)
+        .assert_ty_ref(interner)
+        .clone();
+
+    let principal_bound_a = bounds_a
+        .skip_binders()
+        .iter(interner)
+        .find(|bound| bound.trait_id() == Some(principal_a))
+        .unwrap();
+    let super_trait_refs = principal_bound_a.map_ref(|bound| match bound {
+        WhereClause::Implemented(tr) => {
+            super_traits(db, principal_a).substitute(interner, &tr.substitution)
+        }
+        _ => unreachable!(),
+    });
+
+    // The associated type bindings of the target, like `Item = u32` in
+    // `dyn Super<Item = u32>`, must hold for the source type.
+    let target_bindings: Vec<Goal<I>> = bounds_b
+        .clone()
+        .substitute(interner, &Substitution::from1(interner, source_ty))
+        .iter(interner)
+        .filter(|bound| matches!(bound.skip_binders(), WhereClause::AliasEq(_)))
+        .map(|bound| bound.clone().cast(interner))
+        .collect();
+
+    // Check that source lifetime outlives target lifetime
+    let lifetime_outlives_goal: Goal<I> = WhereClause::LifetimeOutlives(LifetimeOutlives {
+        a: dyn_a.lifetime.clone(),
+        b: dyn_b.lifetime.clone(),
+    })
+    .cast(interner);
+
+    for super_trait_ref in super_trait_refs.skip_binders() {
+        if super_trait_ref.skip_binders().trait_id != principal_b {
+            continue;
+        }
+        let super_bound = Binders::new(super_trait_refs.binders.clone(), super_trait_ref.clone())
+            .fuse_binders(interner)
+            .map(WhereClause::Implemented);
+
+        // Construct a new trait object type by taking the target ty and
+        // replacing its principal with the super trait of the source's
+        // principal, changing nothing else. For the upcast to be valid, this
+        // new type should be equal to target type.
+        let new_source_ty = TyKind::Dyn(DynTy {
+            bounds: bounds_b.map_ref(|bounds| {
+                QuantifiedWhereClauses::from_iter(
+                    interner,
+                    bounds.iter(interner).map(|bound| {
+                        if bound.trait_id() == Some(principal_b) {
+                            super_bound.clone()
+                        } else {
+                            bound.clone()
+                        }
+                    }),
+                )
+            }),
+            lifetime: dyn_b.lifetime.clone(),
+        })
+        .intern(interner);
+
+        let eq_goal: Goal<I> = EqGoal {
+            a: new_source_ty.cast(interner),
+            b: target_ty.clone().cast(interner),
+        }
+        .cast(interner);
+
+        builder.push_clause(
+            trait_ref.clone(),
+            iter::once(eq_goal)
+                .chain(target_bindings.iter().cloned())
+                .chain(iter::once(lifetime_outlives_goal.clone())),
+        );
+    }
+}
+
 pub fn add_unsize_program_clauses<I: Interner>(
     db: &dyn RustIrDatabase<I>,
     builder: &mut ClauseBuilder<'_, I>,
@@ -189,31 +283,44 @@ pub fn add_unsize_program_clauses<I: Interner>(
 
     match (source_ty.kind(interner), target_ty.kind(interner)) {
         // dyn Trait + AutoX + 'a -> dyn Trait + AutoY + 'b
-        (
-            TyKind::Dyn(DynTy {
+        (TyKind::Dyn(dyn_a), TyKind::Dyn(dyn_b)) => {
+            let DynTy {
                 bounds: bounds_a,
                 lifetime: lifetime_a,
-            }),
-            TyKind::Dyn(DynTy {
+            } = dyn_a;
+            let DynTy {
                 bounds: bounds_b,
                 lifetime: lifetime_b,
-            }),
-        ) => {
+            } = dyn_b;
+
             let principal_a = principal_id(db, bounds_a);
             let principal_b = principal_id(db, bounds_b);
 
             let auto_trait_ids_a: Vec<_> = auto_trait_ids(db, bounds_a).collect();
             let auto_trait_ids_b: Vec<_> = auto_trait_ids(db, bounds_b).collect();
 
-            let may_apply = principal_a == principal_b
-                && auto_trait_ids_b
-                    .iter()
-                    .all(|id_b| auto_trait_ids_a.iter().any(|id_a| id_a == id_b));
+            let may_apply = auto_trait_ids_b
+                .iter()
+                .all(|id_b| auto_trait_ids_a.iter().any(|id_a| id_a == id_b));
 
             if !may_apply {
                 return;
             }
 
+            if principal_a != principal_b {
+                // dyn Sub + AutoX + 'a -> dyn Super + AutoY + 'b
+                if let (Some(principal_a), Some(principal_b)) = (principal_a, principal_b) {
+                    add_upcast_clauses(
+                        db,
+                        builder,
+                        trait_ref,
+                        (dyn_a, principal_a),
+                        (dyn_b, principal_b),
+                    );
+                }
+                return;
+            }
+
             // COMMENT FROM RUSTC:
             // ------------------
             // Require that the traits involved in this upcast are **equal**;
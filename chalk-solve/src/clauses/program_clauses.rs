@@ -1,7 +1,9 @@
 use crate::clauses::builder::ClauseBuilder;
 use crate::rust_ir::*;
 use crate::split::Split;
+use crate::RustIrDatabase;
 use chalk_ir::cast::{Cast, Caster};
+use chalk_ir::fold::shift::Shift;
 use chalk_ir::interner::Interner;
 use chalk_ir::*;
 use std::iter;
@@ -61,7 +63,7 @@ impl<I: Interner> ToProgramClauses<I> for AssociatedTyValue<I> {
     /// Then for the following impl:
     /// ```notrust
     /// impl<T> Iterable for Vec<T> where T: Clone {
-    ///     type IntoIter<'a> = Iter<'a, T>;
+    ///     type IntoIter<'a> = Iter<'a, T> where T: 'a;
     /// }
     /// ```
     ///
@@ -72,7 +74,27 @@ impl<I: Interner> ToProgramClauses<I> for AssociatedTyValue<I> {
     /// forall<'a, T> {
     ///     Normalize(<Vec<T> as Iterable>::IntoIter<'a> -> Iter<'a, T>>) :-
     ///         Implemented(T: Clone),  // (1)
-    ///         Implemented(Iter<'a, T>: 'a).   // (2)
+    ///         Implemented(Iter<'a, T>: 'a),   // (2)
+    ///         TypeOutlives(T: 'a).   // (3)
+    /// }
+    /// ```
+    ///
+    /// If the value is `default`, it may be overridden by impls that
+    /// specialize this one, so it is only revealed when none of those
+    /// that override it apply. For
+    ///
+    /// ```notrust
+    /// impl<T> Iterable for T { default type IntoIter<'a> = Once<'a, T>; }
+    /// impl<T> Iterable for Vec<T> { type IntoIter<'a> = Iter<'a, T>; }
+    /// ```
+    ///
+    /// the value in the first impl gives:
+    ///
+    /// ```notrust
+    /// forall<'a, T> {
+    ///     Normalize(<T as Iterable>::IntoIter<'a> -> Once<'a, T>>) :-
+    ///         Reveal,
+    ///         not { exists<U> { T = Vec<U> } }.   // (4)
     /// }
     /// ```
     fn to_program_clauses(
@@ -113,6 +135,38 @@ impl<I: Interner> ToProgramClauses<I> for AssociatedTyValue<I> {
                 .into_iter()
                 .map(|wc| wc.cloned().substitute(interner, &projection.substitution));
 
+            // 3. any where-clauses on the value itself
+            let value_where_clauses = assoc_ty_value.where_clauses;
+
+            // 4. if the value is `default`, that we may reveal it, and that
+            //    no impl which specializes this one and overrides it applies
+            let default_value_goals = if self.default {
+                let trait_ref = impl_datum
+                    .binders
+                    .map_ref(|b| &b.trait_ref)
+                    .cloned()
+                    .substitute(interner, impl_params);
+                let priorities = builder.db.specialization_priorities(impl_datum.trait_id());
+                let overriding_impls = priorities.higher_than(self.impl_id).filter(|&impl_id| {
+                    builder
+                        .db
+                        .impl_datum(impl_id)
+                        .associated_ty_value_ids
+                        .iter()
+                        .any(|&id| {
+                            builder.db.associated_ty_value(id).associated_ty_id
+                                == self.associated_ty_id
+                        })
+                });
+                iter::once(DomainGoal::Reveal.cast(interner))
+                    .chain(overriding_impls.map(|impl_id| {
+                        impl_applies_goal(builder.db, impl_id, &trait_ref).negate(interner)
+                    }))
+                    .collect()
+            } else {
+                vec![]
+            };
+
             // Create the final program clause:
             //
             // ```notrust
@@ -120,7 +174,8 @@ impl<I: Interner> ToProgramClauses<I> for AssociatedTyValue<I> {
             // forall<'a, T> {
             //     Normalize(<Vec<T> as Iterable>::IntoIter<'a> -> Iter<'a, T>>) :-
             //         Implemented(T: Clone),  // (1)
-            //         Implemented(Iter<'a, T>: 'a).   // (2)
+            //         Implemented(Iter<'a, T>: 'a),   // (2)
+            //         TypeOutlives(T: 'a).   // (3)
             // }
             // ```
             builder.push_clause(
@@ -128,12 +183,54 @@ impl<I: Interner> ToProgramClauses<I> for AssociatedTyValue<I> {
                     alias: AliasTy::Projection(projection.clone()),
                     ty: assoc_ty_value.ty,
                 },
-                impl_where_clauses.chain(assoc_ty_where_clauses),
+                impl_where_clauses
+                    .chain(assoc_ty_where_clauses)
+                    .chain(value_where_clauses)
+                    .casted(interner)
+                    .chain(default_value_goals),
             );
         });
     }
 }
 
+/// The goal that the given impl applies to `trait_ref`. For
+/// `impl<P0..Pn> Trait<T1..Tm> for T0 where WC` and the trait reference
+/// `U0: Trait<U1..Um>`, that is:
+///
+/// ```notrust
+/// exists<P0..Pn> { T0 = U0, ..., Tm = Um, WC }
+/// ```
+fn impl_applies_goal<I: Interner>(
+    db: &dyn RustIrDatabase<I>,
+    impl_id: ImplId<I>,
+    trait_ref: &TraitRef<I>,
+) -> Goal<I> {
+    let interner = db.interner();
+    let impl_datum = db.impl_datum(impl_id);
+    let (binders, bound) = impl_datum.binders.as_ref().into();
+
+    // The parameters of `trait_ref` are moved into the binders of the impl
+    let params_goals = bound
+        .trait_ref
+        .substitution
+        .iter(interner)
+        .cloned()
+        .zip(
+            trait_ref
+                .substitution
+                .iter(interner)
+                .map(|param| param.clone().shifted_in(interner)),
+        )
+        .map(|(a, b)| GoalData::EqGoal(EqGoal { a, b }).intern(interner));
+    let wc_goals = bound.where_clauses.iter().cloned().casted(interner);
+
+    Goal::all(interner, params_goals.chain(wc_goals)).quantify(
+        interner,
+        QuantifierKind::Exists,
+        binders,
+    )
+}
+
 impl<I: Interner> ToProgramClauses<I> for OpaqueTyDatum<I> {
     /// Given `opaque type T<U>: A + B = HiddenTy where U: C;`, we generate:
     ///
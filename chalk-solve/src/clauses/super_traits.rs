@@ -3,8 +3,8 @@ use rustc_hash::FxHashSet;
 use super::builder::ClauseBuilder;
 use crate::RustIrDatabase;
 use chalk_ir::{
-    fold::shift::Shift, interner::Interner, Binders, BoundVar, DebruijnIndex, TraitId, TraitRef,
-    WhereClause,
+    fold::shift::Shift, interner::Interner, AliasEq, AliasTy, Binders, BoundVar, DebruijnIndex,
+    TraitId, TraitRef, WhereClause,
 };
 
 /// Generate `Implemented` clauses for `dyn Trait` and opaque types. We need to generate
@@ -23,6 +23,10 @@ pub(super) fn push_trait_super_clauses<I: Interner>(
     //     `Implemented(dyn Trait: SuperTrait) :- WC`.
     // - for placeholder `!T` of `opaque type T: Trait = HiddenTy`:
     //     `Implemented(!T: SuperTrait) :- WC`
+    //
+    // The associated type bindings in `WC` on the self type, like
+    // `Self: OtherTrait<Item = u32>`, are pushed as facts, as they must hold
+    // for any type implementing `Trait`.
 
     let super_trait_refs =
         super_traits(db, trait_ref.trait_id).substitute(interner, &trait_ref.substitution);
@@ -34,6 +38,18 @@ pub(super) fn push_trait_super_clauses<I: Interner>(
                 .where_clauses()
                 .cloned()
                 .substitute(interner, &super_trait_ref.substitution);
+            let self_ty = super_trait_ref.self_type_parameter(interner);
+            for qwc in &wc {
+                builder.push_binders(qwc.clone(), |builder, wc| match &wc {
+                    WhereClause::AliasEq(AliasEq {
+                        alias: AliasTy::Projection(projection),
+                        ..
+                    }) if projection.self_type_parameter(interner) == self_ty => {
+                        builder.push_fact(wc)
+                    }
+                    _ => {}
+                });
+            }
             builder.push_clause(super_trait_ref, wc);
         });
     }
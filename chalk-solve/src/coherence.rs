@@ -3,7 +3,11 @@ use petgraph::prelude::*;
 use crate::solve::Solver;
 use crate::RustIrDatabase;
 use chalk_ir::interner::Interner;
+#[cfg(feature = "serde")]
+use chalk_ir::interner::SerdeInterner;
 use chalk_ir::{self, ImplId, TraitId};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::fmt;
 use std::sync::Arc;
@@ -17,10 +21,11 @@ pub struct CoherenceSolver<'a, I: Interner> {
     trait_id: TraitId<I>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum CoherenceError<I: Interner> {
     OverlappingImpls(TraitId<I>),
     FailedOrphanCheck(TraitId<I>),
+    SpecializedNonDefaultValue(TraitId<I>),
 }
 
 impl<I: Interner> fmt::Display for CoherenceError<I> {
@@ -32,6 +37,11 @@ impl<I: Interner> fmt::Display for CoherenceError<I> {
             CoherenceError::FailedOrphanCheck(id) => {
                 write!(f, "impl for trait `{:?}` violates the orphan rules", id)
             }
+            CoherenceError::SpecializedNonDefaultValue(id) => write!(
+                f,
+                "impl of trait `{:?}` overrides an associated type value that is not `default`",
+                id
+            ),
         }
     }
 }
@@ -41,10 +51,35 @@ impl<I: Interner> std::error::Error for CoherenceError<I> {}
 /// Stores the specialization priorities for a set of impls.
 /// This basically encodes which impls specialize one another.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct SpecializationPriorities<I: Interner> {
+    #[cfg_attr(feature = "serde", serde(with = "priority_pairs"))]
     map: BTreeMap<ImplId<I>, SpecializationPriority>,
 }
 
+/// Serializes the priorities as a sequence of pairs, since formats like
+/// JSON only allow strings as the keys of maps.
+#[cfg(feature = "serde")]
+mod priority_pairs {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub(super) fn serialize<I: SerdeInterner, S: Serializer>(
+        map: &BTreeMap<ImplId<I>, SpecializationPriority>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(map)
+    }
+
+    pub(super) fn deserialize<'de, I: SerdeInterner, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<ImplId<I>, SpecializationPriority>, D::Error> {
+        let pairs = Vec::<(ImplId<I>, SpecializationPriority)>::deserialize(deserializer)?;
+        Ok(pairs.into_iter().collect())
+    }
+}
+
 impl<I: Interner> SpecializationPriorities<I> {
     pub fn new() -> Self {
         Self {
@@ -57,11 +92,28 @@ impl<I: Interner> SpecializationPriorities<I> {
         self.map[&impl_id]
     }
 
-    /// Store the priority of an impl (used during construction).
-    /// Panics if we have already stored the priority for this impl.
-    fn insert(&mut self, impl_id: ImplId<I>, p: SpecializationPriority) {
-        let old_value = self.map.insert(impl_id, p);
-        assert!(old_value.is_none());
+    /// The impls in the set with a higher priority than the given impl.
+    /// Any of them that applies to the same types as that impl specializes it.
+    pub fn higher_than(&self, impl_id: ImplId<I>) -> impl Iterator<Item = ImplId<I>> + '_ {
+        let higher = self.map.get(&impl_id).map(|&p| {
+            self.map
+                .iter()
+                .filter(move |&(_, &q)| q > p)
+                .map(|(&impl_id, _)| impl_id)
+        });
+        higher.into_iter().flatten()
+    }
+
+    /// Store the priority of an impl (used during construction), unless
+    /// it already has one at least as high. Returns whether it was stored.
+    fn raise(&mut self, impl_id: ImplId<I>, p: SpecializationPriority) -> bool {
+        match self.map.get(&impl_id) {
+            Some(&old_value) if old_value >= p => false,
+            _ => {
+                self.map.insert(impl_id, p);
+                true
+            }
+        }
     }
 }
 
@@ -69,6 +121,7 @@ impl<I: Interner> SpecializationPriorities<I> {
 /// priority (if both apply to the same types). Impls with equal
 /// priority should never apply to the same set of input types.
 #[derive(Copy, Clone, Default, PartialOrd, Ord, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SpecializationPriority(usize);
 
 impl<'a, I> CoherenceSolver<'a, I>
@@ -117,10 +170,43 @@ where
             forest.add_edge(less_special, more_special, ());
         })?;
 
+        // Only `default` values may be overridden by a more special impl.
+        for (less_special, more_special, ()) in forest.all_edges() {
+            if self.overrides_non_default_value(less_special, more_special) {
+                return Err(CoherenceError::SpecializedNonDefaultValue(self.trait_id));
+            }
+        }
+
         Ok(forest.into_graph())
     }
 
+    // Test if the more special impl gives a value to an associated type
+    // whose value in the less special impl is not `default`.
+    fn overrides_non_default_value(
+        &self,
+        less_special: ImplId<I>,
+        more_special: ImplId<I>,
+    ) -> bool {
+        let values = |impl_id| {
+            self.db
+                .impl_datum(impl_id)
+                .associated_ty_value_ids
+                .iter()
+                .map(|&id| self.db.associated_ty_value(id))
+                .collect::<Vec<_>>()
+        };
+        let less_special_values = values(less_special);
+        values(more_special).iter().any(|more_special_value| {
+            less_special_values.iter().any(|less_special_value| {
+                less_special_value.associated_ty_id == more_special_value.associated_ty_id
+                    && !less_special_value.default
+            })
+        })
+    }
+
     // Recursively set priorities for those node and all of its children.
+    // An impl is reached once for each impl it specializes, and ends up
+    // with the priority of the longest path to it.
     fn set_priorities(
         &self,
         idx: NodeIndex,
@@ -128,12 +214,15 @@ where
         p: usize,
         map: &mut SpecializationPriorities<I>,
     ) {
-        // Get the impl datum recorded at this node and reset its priority
+        // Get the impl datum recorded at this node and raise its priority;
+        // its children have been visited already if it is not raised
         {
             let impl_id = forest
                 .node_weight(idx)
                 .expect("index should be a valid index into graph");
-            map.insert(*impl_id, SpecializationPriority(p));
+            if !map.raise(*impl_id, SpecializationPriority(p)) {
+                return;
+            }
         }
 
         // Visit all children of this node, setting their priority to this + 1
@@ -8,14 +8,21 @@ use crate::rust_ir::*;
 use chalk_ir::{interner::Interner, *};
 use itertools::Itertools;
 
-use crate::{logging_db::RecordedItemId, split::Split, RustIrDatabase};
+use crate::{
+    logging_db::RecordedItemId,
+    solve::{explain::FailureExplanation, proof_tree::ProofTree},
+    split::Split,
+    RustIrDatabase,
+};
 
 #[macro_use]
 mod utils;
 
 mod bounds;
+mod explain;
 mod identifiers;
 mod items;
+mod proof_tree;
 mod render_trait;
 mod state;
 mod stub;
@@ -36,6 +43,34 @@ where
     writeln!(f, "{}", v.display(ws))
 }
 
+/// Writes an item whose id is `id`. If the alias of the item is a path, which
+/// it is when its name is ambiguous, the item is declared in `mod` blocks for
+/// the modules of the path, so that the path leads to it.
+fn write_named_item<F, I, Id, T>(
+    f: &mut F,
+    ws: &InternalWriterState<'_, I>,
+    id: &Id,
+    v: &T,
+) -> Result
+where
+    F: std::fmt::Write + ?Sized,
+    I: Interner,
+    Id: RenderAsRust<I>,
+    T: RenderAsRust<I>,
+{
+    let alias = id.display(ws).to_string();
+    let modules: Vec<_> = alias.split("::").collect();
+    let modules = &modules[..modules.len() - 1];
+    for module in modules {
+        writeln!(f, "mod {} {{", module)?;
+    }
+    write_item(f, ws, v)?;
+    for _ in modules {
+        writeln!(f, "}}")?;
+    }
+    Ok(())
+}
+
 /// Writes stubs for items which were referenced by name, but for which we
 /// didn't directly access. For instance, traits mentioned in where bounds which
 /// are only usually checked during well-formedness, when we weren't recording
@@ -78,19 +113,19 @@ where
             }
             RecordedItemId::Adt(id) => {
                 let v = ws.db().adt_datum(id);
-                write_item(f, &InternalWriterState::new(ws), &*v)?;
+                write_named_item(f, &InternalWriterState::new(ws), &id, &*v)?;
             }
             RecordedItemId::Trait(id) => {
                 let v = ws.db().trait_datum(id);
-                write_item(f, &InternalWriterState::new(ws), &*v)?;
+                write_named_item(f, &InternalWriterState::new(ws), &id, &*v)?;
             }
             RecordedItemId::OpaqueTy(id) => {
                 let v = ws.db().opaque_ty_data(id);
-                write_item(f, &InternalWriterState::new(ws), &*v)?;
+                write_named_item(f, &InternalWriterState::new(ws), &id, &*v)?;
             }
             RecordedItemId::FnDef(id) => {
                 let v = ws.db().fn_def_datum(id);
-                write_item(f, &InternalWriterState::new(ws), &*v)?;
+                write_named_item(f, &InternalWriterState::new(ws), &id, &*v)?;
             }
             RecordedItemId::Generator(id) => {
                 let generator = ws.db().generator_datum(id);
@@ -102,6 +137,99 @@ where
     Ok(())
 }
 
+/// Writes out a [`FailureExplanation`], followed by a note naming the most
+/// deeply nested bound that failed, if that isn't the top-level one.
+pub fn write_failure_explanation<F, I, DB, P>(
+    f: &mut F,
+    ws: &WriterState<I, DB, P>,
+    explanation: &FailureExplanation<I>,
+) -> Result
+where
+    F: std::fmt::Write + ?Sized,
+    I: Interner,
+    DB: RustIrDatabase<I>,
+    P: Borrow<DB>,
+{
+    let s = &InternalWriterState::new(ws);
+    write!(f, "{}", RenderAsRust::display(explanation, s))?;
+    let deepest = explanation.deepest_failure();
+    if !std::ptr::eq(deepest, explanation) {
+        let interner = ws.db().interner();
+        let trait_ref = crate::infer::InferenceTable::new()
+            .instantiate_canonical(interner, deepest.trait_ref.clone());
+        write!(
+            f,
+            "\nnote: the deepest unsatisfied bound is `{}`",
+            trait_ref.display(s)
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes out a [`ProofTree`], one goal per line, with each goal followed by
+/// the clause it was resolved against and then the proofs of its subgoals.
+pub fn write_proof_tree<F, I, DB, P>(
+    f: &mut F,
+    ws: &WriterState<I, DB, P>,
+    tree: &ProofTree<I>,
+) -> Result
+where
+    F: std::fmt::Write + ?Sized,
+    I: Interner,
+    DB: RustIrDatabase<I>,
+    P: Borrow<DB>,
+{
+    let s = &InternalWriterState::new(ws);
+    write!(f, "{}", RenderAsRust::display(tree, s))
+}
+
+/// Writes out `arg`, such as the value of a variable in a solution. Inference
+/// variables, and bound variables that are not bound within `arg`, are
+/// written as they are debug-printed, like `?0` and `^0.0`.
+pub fn write_generic_arg<F, I, DB, P>(
+    f: &mut F,
+    ws: &WriterState<I, DB, P>,
+    arg: &GenericArg<I>,
+) -> Result
+where
+    F: std::fmt::Write + ?Sized,
+    I: Interner,
+    DB: RustIrDatabase<I>,
+    P: Borrow<DB>,
+{
+    let s = &InternalWriterState::new(ws).with_debug_vars();
+    write!(f, "{}", arg.display(s))
+}
+
+/// Writes out a lifetime constraint of a solution, inside an `if (...) { }`
+/// giving its environment if that isn't empty. Variables are written as by
+/// [`write_generic_arg`].
+pub fn write_constraint<F, I, DB, P>(
+    f: &mut F,
+    ws: &WriterState<I, DB, P>,
+    constraint: &InEnvironment<Constraint<I>>,
+) -> Result
+where
+    F: std::fmt::Write + ?Sized,
+    I: Interner,
+    DB: RustIrDatabase<I>,
+    P: Borrow<DB>,
+{
+    let s = &InternalWriterState::new(ws).with_debug_vars();
+    let interner = ws.db().interner();
+    let clauses = &constraint.environment.clauses;
+    if clauses.is_empty(interner) {
+        write!(f, "{}", constraint.goal.display(s))
+    } else {
+        write!(
+            f,
+            "if ({}) {{ {} }}",
+            clauses.iter(interner).map(|c| c.display(s)).format("; "),
+            constraint.goal.display(s)
+        )
+    }
+}
+
 /// Displays a set of bounds, all targeting `Self`, as just the trait names,
 /// separated by `+`.
 ///
@@ -97,6 +97,19 @@ impl<I: Interner> RenderAsRust<I> for WhereClause<I> {
     }
 }
 
+/// This renders a lifetime constraint of a solution like the outlives bound
+/// it stands for.
+impl<I: Interner> RenderAsRust<I> for Constraint<I> {
+    fn fmt(&self, s: &InternalWriterState<'_, I>, f: &'_ mut Formatter<'_>) -> Result {
+        match self {
+            Constraint::LifetimeOutlives(a, b) => write!(f, "{}: {}", a.display(s), b.display(s)),
+            Constraint::TypeOutlives(ty, lifetime) => {
+                write!(f, "{}: {}", ty.display(s), lifetime.display(s))
+            }
+        }
+    }
+}
+
 /// This renders `TraitRef` as a clause in a where clause, as opposed to its
 /// usage in other places.
 impl<I: Interner> RenderAsRust<I> for TraitRef<I> {
@@ -0,0 +1,90 @@
+//! Writer logic for "why not?" explanations of failed goals.
+//!
+//! Unlike the other writers, the output here is not meant to be parsed back
+//! in; it is a diagnostic that quotes types and impls in `.chalk` syntax.
+
+use std::fmt::{Debug, Formatter, Result};
+
+use crate::infer::InferenceTable;
+use crate::solve::explain::{CandidateFailure, FailureExplanation};
+use chalk_ir::{fold::Fold, interner::HasInterner, interner::Interner, Canonical};
+
+use super::{items::write_impl_header, render_trait::RenderAsRust, state::InternalWriterState};
+
+/// Replaces the canonical variables in `canonical` with inference variables,
+/// which are written as `_`.
+pub(super) fn instantiate<I, T>(interner: &I, canonical: &Canonical<T>) -> T::Result
+where
+    I: Interner,
+    T: HasInterner<Interner = I> + Fold<I> + Clone + Debug,
+{
+    InferenceTable::new().instantiate_canonical(interner, canonical.clone())
+}
+
+impl<I: Interner> RenderAsRust<I> for FailureExplanation<I> {
+    fn fmt(&self, s: &InternalWriterState<'_, I>, f: &mut Formatter<'_>) -> Result {
+        let interner = s.db().interner();
+        let trait_ref = instantiate(interner, &self.trait_ref);
+        write!(
+            f,
+            "{}the trait bound `{}` is not satisfied",
+            s.indent(),
+            trait_ref.display(s)
+        )?;
+
+        let s = &s.add_indent();
+        if self.candidates.is_empty() {
+            write!(
+                f,
+                "\n{}there are no impls of `{}`",
+                s.indent(),
+                trait_ref.trait_id.display(s)
+            )?;
+        }
+        for candidate in &self.candidates {
+            let impl_datum = s.db().impl_datum(candidate.impl_id);
+            write!(f, "\n{}", s.indent())?;
+            write_impl_header(&s.add_debrujin_index(None), &impl_datum, f)?;
+
+            let s = &s.add_indent();
+            write!(f, "\n{}", s.indent())?;
+            match &candidate.failure {
+                CandidateFailure::Unification { parameter } => {
+                    let impl_parameter = impl_datum
+                        .binders
+                        .skip_binders()
+                        .trait_ref
+                        .substitution
+                        .at(interner, *parameter);
+                    write!(
+                        f,
+                        "`{}` does not unify with `{}`",
+                        trait_ref.substitution.at(interner, *parameter).display(s),
+                        impl_parameter.display(&s.add_debrujin_index(None)),
+                    )?;
+                }
+                CandidateFailure::WhereClause {
+                    where_clause,
+                    cause,
+                } => {
+                    let where_clause = instantiate(interner, where_clause);
+                    write!(
+                        f,
+                        "requires `{}`, which is not satisfied",
+                        where_clause.display(s)
+                    )?;
+                    if let Some(cause) = cause {
+                        write!(f, "\n{}", RenderAsRust::display(&**cause, &s.add_indent()))?;
+                    }
+                }
+                CandidateFailure::Inconclusive => {
+                    write!(
+                        f,
+                        "applies, but its where clauses cannot all be proven together"
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -63,6 +63,14 @@ macro_rules! write_flags {
     };
 }
 
+/// The name the item `id` is declared with: the last segment of its alias,
+/// as `write_items` declares an item whose alias is a path in the modules of
+/// the path.
+fn item_name<I: Interner>(s: &InternalWriterState<'_, I>, id: &impl RenderAsRust<I>) -> String {
+    let alias = id.display(s).to_string();
+    alias.rsplit("::").next().unwrap().to_owned()
+}
+
 impl<'a, I: Interner> RenderAsRust<I> for (&'a GeneratorDatum<I>, &'a GeneratorWitnessDatum<I>) {
     fn fmt(&self, _s: &InternalWriterState<'_, I>, _f: &'_ mut Formatter<'_>) -> Result {
         unimplemented!()
@@ -103,9 +111,9 @@ impl<I: Interner> RenderAsRust<I> for AdtDatum<I> {
 
         // name
         match self.kind {
-            AdtKind::Struct => write!(f, "struct {}", self.id.display(s),)?,
-            AdtKind::Enum => write!(f, "enum {}", self.id.display(s),)?,
-            AdtKind::Union => write!(f, "union {}", self.id.display(s),)?,
+            AdtKind::Struct => write!(f, "struct {}", item_name(s, &self.id))?,
+            AdtKind::Enum => write!(f, "enum {}", item_name(s, &self.id))?,
+            AdtKind::Union => write!(f, "union {}", item_name(s, &self.id))?,
         }
         write_joined_non_empty_list!(f, "<{}>", s.binder_var_display(&self.binders.binders), ", ")?;
 
@@ -203,13 +211,14 @@ impl<I: Interner> RenderAsRust<I> for TraitDatum<I> {
                 WellKnownTrait::Unpin => "unpin",
                 WellKnownTrait::CoerceUnsized => "coerce_unsized",
                 WellKnownTrait::DiscriminantKind => "discriminant_kind",
+                WellKnownTrait::Deref => "deref",
             };
             writeln!(f, "#[lang({})]", name)?;
         }
 
         // trait declaration
         let binders = s.binder_var_display(&self.binders.binders).skip(1);
-        write!(f, "trait {}", self.id.display(s))?;
+        write!(f, "trait {}", item_name(s, &self.id))?;
         write_joined_non_empty_list!(f, "<{}>", binders, ", ")?;
 
         // where clauses
@@ -237,12 +246,50 @@ impl<I: Interner> RenderAsRust<I> for TraitDatum<I> {
     }
 }
 
+/// Writes the header of an impl, e.g. `impl<T> Foo<T> for Bar<T>`, without
+/// its where clauses or body.
+///
+/// `s` must already have a debrujin index added for the impl's binders.
+pub(super) fn write_impl_header<I: Interner>(
+    s: &InternalWriterState<'_, I>,
+    datum: &ImplDatum<I>,
+    f: &mut Formatter<'_>,
+) -> Result {
+    let interner = s.db().interner();
+    let binders = s.binder_var_display(&datum.binders.binders);
+    let trait_ref = &datum.binders.skip_binders().trait_ref;
+
+    // impl keyword
+    // impl<T> Foo<T> for Bar<T> where T: Baz { }
+    // ^^^^
+    write!(f, "impl")?;
+
+    // generic binders
+    // impl<T> Foo<T> for Bar<T> where T: Baz
+    //     ^^^
+    write_joined_non_empty_list!(f, "<{}>", binders, ", ")?;
+
+    // trait, type and parameters
+    // impl<T> Foo<T> for Bar<T> where T: Baz { }
+    //         ^^^^^^^^^^^^^^^^^
+    let full_trait_name = display_type_with_generics(
+        s,
+        trait_ref.trait_id,
+        // Ignore automatically added Self parameter by skipping first parameter
+        &trait_ref.substitution.as_slice(interner)[1..],
+    );
+    write!(
+        f,
+        " {}{} for {}",
+        datum.polarity.display(s),
+        full_trait_name,
+        trait_ref.self_type_parameter(interner).display(s)
+    )
+}
+
 impl<I: Interner> RenderAsRust<I> for ImplDatum<I> {
     fn fmt(&self, s: &InternalWriterState<'_, I>, f: &'_ mut Formatter<'_>) -> Result {
-        let interner = s.db().interner();
-
         let s = &s.add_debrujin_index(None);
-        let binders = s.binder_var_display(&self.binders.binders);
         let value = self.binders.skip_binders();
 
         // annotations
@@ -253,33 +300,7 @@ impl<I: Interner> RenderAsRust<I> for ImplDatum<I> {
             writeln!(f, "#[upstream]")?;
         }
 
-        // impl keyword
-        // impl<T> Foo<T> for Bar<T> where T: Baz { }
-        // ^^^^
-        write!(f, "impl")?;
-        let trait_ref = &value.trait_ref;
-
-        // generic binders
-        // impl<T> Foo<T> for Bar<T> where T: Baz
-        //     ^^^
-        write_joined_non_empty_list!(f, "<{}>", binders, ", ")?;
-
-        // trait, type and parameters
-        // impl<T> Foo<T> for Bar<T> where T: Baz { }
-        //         ^^^^^^^^^^^^^^^^^
-        let full_trait_name = display_type_with_generics(
-            s,
-            trait_ref.trait_id,
-            // Ignore automatically added Self parameter by skipping first parameter
-            &trait_ref.substitution.as_slice(interner)[1..],
-        );
-        write!(
-            f,
-            " {}{} for {}",
-            self.polarity.display(s),
-            full_trait_name,
-            trait_ref.self_type_parameter(interner).display(s)
-        )?;
+        write_impl_header(s, self, f)?;
 
         // where clauses
         // impl<T> Foo<T> for Bar<T> where T: Baz { }
@@ -314,7 +335,7 @@ impl<I: Interner> RenderAsRust<I> for OpaqueTyDatum<I> {
     fn fmt(&self, s: &InternalWriterState<'_, I>, f: &mut Formatter<'_>) -> Result {
         let s = &s.add_debrujin_index(None);
         let bounds = self.bound.skip_binders();
-        write!(f, "opaque type {}", self.opaque_ty_id.display(s))?;
+        write!(f, "opaque type {}", item_name(s, &self.opaque_ty_id))?;
         write_joined_non_empty_list!(f, "<{}>", s.binder_var_display(&self.bound.binders), ", ")?;
         {
             let s = &s.add_debrujin_index(Some(0));
@@ -440,9 +461,19 @@ impl<I: Interner> RenderAsRust<I> for AssociatedTyValue<I> {
             .db()
             .split_associated_ty_value_parameters(&display_params, self);
 
-        write!(f, "{}type {}", s.indent(), assoc_ty_data.id.display(s))?;
+        write!(f, "{}", s.indent())?;
+        if self.default {
+            write!(f, "default ")?;
+        }
+        write!(f, "type {}", assoc_ty_data.id.display(s))?;
         write_joined_non_empty_list!(f, "<{}>", &assoc_ty_value_display, ", ")?;
-        write!(f, " = {};", value.ty.display(s))?;
+        write!(f, " = {}", value.ty.display(s))?;
+        if !value.where_clauses.is_empty() {
+            let where_s = &s.add_indent();
+            let where_clauses = value.where_clauses.display(where_s);
+            write!(f, "\n{}where\n{}", s.indent(), where_clauses)?;
+        }
+        write!(f, ";")?;
         Ok(())
     }
 }
@@ -455,7 +486,7 @@ impl<I: Interner> RenderAsRust<I> for FnDefDatum<I> {
         // declaration
         // fn foo<T>(arg: u32, arg2: T) -> Result<T> where T: Bar
         // ^^^^^^
-        write!(f, "fn {}", s.db().fn_def_name(self.id))?;
+        write!(f, "fn {}", item_name(s, &self.id))?;
 
         // binders
         // fn foo<T>(arg: u32, arg2: T) -> Result<T> where T: Bar
@@ -0,0 +1,154 @@
+//! Writer logic for proof trees, and for the goals and program clauses they
+//! are made of.
+//!
+//! Like "why not?" explanations, the output here is a diagnostic; goals and
+//! clauses are written in `.chalk` syntax, but the tree as a whole is not
+//! meant to be parsed back in.
+
+use std::fmt::{Formatter, Result};
+
+use crate::solve::proof_tree::ProofTree;
+use chalk_ir::{interner::Interner, *};
+use itertools::Itertools;
+
+use super::{explain::instantiate, render_trait::RenderAsRust, state::InternalWriterState};
+
+impl<I: Interner> RenderAsRust<I> for Goal<I> {
+    fn fmt(&self, s: &InternalWriterState<'_, I>, f: &'_ mut Formatter<'_>) -> Result {
+        let interner = s.db().interner();
+        match self.data(interner) {
+            GoalData::Quantified(kind, binders) => {
+                let keyword = match kind {
+                    QuantifierKind::ForAll => "forall",
+                    QuantifierKind::Exists => "exists",
+                };
+                let s = &s.add_debrujin_index(None);
+                if binders.binders.is_empty(interner) {
+                    return binders.skip_binders().fmt(s, f);
+                }
+                write!(
+                    f,
+                    "{}<{}> ",
+                    keyword,
+                    s.binder_var_display(&binders.binders).format(", ")
+                )?;
+                write!(f, "{{ {} }}", binders.skip_binders().display(s))
+            }
+            GoalData::Implies(clauses, goal) => write!(
+                f,
+                "if ({}) {{ {} }}",
+                clauses.iter(interner).map(|c| c.display(s)).format("; "),
+                goal.display(s)
+            ),
+            GoalData::All(goals) => write!(
+                f,
+                "{}",
+                goals.iter(interner).map(|g| g.display(s)).format(", ")
+            ),
+            GoalData::Not(goal) => write!(f, "not {{ {} }}", goal.display(s)),
+            GoalData::EqGoal(eq) => write!(f, "{} = {}", eq.a.display(s), eq.b.display(s)),
+            GoalData::SubtypeGoal(subtype) => write!(
+                f,
+                "Subtype({}, {})",
+                subtype.a.display(s),
+                subtype.b.display(s)
+            ),
+            GoalData::DomainGoal(domain_goal) => domain_goal.fmt(s, f),
+            GoalData::CannotProve => write!(f, "CannotProve"),
+        }
+    }
+}
+
+impl<I: Interner> RenderAsRust<I> for DomainGoal<I> {
+    fn fmt(&self, s: &InternalWriterState<'_, I>, f: &'_ mut Formatter<'_>) -> Result {
+        match self {
+            DomainGoal::Holds(where_clause) => where_clause.fmt(s, f),
+            DomainGoal::WellFormed(WellFormed::Ty(ty)) => {
+                write!(f, "WellFormed({})", ty.display(s))
+            }
+            DomainGoal::WellFormed(WellFormed::Trait(trait_ref)) => {
+                write!(f, "WellFormed({})", trait_ref.display(s))
+            }
+            DomainGoal::FromEnv(FromEnv::Ty(ty)) => write!(f, "FromEnv({})", ty.display(s)),
+            DomainGoal::FromEnv(FromEnv::Trait(trait_ref)) => {
+                write!(f, "FromEnv({})", trait_ref.display(s))
+            }
+            DomainGoal::Normalize(normalize) => write!(
+                f,
+                "Normalize({} -> {})",
+                normalize.alias.display(s),
+                normalize.ty.display(s)
+            ),
+            DomainGoal::IsLocal(ty) => write!(f, "IsLocal({})", ty.display(s)),
+            DomainGoal::IsUpstream(ty) => write!(f, "IsUpstream({})", ty.display(s)),
+            DomainGoal::IsFullyVisible(ty) => write!(f, "IsFullyVisible({})", ty.display(s)),
+            DomainGoal::LocalImplAllowed(trait_ref) => {
+                write!(f, "LocalImplAllowed({})", trait_ref.display(s))
+            }
+            DomainGoal::Compatible => write!(f, "Compatible"),
+            DomainGoal::DownstreamType(ty) => write!(f, "DownstreamType({})", ty.display(s)),
+            DomainGoal::Reveal => write!(f, "Reveal"),
+            DomainGoal::ObjectSafe(trait_id) => write!(f, "ObjectSafe({})", trait_id.display(s)),
+        }
+    }
+}
+
+/// This renders a `ProgramClause` in the syntax of a `clause` item:
+/// `forall<T> { Consequence if Condition1, Condition2 }`, or
+/// `Consequence :- Condition1, Condition2` when nothing is quantified.
+impl<I: Interner> RenderAsRust<I> for ProgramClause<I> {
+    fn fmt(&self, s: &InternalWriterState<'_, I>, f: &'_ mut Formatter<'_>) -> Result {
+        let interner = s.db().interner();
+        let binders = &self.data(interner).0;
+        let s = &s.add_debrujin_index(None);
+        let implication = binders.skip_binders();
+        let consequence = implication.consequence.display(s);
+        let conditions = implication
+            .conditions
+            .iter(interner)
+            .map(|c| c.display(s))
+            .format(", ");
+        match (
+            binders.binders.is_empty(interner),
+            implication.conditions.is_empty(interner),
+        ) {
+            (true, true) => write!(f, "{}", consequence),
+            (true, false) => write!(f, "{} :- {}", consequence, conditions),
+            (false, conditions_empty) => {
+                write!(
+                    f,
+                    "forall<{}> {{ {}",
+                    s.binder_var_display(&binders.binders).format(", "),
+                    consequence
+                )?;
+                if !conditions_empty {
+                    write!(f, " if {}", conditions)?;
+                }
+                write!(f, " }}")
+            }
+        }
+    }
+}
+
+/// Variables left unresolved by the answer are written as `_`. Each subgoal
+/// is written two levels of indentation below its parent, with the clause
+/// that the parent was resolved against in between.
+impl<I: Interner> RenderAsRust<I> for ProofTree<I> {
+    fn fmt(&self, s: &InternalWriterState<'_, I>, f: &'_ mut Formatter<'_>) -> Result {
+        let interner = s.db().interner();
+        let goal = instantiate(interner, &self.goal).goal;
+        write!(f, "{}{}", s.indent(), goal.display(s))?;
+        if self.cycle {
+            return write!(f, " (cycle)");
+        }
+        let s = &s.add_indent();
+        if let Some(clause) = &self.clause {
+            write!(f, "\n{}via {}", s.indent(), clause.display(s))?;
+        }
+        let s = &s.add_indent();
+        for subgoal in &self.subgoals {
+            write!(f, "\n{}", RenderAsRust::display(subgoal, s))?;
+        }
+        Ok(())
+    }
+}
@@ -10,7 +10,7 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-use crate::RustIrDatabase;
+use crate::{display::render_trait::RenderAsRust, RustIrDatabase};
 use chalk_ir::{interner::Interner, *};
 use itertools::Itertools;
 
@@ -44,11 +44,13 @@ enum UnifiedId<I: Interner> {
 
 #[derive(Debug)]
 pub struct IdAliasStore<T: Ord> {
-    /// Map from the DefIds we've encountered to a u32 alias id unique to all ids
-    /// the same name.
-    aliases: BTreeMap<T, u32>,
+    /// Map from the DefIds we've encountered to their aliases.
+    aliases: BTreeMap<T, String>,
     /// Map from each name to the next unused u32 alias id.
     next_unused_for_name: BTreeMap<String, u32>,
+    /// Map from each unqualified name to the full name of the first item
+    /// that goes by it.
+    full_names: BTreeMap<String, String>,
 }
 
 impl<T: Ord> Default for IdAliasStore<T> {
@@ -56,26 +58,43 @@ impl<T: Ord> Default for IdAliasStore<T> {
         IdAliasStore {
             aliases: BTreeMap::default(),
             next_unused_for_name: BTreeMap::default(),
+            full_names: BTreeMap::default(),
         }
     }
 }
 
 impl<T: Copy + Ord> IdAliasStore<T> {
+    /// The alias for the item `id`, whose name is `name`. Items in modules
+    /// are named by their paths, like `foo::Bar`; those go by the last
+    /// segment of the path, unless that is ambiguous, in which case they go
+    /// by the whole path.
     fn alias_for_id_name(&mut self, id: T, name: String) -> String {
         let next_unused_for_name = &mut self.next_unused_for_name;
-        let alias = *self.aliases.entry(id).or_insert_with(|| {
-            let next_unused: &mut u32 = next_unused_for_name.entry(name.clone()).or_default();
-            let id = *next_unused;
-            *next_unused += 1;
-            id
-        });
-        // If there are no conflicts, keep the name the same so that we don't
-        // need name-agnostic equality in display tests.
-        if alias == 0 {
-            name
-        } else {
-            format!("{}_{}", name, alias)
-        }
+        let full_names = &mut self.full_names;
+        self.aliases
+            .entry(id)
+            .or_insert_with(|| {
+                let unqualified = name.rsplit("::").next().unwrap().to_owned();
+                let full_name = full_names
+                    .entry(unqualified.clone())
+                    .or_insert_with(|| name.clone());
+                let name = if *full_name == name {
+                    unqualified
+                } else {
+                    name
+                };
+                let next_unused: &mut u32 = next_unused_for_name.entry(name.clone()).or_default();
+                let alias = *next_unused;
+                *next_unused += 1;
+                // If there are no conflicts, keep the name the same so that
+                // we don't need name-agnostic equality in display tests.
+                if alias == 0 {
+                    name
+                } else {
+                    format!("{}_{}", name, alias)
+                }
+            })
+            .clone()
     }
 }
 
@@ -183,6 +202,9 @@ pub(super) struct InternalWriterState<'a, I: Interner> {
     remapping: Rc<BTreeMap<InvertedBoundVar, InvertedBoundVar>>,
     // the inverted_bound_var which maps to "Self"
     self_mapping: Option<InvertedBoundVar>,
+    // whether variables which are not bound by what is being written are
+    // written as they are debug-printed
+    debug_vars: bool,
 }
 
 type IndexWithinBinding = usize;
@@ -200,6 +222,7 @@ impl<'a, I: Interner> InternalWriterState<'a, I> {
             debrujin_indices_deep: 0,
             remapping: Rc::new(BTreeMap::new()),
             self_mapping: None,
+            debug_vars: false,
         }
     }
 
@@ -207,6 +230,24 @@ impl<'a, I: Interner> InternalWriterState<'a, I> {
         self.persistent_state.db
     }
 
+    /// Returns a state which writes inference variables, and bound variables
+    /// not bound by anything being written, as they are debug-printed
+    /// (`?0` and `^0.0`), rather than as `_` and a made-up name.
+    pub(super) fn with_debug_vars(&self) -> Self {
+        InternalWriterState {
+            debug_vars: true,
+            ..self.clone()
+        }
+    }
+
+    pub(super) fn debug_inference_vars(&self) -> bool {
+        self.debug_vars
+    }
+
+    pub(super) fn debug_bound_var(&self, b: &BoundVar) -> bool {
+        self.debug_vars && b.debruijn.depth() >= self.debrujin_indices_deep
+    }
+
     pub(super) fn add_indent(&self) -> Self {
         InternalWriterState {
             indent_level: self.indent_level + 1,
@@ -344,7 +385,13 @@ impl<'a, I: Interner> InternalWriterState<'a, I> {
             .map(move |(parameter, var)| match parameter {
                 VariableKind::Ty(_) => format!("{}", self.apply_mappings(var)),
                 VariableKind::Lifetime => format!("'{}", self.apply_mappings(var)),
-                VariableKind::Const(_ty) => format!("const {}", self.apply_mappings(var)),
+                VariableKind::Const(ty) => match ty.kind(self.db().interner()) {
+                    // Const parameters are `u32` unless they have a type.
+                    TyKind::Scalar(Scalar::Uint(UintTy::U32)) => {
+                        format!("const {}", self.apply_mappings(var))
+                    }
+                    _ => format!("const {}: {}", self.apply_mappings(var), ty.display(self)),
+                },
             })
     }
 }
@@ -112,6 +112,13 @@ impl<I: Interner, DB: RustIrDatabase<I>> RustIrDatabase<I> for StubWrapper<'_, D
         unreachable!("impl items should never be stubbed")
     }
 
+    fn inherent_impl_datum(
+        &self,
+        _impl_id: chalk_ir::ImplId<I>,
+    ) -> std::sync::Arc<crate::rust_ir::InherentImplDatum<I>> {
+        unreachable!("impl items should never be stubbed")
+    }
+
     fn associated_ty_value(
         &self,
         _id: crate::rust_ir::AssociatedTyValueId<I>,
@@ -152,6 +159,10 @@ impl<I: Interner, DB: RustIrDatabase<I>> RustIrDatabase<I> for StubWrapper<'_, D
         unimplemented!("stub display code should call this")
     }
 
+    fn inherent_impls(&self) -> Vec<chalk_ir::ImplId<I>> {
+        unimplemented!("stub display code should call this")
+    }
+
     fn local_impls_to_coherence_check(
         &self,
         trait_id: chalk_ir::TraitId<I>,
@@ -159,6 +170,13 @@ impl<I: Interner, DB: RustIrDatabase<I>> RustIrDatabase<I> for StubWrapper<'_, D
         self.db.local_impls_to_coherence_check(trait_id)
     }
 
+    fn specialization_priorities(
+        &self,
+        trait_id: chalk_ir::TraitId<I>,
+    ) -> Arc<crate::coherence::SpecializationPriorities<I>> {
+        self.db.specialization_priorities(trait_id)
+    }
+
     fn impl_provided_for(
         &self,
         _auto_trait_id: chalk_ir::TraitId<I>,
@@ -258,6 +276,10 @@ impl<I: Interner, DB: RustIrDatabase<I>> RustIrDatabase<I> for StubWrapper<'_, D
         self.db.fn_def_name(fn_def_id)
     }
 
+    fn method_name(&self, impl_id: chalk_ir::ImplId<I>, index: usize) -> String {
+        self.db.method_name(impl_id, index)
+    }
+
     fn discriminant_type(&self, ty: Ty<I>) -> Ty<I> {
         self.db.discriminant_type(ty)
     }
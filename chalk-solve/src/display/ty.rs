@@ -116,11 +116,17 @@ impl<I: Interner> RenderAsRust<I> for TyKind<I> {
                 write!(f, " + {}", dyn_ty.lifetime.display(s))?;
                 Ok(())
             }
+            TyKind::BoundVar(bound_var) if s.debug_bound_var(bound_var) => {
+                write!(f, "{:?}", bound_var)
+            }
             TyKind::BoundVar(bound_var) => write!(f, "{}", s.display_bound_var(bound_var)),
+            TyKind::InferenceVar(var, _) if s.debug_inference_vars() => write!(f, "{:?}", var),
             TyKind::InferenceVar(_, _) => write!(f, "_"),
             TyKind::Alias(alias_ty) => alias_ty.fmt(s, f),
             TyKind::Function(func) => func.fmt(s, f),
-            TyKind::Placeholder(_) => write!(f, "<placeholder>"),
+            TyKind::Placeholder(ix) => {
+                write!(f, "_placeholder_{}_{}", ix.ui.counter, ix.idx)
+            }
         }
     }
 }
@@ -234,7 +240,9 @@ impl<I: Interner> RenderAsRust<I> for Scalar {
 impl<I: Interner> RenderAsRust<I> for LifetimeData<I> {
     fn fmt(&self, s: &InternalWriterState<'_, I>, f: &'_ mut Formatter<'_>) -> Result {
         match self {
+            LifetimeData::BoundVar(v) if s.debug_bound_var(v) => write!(f, "'{:?}", v),
             LifetimeData::BoundVar(v) => write!(f, "'{}", s.display_bound_var(v)),
+            LifetimeData::InferenceVar(v) if s.debug_inference_vars() => write!(f, "'{:?}", v),
             LifetimeData::InferenceVar(_) => write!(f, "'_"),
             LifetimeData::Placeholder(ix) => {
                 write!(f, "'_placeholder_{}_{}", ix.ui.counter, ix.idx)
@@ -251,17 +259,38 @@ impl<I: Interner> RenderAsRust<I> for LifetimeData<I> {
 
 impl<I: Interner> RenderAsRust<I> for ConstData<I> {
     fn fmt(&self, s: &InternalWriterState<'_, I>, f: &'_ mut Formatter<'_>) -> Result {
-        write!(f, "{}", self.value.display(s))
+        write!(f, "{}", self.value.display(s))?;
+        // Integer literals are `u32` unless they have a suffix.
+        if let ConstValue::Concrete(_) = self.value {
+            match self.ty.kind(s.db().interner()) {
+                TyKind::Scalar(Scalar::Uint(UintTy::U32)) => {}
+                TyKind::Scalar(scalar @ Scalar::Int(_))
+                | TyKind::Scalar(scalar @ Scalar::Uint(_)) => {
+                    write!(f, "{}", scalar.display(s))?
+                }
+                _ => {}
+            }
+        }
+        Ok(())
     }
 }
 
 impl<I: Interner> RenderAsRust<I> for ConstValue<I> {
     fn fmt(&self, s: &InternalWriterState<'_, I>, f: &mut Formatter<'_>) -> Result {
         match self {
+            ConstValue::BoundVar(v) if s.debug_bound_var(v) => write!(f, "{:?}", v),
             ConstValue::BoundVar(v) => write!(f, "{}", s.display_bound_var(v)),
+            ConstValue::InferenceVar(v) if s.debug_inference_vars() => write!(f, "{:?}", v),
             ConstValue::InferenceVar(_) => write!(f, "_"),
             ConstValue::Placeholder(_) => write!(f, "<const placeholder>"),
             ConstValue::Concrete(value) => write!(f, "{:?}", value.interned),
+            ConstValue::Unevaluated(unevaluated) => write!(
+                f,
+                "{{{} {} {}}}",
+                unevaluated.lhs.display(s),
+                unevaluated.op.symbol(),
+                unevaluated.rhs.display(s)
+            ),
         }
     }
 }
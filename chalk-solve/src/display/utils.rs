@@ -28,7 +28,8 @@ macro_rules! write_joined_non_empty_list {
 /// method into something usable by the `display` module.
 ///
 /// This is specifically useful when implementing
-/// [`RustIrDatabase`][crate::RustIrDatabase] `name_*` methods.
+/// [`RustIrDatabase`][crate::RustIrDatabase] `name_*` methods. Paths, like
+/// `foo::Bar`, are kept as paths.
 pub fn sanitize_debug_name(func: impl Fn(&mut Formatter<'_>) -> Option<Result>) -> String {
     use std::fmt::Write;
 
@@ -46,6 +47,10 @@ pub fn sanitize_debug_name(func: impl Fn(&mut Formatter<'_>) -> Option<Result>)
         return "Unknown".to_owned();
     }
 
-    // now the actual sanitization
-    debug_out.replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+    // now the actual sanitization, which keeps the separators of paths
+    debug_out
+        .split("::")
+        .map(|segment| segment.replace(|c: char| !c.is_ascii_alphanumeric(), "_"))
+        .collect::<Vec<_>>()
+        .join("::")
 }
@@ -1098,13 +1098,15 @@ impl<'t, I: Interner> Unifier<'t, I> {
 
             // Unifying an inference variables with a non-inference variable.
             (&ConstValue::InferenceVar(var), &ConstValue::Concrete(_))
-            | (&ConstValue::InferenceVar(var), &ConstValue::Placeholder(_)) => {
+            | (&ConstValue::InferenceVar(var), &ConstValue::Placeholder(_))
+            | (&ConstValue::InferenceVar(var), &ConstValue::Unevaluated(_)) => {
                 debug!(?var, ty=?b, "unify_var_ty");
                 self.unify_var_const(var, b)
             }
 
             (&ConstValue::Concrete(_), &ConstValue::InferenceVar(var))
-            | (&ConstValue::Placeholder(_), &ConstValue::InferenceVar(var)) => {
+            | (&ConstValue::Placeholder(_), &ConstValue::InferenceVar(var))
+            | (&ConstValue::Unevaluated(_), &ConstValue::InferenceVar(var)) => {
                 debug!(?var, ty=?a, "unify_var_ty");
                 self.unify_var_const(var, a)
             }
@@ -1121,6 +1123,29 @@ impl<'t, I: Interner> Unifier<'t, I> {
                 }
             }
 
+            // Unevaluated constants are only equal to the same expression
+            // over equal operands, as we cannot evaluate them.
+            (ConstValue::Unevaluated(u1), ConstValue::Unevaluated(u2)) => {
+                if u1.op != u2.op {
+                    return Err(NoSolution);
+                }
+                self.relate_const_const(variance, &u1.lhs, &u2.lhs)?;
+                self.relate_const_const(variance, &u1.rhs, &u2.rhs)
+            }
+
+            // An unevaluated constant may well evaluate to the other
+            // constant, but we cannot tell, so the result is ambiguous.
+            (&ConstValue::Unevaluated(_), &ConstValue::Concrete(_))
+            | (&ConstValue::Concrete(_), &ConstValue::Unevaluated(_))
+            | (&ConstValue::Unevaluated(_), &ConstValue::Placeholder(_))
+            | (&ConstValue::Placeholder(_), &ConstValue::Unevaluated(_)) => {
+                self.goals.push(InEnvironment::new(
+                    self.environment,
+                    GoalData::CannotProve.intern(interner),
+                ));
+                Ok(())
+            }
+
             (&ConstValue::Concrete(_), &ConstValue::Placeholder(_))
             | (&ConstValue::Placeholder(_), &ConstValue::Concrete(_)) => Err(NoSolution),
 
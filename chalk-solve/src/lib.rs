@@ -1,5 +1,6 @@
 #![deny(rust_2018_idioms)]
 
+use crate::coherence::SpecializationPriorities;
 use crate::display::sanitize_debug_name;
 use crate::rust_ir::*;
 use chalk_ir::interner::Interner;
@@ -73,6 +74,16 @@ pub trait RustIrDatabase<I: Interner>: Debug {
     /// Returns the datum for the impl with the given id.
     fn impl_datum(&self, impl_id: ImplId<I>) -> Arc<ImplDatum<I>>;
 
+    /// Returns the datum for the inherent impl with the given id. Inherent
+    /// impls and trait impls never share an id.
+    ///
+    /// This is only called with ids returned by `inherent_impls`, so the
+    /// default implementation, which goes with a database that has no
+    /// inherent impls, panics.
+    fn inherent_impl_datum(&self, impl_id: ImplId<I>) -> Arc<InherentImplDatum<I>> {
+        panic!("no inherent impl with id {:?}", impl_id)
+    }
+
     /// Returns the `AssociatedTyValue` with the given id.
     fn associated_ty_value(&self, id: AssociatedTyValueId<I>) -> Arc<AssociatedTyValue<I>>;
 
@@ -101,6 +112,12 @@ pub trait RustIrDatabase<I: Interner>: Debug {
         binders: &CanonicalVarKinds<I>,
     ) -> Vec<ImplId<I>>;
 
+    /// Returns all the inherent impls, among which methods are looked up. By
+    /// default, there are none.
+    fn inherent_impls(&self) -> Vec<ImplId<I>> {
+        vec![]
+    }
+
     /// Returns the impls that require coherence checking. This is not the
     /// full set of impls that exist:
     ///
@@ -109,6 +126,14 @@ pub trait RustIrDatabase<I: Interner>: Debug {
     ///   impls actually written by users need to be checked.
     fn local_impls_to_coherence_check(&self, trait_id: TraitId<I>) -> Vec<ImplId<I>>;
 
+    /// Returns the specialization priorities of the impls of the given
+    /// trait, as computed by the coherence check. These decide when the
+    /// `default` associated type values of an impl may be revealed. By
+    /// default, no impl specializes another.
+    fn specialization_priorities(&self, _trait_id: TraitId<I>) -> Arc<SpecializationPriorities<I>> {
+        Arc::new(SpecializationPriorities::new())
+    }
+
     /// Returns true if there is an explicit impl of the auto trait
     /// `auto_trait_id` for the type `ty`. This is part of
     /// the auto trait handling -- if there is no explicit impl given
@@ -193,16 +218,28 @@ pub trait RustIrDatabase<I: Interner>: Debug {
         sanitize_debug_name(|f| I::debug_fn_def_id(fn_def_id, f))
     }
 
+    /// Retrieves the name of the method at `index` among the methods of an
+    /// inherent impl. No uniqueness guarantees, but must be a valid Rust
+    /// identifier.
+    fn method_name(&self, _impl_id: ImplId<I>, index: usize) -> String {
+        format!("method_{}", index)
+    }
+
     // Retrieves the discriminant type for a type (mirror of rustc `TyS::discriminant_ty`)
     fn discriminant_type(&self, ty: Ty<I>) -> Ty<I>;
 }
 
 pub use clauses::program_clauses_for_env;
 
+pub use solve::explain_failure;
+pub use solve::FailureExplanation;
 pub use solve::Guidance;
+pub use solve::ProofTree;
 pub use solve::Solution;
 pub use solve::Solver;
+pub use solve::SolverStats;
 pub use solve::SubstitutionResult;
+pub use solve::{lookup_method, MethodCandidate};
 
 #[macro_use]
 mod debug_macros {
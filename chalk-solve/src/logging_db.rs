@@ -11,6 +11,7 @@ use std::{
     sync::Mutex,
 };
 
+use crate::coherence::SpecializationPriorities;
 use crate::rust_ir::*;
 use crate::{
     display::{self, WriterState},
@@ -161,6 +162,11 @@ where
         self.ws.db().impl_datum(impl_id)
     }
 
+    fn inherent_impl_datum(&self, impl_id: ImplId<I>) -> Arc<InherentImplDatum<I>> {
+        // Inherent impls are not recorded, as they never affect the solver.
+        self.ws.db().inherent_impl_datum(impl_id)
+    }
+
     fn hidden_opaque_type(&self, id: OpaqueTyId<I>) -> Ty<I> {
         self.record(id);
         self.ws.db().hidden_opaque_type(id)
@@ -192,11 +198,20 @@ where
         impl_ids
     }
 
+    fn inherent_impls(&self) -> Vec<ImplId<I>> {
+        self.ws.db().inherent_impls()
+    }
+
     fn local_impls_to_coherence_check(&self, trait_id: TraitId<I>) -> Vec<ImplId<I>> {
         self.record(trait_id);
         self.ws.db().local_impls_to_coherence_check(trait_id)
     }
 
+    fn specialization_priorities(&self, trait_id: TraitId<I>) -> Arc<SpecializationPriorities<I>> {
+        self.record(trait_id);
+        self.ws.db().specialization_priorities(trait_id)
+    }
+
     fn impl_provided_for(&self, auto_trait_id: TraitId<I>, ty: &TyKind<I>) -> bool {
         self.record(auto_trait_id);
         if let TyKind::Adt(adt_id, _) = ty {
@@ -255,6 +270,10 @@ where
         self.ws.db().fn_def_name(fn_def_id)
     }
 
+    fn method_name(&self, impl_id: ImplId<I>, index: usize) -> String {
+        self.ws.db().method_name(impl_id, index)
+    }
+
     fn closure_kind(&self, closure_id: ClosureId<I>, substs: &Substitution<I>) -> ClosureKind {
         // TODO: record closure IDs
         self.ws.db().closure_kind(closure_id, substs)
@@ -423,6 +442,10 @@ where
         self.db.impl_datum(impl_id)
     }
 
+    fn inherent_impl_datum(&self, impl_id: ImplId<I>) -> Arc<InherentImplDatum<I>> {
+        self.db.inherent_impl_datum(impl_id)
+    }
+
     fn associated_ty_value(
         &self,
         id: crate::rust_ir::AssociatedTyValueId<I>,
@@ -447,10 +470,18 @@ where
         self.db.impls_for_trait(trait_id, parameters, binders)
     }
 
+    fn inherent_impls(&self) -> Vec<ImplId<I>> {
+        self.db.inherent_impls()
+    }
+
     fn local_impls_to_coherence_check(&self, trait_id: TraitId<I>) -> Vec<ImplId<I>> {
         self.db.local_impls_to_coherence_check(trait_id)
     }
 
+    fn specialization_priorities(&self, trait_id: TraitId<I>) -> Arc<SpecializationPriorities<I>> {
+        self.db.specialization_priorities(trait_id)
+    }
+
     fn impl_provided_for(&self, auto_trait_id: TraitId<I>, ty: &TyKind<I>) -> bool {
         self.db.impl_provided_for(auto_trait_id, ty)
     }
@@ -505,6 +536,10 @@ where
         self.db.fn_def_name(fn_def_id)
     }
 
+    fn method_name(&self, impl_id: ImplId<I>, index: usize) -> String {
+        self.db.method_name(impl_id, index)
+    }
+
     fn closure_kind(&self, closure_id: ClosureId<I>, substs: &Substitution<I>) -> ClosureKind {
         // TODO: record closure IDs
         self.db.closure_kind(closure_id, substs)
@@ -6,6 +6,8 @@ use chalk_derive::{Fold, HasInterner, Visit};
 use chalk_ir::cast::Cast;
 use chalk_ir::fold::shift::Shift;
 use chalk_ir::interner::Interner;
+#[cfg(feature = "serde")]
+use chalk_ir::interner::SerdeInterner;
 use chalk_ir::{
     try_break,
     visit::{ControlFlow, Visit},
@@ -13,6 +15,8 @@ use chalk_ir::{
     OpaqueTyId, ProjectionTy, QuantifiedWhereClause, Substitution, ToGenericArg, TraitId, TraitRef,
     Ty, TyKind, VariableKind, WhereClause, WithKind,
 };
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::iter;
 
 /// Identifier for an "associated type value" found in some impl.
@@ -22,7 +26,23 @@ pub struct AssociatedTyValueId<I: Interner>(pub I::DefId);
 chalk_ir::id_visit!(AssociatedTyValueId);
 chalk_ir::id_fold!(AssociatedTyValueId);
 
+#[cfg(feature = "serde")]
+impl<I: SerdeInterner> Serialize for AssociatedTyValueId<I> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        I::serialize_def_id(&self.0, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, I: SerdeInterner> Deserialize<'de> for AssociatedTyValueId<I> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        I::deserialize_def_id(deserializer).map(AssociatedTyValueId)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Visit)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct ImplDatum<I: Interner> {
     pub polarity: Polarity,
     pub binders: Binders<ImplDatumBound<I>>,
@@ -54,12 +74,15 @@ impl<I: Interner> ImplDatum<I> {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, HasInterner, Fold, Visit)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct ImplDatumBound<I: Interner> {
     pub trait_ref: TraitRef<I>,
     pub where_clauses: Vec<QuantifiedWhereClause<I>>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ImplType {
     Local,
     External,
@@ -67,18 +90,77 @@ pub enum ImplType {
 
 chalk_ir::const_visit!(ImplType);
 
+/// An inherent impl, which gives methods to a type rather than implementing
+/// a trait for it:
+///
+/// ```ignore
+/// impl<T> Vec<T> where T: Clone {
+///     fn push(&mut self, value: T);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Visit)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
+pub struct InherentImplDatum<I: Interner> {
+    pub binders: Binders<InherentImplDatumBound<I>>,
+    pub impl_type: ImplType,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, HasInterner, Fold, Visit)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
+pub struct InherentImplDatumBound<I: Interner> {
+    pub self_ty: Ty<I>,
+    pub where_clauses: Vec<QuantifiedWhereClause<I>>,
+    pub methods: Vec<MethodDatum<I>>,
+}
+
+/// A method of an inherent impl. Its name is given by
+/// [`RustIrDatabase::method_name`](crate::RustIrDatabase::method_name).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, HasInterner, Fold, Visit)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
+pub struct MethodDatum<I: Interner> {
+    /// How the method takes its receiver, whose type is derived from the
+    /// self type of the impl.
+    pub self_kind: SelfKind,
+
+    /// The types of the arguments other than the receiver.
+    pub argument_types: Vec<Ty<I>>,
+
+    pub return_type: Ty<I>,
+}
+
+/// How a method takes its receiver: `self`, `&self` or `&mut self`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SelfKind {
+    Value,
+    Ref,
+    RefMut,
+}
+
+chalk_ir::const_visit!(SelfKind);
+chalk_ir::copy_fold!(SelfKind);
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct DefaultImplDatum<I: Interner> {
     pub binders: Binders<DefaultImplDatumBound<I>>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct DefaultImplDatumBound<I: Interner> {
     pub trait_ref: TraitRef<I>,
     pub accessible_tys: Vec<Ty<I>>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Visit)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct AdtDatum<I: Interner> {
     pub binders: Binders<AdtDatumBound<I>>,
     pub id: AdtId<I>,
@@ -87,6 +169,7 @@ pub struct AdtDatum<I: Interner> {
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AdtKind {
     Struct,
     Enum,
@@ -96,17 +179,22 @@ pub enum AdtKind {
 chalk_ir::const_visit!(AdtKind);
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, HasInterner, Visit)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct AdtDatumBound<I: Interner> {
     pub variants: Vec<AdtVariantDatum<I>>,
     pub where_clauses: Vec<QuantifiedWhereClause<I>>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, HasInterner, Visit)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct AdtVariantDatum<I: Interner> {
     pub fields: Vec<Ty<I>>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AdtFlags {
     pub upstream: bool,
     pub fundamental: bool,
@@ -116,6 +204,8 @@ pub struct AdtFlags {
 chalk_ir::const_visit!(AdtFlags);
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct AdtRepr<I: Interner> {
     pub c: bool,
     pub packed: bool,
@@ -135,6 +225,8 @@ pub struct AdtRepr<I: Interner> {
 /// Note this is distinct from a function pointer, which points to
 /// a function with a given type signature, whereas this represents
 /// a specific function definition.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct FnDefDatum<I: Interner> {
     pub id: FnDefId<I>,
     pub sig: chalk_ir::FnSig<I>,
@@ -159,6 +251,8 @@ impl<I: Interner> Visit<I> for FnDefDatum<I> {
 /// Represents the inputs and outputs on a `FnDefDatum`. This is split
 /// from the where clauses, since these can contain bound lifetimes.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, HasInterner, Visit)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct FnDefInputsAndOutputDatum<I: Interner> {
     /// Types of the function's arguments
     /// ```ignore
@@ -178,6 +272,8 @@ pub struct FnDefInputsAndOutputDatum<I: Interner> {
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, HasInterner, Visit)]
 /// Represents the bounds on a `FnDefDatum`, including
 /// the function definition's type signature and where clauses.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct FnDefDatumBound<I: Interner> {
     /// Inputs and outputs defined on a function
     /// These are needed for late-bound regions in rustc. For example the
@@ -227,6 +323,8 @@ pub struct FnDefDatumBound<I: Interner> {
 /// [`ImplDatum`]: struct.ImplDatum.html
 /// [`AssociatedTyDatum`]: struct.AssociatedTyDatum.html
 #[derive(Visit)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct TraitDatum<I: Interner> {
     pub id: TraitId<I>,
 
@@ -247,6 +345,7 @@ pub struct TraitDatum<I: Interner> {
 /// A list of the traits that are "well known" to chalk, which means that
 /// the chalk-solve crate has special, hard-coded impls for them.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum WellKnownTrait {
     Sized,
     Copy,
@@ -262,6 +361,9 @@ pub enum WellKnownTrait {
     Unpin,
     CoerceUnsized,
     DiscriminantKind,
+    /// The trait `Deref`, whose first associated type is its `Target`. It is
+    /// used to dereference receivers during method lookup.
+    Deref,
 }
 
 chalk_ir::const_visit!(WellKnownTrait);
@@ -291,6 +393,8 @@ impl<I: Interner> TraitDatum<I> {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, HasInterner, Visit)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct TraitDatumBound<I: Interner> {
     /// Where clauses defined on the trait:
     ///
@@ -302,6 +406,7 @@ pub struct TraitDatumBound<I: Interner> {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TraitFlags {
     /// An "auto trait" is one that is "automatically implemented" for every
     /// struct, so long as no explicit impl is given.
@@ -338,6 +443,8 @@ chalk_ir::const_visit!(TraitFlags);
 
 /// An inline bound, e.g. `: Foo<K>` in `impl<K, T: Foo<K>> SomeType<T>`.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, Visit, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub enum InlineBound<I: Interner> {
     TraitBound(TraitBound<I>),
     AliasEqBound(AliasEqBound<I>),
@@ -382,6 +489,8 @@ impl<I: Interner> IntoWhereClauses<I> for QuantifiedInlineBound<I> {
 /// Represents a trait bound on e.g. a type or type parameter.
 /// Does not know anything about what it's binding.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, Visit)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct TraitBound<I: Interner> {
     pub trait_id: TraitId<I>,
     pub args_no_self: Vec<GenericArg<I>>,
@@ -407,6 +516,8 @@ impl<I: Interner> TraitBound<I> {
 /// Represents an alias equality bound on e.g. a type or type parameter.
 /// Does not know anything about what it's binding.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, Visit)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct AliasEqBound<I: Interner> {
     pub trait_bound: TraitBound<I>,
     pub associated_ty_id: AssocTypeId<I>,
@@ -471,6 +582,8 @@ impl<I: Interner, T> Anonymize<I> for [WithKind<I, T>] {
 /// * The *where clauses* `where_clauses` are things that the impl can *assume* to be true
 ///   (but which projectors must prove).
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct AssociatedTyDatum<I: Interner> {
     /// The trait this associated type is defined in.
     pub trait_id: TraitId<I>,
@@ -479,6 +592,13 @@ pub struct AssociatedTyDatum<I: Interner> {
     pub id: AssocTypeId<I>,
 
     /// Name of this associated type.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "I::serialize_identifier",
+            deserialize_with = "I::deserialize_identifier"
+        )
+    )]
     pub name: I::Identifier,
 
     /// These binders represent the `P0...Pm` variables.  The binders
@@ -508,6 +628,8 @@ impl<I: Interner> Visit<I> for AssociatedTyDatum<I> {
 /// Encodes the parts of `AssociatedTyDatum` where the parameters
 /// `P0..Pm` are in scope (`bounds` and `where_clauses`).
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, Visit, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct AssociatedTyDatumBound<I: Interner> {
     /// Bounds on the associated type itself.
     ///
@@ -570,6 +692,8 @@ impl<I: Interner> AssociatedTyDatum<I> {
 /// }
 /// ```
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, Visit)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct AssociatedTyValue<I: Interner> {
     /// Impl in which this associated type value is found.  You might
     /// need to look at this to find the generic parameters defined on
@@ -606,12 +730,29 @@ pub struct AssociatedTyValue<I: Interner> {
     /// }
     /// ```
     pub value: Binders<AssociatedTyValueBound<I>>,
+
+    /// Whether this value is `default`, and so may be overridden by
+    /// impls that specialize this one.
+    ///
+    /// ```ignore
+    /// impl<T> Iterator for Foo<T> {
+    ///     default type Item = XXX; // <-- this value is `default`
+    /// }
+    /// ```
+    pub default: bool,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, Visit, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct AssociatedTyValueBound<I: Interner> {
     /// Type that we normalize to. The X in `type Foo<'a> = X`.
     pub ty: Ty<I>,
+
+    /// Where clauses on the value, like `Self: 'a` in
+    /// `type Foo<'a> = &'a T where Self: 'a`. These must follow from the
+    /// where clauses on the associated type in the trait.
+    pub where_clauses: Vec<QuantifiedWhereClause<I>>,
 }
 
 /// Represents the bounds for an `impl Trait` type.
@@ -620,6 +761,8 @@ pub struct AssociatedTyValueBound<I: Interner> {
 /// opaque type T: A + B = HiddenTy;
 /// ```
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, Visit)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct OpaqueTyDatum<I: Interner> {
     /// The placeholder `!T` that corresponds to the opaque type `T`.
     pub opaque_ty_id: OpaqueTyId<I>,
@@ -629,6 +772,8 @@ pub struct OpaqueTyDatum<I: Interner> {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, HasInterner, Visit)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct OpaqueTyDatumBound<I: Interner> {
     /// Trait bounds for the opaque type. These are bounds that the hidden type must meet.
     pub bounds: Binders<Vec<QuantifiedWhereClause<I>>>,
@@ -641,6 +786,7 @@ pub struct OpaqueTyDatumBound<I: Interner> {
 // The movability of a generator: whether a generator contains self-references,
 // causing it to be !Unpin
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Movability {
     Static,
     Movable,
@@ -649,6 +795,8 @@ chalk_ir::copy_fold!(Movability);
 
 /// Represents a generator type.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct GeneratorDatum<I: Interner> {
     // Can the generator be moved (is Unpin or not)
     pub movability: Movability,
@@ -660,6 +808,8 @@ pub struct GeneratorDatum<I: Interner> {
 
 /// The nested types for a generator. This always appears inside a `GeneratorDatum`
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct GeneratorInputOutputDatum<I: Interner> {
     /// The generator resume type - a value of this type
     /// is supplied by the caller when resuming the generator.
@@ -688,6 +838,8 @@ pub struct GeneratorInputOutputDatum<I: Interner> {
 /// matters when we treat the witness type as a 'constituent type for the
 /// purposes of determining auto trait implementations.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct GeneratorWitnessDatum<I: Interner> {
     /// This binder is identical to the `input_output` binder in `GeneratorWitness` -
     /// it binds the types and lifetimes that the generator is generic over.
@@ -707,11 +859,14 @@ pub struct GeneratorWitnessDatum<I: Interner> {
 /// via an `Ty`. Instead, we handle this `Binders` specially when determining
 /// auto trait impls. See `push_auto_trait_impls_generator_witness` for more details.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub struct GeneratorWitnessExistential<I: Interner> {
     pub types: Binders<Vec<Ty<I>>>,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Polarity {
     Positive,
     Negative,
@@ -732,6 +887,7 @@ impl Polarity {
 /// If the closure kind for a closure is FnMut, for example, then the closure
 /// implements FnMut and FnOnce.
 #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ClosureKind {
     Fn,
     FnMut,
@@ -1,14 +1,31 @@
 use crate::RustIrDatabase;
 use chalk_derive::HasInterner;
 use chalk_ir::interner::Interner;
+#[cfg(feature = "serde")]
+use chalk_ir::interner::SerdeInterner;
 use chalk_ir::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use tracing::debug;
 
+pub mod explain;
+pub mod method;
+pub mod proof_tree;
+pub mod regions;
+pub mod stats;
 pub mod truncate;
 
+pub use explain::{explain_failure, FailureExplanation};
+pub use method::{lookup_method, MethodCandidate};
+pub use proof_tree::ProofTree;
+pub use regions::{solve_regions, RegionError};
+pub use stats::SolverStats;
+
 /// A (possible) solution for a proposed goal.
 #[derive(Clone, Debug, PartialEq, Eq, HasInterner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub enum Solution<I: Interner> {
     /// The goal indeed holds, and there is a unique value for all existential
     /// variables. In this case, we also record a set of lifetime constraints
@@ -25,6 +42,8 @@ pub enum Solution<I: Interner> {
 /// When a goal holds ambiguously (e.g., because there are multiple possible
 /// solutions), we issue a set of *guidance* back to type inference.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "I: SerdeInterner"))]
 pub enum Guidance<I: Interner> {
     /// The existential variables *must* have the given values if the goal is
     /// ever to hold, but that alone isn't enough to guarantee the goal will
@@ -302,6 +321,37 @@ where
         f: &mut dyn FnMut(SubstitutionResult<Canonical<ConstrainedSubst<I>>>, bool) -> bool,
     ) -> bool;
 
+    /// Attempts to solve the given goal, just like `solve`, but also
+    /// returns a [`ProofTree`] recording the program clauses and subgoals
+    /// that led to the solution.
+    ///
+    /// Proof trees are only recorded by solvers that have been asked to do
+    /// so when they were created; otherwise (and for solvers that don't
+    /// support proof trees at all) the returned tree is `None`.
+    fn solve_with_proof_tree(
+        &mut self,
+        program: &dyn RustIrDatabase<I>,
+        goal: &UCanonical<InEnvironment<Goal<I>>>,
+    ) -> Option<(Solution<I>, Option<ProofTree<I>>)> {
+        self.solve(program, goal).map(|solution| (solution, None))
+    }
+
+    /// Returns `true` if the last goal this solver was asked to solve ran
+    /// out of the solver's work budget. The solver then gives up with an
+    /// ambiguous solution, which says nothing about whether the goal holds,
+    /// unlike the ambiguity of a goal that has many solutions.
+    ///
+    /// Solvers without a budget never run out of it.
+    fn budget_exhausted(&self) -> bool {
+        false
+    }
+
+    /// Returns the statistics for the last goal this solver was asked to
+    /// solve, or `None` if it was not created to collect them.
+    fn stats(&self) -> Option<SolverStats> {
+        None
+    }
+
     /// A convenience method for when one doesn't need the actual solution,
     /// only whether or not one exists.
     fn has_unique_solution(
@@ -0,0 +1,277 @@
+//! "Why not?" explanations for goals that have no solution.
+//!
+//! When a trait goal like `Vec<Foo>: Clone` cannot be proven, the solver
+//! itself only reports that there is no solution. [`explain_failure`] walks
+//! back over the impls of the trait and records, for each one, the first
+//! thing that kept it from applying: either a parameter of the impl header
+//! that does not unify with the goal, or a where clause of the impl that
+//! cannot be proven. Failing where clauses are themselves explained in turn,
+//! so the explanation bottoms out in the deepest bound that does not hold.
+
+use crate::display;
+use crate::infer::InferenceTable;
+use crate::solve::Solver;
+use crate::RustIrDatabase;
+use chalk_ir::cast::Cast;
+use chalk_ir::interner::Interner;
+use chalk_ir::*;
+use std::fmt;
+
+/// How deep we follow failing where clauses before giving up.
+const MAX_DEPTH: usize = 8;
+
+/// Explains why a trait bound could not be proven.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FailureExplanation<I: Interner> {
+    /// The trait bound that could not be proven.
+    pub trait_ref: Canonical<TraitRef<I>>,
+
+    /// Each impl of the trait, along with the reason it did not apply.
+    pub candidates: Vec<ImplCandidate<I>>,
+}
+
+/// An impl that was considered when explaining a failed goal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImplCandidate<I: Interner> {
+    pub impl_id: ImplId<I>,
+    pub failure: CandidateFailure<I>,
+}
+
+/// The reason an impl did not apply to a goal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CandidateFailure<I: Interner> {
+    /// The impl header does not unify with the goal. `parameter` is the
+    /// index of the first mismatched parameter in the trait reference
+    /// (where 0 is the `Self` type).
+    Unification { parameter: usize },
+
+    /// The impl header unifies with the goal, but `where_clause` (with the
+    /// unification applied) could not be proven. If it is a trait bound,
+    /// `cause` explains that failure in turn; it is `None` otherwise, or if
+    /// the bound was already being explained further up.
+    WhereClause {
+        where_clause: Canonical<QuantifiedWhereClause<I>>,
+        cause: Option<Box<FailureExplanation<I>>>,
+    },
+
+    /// The impl header unifies with the goal and each of its where clauses
+    /// can be proven on its own, but not all of them together (e.g. because
+    /// they constrain an inference variable in incompatible ways).
+    Inconclusive,
+}
+
+impl<I: Interner> FailureExplanation<I> {
+    /// Returns the explanation for the most deeply nested bound that failed.
+    /// This is often the most useful thing to show to a user: for
+    /// `Vec<Vec<Foo>>: Clone` it is the explanation for `Foo: Clone`.
+    pub fn deepest_failure(&self) -> &FailureExplanation<I> {
+        self.deepest_failure_at(0).0
+    }
+
+    fn deepest_failure_at(&self, depth: usize) -> (&FailureExplanation<I>, usize) {
+        self.candidates
+            .iter()
+            .filter_map(|candidate| match &candidate.failure {
+                CandidateFailure::WhereClause {
+                    cause: Some(cause), ..
+                } => Some(cause.deepest_failure_at(depth + 1)),
+                _ => None,
+            })
+            .fold((self, depth), |deepest, leaf| {
+                if leaf.1 > deepest.1 {
+                    leaf
+                } else {
+                    deepest
+                }
+            })
+    }
+
+    /// Renders this explanation, quoting types and impls in `.chalk` syntax.
+    pub fn display<'a, DB: RustIrDatabase<I>>(
+        &'a self,
+        db: &'a DB,
+    ) -> FailureExplanationDisplay<'a, I, DB> {
+        FailureExplanationDisplay {
+            explanation: self,
+            db,
+        }
+    }
+}
+
+pub struct FailureExplanationDisplay<'a, I: Interner, DB> {
+    explanation: &'a FailureExplanation<I>,
+    db: &'a DB,
+}
+
+impl<'a, I: Interner, DB: RustIrDatabase<I>> fmt::Display for FailureExplanationDisplay<'a, I, DB> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ws = display::WriterState::<I, DB, _>::new(self.db);
+        display::write_failure_explanation(f, &ws, self.explanation)
+    }
+}
+
+/// Explains why `goal` has no solution.
+///
+/// Returns `None` if `goal` does in fact have a solution, or if it is not a
+/// trait goal (possibly under quantifiers and implications), since only
+/// trait goals are explained in terms of impls.
+pub fn explain_failure<I: Interner>(
+    db: &dyn RustIrDatabase<I>,
+    solver: &mut dyn Solver<I>,
+    goal: &UCanonical<InEnvironment<Goal<I>>>,
+) -> Option<FailureExplanation<I>> {
+    if solver.solve(db, goal).is_some() {
+        return None;
+    }
+    Explainer {
+        db,
+        solver,
+        stack: vec![],
+    }
+    .explain(goal)
+}
+
+struct Explainer<'s, I: Interner> {
+    db: &'s dyn RustIrDatabase<I>,
+    solver: &'s mut dyn Solver<I>,
+
+    /// The trait bounds currently being explained, used to cut off cycles.
+    stack: Vec<Canonical<TraitRef<I>>>,
+}
+
+impl<'s, I: Interner> Explainer<'s, I> {
+    fn explain(
+        &mut self,
+        goal: &UCanonical<InEnvironment<Goal<I>>>,
+    ) -> Option<FailureExplanation<I>> {
+        let interner = self.db.interner();
+        let (
+            mut table,
+            _,
+            InEnvironment {
+                mut environment,
+                goal,
+            },
+        ) = InferenceTable::from_canonical(interner, goal.universes, goal.canonical.clone());
+
+        let mut goal = goal;
+        let trait_ref = loop {
+            goal = match goal.data(interner) {
+                GoalData::Quantified(QuantifierKind::ForAll, subgoal) => {
+                    table.instantiate_binders_universally(interner, subgoal.clone())
+                }
+                GoalData::Quantified(QuantifierKind::Exists, subgoal) => {
+                    table.instantiate_binders_existentially(interner, subgoal.clone())
+                }
+                GoalData::Implies(clauses, subgoal) => {
+                    environment =
+                        environment.add_clauses(interner, clauses.iter(interner).cloned());
+                    subgoal.clone()
+                }
+                GoalData::DomainGoal(DomainGoal::Holds(WhereClause::Implemented(trait_ref))) => {
+                    break trait_ref.clone();
+                }
+                _ => return None,
+            };
+        };
+
+        let canonical_trait_ref = table.canonicalize(interner, trait_ref.clone()).quantified;
+        if self.stack.len() >= MAX_DEPTH || self.stack.contains(&canonical_trait_ref) {
+            return None;
+        }
+
+        self.stack.push(canonical_trait_ref.clone());
+        let candidates = self
+            .impls_for_trait(trait_ref.trait_id)
+            .into_iter()
+            .map(|impl_id| {
+                let snapshot = table.snapshot();
+                let failure = self.try_impl(&mut table, &environment, &trait_ref, impl_id);
+                table.rollback_to(snapshot);
+                ImplCandidate { impl_id, failure }
+            })
+            .collect();
+        self.stack.pop();
+
+        Some(FailureExplanation {
+            trait_ref: canonical_trait_ref,
+            candidates,
+        })
+    }
+
+    /// Returns all the positive impls of `trait_id`, regardless of whether
+    /// they could match the goal; the ones that don't are near misses too.
+    fn impls_for_trait(&self, trait_id: TraitId<I>) -> Vec<ImplId<I>> {
+        let interner = self.db.interner();
+        let trait_datum = self.db.trait_datum(trait_id);
+        let binders = CanonicalVarKinds::from_iter(
+            interner,
+            trait_datum
+                .binders
+                .binders
+                .iter(interner)
+                .cloned()
+                .map(|kind| CanonicalVarKind::new(kind, UniverseIndex::root())),
+        );
+        let parameters = trait_datum.binders.identity_substitution(interner);
+        self.db
+            .impls_for_trait(trait_id, parameters.as_slice(interner), &binders)
+            .into_iter()
+            .filter(|&impl_id| self.db.impl_datum(impl_id).is_positive())
+            .collect()
+    }
+
+    fn try_impl(
+        &mut self,
+        table: &mut InferenceTable<I>,
+        environment: &Environment<I>,
+        trait_ref: &TraitRef<I>,
+        impl_id: ImplId<I>,
+    ) -> CandidateFailure<I> {
+        let interner = self.db.interner();
+        let impl_datum = self.db.impl_datum(impl_id);
+        let impl_bound =
+            table.instantiate_binders_existentially(interner, impl_datum.binders.clone());
+
+        // Unify the parameters one at a time, so that we can point at the
+        // first one that doesn't match. Any goals produced by unification
+        // (e.g. for normalizing projections) are ignored here; they show up
+        // again when the where clauses are checked against the solver.
+        let parameters = trait_ref
+            .substitution
+            .iter(interner)
+            .zip(impl_bound.trait_ref.substitution.iter(interner));
+        for (parameter, (a, b)) in parameters.enumerate() {
+            if table
+                .relate(
+                    interner,
+                    self.db.unification_database(),
+                    environment,
+                    Variance::Invariant,
+                    a,
+                    b,
+                )
+                .is_err()
+            {
+                return CandidateFailure::Unification { parameter };
+            }
+        }
+
+        for where_clause in impl_bound.where_clauses {
+            let goal: InEnvironment<Goal<I>> =
+                InEnvironment::new(environment, where_clause.clone().cast(interner));
+            let canonical_goal = table.canonicalize(interner, goal).quantified;
+            let u_canonical_goal =
+                InferenceTable::u_canonicalize(interner, &canonical_goal).quantified;
+            if self.solver.solve(self.db, &u_canonical_goal).is_none() {
+                let cause = self.explain(&u_canonical_goal).map(Box::new);
+                return CandidateFailure::WhereClause {
+                    where_clause: table.canonicalize(interner, where_clause).quantified,
+                    cause,
+                };
+            }
+        }
+
+        CandidateFailure::Inconclusive
+    }
+}
@@ -0,0 +1,301 @@
+//! Method lookup: finding the method that a call like `receiver.name()`
+//! refers to.
+//!
+//! As in rustc, [`lookup_method`] builds the *autoderef steps* of the
+//! receiver type: the type itself, then the type it dereferences to, and so
+//! on. References are dereferenced directly, other types through their
+//! `Deref` impl, and an array is finally unsized to a slice. At each step
+//! `U`, three probes are made in turn:
+//!
+//! 1. by value, for methods whose receiver has type `U`: `self` methods of
+//!    impls for `U`, and `&self` or `&mut self` methods if `U` is a
+//!    reference of that kind;
+//! 2. by autoref, for `&self` methods of impls for `U`;
+//! 3. by mutable autoref, for `&mut self` methods of impls for `U`.
+//!
+//! The first probe that finds an applicable method ends the lookup. A method
+//! is applicable if the self type of its impl unifies with the type probed
+//! and the where clauses of the impl can be proven, if only ambiguously.
+
+use crate::infer::ucanonicalize::UniverseMapExt;
+use crate::infer::{InferenceTable, ParameterEnaVariableExt};
+use crate::rust_ir::{SelfKind, WellKnownTrait};
+use crate::solve::{Solution, Solver};
+use crate::RustIrDatabase;
+use chalk_ir::cast::Cast;
+use chalk_ir::interner::Interner;
+use chalk_ir::*;
+
+/// How many times a receiver is dereferenced before giving up, which also
+/// cuts off types that dereference to themselves.
+const MAX_AUTODEREFS: usize = 16;
+
+/// A method found by [`lookup_method`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MethodCandidate<I: Interner> {
+    pub impl_id: ImplId<I>,
+
+    /// The index of the method among the methods of the impl.
+    pub method: usize,
+
+    /// How many autoderef steps are taken before the method applies.
+    pub autoderefs: usize,
+
+    /// The reference taken of the dereferenced receiver, if any.
+    pub autoref: Option<Mutability>,
+
+    /// The parameters of the impl, as far as the receiver type and the where
+    /// clauses of the impl determine them.
+    pub impl_parameters: Canonical<Substitution<I>>,
+
+    /// Whether the where clauses of the impl hold only ambiguously, e.g.
+    /// because the receiver type is not fully known.
+    pub ambiguous: bool,
+}
+
+/// Looks up the methods named `name` that can be called on `receiver`.
+///
+/// Returns the methods found by the first probe that finds any. More than one
+/// method means that the call is ambiguous; none, that there is no such
+/// method.
+pub fn lookup_method<I: Interner>(
+    db: &dyn RustIrDatabase<I>,
+    solver: &mut dyn Solver<I>,
+    receiver: &UCanonical<InEnvironment<Ty<I>>>,
+    name: &str,
+) -> Vec<MethodCandidate<I>> {
+    let interner = db.interner();
+    let methods: Vec<_> = db
+        .inherent_impls()
+        .into_iter()
+        .flat_map(|impl_id| {
+            let impl_datum = db.inherent_impl_datum(impl_id);
+            let methods = &impl_datum.binders.skip_binders().methods;
+            (0..methods.len())
+                .filter(|&index| db.method_name(impl_id, index) == name)
+                .map(|index| (impl_id, index, methods[index].self_kind))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    if methods.is_empty() {
+        return vec![];
+    }
+
+    let (table, _, InEnvironment { environment, goal }) =
+        InferenceTable::from_canonical(interner, receiver.universes, receiver.canonical.clone());
+    let mut lookup = MethodLookup {
+        db,
+        solver,
+        table,
+        environment,
+        methods,
+    };
+    for (autoderefs, step) in lookup.autoderef_steps(goal).into_iter().enumerate() {
+        let by_value = match step.kind(interner) {
+            TyKind::Ref(Mutability::Not, _, referent) => {
+                vec![
+                    (SelfKind::Value, step.clone()),
+                    (SelfKind::Ref, referent.clone()),
+                ]
+            }
+            TyKind::Ref(Mutability::Mut, _, referent) => vec![
+                (SelfKind::Value, step.clone()),
+                (SelfKind::RefMut, referent.clone()),
+            ],
+            _ => vec![(SelfKind::Value, step.clone())],
+        };
+        let probes = vec![
+            (None, by_value),
+            (Some(Mutability::Not), vec![(SelfKind::Ref, step.clone())]),
+            (Some(Mutability::Mut), vec![(SelfKind::RefMut, step)]),
+        ];
+        for (autoref, receivers) in probes {
+            let candidates = lookup.probe(autoderefs, autoref, &receivers);
+            if !candidates.is_empty() {
+                return candidates;
+            }
+        }
+    }
+    vec![]
+}
+
+struct MethodLookup<'s, I: Interner> {
+    db: &'s dyn RustIrDatabase<I>,
+    solver: &'s mut dyn Solver<I>,
+    table: InferenceTable<I>,
+    environment: Environment<I>,
+
+    /// The methods with the name looked up, along with their impl and how
+    /// they take their receiver.
+    methods: Vec<(ImplId<I>, usize, SelfKind)>,
+}
+
+impl<I: Interner> MethodLookup<'_, I> {
+    fn autoderef_steps(&mut self, receiver: Ty<I>) -> Vec<Ty<I>> {
+        let interner = self.db.interner();
+        let mut steps = vec![receiver];
+        while steps.len() <= MAX_AUTODEREFS {
+            let step = steps.last().unwrap();
+            let step = self
+                .table
+                .normalize_ty_shallow(interner, step)
+                .unwrap_or_else(|| step.clone());
+            match self.deref(&step) {
+                Some(target) => steps.push(target),
+                None => break,
+            }
+        }
+        if let TyKind::Array(element, _) = steps.last().unwrap().kind(interner) {
+            steps.push(TyKind::Slice(element.clone()).intern(interner));
+        }
+        steps
+    }
+
+    /// The type that `ty` dereferences to, if it is known.
+    fn deref(&mut self, ty: &Ty<I>) -> Option<Ty<I>> {
+        let interner = self.db.interner();
+        if let TyKind::Ref(_, _, referent) = ty.kind(interner) {
+            return Some(referent.clone());
+        }
+
+        let deref_trait = self.db.well_known_trait_id(WellKnownTrait::Deref)?;
+        let target_id = *self.db.trait_datum(deref_trait).associated_ty_ids.first()?;
+        let target = self
+            .table
+            .new_variable(UniverseIndex::root())
+            .to_ty(interner);
+        // `Normalize` rather than `AliasEq`, as the latter is always
+        // ambiguous about the target: it could be the placeholder type.
+        let normalize = Normalize {
+            alias: AliasTy::Projection(ProjectionTy {
+                associated_ty_id: target_id,
+                substitution: Substitution::from1(interner, ty.clone()),
+            }),
+            ty: target.clone(),
+        };
+        match self.solve(normalize.cast(interner))? {
+            Solution::Unique(_) => Some(
+                self.table
+                    .normalize_ty_shallow(interner, &target)
+                    .unwrap_or(target),
+            ),
+            Solution::Ambig(_) => None,
+        }
+    }
+
+    /// Makes one probe, for the methods that take their receiver as one of
+    /// `receivers`, each of which is paired with the self type of the impls
+    /// they can come from.
+    fn probe(
+        &mut self,
+        autoderefs: usize,
+        autoref: Option<Mutability>,
+        receivers: &[(SelfKind, Ty<I>)],
+    ) -> Vec<MethodCandidate<I>> {
+        let mut candidates = vec![];
+        for (self_kind, self_ty) in receivers {
+            for (impl_id, method, method_self_kind) in self.methods.clone() {
+                if method_self_kind != *self_kind {
+                    continue;
+                }
+                let snapshot = self.table.snapshot();
+                if let Some((impl_parameters, ambiguous)) = self.match_impl(impl_id, self_ty) {
+                    candidates.push(MethodCandidate {
+                        impl_id,
+                        method,
+                        autoderefs,
+                        autoref,
+                        impl_parameters,
+                        ambiguous,
+                    });
+                }
+                self.table.rollback_to(snapshot);
+            }
+        }
+        candidates
+    }
+
+    /// Checks whether the inherent impl `impl_id` applies to `self_ty`,
+    /// returning its parameters and whether it applies only ambiguously if
+    /// it does.
+    fn match_impl(
+        &mut self,
+        impl_id: ImplId<I>,
+        self_ty: &Ty<I>,
+    ) -> Option<(Canonical<Substitution<I>>, bool)> {
+        let interner = self.db.interner();
+        let impl_datum = self.db.inherent_impl_datum(impl_id);
+        let identity = Binders::new(
+            impl_datum.binders.binders.clone(),
+            impl_datum.binders.identity_substitution(interner),
+        );
+        let impl_parameters = self
+            .table
+            .instantiate_binders_existentially(interner, identity);
+        let impl_bound = impl_datum
+            .binders
+            .clone()
+            .substitute(interner, &impl_parameters);
+
+        let relation = self
+            .table
+            .relate(
+                interner,
+                self.db.unification_database(),
+                &self.environment,
+                Variance::Invariant,
+                self_ty,
+                &impl_bound.self_ty,
+            )
+            .ok()?;
+        let goals = relation.goals.into_iter().map(|goal| goal.goal).chain(
+            impl_bound
+                .where_clauses
+                .into_iter()
+                .map(|where_clause| where_clause.cast(interner)),
+        );
+        let ambiguous = match self.solve(Goal::all(interner, goals))? {
+            Solution::Unique(_) => false,
+            Solution::Ambig(_) => true,
+        };
+        Some((
+            self.table
+                .canonicalize(interner, impl_parameters)
+                .quantified,
+            ambiguous,
+        ))
+    }
+
+    /// Solves `goal` in the environment of the receiver. If it has a unique
+    /// solution, the solution is applied to the inference variables in it.
+    fn solve(&mut self, goal: Goal<I>) -> Option<Solution<I>> {
+        let interner = self.db.interner();
+        let goal = InEnvironment::new(&self.environment, goal);
+        let canonicalized = self.table.canonicalize(interner, goal);
+        let u_canonicalized = InferenceTable::u_canonicalize(interner, &canonicalized.quantified);
+        let solution = self.solver.solve(self.db, &u_canonicalized.quantified)?;
+
+        if let Solution::Unique(subst) = &solution {
+            let subst = u_canonicalized
+                .universes
+                .map_from_canonical(interner, subst);
+            let ConstrainedSubst { subst, .. } = self.table.instantiate_canonical(interner, subst);
+            // The free variables are unified with their values in the empty
+            // environment, as this only substitutes them.
+            let empty_env = Environment::new(interner);
+            for (i, free_var) in canonicalized.free_vars.into_iter().enumerate() {
+                self.table
+                    .relate(
+                        interner,
+                        self.db.unification_database(),
+                        &empty_env,
+                        Variance::Invariant,
+                        &free_var.to_generic_arg(interner),
+                        subst.at(interner, i),
+                    )
+                    .expect("a solution can be applied to the goal it solves");
+            }
+        }
+        Some(solution)
+    }
+}
@@ -0,0 +1,90 @@
+//! Proof trees, recording *why* a goal was proven.
+//!
+//! When proof recording is enabled on a solver, each successful answer
+//! remembers the program clause it was resolved against and the answers to
+//! the subgoals that clause produced. A [`ProofTree`] is the result of
+//! following those records down from the root goal.
+
+use crate::display;
+use crate::RustIrDatabase;
+use chalk_ir::interner::Interner;
+use chalk_ir::*;
+use std::fmt;
+
+/// A record of how a goal was proven.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofTree<I: Interner> {
+    /// The goal that was proven, with the answer substitution applied.
+    pub goal: Canonical<InEnvironment<Goal<I>>>,
+
+    /// The program clause (from the program or from the environment) that
+    /// the goal was resolved against. This is `None` for goals that are not
+    /// domain goals (e.g. `forall` or `if` goals), which are proven by
+    /// simplification into their subgoals instead.
+    pub clause: Option<ProgramClause<I>>,
+
+    /// Proofs for each of the subgoals required to prove `goal`.
+    pub subgoals: Vec<ProofTree<I>>,
+
+    /// True if `goal` already appears further up the tree. In that case,
+    /// `subgoals` is empty, since the proof of `goal` is given by the
+    /// ancestor. This happens for coinductive (and inductive) cycles.
+    pub cycle: bool,
+}
+
+impl<I: Interner> ProofTree<I> {
+    /// Creates a proof tree node for `goal` resolved against `clause`.
+    pub fn new(
+        goal: Canonical<InEnvironment<Goal<I>>>,
+        clause: Option<ProgramClause<I>>,
+        subgoals: Vec<ProofTree<I>>,
+    ) -> Self {
+        ProofTree {
+            goal,
+            clause,
+            subgoals,
+            cycle: false,
+        }
+    }
+
+    /// Creates a leaf for a goal that refers back to one of its ancestors.
+    pub fn cycle(goal: Canonical<InEnvironment<Goal<I>>>) -> Self {
+        ProofTree {
+            goal,
+            clause: None,
+            subgoals: vec![],
+            cycle: true,
+        }
+    }
+
+    /// Returns all the program clauses used anywhere in this tree, in
+    /// depth-first order.
+    pub fn clauses(&self) -> Vec<&ProgramClause<I>> {
+        let mut clauses = vec![];
+        self.push_clauses(&mut clauses);
+        clauses
+    }
+
+    fn push_clauses<'a>(&'a self, clauses: &mut Vec<&'a ProgramClause<I>>) {
+        clauses.extend(self.clause.iter());
+        for subgoal in &self.subgoals {
+            subgoal.push_clauses(clauses);
+        }
+    }
+
+    pub fn display<'a, DB: RustIrDatabase<I>>(&'a self, db: &'a DB) -> ProofTreeDisplay<'a, I, DB> {
+        ProofTreeDisplay { tree: self, db }
+    }
+}
+
+pub struct ProofTreeDisplay<'a, I: Interner, DB> {
+    tree: &'a ProofTree<I>,
+    db: &'a DB,
+}
+
+impl<'a, I: Interner, DB: RustIrDatabase<I>> fmt::Display for ProofTreeDisplay<'a, I, DB> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ws = display::WriterState::<I, DB, _>::new(self.db);
+        display::write_proof_tree(f, &ws, self.tree)
+    }
+}
@@ -0,0 +1,320 @@
+//! Solving the lifetime constraints of a solution.
+//!
+//! The solvers return the lifetime constraints of a unique solution without
+//! checking them. [`solve_regions`] checks them against the outlives facts of
+//! the environment, and simplifies them to a minimal set.
+//!
+//! The constraints and facts are treated as a graph, with an edge from `'a`
+//! to `'b` for each `'a: 'b`. A type outlives a lifetime if all of its
+//! components do, so `&'a Foo: 'b` is broken up into `'a: 'b`; types we
+//! can't see into (placeholders, projections and unresolved variables) are
+//! nodes of their own. The constraints are unsatisfiable if they imply that
+//! one rigid region (a placeholder or `'static`) outlives another when the
+//! facts don't say so. Variables, on the other hand, can always be chosen to
+//! fit, so constraints on them are kept for the caller to solve.
+
+use crate::solve::Solution;
+use chalk_ir::fold::shift::Shift;
+use chalk_ir::interner::Interner;
+use chalk_ir::visit::{ControlFlow, SuperVisit, Visit, VisitExt, Visitor};
+use chalk_ir::*;
+use std::fmt;
+
+/// A constraint that cannot be satisfied in the environment of a goal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionError<I: Interner> {
+    /// The requirement, implied by the constraints of the solution, that is
+    /// not supported by the outlives facts of the environment.
+    pub constraint: Constraint<I>,
+}
+
+impl<I: Interner> fmt::Display for RegionError<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the lifetime constraint `{:?}` cannot be satisfied",
+            self.constraint
+        )
+    }
+}
+
+impl<I: Interner> Solution<I> {
+    /// Solves the lifetime constraints of a unique solution in
+    /// `environment`, which is usually the environment of the goal. Returns
+    /// the solution with its constraints simplified, or an error if they
+    /// cannot be satisfied. Ambiguous solutions have no constraints, and are
+    /// returned as they are.
+    pub fn solve_regions(
+        self,
+        interner: &I,
+        environment: &Environment<I>,
+    ) -> Result<Solution<I>, RegionError<I>> {
+        match self {
+            Solution::Unique(mut constrained) => {
+                constrained.value.constraints =
+                    solve_regions(interner, environment, &constrained.value.constraints)?;
+                Ok(Solution::Unique(constrained))
+            }
+            Solution::Ambig(guidance) => Ok(Solution::Ambig(guidance)),
+        }
+    }
+}
+
+/// Checks `constraints` against the outlives facts in `environment` and in
+/// the environments of the constraints themselves, returning a minimal set
+/// of constraints that is equivalent to them. Each constraint is only
+/// checked against the facts of its own environment, and the constraints
+/// that remain keep their environments.
+pub fn solve_regions<I: Interner>(
+    interner: &I,
+    environment: &Environment<I>,
+    constraints: &Constraints<I>,
+) -> Result<Constraints<I>, RegionError<I>> {
+    // Group the constraints by their environment.
+    let mut groups: Vec<(&Environment<I>, Vec<_>)> = vec![];
+    for constraint in constraints.iter(interner) {
+        let index = match groups
+            .iter()
+            .position(|(env, _)| **env == constraint.environment)
+        {
+            Some(index) => index,
+            None => {
+                groups.push((&constraint.environment, vec![]));
+                groups.len() - 1
+            }
+        };
+        add_edges(interner, &constraint.goal, &mut groups[index].1);
+    }
+
+    let mut simplified = vec![];
+    for (constraint_environment, edges) in groups {
+        let mut facts = vec![];
+        add_facts(interner, environment, &mut facts);
+        add_facts(interner, constraint_environment, &mut facts);
+        for (a, b) in solve_edges(interner, &facts, edges)? {
+            simplified.push(InEnvironment::new(constraint_environment, a.outlives(&b)));
+        }
+    }
+    Ok(Constraints::from_iter(interner, simplified))
+}
+
+/// Checks the constraints `edges` against `facts`, returning a minimal set
+/// of edges that is equivalent to them.
+fn solve_edges<I: Interner>(
+    interner: &I,
+    facts: &[Edge<I>],
+    mut edges: Vec<Edge<I>>,
+) -> Result<Vec<Edge<I>>, RegionError<I>> {
+    // Any relation between rigid regions that the constraints imply has to
+    // follow from the facts alone.
+    let all: Vec<_> = facts.iter().chain(&edges).cloned().collect();
+    let mut nodes: Vec<_> = vec![];
+    for node in all.iter().flat_map(|(a, b)| vec![a, b]) {
+        if node.is_rigid(interner) && !nodes.contains(&node) {
+            nodes.push(node);
+        }
+    }
+    for &a in &nodes {
+        for &b in &nodes {
+            if outlives(interner, &all, a, b) && !outlives(interner, facts, a, b) {
+                return Err(RegionError {
+                    constraint: a.outlives(b),
+                });
+            }
+        }
+    }
+
+    // Drop the constraints that follow from the facts or from the other
+    // constraints.
+    edges.retain(|(a, b)| !outlives(interner, facts, a, b));
+    let mut index = 0;
+    while index < edges.len() {
+        let (a, b) = edges.remove(index);
+        let rest: Vec<_> = facts.iter().chain(&edges).cloned().collect();
+        if !outlives(interner, &rest, &a, &b) {
+            edges.insert(index, (a, b));
+            index += 1;
+        }
+    }
+    Ok(edges)
+}
+
+/// A node of the outlives graph: a lifetime, or a type we can't see into.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Node<I: Interner> {
+    Lifetime(Lifetime<I>),
+    Ty(Ty<I>),
+}
+
+/// An edge `(a, b)` of the outlives graph, standing for `a: b`.
+type Edge<I> = (Node<I>, Node<I>);
+
+impl<I: Interner> Node<I> {
+    /// Rigid nodes stand for a region we know nothing about beyond the
+    /// facts, unlike variables, whose value we may still pick.
+    fn is_rigid(&self, interner: &I) -> bool {
+        match self {
+            Node::Lifetime(lifetime) => matches!(
+                lifetime.data(interner),
+                LifetimeData::Placeholder(_) | LifetimeData::Static
+            ),
+            Node::Ty(ty) => matches!(ty.kind(interner), TyKind::Placeholder(_)),
+        }
+    }
+
+    fn outlives(&self, other: &Node<I>) -> Constraint<I> {
+        match (self, other) {
+            (Node::Lifetime(a), Node::Lifetime(b)) => {
+                Constraint::LifetimeOutlives(a.clone(), b.clone())
+            }
+            (Node::Ty(ty), Node::Lifetime(b)) => Constraint::TypeOutlives(ty.clone(), b.clone()),
+            (_, Node::Ty(_)) => unreachable!("nothing is required to outlive a type"),
+        }
+    }
+}
+
+/// Returns `true` if `a: b` follows from `edges`.
+fn outlives<I: Interner>(
+    interner: &I,
+    edges: &[(Node<I>, Node<I>)],
+    a: &Node<I>,
+    b: &Node<I>,
+) -> bool {
+    // Everything is outlived by `'static`, and outlives `'empty`.
+    let is_static = |node: &Node<I>| match node {
+        Node::Lifetime(lifetime) => matches!(lifetime.data(interner), LifetimeData::Static),
+        Node::Ty(_) => false,
+    };
+    if a == b || is_static(a) {
+        return true;
+    }
+    if let Node::Lifetime(lifetime) = b {
+        if let LifetimeData::Empty(_) = lifetime.data(interner) {
+            return true;
+        }
+    }
+
+    let mut reached = vec![a];
+    let mut index = 0;
+    while let Some(&node) = reached.get(index) {
+        for (from, to) in edges {
+            if from == node && !reached.contains(&to) {
+                if to == b || is_static(to) {
+                    return true;
+                }
+                reached.push(to);
+            }
+        }
+        index += 1;
+    }
+    false
+}
+
+/// Collects the outlives facts of `environment`. Facts mentioning variables
+/// are skipped, since those are variables of the goal rather than of the
+/// solution.
+fn add_facts<I: Interner>(
+    interner: &I,
+    environment: &Environment<I>,
+    facts: &mut Vec<(Node<I>, Node<I>)>,
+) {
+    for clause in environment.clauses.iter(interner) {
+        let implication = &clause.data(interner).0;
+        if !implication.binders.is_empty(interner) || implication.has_free_vars(interner) {
+            continue;
+        }
+        let implication = implication.skip_binders();
+        if !implication.conditions.is_empty(interner) || !implication.constraints.is_empty(interner)
+        {
+            continue;
+        }
+        match &implication.consequence {
+            DomainGoal::Holds(WhereClause::LifetimeOutlives(LifetimeOutlives { a, b })) => {
+                add_edges(
+                    interner,
+                    &Constraint::LifetimeOutlives(a.clone(), b.clone()),
+                    facts,
+                )
+            }
+            DomainGoal::Holds(WhereClause::TypeOutlives(TypeOutlives { ty, lifetime })) => {
+                add_edges(
+                    interner,
+                    &Constraint::TypeOutlives(ty.clone(), lifetime.clone()),
+                    facts,
+                )
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Adds the edges for `constraint`, breaking up type outlives constraints
+/// into constraints on the components of the type.
+fn add_edges<I: Interner>(
+    interner: &I,
+    constraint: &Constraint<I>,
+    edges: &mut Vec<(Node<I>, Node<I>)>,
+) {
+    match constraint {
+        Constraint::LifetimeOutlives(a, b) => {
+            edges.push((Node::Lifetime(a.clone()), Node::Lifetime(b.clone())));
+        }
+        Constraint::TypeOutlives(ty, lifetime) => {
+            let mut components = Components {
+                interner,
+                components: vec![],
+            };
+            let _ = ty.visit_with(&mut components, DebruijnIndex::INNERMOST);
+            for component in components.components {
+                edges.push((component, Node::Lifetime(lifetime.clone())));
+            }
+        }
+    }
+}
+
+/// Collects the components of a type that have to outlive a lifetime for
+/// the type to outlive it.
+struct Components<'i, I: Interner> {
+    interner: &'i I,
+    components: Vec<Node<I>>,
+}
+
+impl<'i, I: Interner> Visitor<'i, I> for Components<'i, I> {
+    type BreakTy = ();
+
+    fn as_dyn(&mut self) -> &mut dyn Visitor<'i, I, BreakTy = Self::BreakTy> {
+        self
+    }
+
+    fn interner(&self) -> &'i I {
+        self.interner
+    }
+
+    fn visit_ty(&mut self, ty: &Ty<I>, outer_binder: DebruijnIndex) -> ControlFlow<()> {
+        match ty.kind(self.interner) {
+            TyKind::Alias(_)
+            | TyKind::Placeholder(_)
+            | TyKind::BoundVar(_)
+            | TyKind::InferenceVar(..) => {
+                // Components that mention lifetimes bound inside the type
+                // (like in `for<'a> fn(<&'a T as Foo>::Assoc)`) are skipped.
+                if let Ok(ty) = ty.clone().shifted_out_to(self.interner, outer_binder) {
+                    self.components.push(Node::Ty(ty));
+                }
+                ControlFlow::CONTINUE
+            }
+            _ => ty.super_visit_with(self.as_dyn(), outer_binder),
+        }
+    }
+
+    fn visit_lifetime(
+        &mut self,
+        lifetime: &Lifetime<I>,
+        outer_binder: DebruijnIndex,
+    ) -> ControlFlow<()> {
+        if let Ok(lifetime) = lifetime.clone().shifted_out_to(self.interner, outer_binder) {
+            self.components.push(Node::Lifetime(lifetime));
+        }
+        ControlFlow::CONTINUE
+    }
+}
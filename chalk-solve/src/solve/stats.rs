@@ -0,0 +1,59 @@
+//! Counters for the work a solver does, to get an idea of what solving a
+//! goal costs.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The work a solver did for a goal. Solvers only collect these when asked
+/// to when they are created; see [`Solver::stats`].
+///
+/// Not every counter applies to every solver. The recursive solver has no
+/// strands, so it leaves those counters at zero.
+///
+/// [`Solver::stats`]: crate::Solver::stats
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SolverStats {
+    /// Tables created for new goals. In the recursive solver, these are the
+    /// nodes of the search graph.
+    pub tables_created: usize,
+
+    /// Strands created, whether for the clauses of a new table or to pursue
+    /// further answers.
+    pub strands_created: usize,
+
+    /// Strands picked up and worked on.
+    pub strands_pursued: usize,
+
+    /// Answers added to tables. In the recursive solver, these are the
+    /// solutions found by each iteration for a goal.
+    pub answers: usize,
+
+    /// Goals whose table (in the SLG solver) or result (in the recursive
+    /// solver) was already there when they came up.
+    pub cache_hits: usize,
+
+    /// Goals that had to be solved from scratch.
+    pub cache_misses: usize,
+
+    /// Goals that came up again while they were being solved.
+    pub cycles: usize,
+
+    /// Goals and answers that were too large, and were treated as ambiguous
+    /// (or floundered) instead.
+    pub truncations: usize,
+}
+
+impl fmt::Display for SolverStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "tables created:  {}", self.tables_created)?;
+        writeln!(f, "strands created: {}", self.strands_created)?;
+        writeln!(f, "strands pursued: {}", self.strands_pursued)?;
+        writeln!(f, "answers:         {}", self.answers)?;
+        writeln!(f, "cache hits:      {}", self.cache_hits)?;
+        writeln!(f, "cache misses:    {}", self.cache_misses)?;
+        writeln!(f, "cycles:          {}", self.cycles)?;
+        write!(f, "truncations:     {}", self.truncations)
+    }
+}
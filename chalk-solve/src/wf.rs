@@ -12,7 +12,7 @@ use chalk_ir::{
 };
 use tracing::debug;
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum WfError<I: Interner> {
     IllFormedTypeDecl(chalk_ir::AdtId<I>),
     IllFormedOpaqueTypeDecl(chalk_ir::OpaqueTyId<I>),
@@ -419,7 +419,7 @@ where
                     &impl_datum,
                 )
             }
-            WellKnownTrait::Clone | WellKnownTrait::Unpin => true,
+            WellKnownTrait::Clone | WellKnownTrait::Unpin | WellKnownTrait::Deref => true,
             // You can't add a manual implementation for the following traits:
             WellKnownTrait::Fn
             | WellKnownTrait::FnOnce
@@ -530,11 +530,24 @@ fn impl_wf_environment<'i, I: Interner>(
 /// }
 ///
 /// impl<T> Foo for Box<T> {
-///     type Item<'a> = Box<&'a T>;
+///     type Item<'a> = Box<&'a T> where T: 'a;
 /// }
 /// ```
 ///
-/// we would issue the following subgoal: `forall<'a> { WellFormed(Box<&'a T>) }`.
+/// we would issue the following subgoal:
+///
+/// ```ignore
+/// forall<'a> {
+///     if (FromEnv(Box<T>: 'a)) {
+///         T: 'a,
+///         if (FromEnv(T: 'a)) { WellFormed(Box<&'a T>), WellFormed(Box<&'a T>: Clone) }
+///     }
+/// }
+/// ```
+///
+/// That is, the where clauses on the value must follow from those on the associated
+/// type in the trait, and the value must be well-formed and meet the bounds assuming
+/// both.
 ///
 /// Note that there is no binder for `T` in the above: the goal we
 /// generate is expected to be exected in the context of the
@@ -544,7 +557,7 @@ fn impl_wf_environment<'i, I: Interner>(
 /// ```ignore
 /// forall<T> {
 ///     WellFormed(Box<T>) /* this comes from the impl, not this routine */,
-///     forall<'a> { WellFormed(Box<&'a T>) },
+///     forall<'a> { /* as above */ },
 /// }
 /// ```
 fn compute_assoc_ty_goal<I: Interner>(
@@ -556,9 +569,9 @@ fn compute_assoc_ty_goal<I: Interner>(
 
     // Create `forall<T, 'a> { .. }`
     Some(gb.forall(
-        &assoc_ty.value.map_ref(|v| &v.ty),
+        &assoc_ty.value.map_ref(|v| (&v.ty, &v.where_clauses)),
         assoc_ty_id,
-        |gb, assoc_ty_substitution, value_ty, assoc_ty_id| {
+        |gb, assoc_ty_substitution, (value_ty, value_where_clauses), assoc_ty_id| {
             let interner = gb.interner();
             let db = gb.db();
 
@@ -611,37 +624,58 @@ fn compute_assoc_ty_goal<I: Interner>(
                     .clone()
                     .substitute(interner, &projection.substitution);
 
-                // Create `if (/* where clauses on associated type value */) { .. }`
+                // Create `if (/* where clauses on associated type */) { .. }`
                 gb.implies(
                     defn_where_clauses
                         .iter()
                         .cloned()
                         .map(|qwc| qwc.into_from_env_goal(interner)),
                     |gb| {
-                        let types = InputTypeCollector::types_in(gb.interner(), value_ty);
-
-                        // We require that `WellFormed(T)` for each type that appears in the value
-                        let wf_goals = types
-                            .into_iter()
-                            .map(|ty| ty.well_formed())
-                            .casted(interner);
-
-                        // Check that the `value_ty` meets the bounds from the trait.
-                        // Here we take the substituted bounds (`defn_bounds`) and we
-                        // supply the self-type `value_ty` to yield the final result.
-                        //
-                        // In our example, the bound was `Clone`, so the combined
-                        // result is `Box<!T>: Clone`. This is then converted to a
-                        // well-formed goal like `WellFormed(Box<!T>: Clone)`.
-                        let bound_goals = defn_bounds
+                        // Check that the where clauses on the value follow from
+                        // those on the associated type.
+                        let value_where_clause_goals: Vec<Goal<I>> = value_where_clauses
                             .iter()
                             .cloned()
-                            .flat_map(|qb| qb.into_where_clauses(interner, (*value_ty).clone()))
-                            .map(|qwc| qwc.into_well_formed_goal(interner))
-                            .casted(interner);
+                            .casted(interner)
+                            .collect();
 
-                        // Concatenate the WF goals of inner types + the requirements from trait
-                        gb.all::<_, Goal<I>>(wf_goals.chain(bound_goals))
+                        // Create `if (/* where clauses on associated type value */) { .. }`
+                        let value_goal = gb.implies(
+                            value_where_clauses
+                                .iter()
+                                .cloned()
+                                .map(|qwc| qwc.into_from_env_goal(interner)),
+                            |gb| {
+                                let types = InputTypeCollector::types_in(gb.interner(), value_ty);
+
+                                // We require that `WellFormed(T)` for each type that appears in the value
+                                let wf_goals = types
+                                    .into_iter()
+                                    .map(|ty| ty.well_formed())
+                                    .casted(interner);
+
+                                // Check that the `value_ty` meets the bounds from the trait.
+                                // Here we take the substituted bounds (`defn_bounds`) and we
+                                // supply the self-type `value_ty` to yield the final result.
+                                //
+                                // In our example, the bound was `Clone`, so the combined
+                                // result is `Box<!T>: Clone`. This is then converted to a
+                                // well-formed goal like `WellFormed(Box<!T>: Clone)`.
+                                let bound_goals = defn_bounds
+                                    .iter()
+                                    .cloned()
+                                    .flat_map(|qb| {
+                                        qb.into_where_clauses(interner, (*value_ty).clone())
+                                    })
+                                    .map(|qwc| qwc.into_well_formed_goal(interner))
+                                    .casted(interner);
+
+                                // Concatenate the WF goals of inner types + the requirements from trait
+                                gb.all::<_, Goal<I>>(wf_goals.chain(bound_goals))
+                            },
+                        );
+
+                        gb.all(value_where_clause_goals.into_iter().chain(Some(value_goal)))
                     },
                 )
             })
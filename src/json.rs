@@ -0,0 +1,382 @@
+//! JSON output, for `--format json`: the outcome of each goal, and the
+//! errors in a program, each printed as a JSON object on its own line.
+//!
+//! Types, lifetimes and constraints are rendered as `.chalk` text by the
+//! `chalk_solve::display` writer, with the names from the program.
+
+use chalk_integration::db::ChalkDatabase;
+use chalk_integration::differential::{Difference, SolverComparison};
+use chalk_integration::error::{ChalkError, ChalkErrorKind, Label};
+use chalk_integration::interner::ChalkIr;
+use chalk_ir::{Canonical, ConstrainedSubst, Constraints, Substitution};
+use chalk_solve::display::{self, WriterState};
+use chalk_solve::{Solution, SolverStats, SubstitutionResult};
+use serde::Serialize;
+
+/// Prints `value` as JSON, on one line.
+pub fn print(value: &impl Serialize) {
+    println!("{}", serde_json::to_string(value).unwrap());
+}
+
+/// The outcome of solving a goal.
+#[derive(Debug, Serialize)]
+pub struct GoalOutput<'a> {
+    pub goal: &'a str,
+    #[serde(flatten)]
+    pub outcome: Outcome,
+    /// Whether the solver ran out of budget and gave up, leaving the goal
+    /// ambiguous.
+    pub budget_exhausted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<SolverStats>,
+}
+
+/// A solution, or the lack of one.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "result", rename_all = "kebab-case")]
+pub enum Outcome {
+    Unique {
+        substitution: Vec<Binding>,
+        constraints: Vec<String>,
+    },
+    Ambiguous {
+        guidance: Guidance,
+    },
+    NoSolution {
+        /// Why there is no solution, if known.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        explanation: Option<String>,
+    },
+}
+
+impl Outcome {
+    pub fn new(db: &ChalkDatabase, solution: &Solution<ChalkIr>) -> Self {
+        match solution {
+            Solution::Unique(constrained) => Outcome::Unique {
+                substitution: bindings(db, &constrained.value.subst),
+                constraints: constraints(db, &constrained.value.constraints),
+            },
+            Solution::Ambig(chalk_solve::Guidance::Definite(subst)) => Outcome::Ambiguous {
+                guidance: Guidance::Definite {
+                    substitution: bindings(db, &subst.value),
+                },
+            },
+            Solution::Ambig(chalk_solve::Guidance::Suggested(subst)) => Outcome::Ambiguous {
+                guidance: Guidance::Suggested {
+                    substitution: bindings(db, &subst.value),
+                },
+            },
+            Solution::Ambig(chalk_solve::Guidance::Unknown) => Outcome::Ambiguous {
+                guidance: Guidance::None,
+            },
+        }
+    }
+
+    fn from_option(db: &ChalkDatabase, solution: &Option<Solution<ChalkIr>>) -> Self {
+        match solution {
+            Some(solution) => Outcome::new(db, solution),
+            None => Outcome::NoSolution { explanation: None },
+        }
+    }
+}
+
+/// The guidance of an ambiguous solution on what the inference variables of
+/// the goal are.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Guidance {
+    Definite { substitution: Vec<Binding> },
+    Suggested { substitution: Vec<Binding> },
+    None,
+}
+
+/// The value of an inference variable of the goal, like `?0 := Foo`.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Binding {
+    pub var: String,
+    pub value: String,
+}
+
+fn bindings(db: &ChalkDatabase, subst: &Substitution<ChalkIr>) -> Vec<Binding> {
+    let ws = WriterState::<ChalkIr, ChalkDatabase, _>::new(db);
+    subst
+        .iter(&ChalkIr)
+        .enumerate()
+        .map(|(index, arg)| {
+            let mut value = String::new();
+            display::write_generic_arg(&mut value, &ws, arg).unwrap();
+            Binding {
+                var: format!("?{}", index),
+                value,
+            }
+        })
+        .collect()
+}
+
+fn constraints(db: &ChalkDatabase, constraints: &Constraints<ChalkIr>) -> Vec<String> {
+    let ws = WriterState::<ChalkIr, ChalkDatabase, _>::new(db);
+    constraints
+        .iter(&ChalkIr)
+        .map(|constraint| {
+            let mut text = String::new();
+            display::write_constraint(&mut text, &ws, constraint).unwrap();
+            text
+        })
+        .collect()
+}
+
+/// An answer to a goal, with `--multiple`. Each answer is printed as soon
+/// as it is found.
+#[derive(Debug, Serialize)]
+pub struct AnswerOutput<'a> {
+    pub goal: &'a str,
+    #[serde(flatten)]
+    pub answer: Answer,
+}
+
+/// What is printed after the answers to a goal, with `--multiple`.
+#[derive(Debug, Serialize)]
+pub struct AnswersDoneOutput<'a> {
+    pub goal: &'a str,
+    /// Whether all the answers were printed, rather than the user stopping
+    /// early.
+    pub complete: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<SolverStats>,
+}
+
+/// One answer to a goal.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "result", rename_all = "kebab-case")]
+pub enum Answer {
+    Definite {
+        substitution: Vec<Binding>,
+        constraints: Vec<String>,
+    },
+    Ambiguous {
+        substitution: Vec<Binding>,
+        constraints: Vec<String>,
+    },
+    Floundered,
+}
+
+impl Answer {
+    pub fn new(
+        db: &ChalkDatabase,
+        answer: &SubstitutionResult<Canonical<ConstrainedSubst<ChalkIr>>>,
+    ) -> Self {
+        match answer {
+            SubstitutionResult::Definite(constrained) => Answer::Definite {
+                substitution: bindings(db, &constrained.value.subst),
+                constraints: constraints(db, &constrained.value.constraints),
+            },
+            SubstitutionResult::Ambiguous(constrained) => Answer::Ambiguous {
+                substitution: bindings(db, &constrained.value.subst),
+                constraints: constraints(db, &constrained.value.constraints),
+            },
+            SubstitutionResult::Floundered => Answer::Floundered,
+        }
+    }
+}
+
+/// The solutions of both solvers, as with `--compare-solvers`.
+#[derive(Debug, Serialize)]
+pub struct ComparisonOutput<'a> {
+    pub goal: &'a str,
+    pub agree: bool,
+    pub slg: Outcome,
+    pub recursive: Outcome,
+    pub differences: Vec<Difference>,
+}
+
+impl<'a> ComparisonOutput<'a> {
+    pub fn new(db: &ChalkDatabase, goal: &'a str, comparison: &SolverComparison) -> Self {
+        ComparisonOutput {
+            goal,
+            agree: comparison.agree(),
+            slg: Outcome::from_option(db, &comparison.slg),
+            recursive: Outcome::from_option(db, &comparison.recursive),
+            differences: comparison.differences(),
+        }
+    }
+}
+
+/// A goal that could not be parsed or lowered.
+#[derive(Debug, Serialize)]
+pub struct GoalErrorOutput<'a> {
+    pub goal: &'a str,
+    pub errors: Vec<Error>,
+}
+
+impl<'a> GoalErrorOutput<'a> {
+    pub fn new(goal: &'a str, error: &ChalkError) -> Self {
+        GoalErrorOutput {
+            goal,
+            errors: vec![Error::new(error, goal)],
+        }
+    }
+}
+
+/// The errors found when checking a program.
+#[derive(Debug, Serialize)]
+pub struct ProgramErrorsOutput {
+    pub errors: Vec<Error>,
+}
+
+impl ProgramErrorsOutput {
+    pub fn new(errors: &[ChalkError], source: &str) -> Self {
+        ProgramErrorsOutput {
+            errors: errors.iter().map(|err| Error::new(err, source)).collect(),
+        }
+    }
+}
+
+/// An error, along with the parts of the source text it points to.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Error {
+    /// `parse`, `lowering`, `well-formedness` or `coherence`.
+    pub kind: &'static str,
+    pub message: String,
+    pub labels: Vec<ErrorLabel>,
+    /// The error as the text output shows it, with snippets of the source.
+    pub rendered: String,
+}
+
+/// A message attached to a span of the source text, given as byte offsets.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct ErrorLabel {
+    pub primary: bool,
+    pub message: String,
+    pub lo: usize,
+    pub hi: usize,
+}
+
+impl Error {
+    pub fn new(error: &ChalkError, source: &str) -> Self {
+        let kind = match error.kind() {
+            ChalkErrorKind::Parse => "parse",
+            ChalkErrorKind::Lowering(_) => "lowering",
+            ChalkErrorKind::WellFormedness(_) => "well-formedness",
+            ChalkErrorKind::Coherence(_) => "coherence",
+        };
+        let label = |primary: bool, label: &Label| ErrorLabel {
+            primary,
+            message: label.message.clone(),
+            lo: label.span.lo,
+            hi: label.span.hi,
+        };
+        Error {
+            kind,
+            message: error.to_string(),
+            labels: error
+                .primary_label()
+                .map(|primary| label(true, primary))
+                .into_iter()
+                .chain(error.secondary_labels().iter().map(|l| label(false, l)))
+                .collect(),
+            rendered: error.render(source),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chalk_integration::db::ChalkDatabase;
+    use chalk_integration::lowering::lower_goal;
+    use chalk_integration::query::LoweringDatabase;
+    use chalk_integration::SolverChoice;
+    use chalk_solve::ext::*;
+    use chalk_solve::RustIrDatabase;
+    use serde_json::json;
+
+    const PROGRAM: &str = "
+        trait Clone { }
+        struct Foo { }
+        struct Bar { }
+        struct Vec<T> { }
+        impl Clone for Foo { }
+        impl Clone for Bar { }
+        impl<T> Clone for Vec<T> where T: Clone { }
+    ";
+
+    /// Solves `goal` against `PROGRAM` and returns its outcome as JSON.
+    fn solve(goal: &str) -> serde_json::Value {
+        let db = ChalkDatabase::with(PROGRAM, SolverChoice::default());
+        db.with_program(|program| {
+            let goal = lower_goal(&chalk_parse::parse_goal(goal).unwrap(), program)
+                .unwrap()
+                .into_peeled_goal(db.interner());
+            serde_json::to_value(Outcome::from_option(&db, &db.solve(&goal))).unwrap()
+        })
+    }
+
+    #[test]
+    fn unique() {
+        assert_eq!(
+            solve("exists<T> { Vec<T> = Vec<Foo> }"),
+            json!({
+                "result": "unique",
+                "substitution": [{ "var": "?0", "value": "Foo" }],
+                "constraints": [],
+            })
+        );
+    }
+
+    #[test]
+    fn rendered_as_chalk() {
+        assert_eq!(
+            solve("exists<T> { T = Vec<Vec<Foo>> }"),
+            json!({
+                "result": "unique",
+                "substitution": [{ "var": "?0", "value": "Vec<Vec<Foo>>" }],
+                "constraints": [],
+            })
+        );
+        assert_eq!(
+            solve("forall<'a> { exists<'b> { &'a Foo: 'b } }"),
+            json!({
+                "result": "unique",
+                "substitution": [{ "var": "?0", "value": "'^0.0" }],
+                "constraints": ["&'_placeholder_1_0 Foo: '^0.0"],
+            })
+        );
+    }
+
+    #[test]
+    fn ambiguous() {
+        assert_eq!(
+            solve("exists<T> { T: Clone }"),
+            json!({ "result": "ambiguous", "guidance": { "kind": "none" } })
+        );
+        assert_eq!(
+            solve("exists<T, U> { T = Foo, U: Clone }"),
+            json!({
+                "result": "ambiguous",
+                "guidance": {
+                    "kind": "definite",
+                    "substitution": [
+                        { "var": "?0", "value": "Foo" },
+                        { "var": "?1", "value": "^0.0" },
+                    ],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn no_solution() {
+        assert_eq!(solve("Vec<u32>: Clone"), json!({ "result": "no-solution" }));
+    }
+
+    #[test]
+    fn program_errors() {
+        let text = "struct Foo { }\nimpl Clone for Foo { }";
+        let db = ChalkDatabase::with(text, SolverChoice::default());
+        let error = db.checked_program().unwrap_err();
+        let output = serde_json::to_value(ProgramErrorsOutput::new(&[error], text)).unwrap();
+        assert_eq!(output["errors"][0]["kind"], "lowering");
+        assert_eq!(output["errors"][0]["labels"][0]["primary"], true);
+        assert_eq!(output["errors"][0]["labels"][0]["lo"], 20);
+    }
+}
@@ -1,15 +1,20 @@
 #[macro_use]
 extern crate serde_derive;
 
+mod json;
+mod script;
+
 use std::fs::File;
 use std::io::Read;
 use std::process::exit;
 
 use chalk_integration::db::ChalkDatabase;
+use chalk_integration::error::ChalkError;
 use chalk_integration::interner::ChalkIr;
 use chalk_integration::lowering::*;
 use chalk_integration::query::LoweringDatabase;
 use chalk_integration::SolverChoice;
+use chalk_ir::{Goal, InEnvironment, UCanonical};
 use chalk_solve::ext::*;
 use chalk_solve::logging;
 use chalk_solve::RustIrDatabase;
@@ -23,14 +28,34 @@ chalk repl
 
 Usage:
   chalk [options]
+  chalk fmt [--check] [<path>...]
   chalk (-h | --help)
 
 Options:
   --help              Show this screen.
   --program=PATH      Specifies the path to the `.chalk` file containing traits/impls.
   --goal=GOAL         Specifies a goal to evaluate (may be given more than once).
-  --overflow-depth=N  Specifies the overflow depth [default: 10].
+  --script=PATH       Runs the goals in a script file and checks their results.
+  --results=PATH      With `--script`, writes the actual results to a new script.
+  --solver=SOLVER     Specifies the solver to use, `slg` or `recursive` [default: slg].
+  --max-size=N        Specifies the maximum size of goals and answers (10 for the SLG
+                      solver and 30 for the recursive solver by default).
+  --overflow-depth=N  Specifies the overflow depth of the recursive solver (100 by default).
+  --expected-answers=N  Specifies the number of answers the SLG solver should expect.
+  --no-cache          Disables caching in the recursive solver.
+  --budget=N          Limits the work done for each goal to N steps: strands pursued by
+                      the SLG solver, or goals taken on by the recursive solver.
   --multiple          Output multiple answers instead of ambiguous solution.
+  --compare-solvers   Solves each goal with both solvers and reports where they disagree.
+  --solve-regions     Checks the lifetime constraints of solutions and simplifies them.
+  --stats             Prints statistics about the work the solver did for each goal.
+  --format=FORMAT     Specifies the output format of goals and program errors, `text` or
+                      `json` [default: text].
+  --check             With `fmt`, lists the files that are not formatted instead of
+                      formatting them.
+
+`chalk fmt` formats the given `.chalk` files in place, or stdin to stdout if there
+are none.
 ";
 
 /// This struct represents the various command line options available.
@@ -38,8 +63,36 @@ Options:
 struct Args {
     flag_program: Option<String>,
     flag_goal: Vec<String>,
-    flag_overflow_depth: usize,
+    flag_script: Option<String>,
+    flag_results: Option<String>,
+    flag_solver: SolverKind,
+    flag_max_size: Option<usize>,
+    flag_overflow_depth: Option<usize>,
+    flag_expected_answers: Option<usize>,
+    flag_no_cache: bool,
+    flag_budget: Option<usize>,
     flag_multiple: bool,
+    flag_compare_solvers: bool,
+    flag_solve_regions: bool,
+    flag_stats: bool,
+    flag_format: OutputFormat,
+    flag_check: bool,
+    cmd_fmt: bool,
+    arg_path: Vec<String>,
+}
+
+/// The solvers that can be chosen with `--solver`.
+#[derive(Copy, Clone, Debug, Deserialize)]
+enum SolverKind {
+    Slg,
+    Recursive,
+}
+
+/// The output formats that can be chosen with `--format`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+enum OutputFormat {
+    Text,
+    Json,
 }
 
 /// A loaded and parsed program.
@@ -58,48 +111,175 @@ impl LoadedProgram {
         Ok(LoadedProgram { text, db })
     }
 
-    /// Parse a goal and attempt to solve it, using the specified solver.
+    /// Checks the program for errors, printing any that are found along with
+    /// the parts of the program text they point to. All syntax and lowering
+    /// errors are printed at once; the later checks only run on a program
+    /// without them.
+    /// Returns `true` if the program is free of errors.
+    fn check(&self, format: OutputFormat) -> bool {
+        let (_, errors) = self.db.partial_program_ir();
+        let errors = if errors.is_empty() {
+            self.db.checked_program().err().into_iter().collect()
+        } else {
+            errors
+        };
+        match format {
+            OutputFormat::Text => {
+                for err in &errors {
+                    eprintln!("{}", err.render(&self.text));
+                }
+            }
+            OutputFormat::Json if errors.is_empty() => {}
+            OutputFormat::Json => json::print(&json::ProgramErrorsOutput::new(&errors, &self.text)),
+        }
+        errors.is_empty()
+    }
+
+    /// Parse a goal and attempt to solve it, using the specified solver. The
+    /// command-line `args` select how: with `--multiple`, each answer is
+    /// printed in turn; with `--compare-solvers`, the goal is solved with both
+    /// solvers instead, and an error is returned if their solutions differ;
+    /// with `--solve-regions`, the lifetime constraints of the solution are
+    /// checked and simplified. If the solver collects statistics, they are
+    /// printed after the solution. With `--format json`, the output is
+    /// printed by [`goal_json`] instead.
+    ///
+    /// [`goal_json`]: LoadedProgram::goal_json
     fn goal(
         &self,
         mut rl: Option<&mut rustyline::Editor<()>>,
         text: &str,
-        multiple_answers: bool,
+        args: &Args,
     ) -> Result<()> {
-        let program = self.db.checked_program()?;
-        let goal = lower_goal(&*chalk_parse::parse_goal(text)?, &*program)?;
-        let peeled_goal = goal.into_peeled_goal(self.db.interner());
-        if multiple_answers {
+        let peeled_goal = match self.lower_goal(text) {
+            Ok(peeled_goal) => peeled_goal,
+            Err(err) => {
+                if args.flag_format == OutputFormat::Json {
+                    json::print(&json::GoalErrorOutput::new(text, &err));
+                }
+                return Err(err.into());
+            }
+        };
+        if args.flag_format == OutputFormat::Json {
+            return self.goal_json(rl, text, &peeled_goal, args);
+        }
+        if args.flag_compare_solvers {
+            let comparison = self.db.compare_solvers(&peeled_goal);
+            println!("{}\n", comparison);
+            if !comparison.agree() {
+                Err("the SLG and recursive solvers disagree")?;
+            }
+        } else if args.flag_multiple {
             if self.db.solve_multiple(&peeled_goal, &mut |v, has_next| {
                 println!("{}\n", v.as_ref().map(|v| v.display(&ChalkIr)));
-                if has_next {
-                    if let Some(ref mut rl) = rl {
-                        loop {
-                            if let Ok(next) = rl.readline("Show next answer (y/n): ") {
-                                if "y" == next {
-                                    return true;
-                                } else if "n" == next {
-                                    return false;
-                                } else {
-                                    println!("Unknown response. Try again.");
-                                }
-                            } else {
-                                return false;
-                            }
-                        }
-                    } else {
-                        true
-                    }
-                } else {
-                    true
-                }
+                show_next_answer(rl.as_deref_mut(), has_next)
             }) {
                 println!("No more solutions");
             }
         } else {
-            match self.db.solve(&peeled_goal) {
-                Some(v) => println!("{}\n", v.display(&ChalkIr)),
-                None => println!("No possible solution.\n"),
+            let mut solution = self.db.solve(&peeled_goal);
+            if args.flag_solve_regions {
+                let environment = &peeled_goal.canonical.value.environment;
+                solution = match solution.map(|v| v.solve_regions(&ChalkIr, environment)) {
+                    Some(Ok(v)) => Some(v),
+                    Some(Err(err)) => {
+                        println!("No possible solution: {}.\n", err);
+                        return Ok(());
+                    }
+                    None => None,
+                };
+            }
+            match solution {
+                Some(v) => {
+                    println!("{}", v.display(&ChalkIr));
+                    if self.db.budget_exhausted() {
+                        println!("The solver ran out of budget and gave up.");
+                    }
+                    println!();
+                }
+                None => {
+                    println!("No possible solution.");
+                    if let Some(explanation) = self.db.explain_failure(&peeled_goal) {
+                        println!("{}", explanation.display(&self.db));
+                    }
+                    println!();
+                }
+            }
+        }
+        if let Some(stats) = self.db.solver_stats() {
+            println!("Solver statistics:\n{}\n", stats);
+        }
+        Ok(())
+    }
+
+    /// Parses and lowers the goal in `text`.
+    fn lower_goal(
+        &self,
+        text: &str,
+    ) -> std::result::Result<UCanonical<InEnvironment<Goal<ChalkIr>>>, ChalkError> {
+        let program = self.db.checked_program()?;
+        let goal = lower_goal(&*chalk_parse::parse_goal(text)?, &program)?;
+        Ok(goal.into_peeled_goal(self.db.interner()))
+    }
+
+    /// Solves `goal` like [`goal`] does, but prints the outcome as JSON. With
+    /// `--multiple`, each answer is printed on a line of its own as it is
+    /// found, followed by a line saying whether those were all the answers.
+    /// The statistics of the solver, if it collects them, are part of the
+    /// outcome.
+    ///
+    /// [`goal`]: LoadedProgram::goal
+    fn goal_json(
+        &self,
+        mut rl: Option<&mut rustyline::Editor<()>>,
+        text: &str,
+        goal: &UCanonical<InEnvironment<Goal<ChalkIr>>>,
+        args: &Args,
+    ) -> Result<()> {
+        if args.flag_compare_solvers {
+            let comparison = self.db.compare_solvers(goal);
+            json::print(&json::ComparisonOutput::new(&self.db, text, &comparison));
+            if !comparison.agree() {
+                Err("the SLG and recursive solvers disagree")?;
             }
+        } else if args.flag_multiple {
+            let complete = self.db.solve_multiple(goal, &mut |answer, has_next| {
+                json::print(&json::AnswerOutput {
+                    goal: text,
+                    answer: json::Answer::new(&self.db, &answer),
+                });
+                show_next_answer(rl.as_deref_mut(), has_next)
+            });
+            json::print(&json::AnswersDoneOutput {
+                goal: text,
+                complete,
+                stats: self.db.solver_stats(),
+            });
+        } else {
+            let outcome = match self.db.solve(goal) {
+                Some(solution) if args.flag_solve_regions => {
+                    let environment = &goal.canonical.value.environment;
+                    match solution.solve_regions(&ChalkIr, environment) {
+                        Ok(solution) => json::Outcome::new(&self.db, &solution),
+                        Err(err) => json::Outcome::NoSolution {
+                            explanation: Some(err.to_string()),
+                        },
+                    }
+                }
+                Some(solution) => json::Outcome::new(&self.db, &solution),
+                None => json::Outcome::NoSolution {
+                    explanation: self
+                        .db
+                        .explain_failure(goal)
+                        .map(|explanation| explanation.display(&self.db).to_string()),
+                },
+            };
+            json::print(&json::GoalOutput {
+                goal: text,
+                outcome,
+                budget_exhausted: self.db.budget_exhausted(),
+                stats: self.db.solver_stats(),
+            });
         }
         Ok(())
     }
@@ -111,16 +291,23 @@ fn run() -> Result<()> {
         .and_then(|d| d.deserialize())
         .unwrap_or_else(|e| e.exit());
 
-    // Validate arguments.
-    if args.flag_overflow_depth == 0 {
-        eprintln!("error: overflow depth must be at least 1");
-        exit(1);
+    if args.cmd_fmt {
+        return fmt(args);
     }
 
+    // Validate arguments.
+    let mut solver_choice = match args.solver_choice() {
+        Ok(solver_choice) => solver_choice,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            exit(1);
+        }
+    };
+
     // Load the .chalk file, if given.
     let mut prog = None;
     if let Some(program) = &args.flag_program {
-        match load_program(args, program) {
+        match load_program(solver_choice, program) {
             Ok(p) => prog = Some(p),
             Err(err) => {
                 eprintln!("error loading program: {}", err);
@@ -129,10 +316,39 @@ fn run() -> Result<()> {
         }
     }
 
-    if args.flag_goal.is_empty() {
+    if let Some(script) = &args.flag_script {
+        let prog = prog.ok_or(
+            "error: cannot run a script without a program; use `--program` to specify one.",
+        )?;
+        if !prog.check(args.flag_format) {
+            exit(1);
+        }
+
+        let mut text = String::new();
+        File::open(script)?.read_to_string(&mut text)?;
+        let entries = script::parse(&text)?;
+        let mut results = match &args.flag_results {
+            Some(path) => Some(File::create(path)?),
+            None => None,
+        };
+
+        // Run the script. If any goal does not yield its expected result,
+        // exit with an error once all of them have run.
+        let passed = prog.db.with_program(|_| {
+            script::run(
+                &prog,
+                &entries,
+                results.as_mut().map(|file| file as &mut dyn std::io::Write),
+            )
+        })?;
+        if !passed {
+            exit(1);
+        }
+        Ok(())
+    } else if args.flag_goal.is_empty() {
         // The user specified no goal. Enter interactive mode.
         readline_loop(&mut rustyline::Editor::new(), "?- ", |rl, line| {
-            if let Err(e) = process(args, line, rl, &mut prog) {
+            if let Err(e) = process(args, line, rl, &mut solver_choice, &mut prog) {
                 eprintln!("error: {}", e);
             }
         })
@@ -142,12 +358,15 @@ fn run() -> Result<()> {
         // happens.
         let prog =
             prog.ok_or("error: cannot eval without a program; use `--program` to specify one.")?;
+        if !prog.check(args.flag_format) {
+            exit(1);
+        }
 
         // Evaluate the goal(s). If any goal returns an error, print the error
         // and exit.
         prog.db.with_program(|_| -> Result<()> {
             for g in &args.flag_goal {
-                if let Err(e) = prog.goal(None, g, args.flag_multiple) {
+                if let Err(e) = prog.goal(None, g, args) {
                     eprintln!("error: {}", e);
                     exit(1);
                 }
@@ -159,6 +378,24 @@ fn run() -> Result<()> {
     }
 }
 
+/// Decides whether to go on to the next answer of a goal, once an answer has
+/// been shown. If there is one and the user is at the REPL (`rl`), they are
+/// asked; otherwise, all the answers are shown.
+fn show_next_answer(rl: Option<&mut rustyline::Editor<()>>, has_next: bool) -> bool {
+    let rl = match rl {
+        Some(rl) if has_next => rl,
+        _ => return true,
+    };
+    loop {
+        match rl.readline("Show next answer (y/n): ") {
+            Ok(next) if next == "y" => return true,
+            Ok(next) if next == "n" => return false,
+            Ok(_) => println!("Unknown response. Try again."),
+            Err(_) => return false,
+        }
+    }
+}
+
 /// Reads input lines from the user. Lines start with the string given by `prompt`.
 /// Each line the user enters is passed to the function `f` for processing.
 ///
@@ -190,13 +427,13 @@ where
 }
 
 /// Process a single command. `args` is a struct containing the command-line
-/// arguments, and `prog` is a parsed `.chalk` file.
-// TODO: Could we pass in an Options struct or something? The Args struct
-// still has Strings where it should have Enums... (e.g. solver_choice)
+/// arguments, `solver_choice` is the solver currently in use, and `prog` is a
+/// parsed `.chalk` file.
 fn process(
     args: &Args,
     command: &str,
     rl: &mut rustyline::Editor<()>,
+    solver_choice: &mut SolverChoice,
     prog: &mut Option<LoadedProgram>,
 ) -> Result<()> {
     if command.is_empty() {
@@ -207,17 +444,32 @@ fn process(
         help()
     } else if command == "program" {
         // Load a .chalk file via stdin, until EOF is found.
-        let chalk_prog = LoadedProgram::new(read_program(rl)?, args.solver_choice())?;
+        let chalk_prog = LoadedProgram::new(read_program(rl)?, *solver_choice)?;
         // Let's do a sanity check before going forward.
-        let _ = chalk_prog.db.checked_program()?;
-        *prog = Some(chalk_prog);
+        if chalk_prog.check(args.flag_format) {
+            *prog = Some(chalk_prog);
+        }
     } else if command.starts_with("load ") {
         // Load a .chalk file.
         let filename = &command["load ".len()..];
-        let chalk_prog = load_program(args, filename)?;
+        let chalk_prog = load_program(*solver_choice, filename)?;
         // Let's do a sanity check before going forward.
-        let _ = chalk_prog.db.checked_program()?;
-        *prog = Some(chalk_prog);
+        if chalk_prog.check(args.flag_format) {
+            *prog = Some(chalk_prog);
+        }
+    } else if command == "solver" {
+        println!("{:?}", solver_choice);
+    } else if let Some(arguments) = command.strip_prefix("solver ") {
+        // Switch solvers, keeping the program that is currently loaded.
+        let stats = parse_solver_command(arguments)?.with_stats(solver_choice.collects_stats());
+        switch_solver(solver_choice, stats, prog)?;
+    } else if command == "stats" || command.starts_with("stats ") {
+        let stats = match command.split_whitespace().nth(1) {
+            None | Some("on") => true,
+            Some("off") => false,
+            Some(_) => Err("expected `stats on` or `stats off`")?,
+        };
+        switch_solver(solver_choice, solver_choice.with_stats(stats), prog)?;
     } else if command.starts_with("debug ") {
         match command.split_whitespace().nth(1) {
             Some(level) => std::env::set_var("CHALK_DEBUG", level),
@@ -243,7 +495,7 @@ fn process(
                 // Assume this is a goal.
                 // TODO: Print out "type 'help' to see available commands" if it
                 // fails to parse?
-                _ => prog.goal(Some(rl), command, args.flag_multiple)?,
+                _ => prog.goal(Some(rl), command, args)?,
             }
             Ok(())
         })?
@@ -252,13 +504,104 @@ fn process(
     Ok(())
 }
 
+/// Switches to the solver described by `new_choice`, keeping the program
+/// that is currently loaded.
+fn switch_solver(
+    solver_choice: &mut SolverChoice,
+    new_choice: SolverChoice,
+    prog: &mut Option<LoadedProgram>,
+) -> Result<()> {
+    *solver_choice = new_choice;
+    if let Some(chalk_prog) = prog.take() {
+        *prog = Some(LoadedProgram::new(chalk_prog.text, *solver_choice)?);
+    }
+    Ok(())
+}
+
 /// Load the file into a string, and parse it.
-// TODO: Could we pass in an Options struct or something? The Args struct
-// still has Strings where it should have Enums... (e.g. solver_choice)
-fn load_program(args: &Args, filename: &str) -> Result<LoadedProgram> {
+fn load_program(solver_choice: SolverChoice, filename: &str) -> Result<LoadedProgram> {
     let mut text = String::new();
     File::open(filename)?.read_to_string(&mut text)?;
-    Ok(LoadedProgram::new(text, args.solver_choice())?)
+    Ok(LoadedProgram::new(text, solver_choice)?)
+}
+
+/// Parses the arguments of a `solver` command, like
+/// `recursive max-size=20 no-cache`.
+fn parse_solver_command(command: &str) -> Result<SolverChoice> {
+    let mut words = command.split_whitespace();
+    let solver = match words.next() {
+        Some("slg") => SolverKind::Slg,
+        Some("recursive") => SolverKind::Recursive,
+        _ => Err("expected `slg` or `recursive`")?,
+    };
+
+    let (mut max_size, mut overflow_depth, mut expected_answers, mut budget) =
+        (None, None, None, None);
+    let mut caching_enabled = true;
+    for word in words {
+        if word == "no-cache" {
+            caching_enabled = false;
+            continue;
+        }
+        let (option, value) = match word.find('=') {
+            Some(i) => (&word[..i], word[i + 1..].parse::<usize>()?),
+            None => Err(format!("expected `<option>=<value>`, found `{}`", word))?,
+        };
+        match option {
+            "max-size" => max_size = Some(value),
+            "overflow-depth" => overflow_depth = Some(value),
+            "expected-answers" => expected_answers = Some(value),
+            "budget" => budget = Some(value),
+            _ => Err(format!("unknown solver option `{}`", option))?,
+        }
+    }
+    solver_choice(
+        solver,
+        max_size,
+        overflow_depth,
+        expected_answers,
+        caching_enabled,
+        budget,
+    )
+}
+
+/// Builds the choice of `solver` with the given limits. The limits that are
+/// not given keep their defaults.
+fn solver_choice(
+    solver: SolverKind,
+    max_size: Option<usize>,
+    overflow_depth: Option<usize>,
+    expected_answers: Option<usize>,
+    caching_enabled: bool,
+    budget: Option<usize>,
+) -> Result<SolverChoice> {
+    if max_size == Some(0) {
+        Err("max size must be at least 1")?;
+    }
+    if overflow_depth == Some(0) {
+        Err("overflow depth must be at least 1")?;
+    }
+    let solver_choice = match solver {
+        SolverKind::Slg => {
+            if overflow_depth.is_some() || !caching_enabled {
+                Err("the overflow depth and caching only apply to the recursive solver")?;
+            }
+            SolverChoice::slg(max_size.unwrap_or(10), expected_answers)
+        }
+        SolverKind::Recursive => {
+            if expected_answers.is_some() {
+                Err("expected answers only apply to the SLG solver")?;
+            }
+            SolverChoice::Recursive {
+                overflow_depth: overflow_depth.unwrap_or(100),
+                caching_enabled,
+                max_size: max_size.unwrap_or(30),
+                budget: None,
+                stats: false,
+            }
+        }
+    };
+    Ok(solver_choice.with_budget(budget))
 }
 
 /// Print out help for commands in interpreter mode.
@@ -272,6 +615,14 @@ fn help() {
     println!("  lowered       print the lowered program");
     println!("  <goal>        attempt to solve <goal>");
     println!("  debug <level> set debug level to <level>");
+    println!("  solver        print the solver in use");
+    println!("  solver <name> [<option>=<value>...] [no-cache]");
+    println!("                switch to the `slg` or `recursive` solver, keeping the");
+    println!("                current program; the options are `max-size`,");
+    println!("                `overflow-depth`, `expected-answers`, and `budget`");
+    println!("  stats [on|off]");
+    println!("                print statistics about the work the solver does for");
+    println!("                each goal, or stop printing them");
 }
 
 /// Read a program from the command-line. Stop reading when EOF is read. If
@@ -287,12 +638,66 @@ fn read_program(rl: &mut rustyline::Editor<()>) -> Result<String> {
 }
 
 impl Args {
-    fn solver_choice(&self) -> SolverChoice {
-        SolverChoice::SLG {
-            max_size: self.flag_overflow_depth,
-            expected_answers: None,
+    fn solver_choice(&self) -> Result<SolverChoice> {
+        solver_choice(
+            self.flag_solver,
+            self.flag_max_size,
+            self.flag_overflow_depth,
+            self.flag_expected_answers,
+            !self.flag_no_cache,
+            self.flag_budget,
+        )
+        .map(|solver_choice| solver_choice.with_stats(self.flag_stats))
+    }
+}
+
+/// Formats the files given to `chalk fmt`, or stdin if there are none. With
+/// `--check`, the files are left as they are, and the ones that would change
+/// are listed instead.
+fn fmt(args: &Args) -> Result<()> {
+    if args.arg_path.is_empty() {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text)?;
+        let formatted = format_program(&text).unwrap_or_else(|err| {
+            eprintln!("error: {}", err);
+            exit(1);
+        });
+        if !args.flag_check {
+            print!("{}", formatted);
+        } else if formatted != text {
+            exit(1);
+        }
+        return Ok(());
+    }
+
+    let mut unformatted = false;
+    for path in &args.arg_path {
+        let text = std::fs::read_to_string(path)?;
+        let formatted = format_program(&text).unwrap_or_else(|err| {
+            eprintln!("error: {}: {}", path, err);
+            exit(1);
+        });
+        if formatted == text {
+            continue;
+        }
+        if args.flag_check {
+            println!("{}", path);
+            unformatted = true;
+        } else {
+            std::fs::write(path, formatted)?;
         }
     }
+    if unformatted {
+        exit(1);
+    }
+    Ok(())
+}
+
+/// Formats a program, if it has no syntax errors. Formatting a program with
+/// errors would drop the items they are in.
+fn format_program(text: &str) -> Result<String> {
+    let program = chalk_parse::parse_program(text)?;
+    Ok(chalk_parse::format::format_program(&program, text))
 }
 
 fn main() {
@@ -0,0 +1,267 @@
+//! Batch mode: running the goals in a script file against a program.
+//!
+//! A script is a list of goals, each optionally followed by the result it is
+//! expected to yield, written like the `goal { } yields { }` test macro:
+//!
+//! ```text
+//! // Comments start with `//`.
+//! goal { Vec<Foo>: Clone } yields { Unique }
+//! goal { exists<T> { Vec<T>: Clone } }
+//! ```
+//!
+//! Expected results are compared as in the tests: whitespace is ignored, and
+//! the expectation only needs to be a prefix of the actual result. A goal
+//! without a solution yields `No possible solution`.
+
+use std::io::Write;
+
+use itertools::Itertools;
+
+use chalk_integration::interner::ChalkIr;
+use chalk_integration::lowering::lower_goal;
+use chalk_integration::query::LoweringDatabase;
+use chalk_solve::ext::*;
+use chalk_solve::RustIrDatabase;
+
+use super::{LoadedProgram, Result};
+
+/// A goal in a script.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Entry {
+    /// The line the entry starts on, counting from 1.
+    pub line: usize,
+    pub goal: String,
+    pub expected: Option<String>,
+}
+
+/// Parses the entries of a script.
+pub fn parse(text: &str) -> Result<Vec<Entry>> {
+    let mut parser = Parser { text, position: 0 };
+    let mut entries = vec![];
+    while parser.skip_trivia() {
+        let line = parser.line();
+        parser.expect_keyword("goal")?;
+        let goal = parser.block()?;
+        parser.skip_trivia();
+        let expected = if parser.eat_keyword("yields") {
+            Some(parser.block()?)
+        } else {
+            None
+        };
+        entries.push(Entry {
+            line,
+            goal,
+            expected,
+        });
+    }
+    Ok(entries)
+}
+
+struct Parser<'t> {
+    text: &'t str,
+    position: usize,
+}
+
+impl<'t> Parser<'t> {
+    fn rest(&self) -> &'t str {
+        &self.text[self.position..]
+    }
+
+    fn line(&self) -> usize {
+        self.text[..self.position].matches('\n').count() + 1
+    }
+
+    /// Skips whitespace and comments. Returns `false` at the end of the text.
+    fn skip_trivia(&mut self) -> bool {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.position += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                self.position += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else {
+                return !trimmed.is_empty();
+            }
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let rest = self.rest();
+        let is_keyword = rest.starts_with(keyword)
+            && !rest[keyword.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_');
+        if is_keyword {
+            self.position += keyword.len();
+        }
+        is_keyword
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<()> {
+        if !self.eat_keyword(keyword) {
+            Err(format!("line {}: expected `{}`", self.line(), keyword))?;
+        }
+        Ok(())
+    }
+
+    /// Parses a `{ ... }` block, returning the text between the braces.
+    fn block(&mut self) -> Result<String> {
+        self.skip_trivia();
+        if !self.rest().starts_with('{') {
+            Err(format!("line {}: expected `{{`", self.line()))?;
+        }
+        let start = self.position + 1;
+        let mut depth = 0;
+        for (offset, c) in self.rest().char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => continue,
+            }
+            if depth == 0 {
+                let end = self.position + offset;
+                self.position = end + 1;
+                return Ok(self.text[start..end].trim().to_string());
+            }
+        }
+        Err(format!("line {}: unclosed `{{`", self.line()))?
+    }
+}
+
+/// Runs the goals in `entries` against `prog`, printing the outcome of each
+/// and a summary. If `results` is given, a copy of the script with the
+/// actual results as expectations is written to it; goals that could not be
+/// solved are followed by a comment giving the error instead. Returns `true` if every
+/// goal yielded its expected result.
+pub fn run(
+    prog: &LoadedProgram,
+    entries: &[Entry],
+    mut results: Option<&mut dyn Write>,
+) -> Result<bool> {
+    let (mut passed, mut failed, mut unchecked) = (0, 0, 0);
+    for entry in entries {
+        let goal = entry.goal.split_whitespace().collect::<Vec<_>>().join(" ");
+        let actual = match solve(prog, &entry.goal) {
+            Ok(actual) => actual,
+            Err(err) => {
+                println!("goal {{ {} }} ... FAILED", goal);
+                println!("    line {}: error: {}", entry.line, err);
+                // The error is written as a comment, so that the results
+                // are still a script that can be run.
+                if let Some(results) = &mut results {
+                    let err = err.to_string().split_whitespace().join(" ");
+                    writeln!(results, "goal {{ {} }} // error: {}", goal, err)?;
+                }
+                failed += 1;
+                continue;
+            }
+        };
+
+        if let Some(results) = &mut results {
+            writeln!(results, "goal {{ {} }} yields {{ {} }}", goal, actual)?;
+        }
+
+        match &entry.expected {
+            Some(expected) if same_result(&actual, expected) => {
+                println!("goal {{ {} }} ... ok", goal);
+                passed += 1;
+            }
+            Some(expected) => {
+                println!("goal {{ {} }} ... FAILED", goal);
+                println!("    line {}: expected: {}", entry.line, expected);
+                println!("    line {}: actual:   {}", entry.line, actual);
+                failed += 1;
+            }
+            None => {
+                println!("goal {{ {} }} ... {}", goal, actual);
+                unchecked += 1;
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "script result: {}. {} passed; {} failed; {} unchecked",
+        if failed == 0 { "ok" } else { "FAILED" },
+        passed,
+        failed,
+        unchecked
+    );
+    Ok(failed == 0)
+}
+
+/// Solves the goal in `text`, returning the solution as text.
+fn solve(prog: &LoadedProgram, text: &str) -> Result<String> {
+    let program = prog.db.checked_program()?;
+    let goal = lower_goal(&*chalk_parse::parse_goal(text)?, &program)?;
+    let peeled_goal = goal.into_peeled_goal(prog.db.interner());
+    let solution = prog.db.solve(&peeled_goal);
+    if prog.db.budget_exhausted() {
+        Err("the solver ran out of budget and gave up")?;
+    }
+    Ok(match solution {
+        Some(solution) => solution.display(&ChalkIr).to_string(),
+        None => "No possible solution".to_string(),
+    })
+}
+
+/// Compares results like the `yields` checks in the tests do.
+fn same_result(actual: &str, expected: &str) -> bool {
+    let strip = |s: &str| s.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+    strip(actual).starts_with(&strip(expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_entries() {
+        let entries = parse(
+            "
+            // A comment.
+            goal { forall<T> { if (T: Foo) { T: Foo } } } yields { Unique }
+            goal {
+                Bar: Foo
+            }
+            ",
+        )
+        .unwrap();
+        assert_eq!(
+            entries,
+            [
+                Entry {
+                    line: 3,
+                    goal: "forall<T> { if (T: Foo) { T: Foo } }".to_string(),
+                    expected: Some("Unique".to_string()),
+                },
+                Entry {
+                    line: 4,
+                    goal: "Bar: Foo".to_string(),
+                    expected: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse("goal { Foo: Bar }\nyield { Unique }")
+                .unwrap_err()
+                .to_string(),
+            "line 2: expected `goal`"
+        );
+        assert_eq!(
+            parse("goal { Foo: Bar").unwrap_err().to_string(),
+            "line 1: unclosed `{`"
+        );
+    }
+
+    #[test]
+    fn compare_results() {
+        assert!(same_result(
+            "Unique; substitution [], lifetime constraints []",
+            "Unique"
+        ));
+        assert!(!same_result("Ambiguous; no inference guidance", "Unique"));
+    }
+}
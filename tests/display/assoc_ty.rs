@@ -318,6 +318,23 @@ fn test_impl_generics_and_assoc_ty_generics_coexist() {
     );
 }
 
+#[test]
+fn test_impl_assoc_ty_where_clauses() {
+    // Test where clauses on an associated type value print correctly, using
+    // both the impl's and the value's own generics.
+    reparse_test!(
+        program {
+            struct Fuu<T> {}
+            trait Bnx {
+                type Assoc<'a> where Self: 'a;
+            }
+            impl<T> Bnx for Fuu<T> {
+                type Assoc<'a> = Fuu<T> where Fuu<T>: 'a, T: 'a;
+            }
+        }
+    );
+}
+
 #[test]
 fn test_impl_assoc_ty_alias() {
     // Test printing `AliasTy` associated type bounds. In other words, test
@@ -58,3 +58,29 @@ fn test_basic_const_values_in_assoc_ty_values() {
         }
     );
 }
+
+#[test]
+fn test_typed_const_values() {
+    // Test we render typed const parameters and suffixed literals correctly.
+    reparse_test!(
+        program {
+            struct Foo<const B: bool, const C: char, const N: i8, const M: u64> { }
+            trait Bar { }
+            impl Bar for Foo<true, 'x', -3i8, 7u64> { }
+            impl<const N: i8> Bar for Foo<false, '\n', N, 0u64> { }
+        }
+    );
+}
+
+#[test]
+fn test_unevaluated_const_values() {
+    // Test we render unevaluated const expressions correctly.
+    reparse_test!(
+        program {
+            struct Foo<const N> { }
+            trait Bar { }
+            impl<const N> Bar for Foo<{N + 1}> { }
+            impl<const N, const M> Bar for Foo<{{N * 2} % M}> { }
+        }
+    );
+}
@@ -45,3 +45,27 @@ fn test_upstream_impl_keyword() {
         }
     );
 }
+
+#[test]
+fn test_default_assoc_ty_values() {
+    // Test we print `default` on associated type values, including those
+    // of a `default impl`.
+    reparse_test!(
+        program {
+            struct Bar {}
+            struct Vec<T> {}
+            trait Foo {
+                type Assoc;
+                type Other;
+            }
+            impl<T> Foo for T {
+                default type Assoc = Bar;
+                type Other = Bar;
+            }
+            default impl<T> Foo for Vec<T> {
+                type Assoc = T;
+                type Other = Bar;
+            }
+        }
+    );
+}
@@ -0,0 +1,55 @@
+#[test]
+fn test_unambiguous_names_in_modules() {
+    // Items in modules are written in the crate root when their names are
+    // unambiguous.
+    reparse_test!(
+        program {
+            mod a {
+                struct Foo {}
+                mod b {
+                    trait Bar {}
+                }
+            }
+            impl a::b::Bar for a::Foo {}
+        }
+        produces {
+            struct Foo {}
+            trait Bar {}
+            impl Bar for Foo {}
+        }
+    );
+}
+
+#[test]
+fn test_ambiguous_names_in_modules() {
+    // Items whose names are ambiguous are written with their full paths, in
+    // the modules of the paths.
+    reparse_test!(
+        program {
+            mod a {
+                struct Foo {}
+            }
+            mod b {
+                struct Foo {}
+                mod c {
+                    trait Foo {}
+                }
+            }
+            impl b::c::Foo for a::Foo {}
+            impl b::c::Foo for b::Foo {}
+        }
+        produces {
+            struct Foo {}
+            mod b {
+                struct Foo {}
+            }
+            mod b {
+                mod c {
+                    trait Foo {}
+                }
+            }
+            impl b::c::Foo for Foo {}
+            impl b::c::Foo for b::Foo {}
+        }
+    );
+}
@@ -55,6 +55,9 @@ where
     fn fn_def_name(&self, _fn_def_id: chalk_ir::FnDefId<I>) -> String {
         "Foo".to_owned()
     }
+    fn method_name(&self, _impl_id: chalk_ir::ImplId<I>, _index: usize) -> String {
+        "foo".to_owned()
+    }
     fn custom_clauses(&self) -> Vec<chalk_ir::ProgramClause<I>> {
         self.db.custom_clauses()
     }
@@ -91,6 +94,12 @@ where
     ) -> std::sync::Arc<chalk_solve::rust_ir::ImplDatum<I>> {
         self.db.impl_datum(impl_id)
     }
+    fn inherent_impl_datum(
+        &self,
+        impl_id: chalk_ir::ImplId<I>,
+    ) -> std::sync::Arc<chalk_solve::rust_ir::InherentImplDatum<I>> {
+        self.db.inherent_impl_datum(impl_id)
+    }
     fn associated_ty_value(
         &self,
         id: chalk_solve::rust_ir::AssociatedTyValueId<I>,
@@ -126,12 +135,21 @@ where
     ) -> Vec<chalk_ir::ImplId<I>> {
         self.db.impls_for_trait(trait_id, parameters, binders)
     }
+    fn inherent_impls(&self) -> Vec<chalk_ir::ImplId<I>> {
+        self.db.inherent_impls()
+    }
     fn local_impls_to_coherence_check(
         &self,
         trait_id: chalk_ir::TraitId<I>,
     ) -> Vec<chalk_ir::ImplId<I>> {
         self.db.local_impls_to_coherence_check(trait_id)
     }
+    fn specialization_priorities(
+        &self,
+        trait_id: chalk_ir::TraitId<I>,
+    ) -> Arc<chalk_solve::coherence::SpecializationPriorities<I>> {
+        self.db.specialization_priorities(trait_id)
+    }
     fn impl_provided_for(
         &self,
         auto_trait_id: chalk_ir::TraitId<I>,
@@ -0,0 +1,349 @@
+//! Tests for the formatter of `.chalk` programs.
+
+use chalk_integration::db::ChalkDatabase;
+use chalk_integration::generator::Generator;
+use chalk_integration::lowering::Lower;
+use chalk_integration::query::LoweringDatabase;
+use chalk_integration::SolverChoice;
+use chalk_parse::format::format_program;
+
+fn format(text: &str) -> String {
+    let program = chalk_parse::parse_program(text).unwrap();
+    format_program(&program, text)
+}
+
+/// Formats `text`, and checks that the result is `expected`, that it is
+/// left as it is by formatting it again, and that it lowers to the same
+/// program as `text`.
+fn format_test(text: &str, expected: &str) {
+    let formatted = format(text);
+    assert_eq!(formatted, expected);
+    assert_eq!(format(&formatted), formatted);
+
+    let program = ChalkDatabase::with(text, SolverChoice::default())
+        .program_ir()
+        .unwrap();
+    let reformatted = ChalkDatabase::with(&formatted, SolverChoice::default())
+        .program_ir()
+        .unwrap();
+    assert!(program == reformatted, "{}", formatted);
+}
+
+#[test]
+fn layout() {
+    format_test(
+        "trait Clone{}  trait Iterator where Self:Clone{type Item:Clone;}
+         struct Foo<T>where T:Clone{a:T,b:(T,u32)}
+         #[upstream] #[fundamental] struct Box<T>{}
+         enum Option<T>{None,Some(T),Pair{a:T,b:T}}
+         impl<T>Iterator for Foo<T>where T:Clone{type Item=T;}
+         impl<T>Clone for Foo<T>{}",
+        "\
+trait Clone { }
+trait Iterator where Self: Clone {
+    type Item: Clone;
+}
+struct Foo<T> where T: Clone {
+    a: T,
+    b: (T, u32),
+}
+#[upstream]
+#[fundamental]
+struct Box<T> { }
+enum Option<T> {
+    None,
+    Some(T),
+    Pair { a: T, b: T },
+}
+impl<T> Iterator for Foo<T> where T: Clone {
+    type Item = T;
+}
+impl<T> Clone for Foo<T> { }
+",
+    );
+}
+
+#[test]
+fn comments() {
+    format_test(
+        "
+
+// The traits.
+trait Foo { }  // A trailing comment.
+trait Bar { }
+
+
+
+// The structs,
+// with a comment over two lines.
+
+struct Baz { }
+// At the end.
+",
+        "\
+// The traits.
+trait Foo { } // A trailing comment.
+trait Bar { }
+
+// The structs,
+// with a comment over two lines.
+
+struct Baz { }
+// At the end.
+",
+    );
+}
+
+#[test]
+fn functions() {
+    format_test(
+        "trait Foo { }
+         struct Bar { }
+         #[variance(Covariant)] unsafe extern \"C\" fn foo<T>(x: T, rest: ...) -> T where T: Foo;
+         fn bar(x: for<'a> fn(&'a Bar) -> &'a Bar, y: unsafe fn(u32, ...));
+         closure baz<T>(&mut self, x: T) -> u32 { T; Bar }
+         closure quux(self,) { }",
+        "\
+trait Foo { }
+struct Bar { }
+#[variance(Covariant)]
+unsafe extern \"C\" fn foo<T>(x: T, rest: ...) -> T where T: Foo;
+fn bar(x: for<'a> fn(&'a Bar) -> &'a Bar, y: unsafe fn(u32, ...));
+closure baz<T>(&mut self, x: T) -> u32 { T; Bar }
+closure quux(self,) { }
+",
+    );
+}
+
+#[test]
+fn types() {
+    format_test(
+        "#[object_safe] trait Foo<'a> { type Assoc<T>: Foo<'a>; }
+         trait Bar<T> { type Item; }
+         struct S<'a, const N, T> {
+             a: &'a mut [T; N],
+             b: *const [u8],
+             c: dyn Foo<'a> + Bar<u32, Item = T> + 'a,
+             d: <T as Foo<'a>>::Assoc<u8>,
+             e: (T,),
+             f: !,
+             g: &'static str,
+         }
+         opaque type O<T>: Bar<T> where T: Foo<'static> = S<'static, 3, T>;
+         extern type Ext;
+         generator static gen<T>[resume = u32, yield = T] -> bool {
+             upvars [T; Ext]
+             witnesses exists<'a> [&'a T]
+         }",
+        "\
+#[object_safe]
+trait Foo<'a> {
+    type Assoc<T>: Foo<'a>;
+}
+trait Bar<T> {
+    type Item;
+}
+struct S<'a, const N, T> {
+    a: &'a mut [T; N],
+    b: *const [u8],
+    c: dyn Foo<'a> + Bar<u32, Item = T> + 'a,
+    d: <T as Foo<'a>>::Assoc<u8>,
+    e: (T,),
+    f: !,
+    g: &'static str,
+}
+opaque type O<T>: Bar<T> where T: Foo<'static> = S<'static, 3, T>;
+extern type Ext;
+generator static gen<T>[resume = u32, yield = T] -> bool {
+    upvars [T; Ext]
+    witnesses exists<'a> [&'a T]
+}
+",
+    );
+}
+
+#[test]
+fn consts() {
+    format_test(
+        "trait Foo { }
+         struct S<const N, const B: bool, const C: char, const I: i64> { }
+         impl<const N> Foo for S<{N - 1}, true, '\\'', -5i64> { }
+         impl Foo for S<3, false, '\\n', 0i64> { }",
+        "\
+trait Foo { }
+struct S<const N, const B: bool, const C: char, const I: i64> { }
+impl<const N> Foo for S<{N - 1}, true, '\\'', -5i64> { }
+impl Foo for S<3, false, '\\n', 0i64> { }
+",
+    );
+}
+
+#[test]
+fn specialization() {
+    format_test(
+        "trait Foo { type Item; }
+         struct Vec<T> { }
+         default impl<T> Foo for T { type Item = T; }
+         impl<T> Foo for Vec<T> { default type Item = u32; }",
+        "\
+trait Foo {
+    type Item;
+}
+struct Vec<T> { }
+default impl<T> Foo for T {
+    type Item = T;
+}
+impl<T> Foo for Vec<T> {
+    default type Item = u32;
+}
+",
+    );
+}
+
+#[test]
+fn clauses() {
+    format_test(
+        "trait Foo { type Item; }
+         trait Bar { }
+         #[auto] trait Send { }
+         forall<T> { T: Foo if T: Bar, (T: Send, <T as Foo>::Item: Bar) }
+         forall<T, U> { T: Bar if forall<'a> { exists<V> { Normalize(<V as Foo>::Item -> U) } } }
+         forall<T> { WellFormed(T: Foo) if not { T: Send }, if (T: Send; forall<U> { U: Bar :- U: Send }) { compatible { exists<U> { T = U } } } }
+         forall { FromEnv(u32) }
+         forall<'a, 'b> { 'a: 'b if Subtype(&'a u32, &'b u32) }
+         impl<T> !Send for Box<T> where T: Foo<Item = u32> { }
+         struct Box<T> { }",
+        "\
+trait Foo {
+    type Item;
+}
+trait Bar { }
+#[auto]
+trait Send { }
+forall<T> { T: Foo if T: Bar, (T: Send, <T as Foo>::Item: Bar) }
+forall<T, U> { T: Bar if forall<'a> { exists<V> { Normalize(<V as Foo>::Item -> U) } } }
+forall<T> { WellFormed(T: Foo) if not { T: Send }, if (T: Send; forall<U> { U: Bar :- U: Send }) { compatible { exists<U> { T = U } } } }
+forall { FromEnv(u32) }
+forall<'a, 'b> { 'a: 'b if Subtype(&'a u32, &'b u32) }
+impl<T> !Send for Box<T> where T: Foo<Item = u32> { }
+struct Box<T> { }
+",
+    );
+}
+
+#[test]
+fn modules() {
+    format_test(
+        "mod std{trait Clone{}
+         mod vec{struct Vec<T>{}
+         // Vectors are cloned element by element.
+
+         impl<T>crate::std::Clone for Vec<T>where T:super::Clone{}}}
+         mod empty{}
+         use std::vec::Vec;use std::Clone as Copy;
+         struct Foo{x:self::Vec<Foo>}
+         impl Copy for Foo{}",
+        "\
+mod std {
+    trait Clone { }
+    mod vec {
+        struct Vec<T> { }
+        // Vectors are cloned element by element.
+
+        impl<T> crate::std::Clone for Vec<T> where T: super::Clone { }
+    }
+}
+mod empty { }
+use std::vec::Vec;
+use std::Clone as Copy;
+struct Foo {
+    x: self::Vec<Foo>,
+}
+impl Copy for Foo { }
+",
+    );
+}
+
+#[test]
+fn crates() {
+    format_test(
+        "crate std{trait Clone{}}
+         #[current] crate mine depends std,{struct Foo{} impl std::Clone for crate::Foo{}}
+         crate empty depends std,mine{}",
+        "\
+crate std {
+    trait Clone { }
+}
+#[current]
+crate mine depends std {
+    struct Foo { }
+    impl std::Clone for crate::Foo { }
+}
+crate empty depends std, mine { }
+",
+    );
+}
+
+#[test]
+fn inherent_impls() {
+    format_test(
+        "#[lang(deref)]trait Deref{type Target;}trait Clone{}struct Vec<T>{}struct Foo{}
+         impl<T>Vec<T>where T:Clone{fn len(&self)->u32;fn push(&mut self,value:T);fn into_iter(self);}
+         #[upstream]impl Foo{}",
+        "\
+#[lang(deref)]
+trait Deref {
+    type Target;
+}
+trait Clone { }
+struct Vec<T> { }
+struct Foo { }
+impl<T> Vec<T> where T: Clone {
+    fn len(&self) -> u32;
+    fn push(&mut self, value: T);
+    fn into_iter(self);
+}
+#[upstream]
+impl Foo { }
+",
+    );
+}
+
+#[test]
+fn generic_associated_types() {
+    format_test(
+        "trait LendingIterator{type Item<'a>where Self:'a;}struct Ref<'a,T>{}struct Windows<T>{}
+         impl<T>LendingIterator for Windows<T>{type Item<'a>=Ref<'a,T>where T:'a;}",
+        "\
+trait LendingIterator {
+    type Item<'a> where Self: 'a;
+}
+struct Ref<'a, T> { }
+struct Windows<T> { }
+impl<T> LendingIterator for Windows<T> {
+    type Item<'a> = Ref<'a, T> where T: 'a;
+}
+",
+    );
+}
+
+#[test]
+fn libstd_is_formatted() {
+    let text = include_str!("../../libstd.chalk");
+    assert_eq!(format(text), text);
+}
+
+#[test]
+fn generated_programs() {
+    for seed in 0..20 {
+        let ast = Generator::new(seed).program();
+        let (program, errors) = ast.lower();
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let text = format_program(&ast, "");
+        let reparsed = ChalkDatabase::with(&text, SolverChoice::default())
+            .program_ir()
+            .unwrap();
+        assert!(*reparsed == program, "{}", text);
+    }
+}
@@ -0,0 +1,23 @@
+//! Runs random programs through the solvers, looking for panics and
+//! disagreements. Set `CHALK_FUZZ_SEEDS` to fuzz more programs than the
+//! default.
+
+use chalk_integration::fuzz::fuzz;
+
+const GOALS_PER_PROGRAM: usize = 5;
+
+#[test]
+fn fuzz_solvers() {
+    let seeds = std::env::var("CHALK_FUZZ_SEEDS")
+        .ok()
+        .and_then(|seeds| seeds.parse().ok())
+        .unwrap_or(50);
+    let failed: Vec<_> = (0..seeds)
+        .map(|seed| fuzz(seed, GOALS_PER_PROGRAM))
+        .filter(|case| !case.failures.is_empty())
+        .collect();
+    for case in &failed {
+        println!("{}", case);
+    }
+    assert!(failed.is_empty(), "{} programs failed", failed.len());
+}
@@ -1 +1,3 @@
+mod fuzz;
 mod panic;
+mod script;
@@ -0,0 +1,74 @@
+//! Runs the `chalk` binary in `--script` mode.
+
+use std::fs;
+use std::process::Command;
+
+/// Writes `program` and `script` to files in a fresh directory, runs them
+/// with `--script` and `--results`, and returns whether the run succeeded
+/// along with the results that were written.
+fn run_script(name: &str, program: &str, script: &str) -> (bool, String) {
+    let dir = std::env::temp_dir().join(format!("chalk-script-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let program_path = dir.join("program.chalk");
+    let script_path = dir.join("script.chalk");
+    let results_path = dir.join("results.chalk");
+    fs::write(&program_path, program).unwrap();
+    fs::write(&script_path, script).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_chalk"))
+        .arg(format!("--program={}", program_path.display()))
+        .arg(format!("--script={}", script_path.display()))
+        .arg(format!("--results={}", results_path.display()))
+        .status()
+        .unwrap();
+    let results = fs::read_to_string(&results_path).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    (status.success(), results)
+}
+
+const PROGRAM: &str = "
+    trait Clone { }
+    struct Foo { }
+    struct Bar { }
+    struct Vec<T> { }
+    impl Clone for Foo { }
+    impl<T> Clone for Vec<T> where T: Clone { }
+";
+
+#[test]
+fn passing_script() {
+    let (success, results) = run_script(
+        "passing",
+        PROGRAM,
+        "
+        goal { Vec<Foo>: Clone } yields { Unique }
+        goal { Bar: Clone }
+        ",
+    );
+    assert!(success);
+    assert_eq!(
+        results,
+        "goal { Vec<Foo>: Clone } yields { Unique; substitution [], lifetime constraints [] }\n\
+         goal { Bar: Clone } yields { No possible solution }\n"
+    );
+}
+
+#[test]
+fn failing_script() {
+    let (success, results) = run_script(
+        "failing",
+        PROGRAM,
+        "
+        goal { Bar: Clone } yields { Unique }
+        goal { Baz: Clone } yields { Unique }
+        goal { Foo: Clone } yields { Unique }
+        ",
+    );
+    assert!(!success);
+    assert_eq!(
+        results,
+        "goal { Bar: Clone } yields { No possible solution }\n\
+         goal { Baz: Clone } // error: invalid parameter name `Baz`\n\
+         goal { Foo: Clone } yields { Unique; substitution [], lifetime constraints [] }\n"
+    );
+}
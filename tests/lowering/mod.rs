@@ -194,7 +194,9 @@ fn atc_accounting() {
     associated_ty_id: (Iterable::Iter),
     value: for<lifetime, type> AssociatedTyValueBound {
         ty: Iter<'^0.0, ^0.1>
+        where_clauses: []
     },
+    default: false,
 }"#
             .replace(",\n", "\n"),
         );
@@ -814,3 +816,377 @@ fn algebraic_data_types() {
         }
     }
 }
+
+#[test]
+fn structured_lowering_error() {
+    use chalk_integration::error::{ChalkErrorKind, RustIrError};
+
+    let text = "struct Foo<T> { }\nstruct Bar { field: Foo }";
+    let error = ChalkDatabase::with(text, SolverChoice::default())
+        .checked_program()
+        .unwrap_err();
+    match error.kind() {
+        ChalkErrorKind::Lowering(RustIrError::IncorrectNumberOfTypeParameters {
+            identifier,
+            expected: 1,
+            actual: 0,
+        }) => assert_eq!(identifier.str.as_ref(), "Foo"),
+        kind => panic!("unexpected error: {:?}", kind),
+    }
+    assert!(error.secondary_labels().is_empty());
+    assert_eq!(
+        error.render(text),
+        "error: `Foo` takes 1 type parameters, not 0
+ --> 2:21
+  |
+2 | struct Bar { field: Foo }
+  |                     ^^^ expected 1 parameters, found 0"
+    );
+}
+
+#[test]
+fn structured_coherence_error() {
+    use chalk_integration::error::ChalkErrorKind;
+    use chalk_solve::coherence::CoherenceError;
+
+    let text = "trait Foo { }\nstruct Bar { }\nimpl Foo for Bar { }\nimpl Foo for Bar { }";
+    let error = ChalkDatabase::with(text, SolverChoice::default())
+        .checked_program()
+        .unwrap_err();
+    assert!(matches!(
+        error.kind(),
+        ChalkErrorKind::Coherence(CoherenceError::OverlappingImpls(_))
+    ));
+    assert_eq!(error.secondary_labels().len(), 2);
+    assert_eq!(
+        error.render(text),
+        "error: overlapping impls of trait `Foo`
+ --> 1:7
+  |
+1 | trait Foo { }
+  |       ^^^ trait declared here
+  |
+3 | impl Foo for Bar { }
+  |      --- impl of this trait
+  |
+4 | impl Foo for Bar { }
+  |      --- impl of this trait"
+    );
+}
+
+// The items in modules are flattened before they are given ids, so the
+// labels must not be looked up by position in the source.
+#[test]
+fn structured_coherence_error_after_module() {
+    let text = "mod m { struct A { } struct B { } }\ntrait Foo { }\nimpl Foo for m::A { }\nimpl Foo for m::A { }";
+    let error = ChalkDatabase::with(text, SolverChoice::default())
+        .checked_program()
+        .unwrap_err();
+    assert_eq!(
+        error.render(text),
+        "error: overlapping impls of trait `Foo`
+ --> 2:7
+  |
+2 | trait Foo { }
+  |       ^^^ trait declared here
+  |
+3 | impl Foo for m::A { }
+  |      --- impl of this trait
+  |
+4 | impl Foo for m::A { }
+  |      --- impl of this trait"
+    );
+}
+
+// The same goes for crates, the ones the current crate cannot see being
+// left out altogether.
+#[test]
+fn structured_coherence_error_in_crate() {
+    let text = "crate other { struct X { } }
+crate std { trait Clone { } }
+#[current] crate mine depends std {
+    struct Foo { }
+    impl std::Clone for Foo { }
+    impl std::Clone for Foo { }
+}";
+    let error = ChalkDatabase::with(text, SolverChoice::default())
+        .checked_program()
+        .unwrap_err();
+    assert_eq!(
+        error.render(text),
+        "error: overlapping impls of trait `std::Clone`
+ --> 2:19
+  |
+2 | crate std { trait Clone { } }
+  |                   ^^^^^ trait declared here
+  |
+5 |     impl std::Clone for Foo { }
+  |          ---------- impl of this trait
+  |
+6 |     impl std::Clone for Foo { }
+  |          ---------- impl of this trait"
+    );
+}
+
+#[test]
+fn all_lowering_errors_reported() {
+    use chalk_integration::error::{ChalkErrorKind, RustIrError};
+    use chalk_integration::interner::ChalkIr;
+    use chalk_ir::TyKind;
+
+    let db = ChalkDatabase::with(
+        "
+        trait Iterator { type Item; }
+        struct Foo<T> { }
+        struct Bar { a: Foo, b: Baz }
+        impl<T> Iterator for Foo<T> where T: Iterator<Itm = T> { type Item = T; }
+        impl Quux for Bar { }
+        ",
+        SolverChoice::default(),
+    );
+    let (program, errors) = db.partial_program_ir();
+    let errors: Vec<_> = errors
+        .iter()
+        .map(|error| match error.kind() {
+            ChalkErrorKind::Lowering(error) => error.clone(),
+            kind => panic!("unexpected error: {:?}", kind),
+        })
+        .collect();
+    assert!(matches!(
+        &errors[..],
+        [
+            RustIrError::IncorrectNumberOfTypeParameters { .. },
+            RustIrError::InvalidParameterName(_),
+            RustIrError::MissingAssociatedType(_),
+            RustIrError::InvalidTraitName(_),
+        ]
+    ));
+    assert_eq!(
+        db.program_ir().unwrap_err().to_string(),
+        "`Foo` takes 1 type parameters, not 0"
+    );
+
+    // The bad field types are replaced by error types.
+    let bar = &program.adt_data[&program.adt_ids[&"Bar".into()]];
+    let fields = &bar.binders.skip_binders().variants[0].fields;
+    assert_eq!(fields.len(), 2);
+    for field in fields {
+        assert!(matches!(field.kind(&ChalkIr), TyKind::Error));
+    }
+
+    // The impl with the bad where clause is kept without it, but the impl of
+    // the unknown trait is left out.
+    assert_eq!(program.impl_data.len(), 1);
+    let impl_datum = program.impl_data.values().next().unwrap();
+    assert!(impl_datum.binders.skip_binders().where_clauses.is_empty());
+}
+
+#[test]
+fn syntax_errors_recovered() {
+    use chalk_parse::ast::{Item, Span};
+
+    let text = "struct Foo { a: }\ntrait Bar { }\nimpl Bar for { }\nstruct Baz { }";
+    let (program, errors) = chalk_parse::parse_program_with_errors(text);
+    let names: Vec<_> = program
+        .items
+        .iter()
+        .map(|item| match item {
+            Item::AdtDefn(defn) => defn.name.str.to_string(),
+            Item::TraitDefn(defn) => defn.name.str.to_string(),
+            item => panic!("unexpected item: {:?}", item),
+        })
+        .collect();
+    assert_eq!(names, ["Bar", "Baz"]);
+    let spans: Vec<_> = errors.iter().map(|error| error.span).collect();
+    assert_eq!(spans, [Some(Span::new(16, 17)), Some(Span::new(45, 46))]);
+    assert!(errors[0].message.starts_with("Unrecognized token `}`"));
+
+    assert_eq!(
+        chalk_parse::parse_program(text).unwrap_err().to_string(),
+        format!("parse error: {}", errors[0])
+    );
+}
+
+#[test]
+fn syntax_errors_reported_with_lowering_errors() {
+    use chalk_integration::error::{ChalkErrorKind, RustIrError};
+    use chalk_parse::ast::Span;
+
+    let text = "struct Foo { a: }\ntrait Bar { }\nimpl Bar for { }\nstruct Baz { b: Quux }";
+    let db = ChalkDatabase::with(text, SolverChoice::default());
+    let (program, errors) = db.partial_program_ir();
+    assert!(matches!(
+        errors.iter().map(|error| error.kind()).collect::<Vec<_>>()[..],
+        [
+            ChalkErrorKind::Parse,
+            ChalkErrorKind::Parse,
+            ChalkErrorKind::Lowering(RustIrError::InvalidParameterName(_)),
+        ]
+    ));
+    let labels: Vec<_> = errors
+        .iter()
+        .map(|error| error.primary_label().unwrap())
+        .map(|label| (label.span, label.message.as_str()))
+        .collect();
+    assert_eq!(
+        labels,
+        [
+            (Span::new(16, 17), "syntax error"),
+            (Span::new(45, 46), "syntax error"),
+            (Span::new(65, 69), "not found in this scope"),
+        ]
+    );
+    assert_eq!(program.trait_data.len(), 1);
+
+    // The first syntax error is the error of the whole program.
+    assert_eq!(db.program_ir().unwrap_err(), errors[0]);
+}
+
+#[test]
+fn unresolved_import() {
+    lowering_error! {
+        program {
+            mod a { struct Foo { } }
+            use a::Bar;
+        }
+        error_msg {
+            "unresolved import `a::Bar`"
+        }
+    }
+}
+
+#[test]
+fn cyclic_imports() {
+    lowering_error! {
+        program {
+            mod a { use crate::b::Foo; }
+            mod b { use crate::a::Foo; }
+        }
+        error_msg {
+            "unresolved import `crate::b::Foo`"
+        }
+    }
+}
+
+// The items of a module are not in scope in its sibling modules.
+#[test]
+fn module_items_not_in_scope() {
+    lowering_error! {
+        program {
+            mod a { trait Foo { } }
+            mod b {
+                struct Bar { }
+                impl Foo for Bar { }
+            }
+        }
+        error_msg {
+            "invalid trait name `Foo`"
+        }
+    }
+}
+
+#[test]
+fn path_through_item() {
+    lowering_error! {
+        program {
+            struct Foo { }
+            struct Bar { field: Foo::Baz }
+        }
+        error_msg {
+            "invalid parameter name `Foo::Baz`"
+        }
+    }
+}
+
+#[test]
+fn unknown_crate() {
+    lowering_error! {
+        program {
+            crate mine depends std { }
+        }
+        error_msg {
+            "unknown crate `std`"
+        }
+    }
+}
+
+#[test]
+fn nested_crate() {
+    lowering_error! {
+        program {
+            mod a {
+                crate std { }
+            }
+        }
+        error_msg {
+            "crate `std` is declared inside another module"
+        }
+    }
+}
+
+#[test]
+fn second_current_crate() {
+    lowering_error! {
+        program {
+            #[current] crate a { }
+            #[current] crate b { }
+        }
+        error_msg {
+            "crate `b` is marked current, but so is another one"
+        }
+    }
+}
+
+// The crates that the current crate does not depend on are left out of the
+// program.
+#[test]
+fn downstream_crates_not_visible() {
+    lowering_error! {
+        program {
+            #[current]
+            crate std {
+                trait Clone { }
+            }
+            crate mine depends std {
+                struct Foo { }
+            }
+            impl std::Clone for mine::Foo { }
+        }
+        error_msg {
+            "invalid parameter name `mine::Foo`"
+        }
+    }
+}
+
+#[test]
+fn inherent_impls() {
+    lowering_success! {
+        program {
+            #[lang(deref)]
+            trait Deref { type Target; }
+            struct Vec<T> { }
+            impl<T> Vec<T> {
+                fn len(&self) -> u32;
+                fn push(&mut self, value: T);
+                fn into_boxed(self) -> Vec<T>;
+            }
+            #[upstream]
+            impl Vec<u32> { }
+        }
+    }
+}
+
+#[test]
+fn inherent_impl_unknown_type() {
+    lowering_error! {
+        program {
+            struct Foo { }
+            impl Foo {
+                fn bar(&self, x: Bar);
+            }
+        }
+        error_msg {
+            "invalid parameter name `Bar`"
+        }
+    }
+}
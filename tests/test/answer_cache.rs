@@ -0,0 +1,178 @@
+//! Tests for keeping the answers of the SLG solver across changes to the
+//! program.
+
+use super::*;
+use chalk_engine::cache::AnswerCache;
+use chalk_engine::solve::SLGSolver;
+use chalk_solve::{Solver, SolverStats};
+use std::sync::{Arc, Mutex};
+
+fn solver_choice() -> SolverChoice {
+    SolverChoice::slg_default()
+        .with_answer_cache(true)
+        .with_stats(true)
+}
+
+/// Solves `goal_text` in the current program of `db`, returning the
+/// rendered solution and the statistics of the solve.
+fn solve(db: &ChalkDatabase, goal_text: &str) -> (String, SolverStats) {
+    db.with_program(|_| {
+        let goal = db
+            .parse_and_lower_goal(goal_text)
+            .unwrap()
+            .into_peeled_goal(db.interner());
+        let solution = match db.solve(&goal) {
+            Some(solution) => solution.display(&ChalkIr).to_string(),
+            None => "No possible solution".to_string(),
+        };
+        (solution, db.solver_stats().unwrap())
+    })
+}
+
+const UNIQUE: &str = "Unique; substitution [], lifetime constraints []";
+
+const PROGRAM: &str = "
+    struct Foo { }
+    struct Bar { }
+    struct Vec<T> { }
+    trait Clone { }
+    trait Debug { }
+    impl Clone for Foo { }
+    impl<T> Clone for Vec<T> where T: Clone { }
+    impl Debug for Bar { }
+";
+
+#[test]
+fn unrelated_edit_keeps_answers() {
+    let mut db = ChalkDatabase::with(PROGRAM, solver_choice());
+    let (solution, stats) = solve(&db, "Vec<Foo>: Clone");
+    assert_eq!(solution, UNIQUE);
+    assert!(stats.tables_created > 0, "{:?}", stats);
+    assert!(!db.answer_cache().is_empty());
+
+    // The edited impl has the same id, and the tables for `Vec<Foo>: Clone`
+    // never looked at it.
+    db.set_program_text(Arc::new(
+        PROGRAM.replace("impl Debug for Bar { }", "impl Debug for Foo { }"),
+    ));
+    let (solution, stats) = solve(&db, "Vec<Foo>: Clone");
+    assert_eq!(solution, UNIQUE);
+    assert_eq!(stats.tables_created, 0, "{:?}", stats);
+    assert!(stats.cache_hits > 0, "{:?}", stats);
+}
+
+#[test]
+fn edit_of_a_subgoal_drops_answers() {
+    let mut db = ChalkDatabase::with(PROGRAM, solver_choice());
+    assert_eq!(solve(&db, "Vec<Foo>: Clone").0, UNIQUE);
+
+    // `Vec<Foo>: Clone` only depends on the impl for `Foo` through its
+    // subgoal `Foo: Clone`, whose table is dropped along with it.
+    db.set_program_text(Arc::new(
+        PROGRAM.replace("impl Clone for Foo { }", "impl Clone for Bar { }"),
+    ));
+    let (solution, stats) = solve(&db, "Vec<Foo>: Clone");
+    assert_eq!(solution, "No possible solution");
+    assert!(stats.tables_created >= 2, "{:?}", stats);
+    assert_eq!(solve(&db, "Vec<Bar>: Clone").0, UNIQUE);
+}
+
+#[test]
+fn new_items_shifting_ids() {
+    let mut db = ChalkDatabase::with(PROGRAM, solver_choice());
+    assert_eq!(solve(&db, "Vec<Foo>: Clone").0, UNIQUE);
+    assert_eq!(solve(&db, "Bar: Clone").0, "No possible solution");
+
+    // Declaring a struct first changes the ids of all the other items.
+    db.set_program_text(Arc::new(format!(
+        "struct Baz {{ }}\nimpl Clone for Baz {{ }}\n{}",
+        PROGRAM
+    )));
+    assert_eq!(solve(&db, "Vec<Foo>: Clone").0, UNIQUE);
+    assert_eq!(solve(&db, "Vec<Baz>: Clone").0, UNIQUE);
+    assert_eq!(solve(&db, "Bar: Clone").0, "No possible solution");
+}
+
+#[test]
+fn answers_are_opt_in() {
+    let mut db = ChalkDatabase::with(PROGRAM, SolverChoice::slg_default().with_stats(true));
+    assert_eq!(solve(&db, "Vec<Foo>: Clone").0, UNIQUE);
+    assert!(db.answer_cache().is_empty());
+
+    db.set_program_text(Arc::new(
+        PROGRAM.replace("impl Debug for Bar { }", "impl Debug for Foo { }"),
+    ));
+    let (solution, stats) = solve(&db, "Vec<Foo>: Clone");
+    assert_eq!(solution, UNIQUE);
+    assert!(stats.tables_created > 0, "{:?}", stats);
+}
+
+#[test]
+fn cycles() {
+    let program = "
+        trait A { }
+        trait B { }
+        trait C { }
+        struct Foo { }
+        struct Bar { }
+        impl<T> A for T where T: B { }
+        impl<T> B for T where T: A { }
+        impl A for Foo { }
+        impl C for Bar { }
+    ";
+    // Finding that there is no solution completes the tables of the cycle
+    // between `Bar: A` and `Bar: B`, so they are kept.
+    let mut db = ChalkDatabase::with(program, solver_choice());
+    assert_eq!(solve(&db, "Bar: B").0, "No possible solution");
+
+    db.set_program_text(Arc::new(
+        program.replace("impl C for Bar", "impl C for Foo"),
+    ));
+    let (solution, stats) = solve(&db, "Bar: B");
+    assert_eq!(solution, "No possible solution");
+    assert_eq!(stats.tables_created, 0, "{:?}", stats);
+
+    db.set_program_text(Arc::new(
+        program.replace("impl A for Foo", "impl A for Bar"),
+    ));
+    assert_eq!(solve(&db, "Bar: B").0, UNIQUE);
+    assert_eq!(solve(&db, "Foo: B").0, "No possible solution");
+}
+
+#[test]
+fn serialized_answers() {
+    let db = ChalkDatabase::with(PROGRAM, SolverChoice::default());
+    let goal = db
+        .parse_and_lower_goal("Vec<Foo>: Clone")
+        .unwrap()
+        .into_peeled_goal(db.interner());
+
+    // Solves the goal with a solver sharing `cache`, returning the rendered
+    // solution and the statistics of the solve.
+    let solve_with = |cache: &Arc<Mutex<AnswerCache<ChalkIr>>>| {
+        db.with_program(|program| {
+            let mut solver = SLGSolver::new(10, None)
+                .with_stats()
+                .with_answer_cache(cache.clone());
+            let solution = solver.solve(program, &goal).unwrap();
+            (
+                solution.display(&ChalkIr).to_string(),
+                solver.stats().unwrap(),
+            )
+        })
+    };
+
+    let cache = Arc::new(Mutex::new(AnswerCache::new()));
+    let (solution, stats) = solve_with(&cache);
+    assert_eq!(solution, UNIQUE);
+    assert!(stats.tables_created > 0, "{:?}", stats);
+
+    // The tables read back from the serialized cache are taken as they are.
+    let json = serde_json::to_string(&*cache.lock().unwrap()).unwrap();
+    let cache: AnswerCache<ChalkIr> = serde_json::from_str(&json).unwrap();
+    assert!(!cache.is_empty());
+    let (solution, stats) = solve_with(&Arc::new(Mutex::new(cache)));
+    assert_eq!(solution, UNIQUE);
+    assert_eq!(stats.tables_created, 0, "{:?}", stats);
+    assert!(stats.cache_hits > 0, "{:?}", stats);
+}
@@ -115,3 +115,39 @@ fn arrays_are_well_formed() {
         }
     }
 }
+
+#[test]
+fn arrays_with_unevaluated_lengths() {
+    test! {
+        program {
+            trait Split { }
+
+            impl<T, const N> Split for [T; {N + 1}] { }
+        }
+
+        goal {
+            forall<const N, T> {
+                [T; {N + 1}]: Split
+            }
+        } yields {
+            "Unique"
+        }
+
+        // The impl may apply to these, but we cannot evaluate `N + 1`.
+        goal {
+            forall<const N, T> {
+                [T; N]: Split
+            }
+        } yields {
+            "Ambiguous; no inference guidance"
+        }
+
+        goal {
+            forall<T> {
+                [T; 1]: Split
+            }
+        } yields {
+            "Ambiguous; no inference guidance"
+        }
+    }
+}
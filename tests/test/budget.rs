@@ -0,0 +1,130 @@
+//! Tests for limiting the work of the solvers with a budget.
+
+use super::*;
+
+const DEEP_CYCLE: &str = "
+    struct Foo { }
+    trait A0 { }
+    trait A1 { }
+    trait A2 { }
+    impl<T> A0 for T where T: A1 { }
+    impl<T> A1 for T where T: A2 { }
+    impl<T> A2 for T where T: A0 { }
+    impl A2 for Foo { }
+    trait B { }
+    impl B for Foo { }
+";
+
+/// Solves each goal in turn with one solver built from `solver_choice`,
+/// returning the rendered solutions and whether the budget ran out.
+fn solve_with_budget(
+    program_text: &str,
+    solver_choice: SolverChoice,
+    goals: &[&str],
+) -> Vec<(String, bool)> {
+    let db = ChalkDatabase::with(program_text, solver_choice);
+    let program = db.checked_program().unwrap();
+    let mut solver = solver_choice.into_solver();
+    chalk_integration::tls::set_current_program(&program, || {
+        goals
+            .iter()
+            .map(|goal_text| {
+                let goal = lower_goal(&chalk_parse::parse_goal(goal_text).unwrap(), &program)
+                    .unwrap()
+                    .into_peeled_goal(db.interner());
+                let solution = match solver.solve(&db, &goal) {
+                    Some(solution) => solution.display(&ChalkIr).to_string(),
+                    None => "No possible solution".to_string(),
+                };
+                (solution, solver.budget_exhausted())
+            })
+            .collect()
+    })
+}
+
+fn both_solvers(budget: usize) -> Vec<SolverChoice> {
+    vec![
+        SolverChoice::slg_default().with_budget(Some(budget)),
+        SolverChoice::recursive_default().with_budget(Some(budget)),
+    ]
+}
+
+#[test]
+fn budget_exhausted() {
+    for solver_choice in both_solvers(2) {
+        let results = solve_with_budget(DEEP_CYCLE, solver_choice, &["Foo: A0"]);
+        assert_eq!(
+            results,
+            vec![("Ambiguous; no inference guidance".to_string(), true)],
+            "{:?}",
+            solver_choice
+        );
+    }
+}
+
+#[test]
+fn budget_not_exhausted() {
+    for solver_choice in both_solvers(1000) {
+        let results = solve_with_budget(DEEP_CYCLE, solver_choice, &["Foo: A0"]);
+        assert_eq!(
+            results,
+            vec![(
+                "Unique; substitution [], lifetime constraints []".to_string(),
+                false
+            )],
+            "{:?}",
+            solver_choice
+        );
+    }
+}
+
+#[test]
+fn genuine_ambiguity() {
+    // Running out of budget is told apart from a goal that really is
+    // ambiguous.
+    let program = "
+        trait Foo { }
+        struct Bar { }
+        struct Baz { }
+        impl Foo for Bar { }
+        impl Foo for Baz { }
+    ";
+    for solver_choice in both_solvers(1000) {
+        let results = solve_with_budget(program, solver_choice, &["exists<T> { T: Foo }"]);
+        assert_eq!(
+            results,
+            vec![("Ambiguous; no inference guidance".to_string(), false)],
+            "{:?}",
+            solver_choice
+        );
+    }
+}
+
+#[test]
+fn budget_is_per_solve() {
+    // The budget starts afresh for each goal, so a goal that needs little
+    // work succeeds after one that ran out.
+    for solver_choice in both_solvers(2) {
+        let results = solve_with_budget(DEEP_CYCLE, solver_choice, &["Foo: A0", "Foo: B"]);
+        assert!(results[0].1, "{:?}", solver_choice);
+        assert_eq!(
+            results[1],
+            (
+                "Unique; substitution [], lifetime constraints []".to_string(),
+                false
+            ),
+            "{:?}",
+            solver_choice
+        );
+    }
+}
+
+#[test]
+fn program_checks_ignore_budget() {
+    // The coherence check has to solve goals about the impls of `A2`, which
+    // would run out of a budget of 0.
+    for solver_choice in both_solvers(0) {
+        let db = ChalkDatabase::with(DEEP_CYCLE, solver_choice);
+        assert!(db.checked_program().is_ok(), "{:?}", solver_choice);
+    }
+}
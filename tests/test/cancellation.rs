@@ -0,0 +1,133 @@
+//! Tests for cancelling the recursive solver through the `should_continue`
+//! callback of `solve_limited`.
+
+use super::*;
+use std::cell::Cell;
+
+/// A cycle of `depth` traits, each implemented for everything that
+/// implements the next, with the cycle broken by an impl for `Foo`.
+fn deep_cycle(depth: usize) -> String {
+    let mut program = String::from("struct Foo { }\n");
+    for i in 0..depth {
+        program.push_str(&format!("trait A{} {{ }}\n", i));
+        program.push_str(&format!(
+            "impl<T> A{} for T where T: A{} {{ }}\n",
+            i,
+            (i + 1) % depth
+        ));
+    }
+    program.push_str(&format!("impl A{} for Foo {{ }}\n", depth - 1));
+    program
+}
+
+/// Solves each goal in turn with one recursive solver, cancelling after
+/// `should_continue` has been polled `budget` times (or never, if `budget`
+/// is `None`). Returns the rendered solutions.
+fn solve_limited(program_text: &str, goals: &[(&str, Option<usize>)]) -> Vec<String> {
+    let db = ChalkDatabase::with(program_text, SolverChoice::recursive_default());
+    let program = db.checked_program().unwrap();
+    let mut solver = SolverChoice::recursive_default().into_solver();
+    chalk_integration::tls::set_current_program(&program, || {
+        goals
+            .iter()
+            .map(|&(goal_text, budget)| {
+                let goal = lower_goal(&chalk_parse::parse_goal(goal_text).unwrap(), &program)
+                    .unwrap()
+                    .into_peeled_goal(db.interner());
+                let polls = Cell::new(0);
+                let should_continue = || {
+                    polls.set(polls.get() + 1);
+                    match budget {
+                        Some(budget) => polls.get() <= budget,
+                        None => true,
+                    }
+                };
+                match solver.solve_limited(&db, &goal, &should_continue) {
+                    Some(solution) => solution.display(&ChalkIr).to_string(),
+                    None => "No possible solution".to_string(),
+                }
+            })
+            .collect()
+    })
+}
+
+#[test]
+fn cancel_immediately() {
+    let results = solve_limited(&deep_cycle(3), &[("Foo: A0", Some(0))]);
+    assert_eq!(results, vec!["Ambiguous; no inference guidance"]);
+}
+
+#[test]
+fn uncancelled_solve() {
+    let results = solve_limited(&deep_cycle(10), &[("Foo: A0", None)]);
+    assert_eq!(
+        results,
+        vec!["Unique; substitution [], lifetime constraints []"]
+    );
+}
+
+#[test]
+fn cancel_partway_through_deep_cycle() {
+    // However far into the cycle we get before being cancelled, the result
+    // is ambiguous, and solving the goal again afterwards with the same
+    // solver gives the right answer, so nothing wrong was cached.
+    let program = deep_cycle(10);
+    for budget in 1..40 {
+        let results = solve_limited(&program, &[("Foo: A0", Some(budget)), ("Foo: A0", None)]);
+        assert_eq!(
+            results,
+            vec![
+                "Ambiguous; no inference guidance",
+                "Unique; substitution [], lifetime constraints []",
+            ],
+            "budget {}",
+            budget
+        );
+    }
+}
+
+#[test]
+fn cancel_after_caching_subgoals() {
+    // The first goal is solved completely, caching the whole cycle. Solving
+    // a goal that only needs cached results then succeeds even though we
+    // are cancelled right after starting it.
+    let program = deep_cycle(5);
+    let results = solve_limited(&program, &[("Foo: A0", None), ("Foo: A3", Some(0))]);
+    assert_eq!(
+        results,
+        vec![
+            "Unique; substitution [], lifetime constraints []",
+            "Unique; substitution [], lifetime constraints []",
+        ]
+    );
+}
+
+#[test]
+fn cancel_ambiguous_cycle() {
+    let program = "
+        #[marker]
+        trait A { }
+        #[marker]
+        trait B { }
+
+        struct Foo { }
+        struct Vec<T> { }
+
+        impl<T> A for T where T: B { }
+        impl A for Foo { }
+
+        impl<T> B for T where T: A { }
+        impl<T> B for Vec<T> where T: B { }
+    ";
+    for budget in 0..20 {
+        let results = solve_limited(
+            program,
+            &[
+                ("exists<T> { T: A }", Some(budget)),
+                ("exists<T> { T: A }", None),
+            ],
+        );
+        assert_eq!(results[0], "Ambiguous; no inference guidance");
+        assert_eq!(results[1], "Ambiguous; no inference guidance");
+    }
+}
@@ -587,3 +587,79 @@ fn fundamental_type_multiple_parameters() {
         }
     }
 }
+
+#[test]
+fn orphan_check_across_crates() {
+    lowering_error! {
+        program {
+            crate std {
+                trait Clone { }
+                struct Vec<T> { }
+            }
+            crate mine depends std {
+                impl<T> std::Clone for std::Vec<T> { }
+            }
+        } error_msg {
+            "impl for trait `std::Clone` violates the orphan rules"
+        }
+    }
+
+    // The impls of upstream crates are not checked, and a local type makes
+    // the impl allowed.
+    lowering_success! {
+        program {
+            crate std {
+                trait Clone { }
+                trait From<T> { }
+                struct Vec<T> { }
+                struct String { }
+                impl<T> Clone for Vec<T> where T: Clone { }
+            }
+            crate mine depends std {
+                struct Local { }
+                impl std::Clone for Local { }
+                impl std::From<Local> for std::Vec<std::String> { }
+            }
+        }
+    }
+
+    // Seen from `std`, `mine` is a downstream crate, and so its impls are not
+    // part of the program.
+    lowering_success! {
+        program {
+            #[current]
+            crate std {
+                trait Clone { }
+                struct Vec<T> { }
+            }
+            crate mine depends std {
+                impl<T> std::Clone for std::Vec<T> { }
+            }
+        }
+    }
+}
+
+#[test]
+fn downstream_impl_of_fundamental_across_crates() {
+    // The same as `downstream_impl_of_fundamental_43355`, with `Box` in a
+    // crate of its own rather than marked `#[upstream]`.
+    lowering_error! {
+        program {
+            crate alloc {
+                #[fundamental]
+                struct Box<T> { }
+            }
+            crate mine depends alloc {
+                trait Trait1<X> { }
+                trait Trait2<X> { }
+
+                struct A { }
+
+                impl<X, T> Trait1<X> for T where T: Trait2<X> { }
+                impl<X> Trait1<alloc::Box<X>> for A { }
+            }
+        } error_msg {
+            "overlapping impls of trait `mine::Trait1`"
+        }
+    }
+}
@@ -360,3 +360,59 @@ fn local_impl_allowed_for_traits() {
         goal { forall<'a, T> { LocalImplAllowed(Upstream: UpstreamTrait<'a, Upstream, Local, T>) } } yields { "Unique" }
     }
 }
+
+#[test]
+fn crates_decide_local_and_upstream() {
+    test! {
+        program {
+            crate core {
+                struct Core { }
+            }
+            crate std depends core {
+                struct Vec<T> { }
+                trait Clone { }
+            }
+            #[current]
+            crate mine depends std {
+                struct Local { }
+                trait LocalTrait { }
+            }
+            crate theirs depends mine {
+                struct Theirs { }
+            }
+        }
+
+        // Only the current crate is local; the crates it depends on, even
+        // indirectly, are upstream.
+        goal { IsLocal(mine::Local) } yields { "Unique" }
+        goal { IsUpstream(mine::Local) } yields { "No possible solution" }
+        goal { IsUpstream(std::Vec<mine::Local>) } yields { "Unique" }
+        goal { IsUpstream(core::Core) } yields { "Unique" }
+
+        goal { LocalImplAllowed(core::Core: mine::LocalTrait) } yields { "Unique" }
+        goal { LocalImplAllowed(core::Core: std::Clone) } yields { "No possible solution" }
+        goal { LocalImplAllowed(mine::Local: std::Clone) } yields { "Unique" }
+        goal { forall<T> { LocalImplAllowed(std::Vec<T>: std::Clone) } } yields { "No possible solution" }
+    }
+
+    // Seen from `theirs`, `mine` is upstream as well.
+    test! {
+        program {
+            crate std {
+                struct Vec<T> { }
+                trait Clone { }
+            }
+            crate mine depends std {
+                struct Local { }
+            }
+            crate theirs depends mine {
+                struct Theirs { }
+            }
+        }
+
+        goal { IsUpstream(mine::Local) } yields { "Unique" }
+        goal { IsLocal(theirs::Theirs) } yields { "Unique" }
+        goal { LocalImplAllowed(mine::Local: std::Clone) } yields { "No possible solution" }
+        goal { LocalImplAllowed(theirs::Theirs: std::Clone) } yields { "Unique" }
+    }
+}
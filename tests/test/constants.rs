@@ -149,3 +149,195 @@ fn placeholders_eq() {
         }
     }
 }
+
+#[test]
+fn typed_const_params() {
+    test! {
+        program {
+            struct Flag<const B: bool> {}
+            struct Byte<const N: u8> {}
+            struct Letter<const C: char> {}
+            struct Offset<const N: i32> {}
+
+            trait Trait {}
+
+            impl Trait for Flag<true> {}
+            impl Trait for Byte<3u8> {}
+            impl Trait for Letter<'x'> {}
+            impl Trait for Offset<-4i32> {}
+        }
+
+        goal {
+            exists<const B: bool> {
+                Flag<B>: Trait
+            }
+        } yields {
+            "Unique; substitution [?0 := true], lifetime constraints []"
+        }
+
+        goal {
+            Flag<false>: Trait
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            Byte<3u8>: Trait
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            exists<const C: char> {
+                Letter<C>: Trait
+            }
+        } yields {
+            "Unique; substitution [?0 := 'x'], lifetime constraints []"
+        }
+
+        goal {
+            Offset<-4i32>: Trait
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            Offset<4i32>: Trait
+        } yields {
+            "No possible solution"
+        }
+    }
+}
+
+#[test]
+fn const_values_of_different_types() {
+    test! {
+        program {}
+
+        goal {
+            3u8 = 3u16
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            3u8 = 3u8
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            exists<const N: u16> {
+                N = 3u8
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            exists<const N> {
+                N = 3
+            }
+        } yields {
+            "Unique; substitution [?0 := 3], lifetime constraints []"
+        }
+    }
+}
+
+#[test]
+fn unevaluated_consts() {
+    test! {
+        program {
+            struct S<const N> {}
+
+            trait Trait {}
+
+            impl<const N> Trait for S<{N + 1}> {}
+        }
+
+        goal {
+            forall<const N> {
+                S<{N + 1}>: Trait
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<const N> {
+                S<{1 + N}>: Trait
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            forall<const N> {
+                S<{N - 1}>: Trait
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            exists<const M> {
+                S<{M + 1}>: Trait
+            }
+        } yields {
+            "Unique; for<?U0> { substitution [?0 := ^0.0], lifetime constraints [] }"
+        }
+
+        // `N = 2` makes the impl apply, but we cannot evaluate `N + 1`.
+        goal {
+            S<3>: Trait
+        } yields {
+            "Ambiguous; no inference guidance"
+        }
+
+        goal {
+            not { S<3>: Trait }
+        } yields {
+            "Ambiguous; no inference guidance"
+        }
+    }
+}
+
+#[test]
+fn mismatched_const_operand_types() {
+    lowering_error! {
+        program {
+            struct S<const N: u8> {}
+            struct T<const N: u16> {
+                field: S<{N + 1u8}>
+            }
+        }
+        error_msg {
+            "mismatched operand types in const expression: `Uint(U16)` and `Uint(U8)`"
+        }
+    }
+}
+
+#[test]
+fn mismatched_const_parameter_type() {
+    lowering_error! {
+        program {
+            struct Flag<const B: bool> {}
+            trait Trait {}
+            impl Trait for Flag<3> {}
+        }
+        error_msg {
+            "mismatched type for a const parameter of `Flag`: expected `Bool`, found `Uint(U32)`"
+        }
+    }
+
+    lowering_error! {
+        program {
+            trait Trait<const N: u8> {}
+            struct S {}
+            impl Trait<3u16> for S {}
+        }
+        error_msg {
+            "mismatched type for a const parameter of `Trait`: expected `Uint(U8)`, found `Uint(U16)`"
+        }
+    }
+}
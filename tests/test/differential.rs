@@ -0,0 +1,96 @@
+//! Tests for comparing the solutions of the SLG and recursive solvers.
+
+use super::*;
+use chalk_integration::differential::{Difference, SolverComparison};
+
+/// Solves `goal_text` against `program_text` with both solvers, returning
+/// the comparison along with its differences and rendering.
+fn compare(program_text: &str, goal_text: &str) -> (SolverComparison, Vec<Difference>, String) {
+    let db = ChalkDatabase::with(program_text, SolverChoice::default());
+    let program = db.checked_program().unwrap();
+    chalk_integration::tls::set_current_program(&program, || {
+        let goal = lower_goal(&chalk_parse::parse_goal(goal_text).unwrap(), &program)
+            .unwrap()
+            .into_peeled_goal(db.interner());
+        let comparison = db.compare_solvers(&goal);
+        let differences = comparison.differences();
+        let rendered = comparison.to_string();
+        println!("{}", rendered);
+        (comparison, differences, rendered)
+    })
+}
+
+#[test]
+fn solvers_agree() {
+    let (comparison, differences, rendered) = compare(
+        "
+        trait Foo { }
+        struct Bar { }
+        impl Foo for Bar { }
+        ",
+        "exists<T> { T: Foo }",
+    );
+    assert!(comparison.agree());
+    assert!(differences.is_empty());
+    assert_eq!(
+        rendered,
+        "solvers agree: Unique; substitution [?0 := Bar], lifetime constraints []"
+    );
+}
+
+#[test]
+fn constraint_order_is_ignored() {
+    let (comparison, _, _) = compare(
+        "
+        trait Foo { }
+        struct Ref<'a, 'b> { }
+        impl<'a, 'b> Foo for Ref<'a, 'b> where 'a: 'b, 'b: 'a { }
+        ",
+        "forall<'a, 'b> { Ref<'a, 'b>: Foo }",
+    );
+    assert!(comparison.agree());
+}
+
+#[test]
+fn solvers_disagree() {
+    let (comparison, differences, rendered) = compare(
+        "
+        struct Vec<T> { }
+        trait A { }
+        trait B { }
+
+        impl<T> A for Vec<T> where T: A, T: B { }
+
+        impl A for u32 { }
+        impl B for u32 { }
+
+        impl A for i32 { }
+        impl B for i8 { }
+        ",
+        "exists<T> { Vec<T>: A }",
+    );
+    assert!(!comparison.agree());
+    assert_eq!(
+        differences,
+        vec![
+            Difference {
+                part: "outcome".to_string(),
+                slg: Some("unique".to_string()),
+                recursive: Some("ambiguous without guidance".to_string()),
+            },
+            Difference {
+                part: "?0".to_string(),
+                slg: Some("Uint(U32)".to_string()),
+                recursive: None,
+            },
+        ]
+    );
+    assert_eq!(
+        rendered,
+        "solvers disagree:
+  slg:       Unique; substitution [?0 := Uint(U32)], lifetime constraints []
+  recursive: Ambiguous; no inference guidance
+  outcome: slg has `unique`, recursive has `ambiguous without guidance`
+  ?0: slg has `Uint(U32)`, recursive has nothing"
+    );
+}
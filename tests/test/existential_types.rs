@@ -417,3 +417,42 @@ fn dyn_associated_type_binding() {
         }
     }
 }
+
+#[test]
+fn dyn_super_trait_associated_type_binding() {
+    test! {
+        program {
+            trait Iterator { type Item; }
+            trait U32Iterator where Self: Iterator<Item = u32> {}
+        }
+
+        goal {
+            forall<'s> {
+                <dyn U32Iterator + 's as Iterator>::Item = u32
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<'s> {
+                <dyn U32Iterator + 's as Iterator>::Item = u64
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            exists<T> {
+                forall<'s> {
+                    <dyn U32Iterator + 's as Iterator>::Item = T
+                }
+            }
+        } yields[SolverChoice::recursive_default()] {
+            "Unique; substitution [?0 := Uint(U32)], lifetime constraints []"
+        } yields[SolverChoice::slg_default()] {
+            // #234
+            "Ambiguous"
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Tests for the "why not?" explanations of goals that have no solution.
+
+use super::*;
+use chalk_solve::solve::explain::CandidateFailure;
+use chalk_solve::{explain_failure, FailureExplanation};
+
+/// Explains why `goal_text` fails against `program_text`, returning the
+/// explanation along with its rendering.
+fn explain(program_text: &str, goal_text: &str) -> Option<(FailureExplanation<ChalkIr>, String)> {
+    let db = ChalkDatabase::with(program_text, SolverChoice::default());
+    let program = db.checked_program().unwrap();
+    chalk_integration::tls::set_current_program(&program, || {
+        let goal = lower_goal(&chalk_parse::parse_goal(goal_text).unwrap(), &program)
+            .unwrap()
+            .into_peeled_goal(db.interner());
+        let mut solver = SolverChoice::default().into_solver();
+        let explanation = explain_failure(&db, &mut *solver, &goal)?;
+        let rendered = explanation.display(&db).to_string();
+        println!("{}", rendered);
+        Some((explanation, rendered))
+    })
+}
+
+#[test]
+fn solvable_goal_is_not_explained() {
+    assert!(explain(
+        "trait Foo { } struct Bar { } impl Foo for Bar { }",
+        "Bar: Foo"
+    )
+    .is_none());
+}
+
+#[test]
+fn no_impls() {
+    let (explanation, rendered) = explain("trait Foo { } struct Bar { }", "Bar: Foo").unwrap();
+    assert!(explanation.candidates.is_empty());
+    assert_eq!(
+        rendered,
+        "the trait bound `Bar: Foo` is not satisfied\n  there are no impls of `Foo`"
+    );
+}
+
+#[test]
+fn unification_failure() {
+    let (_, rendered) = explain(
+        "
+        trait Foo<T> { }
+        struct Bar { }
+        struct Baz { }
+        impl Foo<Baz> for Bar { }
+        ",
+        "Bar: Foo<Bar>",
+    )
+    .unwrap();
+    assert_eq!(
+        rendered,
+        "the trait bound `Bar: Foo<Bar>` is not satisfied
+  impl Foo<Baz> for Bar
+    `Bar` does not unify with `Baz`"
+    );
+}
+
+#[test]
+fn nested_where_clause() {
+    let (explanation, rendered) = explain(
+        "
+        trait Clone { }
+        struct Foo { }
+        struct Bar { }
+        struct Vec<T> { }
+        impl Clone for Foo { }
+        impl<T> Clone for Vec<T> where T: Clone { }
+        ",
+        "Vec<Vec<Bar>>: Clone",
+    )
+    .unwrap();
+    assert_eq!(explanation.candidates.len(), 2);
+    assert!(rendered.contains("requires `Vec<Bar>: Clone`, which is not satisfied"));
+    assert!(rendered.contains("requires `Bar: Clone`, which is not satisfied"));
+    assert!(rendered.ends_with("note: the deepest unsatisfied bound is `Bar: Clone`"));
+    // Neither impl unifies with `Bar: Clone`.
+    let deepest = explanation.deepest_failure();
+    assert!(deepest.candidates.iter().all(|candidate| matches!(
+        candidate.failure,
+        CandidateFailure::Unification { parameter: 0 }
+    )));
+}
+
+#[test]
+fn goal_under_binders() {
+    let (_, rendered) = explain(
+        "
+        trait Clone { }
+        struct Vec<T> { }
+        impl<T> Clone for Vec<T> where T: Clone { }
+        ",
+        "exists<T> { Vec<T>: Clone }",
+    )
+    .unwrap();
+    assert!(rendered.starts_with("the trait bound `Vec<_>: Clone` is not satisfied"));
+}
+
+#[test]
+fn cycle_is_cut_off() {
+    let (explanation, _) = explain(
+        "
+        trait Foo { }
+        struct Bar<T> { }
+        struct Baz { }
+        impl<T> Foo for Bar<T> where Bar<T>: Foo { }
+        ",
+        "Bar<Baz>: Foo",
+    )
+    .unwrap();
+    assert_eq!(explanation.candidates.len(), 1);
+    match &explanation.candidates[0].failure {
+        CandidateFailure::WhereClause { cause, .. } => {
+            assert!(cause.is_none())
+        }
+        failure => panic!("unexpected failure: {:?}", failure),
+    }
+}
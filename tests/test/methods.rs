@@ -0,0 +1,185 @@
+//! Tests for method lookup on inherent impls.
+
+use super::*;
+use chalk_integration::lowering::lower_ty;
+use chalk_ir::{Canonical, CanonicalVarKinds, Environment, InEnvironment, Mutability, UCanonical};
+
+/// Looks up the method `name` on a receiver of type `receiver_text`,
+/// returning for each candidate the self type of its impl, the number of
+/// autoderefs and the autoref taken.
+fn lookup(
+    program_text: &str,
+    receiver_text: &str,
+    name: &str,
+) -> Vec<(String, usize, Option<Mutability>)> {
+    let db = ChalkDatabase::with(program_text, SolverChoice::default());
+    let program = db.checked_program().unwrap();
+    chalk_integration::tls::set_current_program(&program, || {
+        let interner = db.interner();
+        let ty = lower_ty(&chalk_parse::parse_ty(receiver_text).unwrap(), &program).unwrap();
+        let receiver = UCanonical {
+            canonical: Canonical {
+                value: InEnvironment::new(&Environment::new(interner), ty),
+                binders: CanonicalVarKinds::empty(interner),
+            },
+            universes: 1,
+        };
+        db.lookup_method(&receiver, name)
+            .into_iter()
+            .map(|candidate| {
+                assert!(!candidate.ambiguous);
+                let impl_datum = db.inherent_impl_datum(candidate.impl_id);
+                let self_ty = format!("{:?}", impl_datum.binders.skip_binders().self_ty);
+                (self_ty, candidate.autoderefs, candidate.autoref)
+            })
+            .collect()
+    })
+}
+
+fn candidate(
+    self_ty: &str,
+    autoderefs: usize,
+    autoref: Option<Mutability>,
+) -> (String, usize, Option<Mutability>) {
+    (self_ty.to_string(), autoderefs, autoref)
+}
+
+#[test]
+fn by_value() {
+    assert_eq!(
+        lookup("struct Foo { } impl Foo { fn bar(self); }", "Foo", "bar"),
+        vec![candidate("Foo", 0, None)]
+    );
+}
+
+#[test]
+fn autoref() {
+    assert_eq!(
+        lookup("struct Foo { } impl Foo { fn bar(&self); }", "Foo", "bar"),
+        vec![candidate("Foo", 0, Some(Mutability::Not))]
+    );
+    assert_eq!(
+        lookup(
+            "struct Foo { } impl Foo { fn bar(&mut self, x: u32) -> u32; }",
+            "Foo",
+            "bar"
+        ),
+        vec![candidate("Foo", 0, Some(Mutability::Mut))]
+    );
+}
+
+#[test]
+fn reference_receivers() {
+    let program = "struct Foo { } impl Foo { fn by_ref(&self); fn by_mut(&mut self); }";
+    assert_eq!(
+        lookup(program, "&'static Foo", "by_ref"),
+        vec![candidate("Foo", 0, None)]
+    );
+    assert_eq!(
+        lookup(program, "&'static mut Foo", "by_mut"),
+        vec![candidate("Foo", 0, None)]
+    );
+    assert_eq!(
+        lookup(program, "&'static &'static Foo", "by_ref"),
+        vec![candidate("Foo", 1, None)]
+    );
+    // A shared reference is dereferenced, then mutably borrowed again.
+    assert_eq!(
+        lookup(program, "&'static Foo", "by_mut"),
+        vec![candidate("Foo", 1, Some(Mutability::Mut))]
+    );
+}
+
+#[test]
+fn autoderef_through_deref() {
+    let program = "
+        #[lang(deref)]
+        trait Deref { type Target; }
+        struct Box<T> { }
+        impl<T> Deref for Box<T> { type Target = T; }
+        struct Foo { }
+        impl Foo { fn bar(&self); }
+    ";
+    assert_eq!(
+        lookup(program, "Box<Foo>", "bar"),
+        vec![candidate("Foo", 1, Some(Mutability::Not))]
+    );
+    assert_eq!(
+        lookup(program, "&'static Box<Box<Foo>>", "bar"),
+        vec![candidate("Foo", 3, Some(Mutability::Not))]
+    );
+}
+
+#[test]
+fn outer_methods_shadow_inner_ones() {
+    let program = "
+        #[lang(deref)]
+        trait Deref { type Target; }
+        struct Box<T> { }
+        impl<T> Deref for Box<T> { type Target = T; }
+        impl<T> Box<T> { fn bar(&self); }
+        struct Foo { }
+        impl Foo { fn bar(self); }
+    ";
+    // The autoref'd method of `Box<Foo>` comes before the by-value method
+    // of `Foo`, one autoderef step later.
+    assert_eq!(
+        lookup(program, "Box<Foo>", "bar"),
+        vec![candidate("Box<^0.0>", 0, Some(Mutability::Not))]
+    );
+}
+
+#[test]
+fn deref_cycles_end() {
+    let program = "
+        #[lang(deref)]
+        trait Deref { type Target; }
+        struct Loop { }
+        impl Deref for Loop { type Target = Loop; }
+        struct Foo { }
+        impl Foo { fn bar(&self); }
+    ";
+    assert_eq!(lookup(program, "Loop", "bar"), vec![]);
+}
+
+#[test]
+fn where_clauses() {
+    let program = "
+        trait Clone { }
+        struct Wrapper<T> { }
+        struct Foo { }
+        struct Bar { }
+        impl Clone for Foo { }
+        impl<T> Wrapper<T> where T: Clone { fn cloned(&self) -> T; }
+    ";
+    assert_eq!(
+        lookup(program, "Wrapper<Foo>", "cloned"),
+        vec![candidate("Wrapper<^0.0>", 0, Some(Mutability::Not))]
+    );
+    assert_eq!(lookup(program, "Wrapper<Bar>", "cloned"), vec![]);
+}
+
+#[test]
+fn ambiguous_methods() {
+    let program = "
+        struct Wrapper<T> { }
+        struct Foo { }
+        impl<T> Wrapper<T> { fn bar(self); }
+        impl Wrapper<Foo> { fn bar(self); }
+    ";
+    assert_eq!(
+        lookup(program, "Wrapper<Foo>", "bar"),
+        vec![
+            candidate("Wrapper<^0.0>", 0, None),
+            candidate("Wrapper<Foo>", 0, None),
+        ]
+    );
+}
+
+#[test]
+fn no_such_method() {
+    assert_eq!(
+        lookup("struct Foo { } impl Foo { fn bar(self); }", "Foo", "baz"),
+        vec![]
+    );
+}
@@ -2,11 +2,11 @@
 
 use crate::test_util::assert_same;
 use chalk_integration::db::ChalkDatabase;
+use chalk_integration::differential::normalize;
 use chalk_integration::interner::ChalkIr;
 use chalk_integration::lowering::lower_goal;
 use chalk_integration::query::LoweringDatabase;
 use chalk_integration::SolverChoice;
-use chalk_ir::Constraints;
 use chalk_solve::ext::*;
 use chalk_solve::logging::with_tracing_logs;
 use chalk_solve::RustIrDatabase;
@@ -17,16 +17,9 @@ mod bench;
 mod coherence;
 mod wf_lowering;
 
-pub fn assert_result(mut result: Option<Solution<ChalkIr>>, expected: &str, interner: &ChalkIr) {
+pub fn assert_result(result: Option<Solution<ChalkIr>>, expected: &str) {
     // sort constraints, since the different solvers may output them in different order
-    match &mut result {
-        Some(Solution::Unique(solution)) => {
-            let mut sorted = solution.value.constraints.as_slice(interner).to_vec();
-            sorted.sort_by_key(|c| format!("{:?}", c));
-            solution.value.constraints = Constraints::from_iter(interner, sorted);
-        }
-        _ => {}
-    }
+    let result = result.map(normalize);
     let result = match result {
         Some(v) => format!("{}", v.display(&ChalkIr)),
         None => format!("No possible solution"),
@@ -265,7 +258,7 @@ fn solve_goal(program_text: &str, goals: Vec<(&str, SolverChoice, TestGoal)>, co
                 match expected {
                     TestGoal::Aggregated(expected) => {
                         let result = db.solve(&peeled_goal);
-                        assert_result(result, expected, db.interner());
+                        assert_result(result, expected);
                     }
                     TestGoal::All(expected) => {
                         let mut expected = expected.into_iter();
@@ -321,15 +314,20 @@ fn solve_goal(program_text: &str, goals: Vec<(&str, SolverChoice, TestGoal)>, co
     })
 }
 
+mod answer_cache;
 mod arrays;
 mod auto_traits;
+mod budget;
+mod cancellation;
 mod closures;
 mod coherence_goals;
 mod coinduction;
 mod constants;
 mod cycle;
+mod differential;
 mod discriminant_kind;
 mod existential_types;
+mod failure_explanations;
 mod fn_def;
 mod foreign_types;
 mod functions;
@@ -337,16 +335,23 @@ mod generators;
 mod implied_bounds;
 mod impls;
 mod lifetimes;
+mod methods;
 mod misc;
+mod modules;
 mod negation;
 mod never;
 mod numerics;
 mod object_safe;
 mod opaque_types;
 mod projection;
+mod proof_trees;
 mod refs;
+mod regions;
 mod scalars;
+mod serialization;
 mod slices;
+mod specialization;
+mod stats;
 mod string;
 mod subtype;
 mod tuples;
@@ -0,0 +1,204 @@
+//! Tests for modules and `use` declarations
+
+use super::*;
+
+#[test]
+fn items_in_modules() {
+    test! {
+        program {
+            mod std {
+                trait Clone { }
+                struct Vec<T> { }
+                impl<T> Clone for Vec<T> where T: Clone { }
+            }
+
+            struct Foo { }
+            impl std::Clone for Foo { }
+        }
+
+        goal {
+            std::Vec<Foo>: std::Clone
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<T> { std::Vec<T>: std::Clone }
+        } yields {
+            "No possible solution"
+        }
+    }
+}
+
+#[test]
+fn same_name_in_different_modules() {
+    test! {
+        program {
+            trait Foo { }
+            mod a { struct S { } }
+            mod b { struct S { } }
+            impl Foo for a::S { }
+        }
+
+        goal {
+            a::S: Foo
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            b::S: Foo
+        } yields {
+            "No possible solution"
+        }
+    }
+}
+
+// Names in scope in a module shadow those in the crate root, which are in
+// scope everywhere else.
+#[test]
+fn module_scope_shadows_root() {
+    test! {
+        program {
+            trait Foo { }
+            struct S { }
+            mod m {
+                struct S { }
+                impl Foo for S { }
+                impl Foo for crate::S { }
+                mod n {
+                    trait Bar { }
+                    struct T { }
+                    impl Bar for super::S { }
+                    impl Bar for self::T { }
+                }
+            }
+        }
+
+        goal {
+            m::S: Foo
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            S: Foo
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            m::S: m::n::Bar
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            S: m::n::Bar
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            m::n::T: m::n::Bar
+        } yields {
+            "Unique"
+        }
+    }
+}
+
+#[test]
+fn use_declarations() {
+    test! {
+        program {
+            mod a {
+                trait Foo { }
+                mod b { struct Bar { } }
+            }
+            mod c {
+                use crate::a::Foo;
+                use super::a::b::Bar as Baz;
+                impl Foo for Baz { }
+            }
+        }
+
+        goal {
+            a::b::Bar: a::Foo
+        } yields {
+            "Unique"
+        }
+    }
+}
+
+// An import can go through a module that another import brings into scope,
+// wherever it is.
+#[test]
+fn use_through_use() {
+    test! {
+        program {
+            mod a {
+                use b::Bar;
+                use crate::c::d as b;
+                trait Foo { }
+                impl Foo for Bar { }
+            }
+            mod c {
+                mod d { struct Bar { } }
+            }
+        }
+
+        goal {
+            c::d::Bar: a::Foo
+        } yields {
+            "Unique"
+        }
+    }
+}
+
+// Goals are written in the crate root, and see what it imports.
+#[test]
+fn goals_use_root_imports() {
+    test! {
+        program {
+            mod a {
+                trait Foo { }
+                struct Bar { }
+                impl Foo for Bar { }
+            }
+            use a::Foo;
+            use a::Bar as Baz;
+        }
+
+        goal {
+            Baz: Foo
+        } yields {
+            "Unique"
+        }
+    }
+}
+
+// In a declared crate, `crate` is the root of that crate.
+#[test]
+fn crate_paths_in_crates() {
+    test! {
+        program {
+            crate std {
+                trait Clone { }
+                mod vec {
+                    struct Vec<T> { }
+                    impl<T> crate::Clone for Vec<T> { }
+                }
+            }
+            crate mine depends std {
+                struct Foo { }
+                impl std::Clone for crate::Foo { }
+            }
+        }
+
+        goal {
+            std::vec::Vec<mine::Foo>: std::Clone
+        } yields {
+            "Unique"
+        }
+    }
+}
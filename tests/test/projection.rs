@@ -578,6 +578,58 @@ fn normalize_gat_with_where_clause2() {
     }
 }
 
+#[test]
+fn normalize_gat_with_value_where_clause() {
+    test! {
+        program {
+            trait Sized { }
+            trait LendingIterator {
+                type Item<'a> where Self: 'a;
+            }
+
+            struct Ref<'a, T> { }
+            struct Windows<T> { }
+            impl<T> LendingIterator for Windows<T> where T: Sized {
+                type Item<'a> = Ref<'a, T> where T: 'a;
+            }
+        }
+
+        goal {
+            forall<'a, T> {
+                exists<U> {
+                    Normalize(<Windows<T> as LendingIterator>::Item<'a> -> U)
+                }
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            forall<'a, T> {
+                if (T: Sized) {
+                    exists<U> {
+                        Normalize(<Windows<T> as LendingIterator>::Item<'a> -> U)
+                    }
+                }
+            }
+        } yields {
+            "Unique; substitution [?0 := Ref<'!1_0, !1_1>], lifetime constraints [InEnvironment { environment: Env([]), goal: !1_1: '!1_0 }, InEnvironment { environment: Env([]), goal: Windows<!1_1>: '!1_0 }]"
+        }
+
+        goal {
+            forall<T> {
+                if (T: Sized) {
+                    forall<'a> {
+                        <Windows<T> as LendingIterator>::Item<'a> = Ref<'a, T>
+                    }
+                }
+            }
+        } yields {
+            "Unique"
+        }
+    }
+}
+
 #[test]
 fn normalize_gat_with_higher_ranked_trait_bound() {
     test! {
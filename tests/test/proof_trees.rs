@@ -0,0 +1,167 @@
+//! Tests for the proof trees recorded by the solvers.
+
+use super::*;
+use chalk_engine::solve::SLGSolver;
+use chalk_recursive::RecursiveSolver;
+use chalk_solve::{ProofTree, Solver};
+
+fn solvers() -> Vec<Box<dyn Solver<ChalkIr>>> {
+    vec![
+        Box::new(SLGSolver::new(10, None).with_proof_trees()),
+        Box::new(RecursiveSolver::new(100, 30, true).with_proof_trees()),
+    ]
+}
+
+/// Solves `goal_text` against `program_text` with each solver, recording
+/// proof trees, and passes the solution, the tree and the tree as it is
+/// displayed to `check`.
+fn with_proof_trees(
+    program_text: &str,
+    goal_text: &str,
+    check: impl Fn(Solution<ChalkIr>, ProofTree<ChalkIr>, String),
+) {
+    let db = ChalkDatabase::with(program_text, SolverChoice::default());
+    let program = db.checked_program().unwrap();
+    for mut solver in solvers() {
+        chalk_integration::tls::set_current_program(&program, || {
+            let goal = lower_goal(&chalk_parse::parse_goal(goal_text).unwrap(), &program)
+                .unwrap()
+                .into_peeled_goal(db.interner());
+            let (solution, tree) = solver
+                .solve_with_proof_tree(&db, &goal)
+                .expect("goal has no solution");
+            let tree = tree.expect("no proof tree recorded");
+            let rendered = tree.display(&db).to_string();
+            println!("using solver: {:?}", solver);
+            println!("{}", rendered);
+            check(solution, tree, rendered);
+        });
+    }
+}
+
+fn goals(tree: &ProofTree<ChalkIr>) -> Vec<String> {
+    let mut goals = vec![format!("{:?}", tree.goal.value.goal)];
+    for subgoal in &tree.subgoals {
+        goals.extend(self::goals(subgoal));
+    }
+    goals
+}
+
+#[test]
+fn impl_with_where_clause() {
+    with_proof_trees(
+        "
+        trait Clone { }
+        struct Foo { }
+        struct Vec<T> { }
+        impl Clone for Foo { }
+        impl<T> Clone for Vec<T> where T: Clone { }
+        ",
+        "Vec<Foo>: Clone",
+        |solution, tree, _| {
+            assert!(solution.is_unique());
+            let goals = goals(&tree);
+            assert!(goals.contains(&"Implemented(Vec<Foo>: Clone)".to_string()));
+            assert!(goals.contains(&"Implemented(Foo: Clone)".to_string()));
+            // One clause from each impl.
+            let clauses: Vec<String> = tree
+                .clauses()
+                .iter()
+                .map(|clause| format!("{:?}", clause))
+                .collect();
+            assert!(clauses
+                .iter()
+                .any(|c| c.contains("Implemented(Vec<^0.0>: Clone)")));
+            assert!(clauses.iter().any(|c| c == "for<> Implemented(Foo: Clone)"));
+        },
+    );
+}
+
+#[test]
+fn rendered_in_chalk_syntax() {
+    with_proof_trees(
+        "
+        trait Clone { }
+        struct Foo { }
+        struct Vec<T> { }
+        impl Clone for Foo { }
+        impl<T> Clone for Vec<T> where T: Clone { }
+        ",
+        "Vec<Foo>: Clone",
+        |_, _, rendered| {
+            assert!(rendered.starts_with(
+                "Vec<Foo>: Clone\n  via forall<_1_0> { Vec<_1_0>: Clone if _1_0: Clone }\n"
+            ));
+            // How deeply the where clause is nested depends on the solver.
+            assert!(rendered.ends_with("  via Foo: Clone"));
+        },
+    );
+}
+
+#[test]
+fn inferred_variables_are_applied() {
+    with_proof_trees(
+        "
+        trait Foo { }
+        struct Bar { }
+        impl Foo for Bar { }
+        ",
+        "exists<T> { T: Foo }",
+        |solution, tree, _| {
+            assert!(solution.is_unique());
+            assert!(goals(&tree).contains(&"Implemented(Bar: Foo)".to_string()));
+        },
+    );
+}
+
+#[test]
+fn environment_clause() {
+    with_proof_trees(
+        "
+        trait Foo { }
+        ",
+        "forall<T> { if (T: Foo) { T: Foo } }",
+        |solution, tree, _| {
+            assert!(solution.is_unique());
+            // The assumption `T: Foo` shows up as a clause from the environment.
+            assert!(tree
+                .clauses()
+                .iter()
+                .any(|clause| format!("{:?}", clause) == "for<> FromEnv(!1_0: Foo)"));
+        },
+    );
+}
+
+#[test]
+fn auto_trait_cycle() {
+    with_proof_trees(
+        "
+        #[auto] trait Send { }
+        struct List<T> { data: T, next: List<T> }
+        struct Foo { }
+        ",
+        "List<Foo>: Send",
+        |solution, tree, _| {
+            assert!(solution.is_unique());
+            assert!(goals(&tree).contains(&"Implemented(Foo: Send)".to_string()));
+        },
+    );
+}
+
+#[test]
+fn not_recorded_by_default() {
+    let db = ChalkDatabase::with(
+        "trait Foo { } struct Bar { } impl Foo for Bar { }",
+        SolverChoice::default(),
+    );
+    let program = db.checked_program().unwrap();
+    chalk_integration::tls::set_current_program(&program, || {
+        let goal = lower_goal(&chalk_parse::parse_goal("Bar: Foo").unwrap(), &program)
+            .unwrap()
+            .into_peeled_goal(db.interner());
+        let mut solver = SLGSolver::new(10, None);
+        let (solution, tree) = solver.solve_with_proof_tree(&db, &goal).unwrap();
+        assert!(solution.is_unique());
+        assert!(tree.is_none());
+    });
+}
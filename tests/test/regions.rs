@@ -0,0 +1,157 @@
+//! Tests for solving the lifetime constraints of solutions.
+
+use super::*;
+
+/// Solves `goal_text` against `program_text` with both solvers, then solves
+/// the regions of the solution. Returns the rendered solution, or the region
+/// error.
+fn solve_regions(program_text: &str, goal_text: &str) -> String {
+    let db = ChalkDatabase::with(program_text, SolverChoice::default());
+    let program = db.checked_program().unwrap();
+    let results: Vec<_> = [SolverChoice::slg_default(), SolverChoice::recursive_default()]
+        .iter()
+        .map(|&solver_choice| {
+            chalk_integration::tls::set_current_program(&program, || {
+                let goal = lower_goal(&chalk_parse::parse_goal(goal_text).unwrap(), &program)
+                    .unwrap()
+                    .into_peeled_goal(db.interner());
+                let solution = solver_choice
+                    .into_solver()
+                    .solve(&db, &goal)
+                    .expect("goal has no solution");
+                match solution.solve_regions(&ChalkIr, &goal.canonical.value.environment) {
+                    Ok(solution) => solution.display(&ChalkIr).to_string(),
+                    Err(err) => err.to_string(),
+                }
+            })
+        })
+        .collect();
+    assert_eq!(results[0], results[1]);
+    println!("{}", results[0]);
+    results[0].clone()
+}
+
+#[test]
+fn placeholder_does_not_outlive_static() {
+    assert_eq!(
+        solve_regions("struct Foo { }", "forall<'a> { &'a Foo: 'static }"),
+        "the lifetime constraint `'!1_0: 'static` cannot be satisfied"
+    );
+}
+
+#[test]
+fn static_outlives_everything() {
+    assert_eq!(
+        solve_regions("struct Foo { }", "forall<'a> { &'static Foo: 'a }"),
+        "Unique; substitution [], lifetime constraints []"
+    );
+}
+
+#[test]
+fn outlives_from_environment() {
+    assert_eq!(
+        solve_regions(
+            "struct Foo { }",
+            "forall<'a, 'b, 'c> { if ('a: 'b; 'b: 'c) { &'a Foo: 'c } }"
+        ),
+        "Unique; substitution [], lifetime constraints []"
+    );
+    assert_eq!(
+        solve_regions(
+            "struct Foo { }",
+            "forall<'a, 'b, 'c> { if ('a: 'b) { &'a Foo: 'c } }"
+        ),
+        "the lifetime constraint `'!1_0: '!1_2` cannot be satisfied"
+    );
+}
+
+#[test]
+fn type_outlives_from_environment() {
+    let program = "struct Foo<T> { }";
+    assert_eq!(
+        solve_regions(program, "forall<T> { if (T: 'static) { Foo<T>: 'static } }"),
+        "Unique; substitution [], lifetime constraints []"
+    );
+    assert_eq!(
+        solve_regions(program, "forall<T> { Foo<T>: 'static }"),
+        "the lifetime constraint `!1_0: 'static` cannot be satisfied"
+    );
+}
+
+#[test]
+fn constraints_through_impls() {
+    let program = "
+        struct Foo { }
+        trait Bar { }
+        impl<'a> Bar for &'a Foo where 'a: 'static { }
+    ";
+    assert_eq!(
+        solve_regions(program, "forall<'a> { &'a Foo: Bar }"),
+        "the lifetime constraint `'!1_0: 'static` cannot be satisfied"
+    );
+    assert_eq!(
+        solve_regions(program, "&'static Foo: Bar"),
+        "Unique; substitution [], lifetime constraints []"
+    );
+}
+
+#[test]
+fn constraints_on_variables_are_kept() {
+    assert_eq!(
+        solve_regions(
+            "struct Foo { }",
+            "exists<'x> { forall<'a, 'b> { if ('a: 'b) { &'a Foo: 'x, &'b Foo: 'x } } }"
+        ),
+        "Unique; for<?U0> { substitution [?0 := '^0.0], lifetime constraints [\
+         InEnvironment { environment: Env([]), goal: '!1_1: '^0.0 }] }"
+    );
+}
+
+#[test]
+fn constraints_keep_their_environments() {
+    use chalk_ir::cast::Cast;
+    use chalk_ir::*;
+
+    let interner = &ChalkIr;
+    let placeholder = |idx| {
+        LifetimeData::Placeholder(PlaceholderIndex {
+            ui: UniverseIndex { counter: 1 },
+            idx,
+        })
+        .intern(interner)
+    };
+    let (a, b) = (placeholder(0), placeholder(1));
+    let x = LifetimeData::BoundVar(BoundVar::new(DebruijnIndex::INNERMOST, 0)).intern(interner);
+    let empty = Environment::new(interner);
+    let a_outlives_b = DomainGoal::Holds(WhereClause::LifetimeOutlives(LifetimeOutlives { a, b }));
+    let with_fact = empty.add_clauses(interner, Some(a_outlives_b.cast(interner)));
+    let constraints =
+        |constraints: Vec<(&Environment<ChalkIr>, Lifetime<ChalkIr>, Lifetime<ChalkIr>)>| {
+            Constraints::from_iter(
+                interner,
+                constraints.into_iter().map(|(environment, a, b)| {
+                    InEnvironment::new(environment, Constraint::LifetimeOutlives(a, b))
+                }),
+            )
+        };
+    let solve = |constraints| chalk_solve::solve::solve_regions(interner, &empty, &constraints);
+
+    // The fact `'a: 'b` only holds for the first of the two constraints.
+    assert_eq!(
+        solve(constraints(vec![(&with_fact, a, b), (&empty, a, b)]))
+            .unwrap_err()
+            .to_string(),
+        "the lifetime constraint `'!1_0: '!1_1` cannot be satisfied"
+    );
+
+    // `'a: 'x` follows from the fact and `'b: 'x`, but only where the fact
+    // holds.
+    assert_eq!(
+        solve(constraints(vec![
+            (&with_fact, a, x),
+            (&with_fact, b, x),
+            (&empty, a, x),
+        ])),
+        Ok(constraints(vec![(&with_fact, b, x), (&empty, a, x)]))
+    );
+}
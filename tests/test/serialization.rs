@@ -0,0 +1,98 @@
+//! Tests for serializing the IR and solutions with serde.
+
+use super::*;
+use chalk_ir::{Goal, InEnvironment, ProgramClause, UCanonical};
+
+const PROGRAM: &str = "
+    #[lang(sized)]
+    trait Sized { }
+    trait Iterator { type Item; }
+    trait Clone { }
+    struct Foo { }
+    struct Vec<T> { }
+    struct Array<T, const N> { }
+    impl Clone for Foo { }
+    impl<T> Clone for Vec<T> where T: Clone { }
+    impl<T> Iterator for Vec<T> { type Item = T; }
+    fn bar<'a, T>(x: &'a T) -> T;
+";
+
+/// Serializes `value` to JSON and back.
+fn round_trip<T>(value: &T) -> T
+where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+{
+    let json = serde_json::to_string(value).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+fn lower(db: &ChalkDatabase, goal_text: &str) -> UCanonical<InEnvironment<Goal<ChalkIr>>> {
+    let program = db.checked_program().unwrap();
+    lower_goal(&chalk_parse::parse_goal(goal_text).unwrap(), &program)
+        .unwrap()
+        .into_peeled_goal(db.interner())
+}
+
+#[test]
+fn goals() {
+    let db = ChalkDatabase::with(PROGRAM, SolverChoice::default());
+    for goal_text in &[
+        "Vec<Foo>: Clone",
+        "forall<T> { if (T: Clone) { Vec<T>: Clone } }",
+        "exists<T> { <Vec<T> as Iterator>::Item = Foo }",
+        "exists<'a, T> { &'a mut T: Sized }",
+        "Array<u32, 3>: Sized",
+        "exists<const N> { Array<fn(u8) -> (i32, str), N>: Clone }",
+        "not { dyn Clone + 'static: Clone }",
+        "WellFormed(Vec<*const [bool]>)",
+    ] {
+        let goal = lower(&db, goal_text);
+        assert_eq!(round_trip(&goal), goal, "{}", goal_text);
+    }
+}
+
+#[test]
+fn program_clauses() {
+    let db = ChalkDatabase::with(PROGRAM, SolverChoice::default());
+    let clauses: Vec<ProgramClause<ChalkIr>> = db.environment().unwrap().program_clauses.clone();
+    assert!(!clauses.is_empty());
+    assert_eq!(round_trip(&clauses), clauses);
+}
+
+#[test]
+fn solutions() {
+    let db = ChalkDatabase::with(PROGRAM, SolverChoice::default());
+    let program = db.checked_program().unwrap();
+    chalk_integration::tls::set_current_program(&program, || {
+        for goal_text in &[
+            "Vec<Foo>: Clone",
+            "exists<T> { Vec<T>: Clone }",
+            "exists<T> { <Vec<Foo> as Iterator>::Item = T }",
+            "forall<'a> { exists<'b> { &'a Foo = &'b Foo } }",
+            "Vec<u32>: Clone",
+        ] {
+            let goal = lower(&db, goal_text);
+            let solution = db.solve(&goal);
+            assert_eq!(round_trip(&solution), solution, "{}", goal_text);
+        }
+    });
+}
+
+#[test]
+fn replay_saved_goal() {
+    // A goal saved to disk can be solved again later against the same
+    // program.
+    let db = ChalkDatabase::with(PROGRAM, SolverChoice::default());
+    let goal = lower(&db, "forall<T> { if (T: Clone) { Vec<T>: Clone } }");
+    let saved = serde_json::to_string(&goal).unwrap();
+
+    let db = ChalkDatabase::with(PROGRAM, SolverChoice::default());
+    let goal: UCanonical<InEnvironment<Goal<ChalkIr>>> = serde_json::from_str(&saved).unwrap();
+    let program = db.checked_program().unwrap();
+    chalk_integration::tls::set_current_program(&program, || {
+        assert_result(
+            db.solve(&goal),
+            "Unique; substitution [], lifetime constraints []",
+        );
+    });
+}
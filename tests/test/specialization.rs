@@ -0,0 +1,261 @@
+//! Tests related to specialization and `default` associated type values.
+
+use super::*;
+
+#[test]
+fn most_specialized_value() {
+    test! {
+        program {
+            trait Foo { type Item; }
+
+            struct Bar { }
+            struct Baz { }
+            struct Vec<T> { }
+
+            impl<T> Foo for T { default type Item = Bar; }
+            impl<T> Foo for Vec<T> { type Item = Baz; }
+        }
+
+        goal {
+            exists<U> {
+                Normalize(<Vec<u8> as Foo>::Item -> U)
+            }
+        } yields {
+            "Unique; substitution [?0 := Baz], lifetime constraints []"
+        }
+
+        goal {
+            exists<U> {
+                if (Reveal) {
+                    Normalize(<Vec<u8> as Foo>::Item -> U)
+                }
+            }
+        } yields {
+            "Unique; substitution [?0 := Baz], lifetime constraints []"
+        }
+
+        goal {
+            forall<T> {
+                if (Reveal) {
+                    <Vec<T> as Foo>::Item = Baz
+                }
+            }
+        } yields {
+            "Unique"
+        }
+    }
+}
+
+#[test]
+fn default_value_only_revealed() {
+    test! {
+        program {
+            trait Foo { type Item; }
+
+            struct Bar { }
+            struct Baz { }
+            struct Vec<T> { }
+
+            impl<T> Foo for T { default type Item = Bar; }
+            impl<T> Foo for Vec<T> { type Item = Baz; }
+        }
+
+        goal {
+            exists<U> {
+                Normalize(<u8 as Foo>::Item -> U)
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            <u8 as Foo>::Item = Bar
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            exists<U> {
+                if (Reveal) {
+                    Normalize(<u8 as Foo>::Item -> U)
+                }
+            }
+        } yields {
+            "Unique; substitution [?0 := Bar], lifetime constraints []"
+        }
+
+        goal {
+            if (Reveal) {
+                <u8 as Foo>::Item = Bar
+            }
+        } yields {
+            "Unique"
+        }
+    }
+}
+
+#[test]
+fn default_value_inherited() {
+    test! {
+        program {
+            trait Foo { type Item; type Other; }
+
+            struct Bar { }
+            struct Baz { }
+            struct Vec<T> { }
+
+            default impl<T> Foo for T {
+                type Item = Bar;
+                type Other = Bar;
+            }
+            impl<T> Foo for Vec<T> { type Item = Baz; }
+        }
+
+        // `Other` isn't overridden, so the value of the less special impl
+        // is used for `Vec<T>` as well.
+        goal {
+            exists<U> {
+                if (Reveal) {
+                    Normalize(<Vec<u8> as Foo>::Item -> U)
+                }
+            }
+        } yields {
+            "Unique; substitution [?0 := Baz], lifetime constraints []"
+        }
+
+        goal {
+            exists<U> {
+                if (Reveal) {
+                    Normalize(<Vec<u8> as Foo>::Other -> U)
+                }
+            }
+        } yields {
+            "Unique; substitution [?0 := Bar], lifetime constraints []"
+        }
+
+        goal {
+            exists<U> {
+                Normalize(<Vec<u8> as Foo>::Other -> U)
+            }
+        } yields {
+            "No possible solution"
+        }
+    }
+}
+
+#[test]
+fn specialization_chain() {
+    test! {
+        program {
+            trait Foo { type Item; }
+            trait Marker { }
+
+            struct A { }
+            struct B { }
+            struct C { }
+            struct Vec<T> { }
+
+            impl Marker for u8 { }
+
+            impl<T> Foo for T { default type Item = A; }
+            impl<T> Foo for Vec<T> { default type Item = B; }
+            impl<T> Foo for Vec<T> where T: Marker { type Item = C; }
+        }
+
+        goal {
+            exists<U> {
+                if (Reveal) {
+                    Normalize(<u32 as Foo>::Item -> U)
+                }
+            }
+        } yields {
+            "Unique; substitution [?0 := A], lifetime constraints []"
+        }
+
+        goal {
+            exists<U> {
+                if (Reveal) {
+                    Normalize(<Vec<u32> as Foo>::Item -> U)
+                }
+            }
+        } yields {
+            "Unique; substitution [?0 := B], lifetime constraints []"
+        }
+
+        goal {
+            exists<U> {
+                Normalize(<Vec<u8> as Foo>::Item -> U)
+            }
+        } yields {
+            "Unique; substitution [?0 := C], lifetime constraints []"
+        }
+
+        goal {
+            exists<U> {
+                if (Reveal) {
+                    Normalize(<Vec<u8> as Foo>::Item -> U)
+                }
+            }
+        } yields {
+            "Unique; substitution [?0 := C], lifetime constraints []"
+        }
+    }
+}
+
+#[test]
+fn overriding_non_default_value() {
+    lowering_error! {
+        program {
+            trait Foo { type Item; }
+
+            struct Bar { }
+            struct Vec<T> { }
+
+            impl<T> Foo for T { type Item = Bar; }
+            impl<T> Foo for Vec<T> { type Item = T; }
+        } error_msg {
+            "impl of trait `Foo` overrides an associated type value that is not `default`"
+        }
+    }
+}
+
+/// The program works out the priorities on its own when solved against
+/// directly, rather than through the database.
+#[test]
+fn solving_against_program() {
+    let db = ChalkDatabase::with(
+        "
+        trait Foo { type Item; }
+
+        struct Bar { }
+        struct Baz { }
+        struct Vec<T> { }
+
+        impl<T> Foo for T { default type Item = Bar; }
+        impl<T> Foo for Vec<T> { type Item = Baz; }
+        ",
+        SolverChoice::default(),
+    );
+    let program = db.checked_program().unwrap();
+    let solve = |goal_text: &str| {
+        chalk_integration::tls::set_current_program(&program, || {
+            let goal = lower_goal(&chalk_parse::parse_goal(goal_text).unwrap(), &program)
+                .unwrap()
+                .into_peeled_goal(db.interner());
+            let solution = SolverChoice::default()
+                .into_solver()
+                .solve(&*program, &goal)
+                .unwrap();
+            solution.display(&ChalkIr).to_string()
+        })
+    };
+
+    assert_eq!(
+        solve("exists<U> { if (Reveal) { Normalize(<Vec<u8> as Foo>::Item -> U) } }"),
+        "Unique; substitution [?0 := Baz], lifetime constraints []"
+    );
+    assert_eq!(
+        solve("exists<U> { if (Reveal) { Normalize(<u8 as Foo>::Item -> U) } }"),
+        "Unique; substitution [?0 := Bar], lifetime constraints []"
+    );
+}
@@ -0,0 +1,128 @@
+//! Tests for the statistics the solvers collect.
+
+use super::*;
+use chalk_solve::SolverStats;
+
+/// Solves each goal in turn with one solver built from `solver_choice`,
+/// returning the statistics for each.
+fn solve_with_stats(
+    program_text: &str,
+    solver_choice: SolverChoice,
+    goals: &[&str],
+) -> Vec<Option<SolverStats>> {
+    let db = ChalkDatabase::with(program_text, solver_choice);
+    let program = db.checked_program().unwrap();
+    let mut solver = solver_choice.into_solver();
+    chalk_integration::tls::set_current_program(&program, || {
+        goals
+            .iter()
+            .map(|goal_text| {
+                let goal = lower_goal(&chalk_parse::parse_goal(goal_text).unwrap(), &program)
+                    .unwrap()
+                    .into_peeled_goal(db.interner());
+                solver.solve(&db, &goal);
+                solver.stats()
+            })
+            .collect()
+    })
+}
+
+fn both_solvers() -> Vec<SolverChoice> {
+    vec![
+        SolverChoice::slg_default().with_stats(true),
+        SolverChoice::recursive_default().with_stats(true),
+    ]
+}
+
+const PROGRAM: &str = "
+    struct Foo { }
+    struct Vec<T> { }
+    trait Clone { }
+    impl Clone for Foo { }
+    impl<T> Clone for Vec<T> where T: Clone { }
+";
+
+#[test]
+fn stats_are_opt_in() {
+    for solver_choice in &[
+        SolverChoice::slg_default(),
+        SolverChoice::recursive_default(),
+    ] {
+        let stats = solve_with_stats(PROGRAM, *solver_choice, &["Foo: Clone"]);
+        assert_eq!(stats, vec![None], "{:?}", solver_choice);
+    }
+}
+
+#[test]
+fn tables_and_answers() {
+    for solver_choice in both_solvers() {
+        let stats = solve_with_stats(PROGRAM, solver_choice, &["Vec<Vec<Foo>>: Clone"]);
+        let stats = stats[0].unwrap();
+        assert!(
+            stats.tables_created >= 3,
+            "{:?}: {:?}",
+            solver_choice,
+            stats
+        );
+        assert!(stats.answers >= 3, "{:?}: {:?}", solver_choice, stats);
+        assert_eq!(stats.cycles, 0, "{:?}", solver_choice);
+        assert_eq!(stats.truncations, 0, "{:?}", solver_choice);
+        if let SolverChoice::SLG { .. } = solver_choice {
+            assert!(stats.strands_created >= 3, "{:?}", stats);
+            assert!(stats.strands_pursued >= 3, "{:?}", stats);
+        }
+    }
+}
+
+#[test]
+fn stats_are_per_goal() {
+    // The second time around, the solvers find the goal in their tables
+    // or caches, and have no new tables to create.
+    for solver_choice in both_solvers() {
+        let stats = solve_with_stats(
+            PROGRAM,
+            solver_choice,
+            &["Vec<Foo>: Clone", "Vec<Foo>: Clone"],
+        );
+        let (first, second) = (stats[0].unwrap(), stats[1].unwrap());
+        assert!(first.tables_created > 0, "{:?}: {:?}", solver_choice, first);
+        assert_eq!(second.tables_created, 0, "{:?}", solver_choice);
+        assert!(second.cache_hits > 0, "{:?}: {:?}", solver_choice, second);
+    }
+}
+
+#[test]
+fn cycles() {
+    let program = "
+        trait A { }
+        trait B { }
+        struct Foo { }
+        impl<T> A for T where T: B { }
+        impl<T> B for T where T: A { }
+        impl A for Foo { }
+    ";
+    for solver_choice in both_solvers() {
+        let stats = solve_with_stats(program, solver_choice, &["Foo: B"]);
+        let stats = stats[0].unwrap();
+        assert!(stats.cycles > 0, "{:?}: {:?}", solver_choice, stats);
+    }
+}
+
+#[test]
+fn truncations() {
+    let program = "
+        trait Foo { }
+        struct S<T> { }
+        struct Zero { }
+        impl<T> Foo for S<T> where T: Foo { }
+        impl Foo for Zero { }
+    ";
+    // A type deeper than the maximum size of both solvers.
+    let deep = (0..40).fold("Zero".to_string(), |ty, _| format!("S<{}>", ty));
+    let goal = format!("{}: Foo", deep);
+    for solver_choice in both_solvers() {
+        let stats = solve_with_stats(program, solver_choice, &[&goal]);
+        let stats = stats[0].unwrap();
+        assert!(stats.truncations > 0, "{:?}: {:?}", solver_choice, stats);
+    }
+}
@@ -142,6 +142,154 @@ fn dyn_to_dyn_unsizing() {
     }
 }
 
+#[test]
+fn dyn_upcasting() {
+    test! {
+        program {
+            #[lang(unsize)]
+            trait Unsize<T> {}
+
+            #[object_safe]
+            trait Super {}
+            #[object_safe]
+            trait Middle where Self: Super {}
+            #[object_safe]
+            trait Sub where Self: Middle {}
+            #[object_safe]
+            trait Unrelated {}
+
+            #[object_safe]
+            trait GenericSuper<T> {}
+            #[object_safe]
+            trait GenericSub<T> where Self: GenericSuper<Vec<T>> {}
+
+            #[object_safe]
+            trait Iterator {
+                type Item;
+            }
+            #[object_safe]
+            trait U32Iterator where Self: Iterator<Item = u32> {}
+            #[object_safe]
+            trait AnyIterator where Self: Iterator {}
+
+            #[auto]
+            #[object_safe]
+            trait Auto1 {}
+
+            #[auto]
+            #[object_safe]
+            trait Auto2 {}
+
+            struct Vec<T> {}
+        }
+
+        goal {
+            forall<'a> {
+                forall<'b> {
+                    dyn Middle + 'a: Unsize<dyn Super + 'b>
+                }
+            }
+        } yields {
+            "Unique; substitution [], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_0: '!2_0 }]"
+        }
+
+        // Super traits of super traits
+        goal {
+            forall<'a> {
+                dyn Sub + 'a: Unsize<dyn Super + 'a>
+            }
+        } yields {
+            "Unique; substitution [], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_0: '!1_0 }]"
+        }
+
+        // No downcasting
+        goal {
+            forall<'a> {
+                dyn Super + 'a: Unsize<dyn Middle + 'a>
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            forall<'a> {
+                dyn Sub + 'a: Unsize<dyn Unrelated + 'a>
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        // Auto traits are kept or dropped as without upcasting
+        goal {
+            forall<'a> {
+                dyn Sub + Auto1 + Auto2 + 'a: Unsize<dyn Super + Auto1 + 'a>
+            }
+        } yields {
+            "Unique; substitution [], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_0: '!1_0 }]"
+        }
+
+        goal {
+            forall<'a> {
+                dyn Sub + Auto1 + 'a: Unsize<dyn Super + Auto1 + Auto2 + 'a>
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        // Generic super traits
+        goal {
+            forall<'a> {
+                dyn GenericSub<u32> + 'a: Unsize<dyn GenericSuper<Vec<u32>> + 'a>
+            }
+        } yields {
+            "Unique; substitution [], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_0: '!1_0 }]"
+        }
+
+        goal {
+            forall<'a> {
+                dyn GenericSub<u32> + 'a: Unsize<dyn GenericSuper<u32> + 'a>
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            forall<'a> {
+                exists<T> {
+                    dyn GenericSub<u32> + 'a: Unsize<dyn GenericSuper<T> + 'a>
+                }
+            }
+        } yields {
+            "Unique; substitution [?0 := Vec<Uint(U32)>], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_0: '!1_0 }]"
+        }
+
+        // Associated type bindings given by the super trait where clause
+        goal {
+            forall<'a> {
+                dyn U32Iterator + 'a: Unsize<dyn Iterator<Item = u32> + 'a>
+            }
+        } yields {
+            "Unique; substitution [], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_0: '!1_0 }]"
+        }
+
+        goal {
+            forall<'a> {
+                dyn U32Iterator + 'a: Unsize<dyn Iterator<Item = u64> + 'a>
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            forall<'a> {
+                dyn AnyIterator + 'a: Unsize<dyn Iterator<Item = u32> + 'a>
+            }
+        } yields {
+            "No possible solution"
+        }
+    }
+}
+
 #[test]
 fn ty_to_dyn_unsizing() {
     test! {
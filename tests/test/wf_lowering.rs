@@ -562,6 +562,77 @@ fn higher_ranked_inline_bound_on_gat() {
     }
 }
 
+#[test]
+fn gat_value_where_clauses() {
+    lowering_success! {
+        program {
+            trait Ord { }
+            struct Set<T> where T: Ord { }
+            struct Val { }
+
+            trait Collection {
+                type Member<T> where T: Ord;
+            }
+
+            impl Collection for Val {
+                type Member<T> = Set<T> where T: Ord;
+            }
+        }
+    }
+
+    lowering_success! {
+        program {
+            struct Ref<'a, T> { }
+            struct Windows<T> { }
+
+            trait LendingIterator {
+                type Item<'a> where Self: 'a;
+            }
+
+            impl<T> LendingIterator for Windows<T> {
+                type Item<'a> = Ref<'a, T> where Windows<T>: 'a;
+            }
+        }
+    }
+
+    // The where clauses on the value must follow from those in the trait.
+    lowering_error! {
+        program {
+            trait Ord { }
+            trait Hash { }
+            struct Set<T> where T: Ord { }
+            struct Val { }
+
+            trait Collection {
+                type Member<T> where T: Hash;
+            }
+
+            impl Collection for Val {
+                type Member<T> = Set<T> where T: Ord;
+            }
+        } error_msg {
+            "trait impl for `Collection` does not meet well-formedness requirements"
+        }
+    }
+
+    lowering_error! {
+        program {
+            trait Ord { }
+            struct Val { }
+
+            trait Collection {
+                type Member<T>;
+            }
+
+            impl Collection for Val {
+                type Member<T> = Val where T: Ord;
+            }
+        } error_msg {
+            "trait impl for `Collection` does not meet well-formedness requirements"
+        }
+    }
+}
+
 #[test]
 fn assoc_type_recursive_bound() {
     lowering_error! {